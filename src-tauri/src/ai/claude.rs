@@ -3,14 +3,17 @@ use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
 use crate::error::{AppError, AppResult};
-use super::prompts;
-use super::provider::AiProvider;
-use super::types::*;
+use super::prompts::PromptSet;
+use super::provider::{AiProvider, CompletionRequest};
+use super::schema::OutputSchema;
+
+const CLAUDE_API_URL: &str = "https://api.anthropic.com/v1/messages";
 
 pub struct ClaudeProvider {
     client: Client,
     api_key: String,
     model: String,
+    prompts: PromptSet,
 }
 
 #[derive(Serialize)]
@@ -19,6 +22,25 @@ struct ClaudeRequest {
     max_tokens: i32,
     system: String,
     messages: Vec<ClaudeMessage>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<ClaudeTool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<ClaudeToolChoice>,
+}
+
+/// Structured output is requested as a single forced tool call whose
+/// `input_schema` is the expected response schema.
+#[derive(Serialize)]
+struct ClaudeTool {
+    name: String,
+    description: String,
+    input_schema: serde_json::Value,
+}
+
+#[derive(Serialize)]
+struct ClaudeToolChoice {
+    r#type: String,
+    name: String,
 }
 
 #[derive(Serialize)]
@@ -30,11 +52,15 @@ struct ClaudeMessage {
 #[derive(Deserialize)]
 struct ClaudeResponse {
     content: Vec<ClaudeContent>,
+    stop_reason: Option<String>,
 }
 
 #[derive(Deserialize)]
 struct ClaudeContent {
+    r#type: String,
     text: Option<String>,
+    name: Option<String>,
+    input: Option<serde_json::Value>,
 }
 
 impl ClaudeProvider {
@@ -43,27 +69,46 @@ impl ClaudeProvider {
             client: Client::new(),
             api_key,
             model: model.unwrap_or_else(|| "claude-sonnet-4-5-20250929".to_string()),
+            prompts: PromptSet::builtin(),
         }
     }
 
-    async fn call_api(&self, system: &str, prompt: &str, max_tokens: i32) -> AppResult<String> {
-        let request = ClaudeRequest {
+    pub fn with_prompts(mut self, prompts: PromptSet) -> Self {
+        self.prompts = prompts;
+        self
+    }
+
+    fn build_request(&self, request: &CompletionRequest) -> ClaudeRequest {
+        let (tools, tool_choice) = match &request.schema {
+            Some(schema) => (vec![tool_for(schema)], Some(ClaudeToolChoice {
+                r#type: "tool".to_string(),
+                name: schema.name.clone(),
+            })),
+            None => (Vec::new(), None),
+        };
+        ClaudeRequest {
             model: self.model.clone(),
-            max_tokens,
-            system: system.to_string(),
+            max_tokens: request.max_tokens,
+            system: request.system.clone(),
             messages: vec![ClaudeMessage {
                 role: "user".to_string(),
-                content: prompt.to_string(),
+                content: request.user.clone(),
             }],
-        };
+            tools,
+            tool_choice,
+        }
+    }
+
+    async fn call_api(&self, request: &CompletionRequest) -> AppResult<ClaudeResponse> {
+        let body = self.build_request(request);
 
         let response = self
             .client
-            .post("https://api.anthropic.com/v1/messages")
+            .post(CLAUDE_API_URL)
             .header("x-api-key", &self.api_key)
             .header("anthropic-version", "2023-06-01")
             .header("content-type", "application/json")
-            .json(&request)
+            .json(&body)
             .send()
             .await
             .map_err(|e| AppError::AiProvider(format!("Claude API connection failed: {e}")))?;
@@ -76,14 +121,50 @@ impl ClaudeProvider {
             )));
         }
 
-        let claude_resp: ClaudeResponse = response.json().await
-            .map_err(|e| AppError::AiProvider(format!("Failed to parse Claude response: {e}")))?;
+        response.json().await
+            .map_err(|e| AppError::AiProvider(format!("Failed to parse Claude response: {e}")))
+    }
+}
+
+fn tool_for(schema: &OutputSchema) -> ClaudeTool {
+    ClaudeTool {
+        name: schema.name.clone(),
+        description: schema.description.clone(),
+        input_schema: schema.schema.clone(),
+    }
+}
+
+/// Pulls the model output out of a response: the forced tool call's input
+/// when a schema was sent, otherwise the first text block.
+fn response_output(response: ClaudeResponse, request: &CompletionRequest) -> AppResult<String> {
+    if response.stop_reason.as_deref() == Some("max_tokens") {
+        return Err(AppError::AiProvider(format!(
+            "Claude response was cut off at {} tokens",
+            request.max_tokens
+        )));
+    }
 
-        claude_resp
+    if let Some(schema) = &request.schema {
+        let input = response
             .content
-            .first()
-            .and_then(|c| c.text.clone())
-            .ok_or_else(|| AppError::AiProvider("Empty response from Claude".to_string()))
+            .into_iter()
+            .find(|c| c.r#type == "tool_use" && c.name.as_deref() == Some(schema.name.as_str()))
+            .and_then(|c| c.input)
+            .ok_or_else(|| {
+                AppError::AiProvider(format!("Claude did not call the {} tool", schema.name))
+            })?;
+        return Ok(input.to_string());
+    }
+
+    let text = response
+        .content
+        .into_iter()
+        .find_map(|c| c.text)
+        .ok_or_else(|| AppError::AiProvider("Empty response from Claude".to_string()))?;
+    if request.json {
+        Ok(extract_json_from_text(&text).to_string())
+    } else {
+        Ok(text)
     }
 }
 
@@ -110,63 +191,25 @@ impl AiProvider for ClaudeProvider {
         "claude"
     }
 
-    async fn extract_clauses(
-        &self,
-        text: &str,
-        contract_type: &ContractType,
-    ) -> AppResult<ExtractionResponse> {
-        let system = prompts::extraction_system_prompt(contract_type);
-        let prompt = prompts::extraction_user_prompt(text, contract_type);
-        let response = self.call_api(&system, &prompt, 4096).await?;
-        let json_str = extract_json_from_text(&response);
-        super::ollama::parse_extraction_response_public(json_str)
-    }
-
-    async fn score_risk(
-        &self,
-        extraction: &ExtractionResponse,
-        contract_type: &ContractType,
-    ) -> AppResult<RiskAssessmentResponse> {
-        let system = prompts::risk_system_prompt().to_string();
-        let extraction_json = serde_json::to_string_pretty(extraction)
-            .map_err(|e| AppError::AiProvider(format!("Serialize error: {e}")))?;
-        let prompt = prompts::risk_user_prompt(&extraction_json, contract_type);
-        let response = self.call_api(&system, &prompt, 2048).await?;
-        let json_str = extract_json_from_text(&response);
-        super::ollama::parse_risk_response_public(json_str)
-    }
-
-    async fn compare_documents(
-        &self,
-        text_a: &str,
-        text_b: &str,
-        contract_type: &ContractType,
-    ) -> AppResult<ComparisonResponse> {
-        let system = prompts::comparison_system_prompt().to_string();
-        let prompt = prompts::comparison_user_prompt(text_a, text_b, contract_type);
-        let response = self.call_api(&system, &prompt, 4096).await?;
-        let json_str = extract_json_from_text(&response);
-        super::ollama::parse_comparison_response_public(json_str)
-    }
-
-    async fn generate_summary(
-        &self,
-        extraction: &ExtractionResponse,
-        risk: &RiskAssessmentResponse,
-    ) -> AppResult<String> {
-        let system = prompts::summary_system_prompt().to_string();
-        let extraction_json = serde_json::to_string_pretty(extraction)
-            .map_err(|e| AppError::AiProvider(format!("Serialize error: {e}")))?;
-        let risk_json = serde_json::to_string_pretty(risk)
-            .map_err(|e| AppError::AiProvider(format!("Serialize error: {e}")))?;
-        let prompt = prompts::summary_user_prompt(&extraction_json, &risk_json);
-        self.call_api(&system, &prompt, 2048).await
+    fn prompts(&self) -> &PromptSet {
+        &self.prompts
+    }
+
+    fn endpoint(&self) -> Option<&str> {
+        Some(CLAUDE_API_URL)
+    }
+
+    async fn complete(&self, request: &CompletionRequest) -> AppResult<String> {
+        let response = self.call_api(request).await?;
+        response_output(response, request)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ai::prompts::PromptKind;
+    use crate::ai::schema;
 
     #[test]
     fn test_extract_json_from_markdown() {
@@ -179,4 +222,49 @@ mod tests {
         let text = r#"{"key": "value"}"#;
         assert_eq!(extract_json_from_text(text), r#"{"key": "value"}"#);
     }
+
+    fn risk_request() -> CompletionRequest {
+        let prompt = PromptSet::builtin().risk("{}", &crate::ai::ContractType::Nda);
+        CompletionRequest::json(PromptKind::Risk, prompt, 2048).with_schema(schema::risk())
+    }
+
+    #[test]
+    fn test_schema_is_sent_as_forced_tool() {
+        let provider = ClaudeProvider::new("key".to_string(), None);
+        let body = serde_json::to_value(provider.build_request(&risk_request())).unwrap();
+
+        assert_eq!(body["tools"][0]["name"], "record_risk_assessment");
+        assert_eq!(body["tools"][0]["input_schema"]["type"], "object");
+        assert_eq!(body["tool_choice"]["type"], "tool");
+        assert_eq!(body["tool_choice"]["name"], "record_risk_assessment");
+
+        let summary = CompletionRequest::text(PromptKind::Summary, PromptSet::builtin().summary("{}", "{}"), 100);
+        let body = serde_json::to_value(provider.build_request(&summary)).unwrap();
+        assert!(body.get("tools").is_none());
+        assert!(body.get("tool_choice").is_none());
+    }
+
+    #[test]
+    fn test_tool_use_input_is_returned() {
+        let response: ClaudeResponse = serde_json::from_str(
+            r#"{"content": [
+                {"type": "text", "text": "Recording the assessment."},
+                {"type": "tool_use", "id": "t1", "name": "record_risk_assessment",
+                 "input": {"overall_score": 40, "risk_level": "medium", "flags": [], "summary": "ok"}}
+            ], "stop_reason": "tool_use"}"#,
+        )
+        .unwrap();
+        let output = response_output(response, &risk_request()).unwrap();
+        assert!(crate::ai::parse::parse_risk_response(&output).is_ok());
+    }
+
+    #[test]
+    fn test_truncated_response_is_an_error() {
+        let response: ClaudeResponse = serde_json::from_str(
+            r#"{"content": [{"type": "text", "text": "{\"overall"}], "stop_reason": "max_tokens"}"#,
+        )
+        .unwrap();
+        let err = response_output(response, &risk_request()).unwrap_err();
+        assert!(err.to_string().contains("cut off"));
+    }
 }
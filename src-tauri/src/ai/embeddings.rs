@@ -0,0 +1,131 @@
+use async_trait::async_trait;
+
+use crate::error::{AppError, AppResult};
+use super::prompts::PromptSet;
+use super::provider::{AiProvider, CompletionRequest};
+
+const DIMENSIONS: usize = 512;
+
+/// Words too common in contracts to say anything about a clause.
+const STOPWORDS: &[&str] = &[
+    "a", "an", "and", "any", "are", "as", "at", "be", "by", "for", "from", "has", "have", "in", "is", "it",
+    "its", "of", "on", "or", "such", "that", "the", "this", "to", "under", "which", "will", "with",
+];
+
+/// Stand-in embedder that needs no model: words and their character
+/// trigrams hashed into a fixed-size vector. It matches shared vocabulary and
+/// word stems rather than meaning, but keeps similar-clause search working
+/// offline and never sends text anywhere.
+#[derive(Default)]
+pub struct LocalEmbedder {
+    prompts: PromptSet,
+}
+
+impl LocalEmbedder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// FNV-1a, so stored vectors stay valid across builds.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+fn add_feature(vector: &mut [f32], feature: &str, weight: f32) {
+    let hash = fnv1a(feature.as_bytes());
+    let sign = if hash >> 63 == 0 { 1.0 } else { -1.0 };
+    vector[(hash % DIMENSIONS as u64) as usize] += sign * weight;
+}
+
+fn embed_text(text: &str) -> Vec<f32> {
+    let mut vector = vec![0.0; DIMENSIONS];
+    let lower = text.to_lowercase();
+    let words = lower
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.len() > 1 && !STOPWORDS.contains(word));
+    for word in words {
+        add_feature(&mut vector, word, 1.0);
+        let padded: Vec<char> = format!("<{word}>").chars().collect();
+        for trigram in padded.windows(3) {
+            add_feature(&mut vector, &trigram.iter().collect::<String>(), 0.5);
+        }
+    }
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|v| *v /= norm);
+    }
+    vector
+}
+
+#[async_trait]
+impl AiProvider for LocalEmbedder {
+    fn name(&self) -> &str {
+        "local"
+    }
+
+    fn prompts(&self) -> &PromptSet {
+        &self.prompts
+    }
+
+    async fn complete(&self, _request: &CompletionRequest) -> AppResult<String> {
+        Err(AppError::AiProvider("The local embedder only computes embeddings".to_string()))
+    }
+
+    fn embedding_model(&self) -> Option<String> {
+        Some(format!("local:hash-{DIMENSIONS}"))
+    }
+
+    async fn embed(&self, texts: &[String]) -> AppResult<Vec<Vec<f32>>> {
+        Ok(texts.iter().map(|text| embed_text(text)).collect())
+    }
+}
+
+/// Cosine similarity; 0 when either vector is all zeros or the lengths differ.
+pub fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let (mut dot, mut norm_a, mut norm_b) = (0.0, 0.0, 0.0);
+    for (x, y) in a.iter().zip(b) {
+        dot += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a.sqrt() * norm_b.sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_local_embeddings_rank_related_text_higher() {
+        let embedder = LocalEmbedder::new();
+        let texts = [
+            "The Supplier shall indemnify the Customer against all claims.",
+            "Customer will be indemnified by the Supplier for third-party claims.",
+            "This Agreement is governed by the laws of Delaware.",
+        ]
+        .map(String::from);
+        let vectors = embedder.embed(&texts).await.unwrap();
+
+        assert_eq!(vectors[0].len(), DIMENSIONS);
+        assert!((cosine(&vectors[0], &vectors[0]) - 1.0).abs() < 1e-5);
+        assert!(cosine(&vectors[0], &vectors[1]) > cosine(&vectors[0], &vectors[2]));
+        assert_eq!(embedder.embed(&texts[..1]).await.unwrap()[0], vectors[0]);
+    }
+
+    #[test]
+    fn test_cosine_edge_cases() {
+        assert_eq!(cosine(&[0.0, 0.0], &[1.0, 0.0]), 0.0);
+        assert_eq!(cosine(&[1.0], &[1.0, 0.0]), 0.0);
+        assert!((cosine(&[1.0, 1.0], &[2.0, 2.0]) - 1.0).abs() < 1e-6);
+        assert!((cosine(&[1.0, 0.0], &[-1.0, 0.0]) + 1.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,195 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+
+use crate::error::{AppError, AppResult};
+use super::prompts::{PromptKind, PromptSet};
+use super::provider::{AiProvider, CompletionRequest};
+
+/// Deterministic provider for tests. Responses are scripted per prompt kind
+/// and served in order; the last one for a kind is repeated once the queue
+/// is down to a single entry. Every request is kept for assertions.
+#[derive(Default)]
+pub struct MockProvider {
+    prompts: PromptSet,
+    responses: Mutex<HashMap<PromptKind, VecDeque<Result<String, String>>>>,
+    requests: Mutex<Vec<CompletionRequest>>,
+    unavailable: Option<String>,
+}
+
+impl MockProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_prompts(mut self, prompts: PromptSet) -> Self {
+        self.prompts = prompts;
+        self
+    }
+
+    pub fn respond(self, kind: PromptKind, response: impl Into<String>) -> Self {
+        self.push(kind, Ok(response.into()));
+        self
+    }
+
+    pub fn fail(self, kind: PromptKind, message: impl Into<String>) -> Self {
+        self.push(kind, Err(message.into()));
+        self
+    }
+
+    /// Makes `preflight` fail, as when the configured model is missing.
+    pub fn unavailable(mut self, message: impl Into<String>) -> Self {
+        self.unavailable = Some(message.into());
+        self
+    }
+
+    pub fn requests(&self) -> Vec<CompletionRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    fn push(&self, kind: PromptKind, response: Result<String, String>) {
+        self.responses.lock().unwrap().entry(kind).or_default().push_back(response);
+    }
+}
+
+#[async_trait]
+impl AiProvider for MockProvider {
+    fn name(&self) -> &str {
+        "mock"
+    }
+
+    fn prompts(&self) -> &PromptSet {
+        &self.prompts
+    }
+
+    async fn preflight(&self) -> AppResult<()> {
+        match &self.unavailable {
+            Some(message) => Err(AppError::AiProvider(message.clone())),
+            None => Ok(()),
+        }
+    }
+
+    async fn complete(&self, request: &CompletionRequest) -> AppResult<String> {
+        self.requests.lock().unwrap().push(request.clone());
+
+        let mut responses = self.responses.lock().unwrap();
+        let queue = responses.get_mut(&request.kind).ok_or_else(|| {
+            AppError::AiProvider(format!(
+                "MockProvider has no scripted response for {} prompts",
+                request.kind.as_str()
+            ))
+        })?;
+        let next = if queue.len() > 1 {
+            queue.pop_front()
+        } else {
+            queue.front().cloned()
+        };
+        next.expect("scripted queues are never empty")
+            .map_err(AppError::AiProvider)
+    }
+}
+
+/// Canned responses shared by the pipeline tests.
+pub mod fixtures {
+    pub const NDA_EXTRACTION: &str = r#"{
+        "parties": ["Acme Corp", "Globex Inc"],
+        "effective_date": "2024-01-01",
+        "termination_date": null,
+        "clauses": [
+            {
+                "clause_type": "governing_law",
+                "title": "Governing Law",
+                "text": "This agreement shall be governed by the laws of California.",
+                "section_reference": "Section 8",
+                "importance": "medium"
+            },
+            {
+                "clause_type": "term_and_duration",
+                "title": "Term",
+                "text": "This agreement remains in effect for two years.",
+                "section_reference": "Section 6",
+                "importance": "high"
+            }
+        ],
+        "contract_type": "nda"
+    }"#;
+
+    pub const RISK: &str = r#"{
+        "overall_score": 40,
+        "risk_level": "medium",
+        "flags": [
+            {
+                "category": "confidentiality",
+                "severity": "medium",
+                "description": "Confidentiality obligations survive indefinitely",
+                "clause_reference": "Section 6",
+                "suggestion": "Limit survival to three years"
+            }
+        ],
+        "summary": "Moderate risk overall."
+    }"#;
+
+    pub const COMPARISON: &str = r#"{
+        "classifications": [
+            {
+                "change_id": "C1",
+                "category": "term",
+                "diff_type": "substantive",
+                "significance": "high",
+                "explanation": "Term extended from two to three years"
+            }
+        ],
+        "summary": "The term was extended."
+    }"#;
+
+    pub const TEMPLATE_REVIEW: &str = r#"{
+        "classifications": [
+            {
+                "change_id": "C1",
+                "category": "term",
+                "diff_type": "substantive",
+                "position": "negotiable",
+                "significance": "medium",
+                "explanation": "The draft extends our standard two-year term to three years"
+            }
+        ],
+        "summary": "One negotiable deviation in the term."
+    }"#;
+
+    pub const SUMMARY: &str = "Acme Corp and Globex Inc entered into a mutual NDA.";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::ContractType;
+
+    #[tokio::test]
+    async fn test_scripted_responses_are_served_in_order() {
+        let mock = MockProvider::new()
+            .respond(PromptKind::Summary, "first")
+            .respond(PromptKind::Summary, "second");
+        let request = CompletionRequest::text(
+            PromptKind::Summary,
+            mock.prompts().summary("{}", "{}"),
+            100,
+        );
+
+        assert_eq!(mock.complete(&request).await.unwrap(), "first");
+        assert_eq!(mock.complete(&request).await.unwrap(), "second");
+        assert_eq!(mock.complete(&request).await.unwrap(), "second");
+        assert_eq!(mock.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_unscripted_kind_and_failures_are_errors() {
+        let mock = MockProvider::new().fail(PromptKind::Risk, "rate limited");
+        let extraction = mock.extract_clauses("text", &ContractType::Nda).await;
+        assert!(extraction.unwrap_err().to_string().contains("no scripted response"));
+
+        let parsed = crate::ai::parse::parse_extraction_response(fixtures::NDA_EXTRACTION).unwrap();
+        let risk = mock.score_risk(&parsed, &ContractType::Nda).await;
+        assert!(risk.unwrap_err().to_string().contains("rate limited"));
+    }
+}
@@ -1,12 +1,28 @@
 mod provider;
 mod types;
 pub(crate) mod prompts;
+mod parse;
+pub(crate) mod schema;
 mod ollama;
 mod claude;
 mod openai;
+// Recording and replaying provider traffic is a development aid only
+#[cfg(any(test, debug_assertions))]
+mod replay;
+mod redact;
+mod embeddings;
+pub(crate) mod policy;
+mod registry;
+#[cfg(test)]
+pub(crate) mod mock;
 
 pub(crate) use provider::AiProvider;
 pub(crate) use types::*;
-pub(crate) use ollama::OllamaProvider;
+pub(crate) use ollama::{OllamaHealth, OllamaModel, OllamaProvider, OllamaTaskOptions, PullProgress};
 pub(crate) use claude::ClaudeProvider;
 pub(crate) use openai::OpenAiProvider;
+#[cfg(any(test, debug_assertions))]
+pub(crate) use replay::{RecordingProvider, ReplayProvider};
+pub(crate) use embeddings::{cosine, LocalEmbedder};
+pub(crate) use redact::{Redaction, RedactingProvider, Redactor};
+pub(crate) use registry::{create_embedder, create_provider_for, ollama_provider, ProviderCache};
@@ -1,46 +1,197 @@
+use std::collections::HashMap;
+
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
 use crate::error::{AppError, AppResult};
-use super::prompts;
-use super::provider::AiProvider;
-use super::types::*;
+use super::prompts::{PromptKind, PromptSet};
+use super::provider::{AiProvider, CompletionRequest};
 
 pub struct OllamaProvider {
     client: Client,
     base_url: String,
     model: String,
+    embedding_model: String,
+    prompts: PromptSet,
+    task_options: HashMap<PromptKind, OllamaTaskOptions>,
 }
 
+/// Used for embeddings unless `ollama_embedding_model` says otherwise.
+const DEFAULT_EMBEDDING_MODEL: &str = "nomic-embed-text";
+
 #[derive(Serialize)]
-struct OllamaRequest {
+struct OllamaChatRequest {
     model: String,
-    prompt: String,
-    system: String,
+    messages: Vec<OllamaMessage>,
     stream: bool,
-    format: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<String>,
     options: OllamaOptions,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    keep_alive: Option<String>,
 }
 
-#[derive(Serialize)]
-struct OllamaGenerateRequest {
-    model: String,
-    prompt: String,
-    system: String,
-    stream: bool,
-    options: OllamaOptions,
+#[derive(Serialize, Deserialize)]
+struct OllamaMessage {
+    role: String,
+    content: String,
 }
 
 #[derive(Serialize)]
 struct OllamaOptions {
-    temperature: f64,
+    num_ctx: i32,
     num_predict: i32,
+    temperature: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct OllamaChatResponse {
+    message: OllamaMessage,
+    done_reason: Option<String>,
+}
+
+#[derive(Serialize)]
+struct OllamaEmbedRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+}
+
+#[derive(Deserialize)]
+struct OllamaEmbedResponse {
+    embeddings: Vec<Vec<f32>>,
+}
+
+/// Context window, sampling and residency settings for one kind of prompt.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OllamaTaskOptions {
+    pub num_ctx: i32,
+    /// Output token limit; the request's own limit when unset.
+    pub num_predict: Option<i32>,
+    pub temperature: f64,
+    pub top_p: Option<f64>,
+    pub seed: Option<i64>,
+    /// How long Ollama keeps the model loaded afterwards, e.g. "5m" or "-1".
+    pub keep_alive: Option<String>,
+}
+
+impl OllamaTaskOptions {
+    /// Ollama's own 2k context default truncates most contracts, so every
+    /// task asks for a window sized to its inputs.
+    pub fn defaults(kind: PromptKind) -> Self {
+        let (num_ctx, temperature) = match kind {
+            PromptKind::Extraction => (16384, 0.1),
+            PromptKind::Risk => (8192, 0.1),
+            PromptKind::Comparison => (32768, 0.1),
+            PromptKind::Summary => (8192, 0.3),
+            PromptKind::Question => (16384, 0.1),
+            PromptKind::TemplateReview => (32768, 0.1),
+        };
+        Self {
+            num_ctx,
+            num_predict: None,
+            temperature,
+            top_p: None,
+            seed: None,
+            keep_alive: None,
+        }
+    }
+}
+
+/// An installed model as reported by `/api/tags`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OllamaModel {
+    pub name: String,
+    pub size: i64,
+    pub digest: String,
+    pub modified_at: Option<String>,
+    pub details: Option<OllamaModelDetails>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OllamaModelDetails {
+    pub family: Option<String>,
+    pub parameter_size: Option<String>,
+    pub quantization_level: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct OllamaTagsResponse {
+    models: Vec<OllamaModel>,
 }
 
 #[derive(Deserialize)]
-struct OllamaResponse {
-    response: String,
+struct OllamaVersionResponse {
+    version: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OllamaHealth {
+    pub url: String,
+    pub reachable: bool,
+    pub version: Option<String>,
+    pub model: String,
+    pub model_available: bool,
+    pub error: Option<String>,
+}
+
+/// Latest state of a model pull, built up from the streamed `/api/pull` lines.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PullProgress {
+    pub model: String,
+    pub status: String,
+    pub completed: Option<i64>,
+    pub total: Option<i64>,
+    pub done: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct OllamaPullLine {
+    status: Option<String>,
+    completed: Option<i64>,
+    total: Option<i64>,
+    error: Option<String>,
+}
+
+impl PullProgress {
+    pub fn new(model: &str) -> Self {
+        Self {
+            model: model.to_string(),
+            status: "queued".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn apply(&mut self, line: &str) -> AppResult<()> {
+        let line: OllamaPullLine = serde_json::from_str(line)
+            .map_err(|e| AppError::AiProvider(format!("Failed to parse Ollama pull status: {e}")))?;
+        if let Some(error) = line.error {
+            self.error = Some(error.clone());
+            self.done = true;
+            return Err(AppError::AiProvider(format!("Ollama pull of {} failed: {error}", self.model)));
+        }
+        if let Some(status) = line.status {
+            self.done = status == "success";
+            self.status = status;
+        }
+        // Byte counts are only sent while a layer is downloading
+        if line.total.is_some() {
+            self.total = line.total;
+            self.completed = line.completed.or(Some(0));
+        }
+        Ok(())
+    }
+}
+
+/// Whether an installed model name satisfies the configured one; a model
+/// configured without a tag means `:latest`.
+fn model_matches(installed: &str, wanted: &str) -> bool {
+    installed == wanted || (!wanted.contains(':') && installed == format!("{wanted}:latest"))
 }
 
 impl OllamaProvider {
@@ -49,30 +200,42 @@ impl OllamaProvider {
             client: Client::new(),
             base_url,
             model,
+            embedding_model: DEFAULT_EMBEDDING_MODEL.to_string(),
+            prompts: PromptSet::builtin(),
+            task_options: PromptKind::ALL
+                .into_iter()
+                .map(|kind| (kind, OllamaTaskOptions::defaults(kind)))
+                .collect(),
         }
     }
 
-    async fn generate_json(&self, system: &str, prompt: &str) -> AppResult<String> {
-        let request = OllamaRequest {
-            model: self.model.clone(),
-            prompt: prompt.to_string(),
-            system: system.to_string(),
-            stream: false,
-            format: "json".to_string(),
-            options: OllamaOptions {
-                temperature: 0.1,
-                num_predict: 4096,
-            },
-        };
+    pub fn with_task_options(mut self, kind: PromptKind, options: OllamaTaskOptions) -> Self {
+        self.task_options.insert(kind, options);
+        self
+    }
 
-        let url = format!("{}/api/generate", self.base_url);
+    pub fn with_prompts(mut self, prompts: PromptSet) -> Self {
+        self.prompts = prompts;
+        self
+    }
+
+    pub fn with_embedding_model(mut self, model: String) -> Self {
+        self.embedding_model = model;
+        self
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    async fn get<T: serde::de::DeserializeOwned>(&self, path: &str) -> AppResult<T> {
+        let url = format!("{}{path}", self.base_url);
         let response = self
             .client
-            .post(&url)
-            .json(&request)
+            .get(&url)
             .send()
             .await
-            .map_err(|e| AppError::AiProvider(format!("Ollama connection failed: {e}")))?;
+            .map_err(|e| AppError::AiProvider(format!("Ollama is not reachable at {}: {e}", self.base_url)))?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -82,29 +245,63 @@ impl OllamaProvider {
             )));
         }
 
-        let ollama_resp: OllamaResponse = response.json().await
-            .map_err(|e| AppError::AiProvider(format!("Failed to parse Ollama response: {e}")))?;
+        response.json().await
+            .map_err(|e| AppError::AiProvider(format!("Failed to parse Ollama response: {e}")))
+    }
+
+    pub async fn version(&self) -> AppResult<String> {
+        let response: OllamaVersionResponse = self.get("/api/version").await?;
+        Ok(response.version)
+    }
+
+    pub async fn list_models(&self) -> AppResult<Vec<OllamaModel>> {
+        let response: OllamaTagsResponse = self.get("/api/tags").await?;
+        Ok(response.models)
+    }
 
-        Ok(ollama_resp.response)
+    pub async fn has_model(&self, model: &str) -> AppResult<bool> {
+        Ok(self.list_models().await?.iter().any(|m| model_matches(&m.name, model)))
     }
 
-    async fn generate_text(&self, system: &str, prompt: &str) -> AppResult<String> {
-        let request = OllamaGenerateRequest {
+    /// Reachability, server version and whether the configured model is installed.
+    /// Never fails; problems are reported in `error`.
+    pub async fn health(&self) -> OllamaHealth {
+        let mut health = OllamaHealth {
+            url: self.base_url.clone(),
+            reachable: false,
+            version: None,
             model: self.model.clone(),
-            prompt: prompt.to_string(),
-            system: system.to_string(),
-            stream: false,
-            options: OllamaOptions {
-                temperature: 0.3,
-                num_predict: 2048,
-            },
+            model_available: false,
+            error: None,
         };
+        match self.version().await {
+            Ok(version) => {
+                health.reachable = true;
+                health.version = Some(version);
+            }
+            Err(e) => {
+                health.error = Some(e.to_string());
+                return health;
+            }
+        }
+        match self.has_model(&self.model).await {
+            Ok(available) => health.model_available = available,
+            Err(e) => health.error = Some(e.to_string()),
+        }
+        health
+    }
 
-        let url = format!("{}/api/generate", self.base_url);
-        let response = self
+    /// Downloads a model, calling `on_progress` for every status line Ollama streams.
+    pub async fn pull(
+        &self,
+        model: &str,
+        mut on_progress: impl FnMut(&PullProgress) + Send,
+    ) -> AppResult<PullProgress> {
+        let url = format!("{}/api/pull", self.base_url);
+        let mut response = self
             .client
             .post(&url)
-            .json(&request)
+            .json(&serde_json::json!({ "model": model, "stream": true }))
             .send()
             .await
             .map_err(|e| AppError::AiProvider(format!("Ollama connection failed: {e}")))?;
@@ -117,144 +314,125 @@ impl OllamaProvider {
             )));
         }
 
-        let ollama_resp: OllamaResponse = response.json().await
-            .map_err(|e| AppError::AiProvider(format!("Failed to parse Ollama response: {e}")))?;
-
-        Ok(ollama_resp.response)
+        let mut progress = PullProgress::new(model);
+        let mut buffer = String::new();
+        while let Some(chunk) = response.chunk().await? {
+            buffer.push_str(&String::from_utf8_lossy(&chunk));
+            while let Some(newline) = buffer.find('\n') {
+                let line: String = buffer.drain(..=newline).collect();
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let applied = progress.apply(line.trim());
+                on_progress(&progress);
+                applied?;
+            }
+        }
+        if !progress.done {
+            return Err(AppError::AiProvider(format!(
+                "Ollama pull of {model} ended before completing (last status: {})",
+                progress.status
+            )));
+        }
+        Ok(progress)
     }
-}
 
-fn parse_extraction_response(json_str: &str) -> AppResult<ExtractionResponse> {
-    #[derive(Deserialize)]
-    struct RawExtraction {
-        parties: Option<Vec<String>>,
-        effective_date: Option<String>,
-        termination_date: Option<String>,
-        clauses: Option<Vec<RawClause>>,
-        contract_type: Option<String>,
+    /// Loads the model into memory so the first analysis of a batch does not
+    /// pay the load time. `keep_alive` uses Ollama's duration syntax, e.g. "10m".
+    pub async fn warm(&self, keep_alive: &str) -> AppResult<()> {
+        let url = format!("{}/api/generate", self.base_url);
+        let response = self
+            .client
+            .post(&url)
+            .json(&serde_json::json!({ "model": self.model, "keep_alive": keep_alive, "stream": false }))
+            .send()
+            .await
+            .map_err(|e| AppError::AiProvider(format!("Ollama connection failed: {e}")))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(AppError::AiProvider(format!(
+                "Ollama returned {status}: {body}"
+            )));
+        }
+        Ok(())
     }
 
-    #[derive(Deserialize)]
-    struct RawClause {
-        clause_type: Option<String>,
-        title: Option<String>,
-        text: Option<String>,
-        section_reference: Option<String>,
-        importance: Option<String>,
+    fn build_request(&self, request: &CompletionRequest) -> OllamaChatRequest {
+        let options = self
+            .task_options
+            .get(&request.kind)
+            .cloned()
+            .unwrap_or_else(|| OllamaTaskOptions::defaults(request.kind));
+        OllamaChatRequest {
+            model: self.model.clone(),
+            messages: vec![
+                OllamaMessage {
+                    role: "system".to_string(),
+                    content: request.system.clone(),
+                },
+                OllamaMessage {
+                    role: "user".to_string(),
+                    content: request.user.clone(),
+                },
+            ],
+            stream: false,
+            format: request.json.then(|| "json".to_string()),
+            options: OllamaOptions {
+                num_ctx: options.num_ctx,
+                num_predict: options.num_predict.unwrap_or(request.max_tokens),
+                temperature: options.temperature,
+                top_p: options.top_p,
+                seed: options.seed,
+            },
+            keep_alive: options.keep_alive,
+        }
     }
 
-    let raw: RawExtraction = serde_json::from_str(json_str)
-        .map_err(|e| AppError::AiProvider(format!("Failed to parse extraction JSON: {e}\nRaw: {json_str}")))?;
-
-    let clauses = raw
-        .clauses
-        .unwrap_or_default()
-        .into_iter()
-        .filter(|c| c.text.is_some() && c.clause_type.is_some())
-        .map(|c| {
-            let ct = c.clause_type.unwrap_or_default();
-            ExtractedClause {
-            clause_type: ct.clone(),
-            title: c.title.unwrap_or_else(|| ct.clone()),
-            text: c.text.unwrap_or_default(),
-            section_reference: c.section_reference,
-            importance: c.importance.unwrap_or_else(|| "medium".to_string()),
-        }})
-        .collect();
-
-    Ok(ExtractionResponse {
-        parties: raw.parties.unwrap_or_default(),
-        effective_date: raw.effective_date,
-        termination_date: raw.termination_date,
-        clauses,
-        contract_type: raw.contract_type.unwrap_or_default(),
-        raw_json: json_str.to_string(),
-    })
-}
+    async fn chat(&self, request: &CompletionRequest) -> AppResult<OllamaChatResponse> {
+        let body = self.build_request(request);
 
-fn parse_risk_response(json_str: &str) -> AppResult<RiskAssessmentResponse> {
-    #[derive(Deserialize)]
-    struct RawRisk {
-        overall_score: Option<i32>,
-        risk_level: Option<String>,
-        flags: Option<Vec<RawFlag>>,
-        summary: Option<String>,
-    }
+        let url = format!("{}/api/chat", self.base_url);
+        let response = self
+            .client
+            .post(&url)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| AppError::AiProvider(format!("Ollama connection failed: {e}")))?;
 
-    #[derive(Deserialize)]
-    struct RawFlag {
-        category: Option<String>,
-        severity: Option<String>,
-        description: Option<String>,
-        clause_reference: Option<String>,
-        suggestion: Option<String>,
-    }
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(AppError::AiProvider(format!(
+                "Ollama returned {status}: {body}"
+            )));
+        }
 
-    let raw: RawRisk = serde_json::from_str(json_str)
-        .map_err(|e| AppError::AiProvider(format!("Failed to parse risk JSON: {e}\nRaw: {json_str}")))?;
-
-    let score = raw.overall_score.unwrap_or(50);
-    let level = raw.risk_level.unwrap_or_else(|| {
-        if score <= 33 { "low" } else if score <= 66 { "medium" } else { "high" }.to_string()
-    });
-
-    Ok(RiskAssessmentResponse {
-        overall_score: score,
-        risk_level: level,
-        flags: raw
-            .flags
-            .unwrap_or_default()
-            .into_iter()
-            .filter(|f| f.description.is_some())
-            .map(|f| RiskFlag {
-                category: f.category.unwrap_or_else(|| "other".to_string()),
-                severity: f.severity.unwrap_or_else(|| "medium".to_string()),
-                description: f.description.unwrap_or_default(),
-                clause_reference: f.clause_reference,
-                suggestion: f.suggestion,
-            })
-            .collect(),
-        summary: raw.summary.unwrap_or_else(|| "Risk assessment completed.".to_string()),
-    })
+        response.json().await
+            .map_err(|e| AppError::AiProvider(format!("Failed to parse Ollama response: {e}")))
+    }
 }
 
-fn parse_comparison_response(json_str: &str) -> AppResult<ComparisonResponse> {
-    #[derive(Deserialize)]
-    struct RawComparison {
-        differences: Option<Vec<RawDiff>>,
-        summary: Option<String>,
+fn embed_output(response: OllamaEmbedResponse, expected: usize) -> AppResult<Vec<Vec<f32>>> {
+    if response.embeddings.len() != expected {
+        return Err(AppError::AiProvider(format!(
+            "Ollama returned {} embeddings for {expected} inputs",
+            response.embeddings.len()
+        )));
     }
+    Ok(response.embeddings)
+}
 
-    #[derive(Deserialize)]
-    struct RawDiff {
-        category: Option<String>,
-        diff_type: Option<String>,
-        description: Option<String>,
-        text_a: Option<String>,
-        text_b: Option<String>,
-        significance: Option<String>,
+fn response_output(response: OllamaChatResponse, request: &CompletionRequest) -> AppResult<String> {
+    if response.done_reason.as_deref() == Some("length") {
+        return Err(AppError::AiProvider(format!(
+            "Ollama response for the {} prompt was cut off by the token limit; raise num_predict or num_ctx",
+            request.kind.as_str()
+        )));
     }
-
-    let raw: RawComparison = serde_json::from_str(json_str)
-        .map_err(|e| AppError::AiProvider(format!("Failed to parse comparison JSON: {e}\nRaw: {json_str}")))?;
-
-    Ok(ComparisonResponse {
-        differences: raw
-            .differences
-            .unwrap_or_default()
-            .into_iter()
-            .filter(|d| d.description.is_some())
-            .map(|d| Difference {
-                category: d.category.unwrap_or_else(|| "other".to_string()),
-                diff_type: d.diff_type.unwrap_or_else(|| "substantive".to_string()),
-                description: d.description.unwrap_or_default(),
-                text_a: d.text_a,
-                text_b: d.text_b,
-                significance: d.significance.unwrap_or_else(|| "medium".to_string()),
-            })
-            .collect(),
-        summary: raw.summary.unwrap_or_else(|| "Comparison completed.".to_string()),
-    })
+    Ok(response.message.content)
 }
 
 #[async_trait]
@@ -263,68 +441,60 @@ impl AiProvider for OllamaProvider {
         "ollama"
     }
 
-    async fn extract_clauses(
-        &self,
-        text: &str,
-        contract_type: &ContractType,
-    ) -> AppResult<ExtractionResponse> {
-        let system = prompts::extraction_system_prompt(contract_type);
-        let prompt = prompts::extraction_user_prompt(text, contract_type);
-        let response = self.generate_json(&system, &prompt).await?;
-        parse_extraction_response(&response)
+    fn prompts(&self) -> &PromptSet {
+        &self.prompts
     }
 
-    async fn score_risk(
-        &self,
-        extraction: &ExtractionResponse,
-        contract_type: &ContractType,
-    ) -> AppResult<RiskAssessmentResponse> {
-        let system = prompts::risk_system_prompt().to_string();
-        let extraction_json = serde_json::to_string_pretty(extraction)
-            .map_err(|e| AppError::AiProvider(format!("Failed to serialize extraction: {e}")))?;
-        let prompt = prompts::risk_user_prompt(&extraction_json, contract_type);
-        let response = self.generate_json(&system, &prompt).await?;
-        parse_risk_response(&response)
+    fn endpoint(&self) -> Option<&str> {
+        Some(&self.base_url)
     }
 
-    async fn compare_documents(
-        &self,
-        text_a: &str,
-        text_b: &str,
-        contract_type: &ContractType,
-    ) -> AppResult<ComparisonResponse> {
-        let system = prompts::comparison_system_prompt().to_string();
-        let prompt = prompts::comparison_user_prompt(text_a, text_b, contract_type);
-        let response = self.generate_json(&system, &prompt).await?;
-        parse_comparison_response(&response)
+    async fn preflight(&self) -> AppResult<()> {
+        if self.has_model(&self.model).await? {
+            return Ok(());
+        }
+        Err(AppError::AiProvider(format!(
+            "Ollama model '{model}' is not installed at {url}. Pull it from Settings or run `ollama pull {model}`.",
+            model = self.model,
+            url = self.base_url
+        )))
     }
 
-    async fn generate_summary(
-        &self,
-        extraction: &ExtractionResponse,
-        risk: &RiskAssessmentResponse,
-    ) -> AppResult<String> {
-        let system = prompts::summary_system_prompt().to_string();
-        let extraction_json = serde_json::to_string_pretty(extraction)
-            .map_err(|e| AppError::AiProvider(format!("Failed to serialize extraction: {e}")))?;
-        let risk_json = serde_json::to_string_pretty(risk)
-            .map_err(|e| AppError::AiProvider(format!("Failed to serialize risk: {e}")))?;
-        let prompt = prompts::summary_user_prompt(&extraction_json, &risk_json);
-        self.generate_text(&system, &prompt).await
+    async fn complete(&self, request: &CompletionRequest) -> AppResult<String> {
+        let response = self.chat(request).await?;
+        response_output(response, request)
     }
-}
 
-// Public wrappers for shared parsing logic
-pub fn parse_extraction_response_public(json_str: &str) -> AppResult<ExtractionResponse> {
-    parse_extraction_response(json_str)
-}
+    fn embedding_model(&self) -> Option<String> {
+        Some(format!("ollama:{}", self.embedding_model))
+    }
 
-pub fn parse_risk_response_public(json_str: &str) -> AppResult<RiskAssessmentResponse> {
-    parse_risk_response(json_str)
-}
+    async fn embed(&self, texts: &[String]) -> AppResult<Vec<Vec<f32>>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+        let url = format!("{}/api/embed", self.base_url);
+        let response = self
+            .client
+            .post(&url)
+            .json(&OllamaEmbedRequest { model: &self.embedding_model, input: texts })
+            .send()
+            .await
+            .map_err(|e| AppError::AiProvider(format!("Ollama connection failed: {e}")))?;
 
-pub fn parse_comparison_response_public(json_str: &str) -> AppResult<ComparisonResponse> {
-    parse_comparison_response(json_str)
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(AppError::AiProvider(format!(
+                "Ollama returned {status}: {body}. Is the embedding model '{}' installed?",
+                self.embedding_model
+            )));
+        }
+
+        let response: OllamaEmbedResponse = response.json().await
+            .map_err(|e| AppError::AiProvider(format!("Failed to parse Ollama embeddings: {e}")))?;
+        embed_output(response, texts.len())
+    }
 }
 
 #[cfg(test)]
@@ -332,108 +502,94 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_parse_extraction_response() {
-        let json = r#"{
-            "parties": ["Acme Corp", "Globex Inc"],
-            "effective_date": "2024-01-01",
-            "termination_date": null,
-            "clauses": [
-                {
-                    "clause_type": "confidentiality",
-                    "title": "Confidentiality",
-                    "text": "All information shared shall be kept confidential.",
-                    "section_reference": "Section 3",
-                    "importance": "high"
-                },
-                {
-                    "clause_type": "governing_law",
-                    "title": "Governing Law",
-                    "text": "This agreement shall be governed by the laws of California.",
-                    "section_reference": "Section 8",
-                    "importance": "medium"
-                }
-            ],
-            "contract_type": "nda"
-        }"#;
-
-        let result = parse_extraction_response(json).unwrap();
-        assert_eq!(result.parties.len(), 2);
-        assert_eq!(result.parties[0], "Acme Corp");
-        assert_eq!(result.clauses.len(), 2);
-        assert_eq!(result.clauses[0].clause_type, "confidentiality");
-        assert_eq!(result.effective_date, Some("2024-01-01".to_string()));
-    }
-
-    #[test]
-    fn test_parse_extraction_with_missing_fields() {
-        let json = r#"{
-            "parties": ["A"],
-            "clauses": [
-                {"clause_type": "test", "text": "some text"},
-                {"clause_type": null, "text": null}
-            ]
-        }"#;
-
-        let result = parse_extraction_response(json).unwrap();
-        assert_eq!(result.parties.len(), 1);
-        assert_eq!(result.clauses.len(), 1);
+    fn test_chat_request_uses_task_options() {
+        let provider = OllamaProvider::new("http://localhost:11434".into(), "llama3".into())
+            .with_task_options(PromptKind::Risk, OllamaTaskOptions {
+                num_ctx: 4096,
+                num_predict: Some(512),
+                temperature: 0.0,
+                top_p: Some(0.9),
+                seed: Some(7),
+                keep_alive: Some("30m".into()),
+            });
+        let prompts = PromptSet::builtin();
+
+        let risk = CompletionRequest::json(PromptKind::Risk, prompts.risk("{}", &crate::ai::ContractType::Nda), 2048);
+        let body = serde_json::to_value(provider.build_request(&risk)).unwrap();
+        assert_eq!(body["messages"][0]["role"], "system");
+        assert_eq!(body["messages"][1]["role"], "user");
+        assert_eq!(body["format"], "json");
+        assert_eq!(body["options"]["num_ctx"], 4096);
+        assert_eq!(body["options"]["num_predict"], 512);
+        assert_eq!(body["options"]["seed"], 7);
+        assert_eq!(body["keep_alive"], "30m");
+
+        let summary = CompletionRequest::text(PromptKind::Summary, prompts.summary("{}", "{}"), 2048);
+        let body = serde_json::to_value(provider.build_request(&summary)).unwrap();
+        assert!(body.get("format").is_none());
+        assert!(body.get("keep_alive").is_none());
+        assert_eq!(body["options"]["num_ctx"], 8192);
+        assert_eq!(body["options"]["num_predict"], 2048);
+        assert!(body["options"].get("top_p").is_none());
     }
 
     #[test]
-    fn test_parse_risk_response() {
-        let json = r#"{
-            "overall_score": 72,
-            "risk_level": "high",
-            "flags": [
-                {
-                    "category": "indemnification",
-                    "severity": "high",
-                    "description": "No indemnification cap specified",
-                    "clause_reference": "Section 5",
-                    "suggestion": "Add a reasonable cap"
-                }
-            ],
-            "summary": "This contract has significant risk."
-        }"#;
-
-        let result = parse_risk_response(json).unwrap();
-        assert_eq!(result.overall_score, 72);
-        assert_eq!(result.risk_level, "high");
-        assert_eq!(result.flags.len(), 1);
+    fn test_length_done_reason_is_an_error() {
+        let request = CompletionRequest::text(PromptKind::Summary, PromptSet::builtin().summary("{}", "{}"), 16);
+        let response: OllamaChatResponse = serde_json::from_str(
+            r#"{"message": {"role": "assistant", "content": "Acme and"}, "done": true, "done_reason": "length"}"#,
+        )
+        .unwrap();
+        assert!(response_output(response, &request).unwrap_err().to_string().contains("cut off"));
+
+        let response: OllamaChatResponse = serde_json::from_str(
+            r#"{"message": {"role": "assistant", "content": "Done."}, "done": true, "done_reason": "stop"}"#,
+        )
+        .unwrap();
+        assert_eq!(response_output(response, &request).unwrap(), "Done.");
     }
 
     #[test]
-    fn test_parse_risk_defaults() {
-        let json = r#"{"flags": []}"#;
-        let result = parse_risk_response(json).unwrap();
-        assert_eq!(result.overall_score, 50);
-        assert_eq!(result.risk_level, "medium");
+    fn test_embed_request_and_response() {
+        let input = vec!["Governing law".to_string(), "Indemnity".to_string()];
+        let body = serde_json::to_value(OllamaEmbedRequest { model: "nomic-embed-text", input: &input }).unwrap();
+        assert_eq!(body["model"], "nomic-embed-text");
+        assert_eq!(body["input"][1], "Indemnity");
+
+        let response: OllamaEmbedResponse =
+            serde_json::from_str(r#"{"model": "nomic-embed-text", "embeddings": [[0.1, 0.2], [0.3, 0.4]]}"#).unwrap();
+        assert_eq!(embed_output(response, 2).unwrap()[1], vec![0.3, 0.4]);
+        let response: OllamaEmbedResponse = serde_json::from_str(r#"{"embeddings": [[0.1]]}"#).unwrap();
+        assert!(embed_output(response, 2).is_err());
     }
 
     #[test]
-    fn test_parse_comparison_response() {
-        let json = r#"{
-            "differences": [
-                {
-                    "category": "payment",
-                    "diff_type": "substantive",
-                    "description": "Payment terms changed from net-30 to net-60",
-                    "text_a": "Payment due within 30 days",
-                    "text_b": "Payment due within 60 days",
-                    "significance": "high"
-                }
-            ],
-            "summary": "One significant change in payment terms."
-        }"#;
-
-        let result = parse_comparison_response(json).unwrap();
-        assert_eq!(result.differences.len(), 1);
-        assert_eq!(result.differences[0].diff_type, "substantive");
+    fn test_model_matches_implicit_latest_tag() {
+        assert!(model_matches("llama3:latest", "llama3"));
+        assert!(model_matches("llama3:8b", "llama3:8b"));
+        assert!(!model_matches("llama3:8b", "llama3"));
+        assert!(!model_matches("llama3.1:latest", "llama3"));
     }
 
     #[test]
-    fn test_parse_invalid_json() {
-        let result = parse_extraction_response("not json");
-        assert!(result.is_err());
+    fn test_pull_progress_follows_stream() {
+        let mut progress = PullProgress::new("llama3");
+        progress.apply(r#"{"status": "pulling manifest"}"#).unwrap();
+        assert_eq!(progress.status, "pulling manifest");
+        assert_eq!(progress.total, None);
+
+        progress
+            .apply(r#"{"status": "pulling 6a0746a1ec1a", "digest": "sha256:6a07", "total": 4661211424, "completed": 1048576}"#)
+            .unwrap();
+        assert_eq!(progress.completed, Some(1048576));
+        assert!(!progress.done);
+
+        progress.apply(r#"{"status": "success"}"#).unwrap();
+        assert!(progress.done);
+
+        let mut failed = PullProgress::new("nope");
+        assert!(failed.apply(r#"{"error": "pull model manifest: file does not exist"}"#).is_err());
+        assert!(failed.done);
+        assert!(failed.error.is_some());
     }
 }
@@ -3,14 +3,17 @@ use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
 use crate::error::{AppError, AppResult};
-use super::prompts;
-use super::provider::AiProvider;
-use super::types::*;
+use super::prompts::PromptSet;
+use super::provider::{AiProvider, CompletionRequest};
+use super::schema::OutputSchema;
+
+const OPENAI_API_URL: &str = "https://api.openai.com/v1/chat/completions";
 
 pub struct OpenAiProvider {
     client: Client,
     api_key: String,
     model: String,
+    prompts: PromptSet,
 }
 
 #[derive(Serialize)]
@@ -25,6 +28,16 @@ struct OpenAiRequest {
 #[derive(Serialize)]
 struct ResponseFormat {
     r#type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    json_schema: Option<JsonSchemaFormat>,
+}
+
+#[derive(Serialize)]
+struct JsonSchemaFormat {
+    name: String,
+    description: String,
+    schema: serde_json::Value,
+    strict: bool,
 }
 
 #[derive(Serialize)]
@@ -41,11 +54,13 @@ struct OpenAiResponse {
 #[derive(Deserialize)]
 struct OpenAiChoice {
     message: OpenAiMessageResponse,
+    finish_reason: Option<String>,
 }
 
 #[derive(Deserialize)]
 struct OpenAiMessageResponse {
     content: Option<String>,
+    refusal: Option<String>,
 }
 
 impl OpenAiProvider {
@@ -54,39 +69,53 @@ impl OpenAiProvider {
             client: Client::new(),
             api_key,
             model: model.unwrap_or_else(|| "gpt-4o".to_string()),
+            prompts: PromptSet::builtin(),
         }
     }
 
-    async fn call_api(&self, system: &str, prompt: &str, max_tokens: i32, json_mode: bool) -> AppResult<String> {
-        let request = OpenAiRequest {
+    pub fn with_prompts(mut self, prompts: PromptSet) -> Self {
+        self.prompts = prompts;
+        self
+    }
+
+    fn build_request(&self, request: &CompletionRequest) -> OpenAiRequest {
+        OpenAiRequest {
             model: self.model.clone(),
             messages: vec![
                 OpenAiMessage {
                     role: "system".to_string(),
-                    content: system.to_string(),
+                    content: request.system.clone(),
                 },
                 OpenAiMessage {
                     role: "user".to_string(),
-                    content: prompt.to_string(),
+                    content: request.user.clone(),
                 },
             ],
-            temperature: if json_mode { 0.1 } else { 0.3 },
-            max_tokens,
-            response_format: if json_mode {
-                Some(ResponseFormat {
+            temperature: if request.json { 0.1 } else { 0.3 },
+            max_tokens: request.max_tokens,
+            response_format: match (&request.schema, request.json) {
+                (Some(schema), _) => Some(ResponseFormat {
+                    r#type: "json_schema".to_string(),
+                    json_schema: Some(json_schema_format(schema)),
+                }),
+                (None, true) => Some(ResponseFormat {
                     r#type: "json_object".to_string(),
-                })
-            } else {
-                None
+                    json_schema: None,
+                }),
+                (None, false) => None,
             },
-        };
+        }
+    }
+
+    async fn call_api(&self, request: &CompletionRequest) -> AppResult<OpenAiResponse> {
+        let body = self.build_request(request);
 
         let response = self
             .client
-            .post("https://api.openai.com/v1/chat/completions")
+            .post(OPENAI_API_URL)
             .header("Authorization", format!("Bearer {}", self.api_key))
             .header("Content-Type", "application/json")
-            .json(&request)
+            .json(&body)
             .send()
             .await
             .map_err(|e| AppError::AiProvider(format!("OpenAI connection failed: {e}")))?;
@@ -99,70 +128,98 @@ impl OpenAiProvider {
             )));
         }
 
-        let oai_resp: OpenAiResponse = response.json().await
-            .map_err(|e| AppError::AiProvider(format!("Failed to parse OpenAI response: {e}")))?;
+        response.json().await
+            .map_err(|e| AppError::AiProvider(format!("Failed to parse OpenAI response: {e}")))
+    }
+}
 
-        oai_resp
-            .choices
-            .first()
-            .and_then(|c| c.message.content.clone())
-            .ok_or_else(|| AppError::AiProvider("Empty response from OpenAI".to_string()))
+fn json_schema_format(schema: &OutputSchema) -> JsonSchemaFormat {
+    JsonSchemaFormat {
+        name: schema.name.clone(),
+        description: schema.description.clone(),
+        schema: schema.schema.clone(),
+        strict: true,
     }
 }
 
+fn response_output(response: OpenAiResponse, request: &CompletionRequest) -> AppResult<String> {
+    let choice = response
+        .choices
+        .into_iter()
+        .next()
+        .ok_or_else(|| AppError::AiProvider("Empty response from OpenAI".to_string()))?;
+
+    if choice.finish_reason.as_deref() == Some("length") {
+        return Err(AppError::AiProvider(format!(
+            "OpenAI response was cut off at {} tokens",
+            request.max_tokens
+        )));
+    }
+    if let Some(refusal) = choice.message.refusal {
+        return Err(AppError::AiProvider(format!("OpenAI refused the request: {refusal}")));
+    }
+    choice
+        .message
+        .content
+        .ok_or_else(|| AppError::AiProvider("Empty response from OpenAI".to_string()))
+}
+
 #[async_trait]
 impl AiProvider for OpenAiProvider {
     fn name(&self) -> &str {
         "openai"
     }
 
-    async fn extract_clauses(
-        &self,
-        text: &str,
-        contract_type: &ContractType,
-    ) -> AppResult<ExtractionResponse> {
-        let system = prompts::extraction_system_prompt(contract_type);
-        let prompt = prompts::extraction_user_prompt(text, contract_type);
-        let response = self.call_api(&system, &prompt, 4096, true).await?;
-        super::ollama::parse_extraction_response_public(&response)
+    fn prompts(&self) -> &PromptSet {
+        &self.prompts
+    }
+
+    fn endpoint(&self) -> Option<&str> {
+        Some(OPENAI_API_URL)
     }
 
-    async fn score_risk(
-        &self,
-        extraction: &ExtractionResponse,
-        contract_type: &ContractType,
-    ) -> AppResult<RiskAssessmentResponse> {
-        let system = prompts::risk_system_prompt().to_string();
-        let extraction_json = serde_json::to_string_pretty(extraction)
-            .map_err(|e| AppError::AiProvider(format!("Serialize error: {e}")))?;
-        let prompt = prompts::risk_user_prompt(&extraction_json, contract_type);
-        let response = self.call_api(&system, &prompt, 2048, true).await?;
-        super::ollama::parse_risk_response_public(&response)
+    async fn complete(&self, request: &CompletionRequest) -> AppResult<String> {
+        let response = self.call_api(request).await?;
+        response_output(response, request)
     }
+}
 
-    async fn compare_documents(
-        &self,
-        text_a: &str,
-        text_b: &str,
-        contract_type: &ContractType,
-    ) -> AppResult<ComparisonResponse> {
-        let system = prompts::comparison_system_prompt().to_string();
-        let prompt = prompts::comparison_user_prompt(text_a, text_b, contract_type);
-        let response = self.call_api(&system, &prompt, 4096, true).await?;
-        super::ollama::parse_comparison_response_public(&response)
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::prompts::PromptKind;
+    use crate::ai::{schema, ContractType};
+
+    #[test]
+    fn test_schema_is_sent_as_strict_json_schema() {
+        let provider = OpenAiProvider::new("key".to_string(), None);
+        let prompt = PromptSet::builtin().extraction("text", &ContractType::Nda);
+        let request = CompletionRequest::json(PromptKind::Extraction, prompt, 4096)
+            .with_schema(schema::extraction(&ContractType::Nda));
+        let body = serde_json::to_value(provider.build_request(&request)).unwrap();
+
+        let format = &body["response_format"];
+        assert_eq!(format["type"], "json_schema");
+        assert_eq!(format["json_schema"]["name"], "record_extraction");
+        assert_eq!(format["json_schema"]["strict"], true);
+        assert_eq!(format["json_schema"]["schema"]["additionalProperties"], false);
     }
 
-    async fn generate_summary(
-        &self,
-        extraction: &ExtractionResponse,
-        risk: &RiskAssessmentResponse,
-    ) -> AppResult<String> {
-        let system = prompts::summary_system_prompt().to_string();
-        let extraction_json = serde_json::to_string_pretty(extraction)
-            .map_err(|e| AppError::AiProvider(format!("Serialize error: {e}")))?;
-        let risk_json = serde_json::to_string_pretty(risk)
-            .map_err(|e| AppError::AiProvider(format!("Serialize error: {e}")))?;
-        let prompt = prompts::summary_user_prompt(&extraction_json, &risk_json);
-        self.call_api(&system, &prompt, 2048, false).await
+    #[test]
+    fn test_truncation_and_refusal_are_errors() {
+        let prompt = PromptSet::builtin().risk("{}", &ContractType::Nda);
+        let request = CompletionRequest::json(PromptKind::Risk, prompt, 2048).with_schema(schema::risk());
+
+        let truncated: OpenAiResponse = serde_json::from_str(
+            r#"{"choices": [{"message": {"content": "{\"overall"}, "finish_reason": "length"}]}"#,
+        )
+        .unwrap();
+        assert!(response_output(truncated, &request).unwrap_err().to_string().contains("cut off"));
+
+        let refused: OpenAiResponse = serde_json::from_str(
+            r#"{"choices": [{"message": {"content": null, "refusal": "I can't help with that."}, "finish_reason": "stop"}]}"#,
+        )
+        .unwrap();
+        assert!(response_output(refused, &request).unwrap_err().to_string().contains("refused"));
     }
 }
@@ -0,0 +1,335 @@
+use serde::Deserialize;
+
+use crate::error::{AppError, AppResult};
+use super::types::*;
+
+pub fn parse_extraction_response(json_str: &str) -> AppResult<ExtractionResponse> {
+    #[derive(Deserialize)]
+    struct RawExtraction {
+        parties: Option<Vec<String>>,
+        effective_date: Option<String>,
+        termination_date: Option<String>,
+        clauses: Option<Vec<RawClause>>,
+        contract_type: Option<String>,
+    }
+
+    #[derive(Deserialize)]
+    struct RawClause {
+        clause_type: Option<String>,
+        title: Option<String>,
+        text: Option<String>,
+        section_reference: Option<String>,
+        importance: Option<String>,
+    }
+
+    let raw: RawExtraction = serde_json::from_str(json_str)
+        .map_err(|e| AppError::AiProvider(format!("Failed to parse extraction JSON: {e}\nRaw: {json_str}")))?;
+
+    let clauses = raw
+        .clauses
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|c| c.text.is_some() && c.clause_type.is_some())
+        .map(|c| {
+            let ct = c.clause_type.unwrap_or_default();
+            ExtractedClause {
+            clause_type: ct.clone(),
+            title: c.title.unwrap_or_else(|| ct.clone()),
+            text: c.text.unwrap_or_default(),
+            section_reference: c.section_reference,
+            importance: c.importance.unwrap_or_else(|| "medium".to_string()),
+        }})
+        .collect();
+
+    Ok(ExtractionResponse {
+        parties: raw.parties.unwrap_or_default(),
+        effective_date: raw.effective_date,
+        termination_date: raw.termination_date,
+        clauses,
+        contract_type: raw.contract_type.unwrap_or_default(),
+        raw_json: json_str.to_string(),
+    })
+}
+
+pub fn parse_risk_response(json_str: &str) -> AppResult<RiskAssessmentResponse> {
+    #[derive(Deserialize)]
+    struct RawRisk {
+        overall_score: Option<i32>,
+        risk_level: Option<String>,
+        flags: Option<Vec<RawFlag>>,
+        summary: Option<String>,
+    }
+
+    #[derive(Deserialize)]
+    struct RawFlag {
+        category: Option<String>,
+        severity: Option<String>,
+        description: Option<String>,
+        clause_reference: Option<String>,
+        suggestion: Option<String>,
+    }
+
+    let raw: RawRisk = serde_json::from_str(json_str)
+        .map_err(|e| AppError::AiProvider(format!("Failed to parse risk JSON: {e}\nRaw: {json_str}")))?;
+
+    let score = raw.overall_score.unwrap_or(50);
+    let level = raw.risk_level.unwrap_or_else(|| {
+        if score <= 33 { "low" } else if score <= 66 { "medium" } else { "high" }.to_string()
+    });
+
+    Ok(RiskAssessmentResponse {
+        overall_score: score,
+        risk_level: level,
+        flags: raw
+            .flags
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|f| f.description.is_some())
+            .map(|f| RiskFlag {
+                category: f.category.unwrap_or_else(|| "other".to_string()),
+                severity: f.severity.unwrap_or_else(|| "medium".to_string()),
+                description: f.description.unwrap_or_default(),
+                clause_reference: f.clause_reference,
+                suggestion: f.suggestion,
+            })
+            .collect(),
+        summary: raw.summary.unwrap_or_else(|| "Risk assessment completed.".to_string()),
+    })
+}
+
+pub fn parse_comparison_response(json_str: &str) -> AppResult<ComparisonResponse> {
+    #[derive(Deserialize)]
+    struct RawComparison {
+        classifications: Option<Vec<RawClassification>>,
+        summary: Option<String>,
+    }
+
+    #[derive(Deserialize)]
+    struct RawClassification {
+        change_id: Option<String>,
+        category: Option<String>,
+        diff_type: Option<String>,
+        position: Option<String>,
+        significance: Option<String>,
+        explanation: Option<String>,
+    }
+
+    let raw: RawComparison = serde_json::from_str(json_str)
+        .map_err(|e| AppError::AiProvider(format!("Failed to parse comparison JSON: {e}\nRaw: {json_str}")))?;
+
+    Ok(ComparisonResponse {
+        classifications: raw
+            .classifications
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|c| {
+                Some(ChangeClassification {
+                    change_id: c.change_id?.trim().trim_matches(['[', ']']).to_string(),
+                    category: c.category.unwrap_or_else(|| "other".to_string()),
+                    diff_type: c.diff_type.unwrap_or_else(|| "substantive".to_string()),
+                    position: c.position,
+                    significance: c.significance.unwrap_or_else(|| "medium".to_string()),
+                    explanation: c.explanation.unwrap_or_default(),
+                })
+            })
+            .collect(),
+        summary: raw.summary.unwrap_or_else(|| "Comparison completed.".to_string()),
+    })
+}
+
+/// Template reviews share the comparison format; a deviation the model did
+/// not label is treated as open to negotiation.
+pub fn parse_template_review_response(json_str: &str) -> AppResult<ComparisonResponse> {
+    let mut response = parse_comparison_response(json_str)?;
+    for classification in &mut response.classifications {
+        if !matches!(classification.position.as_deref(), Some("acceptable" | "negotiable" | "unacceptable")) {
+            classification.position = Some("negotiable".to_string());
+        }
+    }
+    Ok(response)
+}
+
+pub fn parse_answer_response(json_str: &str) -> AppResult<AnswerResponse> {
+    #[derive(Deserialize)]
+    struct RawAnswer {
+        answer: Option<String>,
+        answered: Option<bool>,
+        citations: Option<Vec<RawCitation>>,
+    }
+
+    #[derive(Deserialize)]
+    struct RawCitation {
+        excerpt: Option<String>,
+        quote: Option<String>,
+    }
+
+    let raw: RawAnswer = serde_json::from_str(json_str)
+        .map_err(|e| AppError::AiProvider(format!("Failed to parse answer JSON: {e}\nRaw: {json_str}")))?;
+    let answer = raw
+        .answer
+        .filter(|a| !a.trim().is_empty())
+        .ok_or_else(|| AppError::AiProvider(format!("Answer JSON has no answer\nRaw: {json_str}")))?;
+
+    Ok(AnswerResponse {
+        answer,
+        answered: raw.answered.unwrap_or(true),
+        citations: raw
+            .citations
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|c| match (c.excerpt, c.quote) {
+                (Some(excerpt), Some(quote)) if !quote.trim().is_empty() => Some(AnswerCitation { excerpt, quote }),
+                _ => None,
+            })
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_extraction_response() {
+        let json = r#"{
+            "parties": ["Acme Corp", "Globex Inc"],
+            "effective_date": "2024-01-01",
+            "termination_date": null,
+            "clauses": [
+                {
+                    "clause_type": "confidentiality",
+                    "title": "Confidentiality",
+                    "text": "All information shared shall be kept confidential.",
+                    "section_reference": "Section 3",
+                    "importance": "high"
+                },
+                {
+                    "clause_type": "governing_law",
+                    "title": "Governing Law",
+                    "text": "This agreement shall be governed by the laws of California.",
+                    "section_reference": "Section 8",
+                    "importance": "medium"
+                }
+            ],
+            "contract_type": "nda"
+        }"#;
+
+        let result = parse_extraction_response(json).unwrap();
+        assert_eq!(result.parties.len(), 2);
+        assert_eq!(result.parties[0], "Acme Corp");
+        assert_eq!(result.clauses.len(), 2);
+        assert_eq!(result.clauses[0].clause_type, "confidentiality");
+        assert_eq!(result.effective_date, Some("2024-01-01".to_string()));
+    }
+
+    #[test]
+    fn test_parse_extraction_with_missing_fields() {
+        let json = r#"{
+            "parties": ["A"],
+            "clauses": [
+                {"clause_type": "test", "text": "some text"},
+                {"clause_type": null, "text": null}
+            ]
+        }"#;
+
+        let result = parse_extraction_response(json).unwrap();
+        assert_eq!(result.parties.len(), 1);
+        assert_eq!(result.clauses.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_risk_response() {
+        let json = r#"{
+            "overall_score": 72,
+            "risk_level": "high",
+            "flags": [
+                {
+                    "category": "indemnification",
+                    "severity": "high",
+                    "description": "No indemnification cap specified",
+                    "clause_reference": "Section 5",
+                    "suggestion": "Add a reasonable cap"
+                }
+            ],
+            "summary": "This contract has significant risk."
+        }"#;
+
+        let result = parse_risk_response(json).unwrap();
+        assert_eq!(result.overall_score, 72);
+        assert_eq!(result.risk_level, "high");
+        assert_eq!(result.flags.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_risk_defaults() {
+        let json = r#"{"flags": []}"#;
+        let result = parse_risk_response(json).unwrap();
+        assert_eq!(result.overall_score, 50);
+        assert_eq!(result.risk_level, "medium");
+    }
+
+    #[test]
+    fn test_parse_comparison_response() {
+        let json = r#"{
+            "classifications": [
+                {
+                    "change_id": "C1",
+                    "category": "payment",
+                    "diff_type": "substantive",
+                    "significance": "high",
+                    "explanation": "Payment terms changed from net-30 to net-60"
+                },
+                { "change_id": "[C2]", "explanation": "Typo fixed" },
+                { "category": "other" }
+            ],
+            "summary": "One significant change in payment terms."
+        }"#;
+
+        let result = parse_comparison_response(json).unwrap();
+        assert_eq!(result.classifications.len(), 2);
+        assert_eq!(result.classifications[0].diff_type, "substantive");
+        assert_eq!(result.classifications[1].change_id, "C2");
+        assert_eq!(result.classifications[1].significance, "medium");
+    }
+
+    #[test]
+    fn test_parse_template_review_response() {
+        let json = r#"{
+            "classifications": [
+                { "change_id": "C1", "position": "unacceptable", "explanation": "Liability cap removed" },
+                { "change_id": "C2", "position": "fine" },
+                { "change_id": "C3" }
+            ],
+            "summary": "The draft removes our liability cap."
+        }"#;
+
+        let result = parse_template_review_response(json).unwrap();
+        let positions: Vec<_> = result.classifications.iter().map(|c| c.position.as_deref()).collect();
+        assert_eq!(positions, vec![Some("unacceptable"), Some("negotiable"), Some("negotiable")]);
+        assert_eq!(parse_comparison_response(json).unwrap().classifications[2].position, None);
+    }
+
+    #[test]
+    fn test_parse_answer_response() {
+        let json = r#"{
+            "answer": "Yes, with prior written consent [E1].",
+            "answered": true,
+            "citations": [
+                {"excerpt": "E1", "quote": "may subcontract with prior written consent"},
+                {"excerpt": "E2", "quote": ""}
+            ]
+        }"#;
+        let result = parse_answer_response(json).unwrap();
+        assert!(result.answered);
+        assert_eq!(result.citations.len(), 1);
+        assert_eq!(result.citations[0].excerpt, "E1");
+
+        assert!(parse_answer_response(r#"{"answer": " ", "citations": []}"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_invalid_json() {
+        let result = parse_extraction_response("not json");
+        assert!(result.is_err());
+    }
+}
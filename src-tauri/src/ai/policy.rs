@@ -0,0 +1,131 @@
+use std::net::IpAddr;
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+use crate::db::documents;
+use crate::error::{AppError, AppResult};
+use super::provider::AiProvider;
+
+/// Where document text may be sent. `LocalOnly` applies when the workspace
+/// `local_only` setting is on or any document involved is flagged local-only.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataPolicy {
+    Standard,
+    LocalOnly,
+}
+
+impl DataPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DataPolicy::Standard => "standard",
+            DataPolicy::LocalOnly => "local_only",
+        }
+    }
+}
+
+pub fn workspace_local_only(conn: &Connection) -> AppResult<bool> {
+    config::get_bool(conn, "local_only")
+}
+
+/// The policy for work on the given documents; the workspace policy when empty.
+pub fn resolve(conn: &Connection, document_ids: &[&str]) -> AppResult<DataPolicy> {
+    if workspace_local_only(conn)? {
+        return Ok(DataPolicy::LocalOnly);
+    }
+    for id in document_ids {
+        if documents::get_by_id(conn, id)?.local_only {
+            return Ok(DataPolicy::LocalOnly);
+        }
+    }
+    Ok(DataPolicy::Standard)
+}
+
+/// Whether a URL points at this machine: `localhost` or a loopback address.
+pub fn is_loopback(url: &str) -> bool {
+    let Ok(parsed) = reqwest::Url::parse(url) else {
+        return false;
+    };
+    match parsed.host_str() {
+        Some("localhost") => true,
+        Some(host) => host
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse::<IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback()),
+        None => false,
+    }
+}
+
+/// Fails with [`AppError::PolicyViolation`] if the provider would send text
+/// anywhere the policy does not allow.
+pub fn enforce(policy: DataPolicy, provider: &dyn AiProvider) -> AppResult<()> {
+    if policy == DataPolicy::Standard {
+        return Ok(());
+    }
+    match provider.endpoint() {
+        Some(endpoint) if !is_loopback(endpoint) => Err(AppError::PolicyViolation(format!(
+            "local-only documents cannot be sent to the {} provider at {endpoint}; \
+             switch to a local Ollama server on this machine",
+            provider.name()
+        ))),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::mock::MockProvider;
+    use crate::ai::{ClaudeProvider, OllamaProvider};
+    use crate::db::{settings, Database};
+
+    #[test]
+    fn test_is_loopback() {
+        assert!(is_loopback("http://localhost:11434"));
+        assert!(is_loopback("http://127.0.0.1:11434"));
+        assert!(is_loopback("http://[::1]:11434"));
+        assert!(!is_loopback("http://192.168.1.20:11434"));
+        assert!(!is_loopback("http://localhost.example.com"));
+        assert!(!is_loopback("https://api.anthropic.com/v1/messages"));
+        assert!(!is_loopback("not a url"));
+    }
+
+    #[test]
+    fn test_enforce_local_only() {
+        let claude = ClaudeProvider::new("key".into(), None);
+        let remote_ollama = OllamaProvider::new("http://gpu-box.lan:11434".into(), "llama3".into());
+        let local_ollama = OllamaProvider::new("http://localhost:11434".into(), "llama3".into());
+
+        assert!(enforce(DataPolicy::Standard, &claude).is_ok());
+        let err = enforce(DataPolicy::LocalOnly, &claude).unwrap_err();
+        assert!(matches!(err, AppError::PolicyViolation(_)));
+        assert!(enforce(DataPolicy::LocalOnly, &remote_ollama).is_err());
+        assert!(enforce(DataPolicy::LocalOnly, &local_ollama).is_ok());
+        assert!(enforce(DataPolicy::LocalOnly, &MockProvider::new()).is_ok());
+    }
+
+    #[test]
+    fn test_resolve_combines_workspace_and_document_flags() {
+        let db = Database::in_memory().unwrap();
+        let conn = db.writer();
+        let doc = documents::insert(&conn, &documents::CreateDocument {
+            filename: "nda.pdf".into(),
+            original_path: "/tmp/nda.pdf".into(),
+            stored_path: "/data/nda.pdf".into(),
+            file_hash: "hash".into(),
+            file_size: 10,
+            contract_type: "nda".into(),
+        }).unwrap();
+
+        assert_eq!(resolve(&conn, &[&doc.id]).unwrap(), DataPolicy::Standard);
+        documents::set_local_only(&conn, &doc.id, true).unwrap();
+        assert_eq!(resolve(&conn, &[&doc.id]).unwrap(), DataPolicy::LocalOnly);
+        assert_eq!(resolve(&conn, &[]).unwrap(), DataPolicy::Standard);
+
+        settings::set(&conn, "local_only", "true").unwrap();
+        assert_eq!(resolve(&conn, &[]).unwrap(), DataPolicy::LocalOnly);
+    }
+}
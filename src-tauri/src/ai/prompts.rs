@@ -1,31 +1,325 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
 use crate::ai::types::ContractType;
+use crate::error::{AppError, AppResult};
+
+/// The prompts the review pipeline sends to a provider. Each kind can be
+/// overridden by a versioned template stored in the database.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum PromptKind {
+    Extraction,
+    Risk,
+    Comparison,
+    Summary,
+    Question,
+    TemplateReview,
+}
+
+impl std::str::FromStr for PromptKind {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "extraction" => Ok(Self::Extraction),
+            "risk" => Ok(Self::Risk),
+            "comparison" => Ok(Self::Comparison),
+            "summary" => Ok(Self::Summary),
+            "question" => Ok(Self::Question),
+            "template_review" => Ok(Self::TemplateReview),
+            other => Err(AppError::Validation(format!("Unknown prompt kind: {other}"))),
+        }
+    }
+}
+
+impl PromptKind {
+    pub const ALL: [PromptKind; 6] =
+        [Self::Extraction, Self::Risk, Self::Comparison, Self::Summary, Self::Question, Self::TemplateReview];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Extraction => "extraction",
+            Self::Risk => "risk",
+            Self::Comparison => "comparison",
+            Self::Summary => "summary",
+            Self::Question => "question",
+            Self::TemplateReview => "template_review",
+        }
+    }
+
+    /// Variables that may appear as `{{name}}` in this kind's templates.
+    pub fn variables(&self) -> &'static [&'static str] {
+        match self {
+            Self::Extraction => &["contract_type", "schema", "document_text"],
+            Self::Risk => &["contract_type", "schema", "extraction_json"],
+            Self::Comparison => &["contract_type", "schema", "changes"],
+            Self::Summary => &["extraction_json", "risk_json"],
+            Self::Question => &["schema", "excerpts", "history", "question"],
+            Self::TemplateReview => &["contract_type", "schema", "changes"],
+        }
+    }
+
+    /// The template shipped with the app, used until a lawyer saves a revision.
+    pub fn builtin(&self) -> PromptTemplate {
+        let (system, user) = match self {
+            Self::Extraction => (EXTRACTION_SYSTEM_TEMPLATE, EXTRACTION_USER_TEMPLATE),
+            Self::Risk => (RISK_SYSTEM_TEMPLATE, RISK_USER_TEMPLATE),
+            Self::Comparison => (COMPARISON_SYSTEM_TEMPLATE, COMPARISON_USER_TEMPLATE),
+            Self::Summary => (SUMMARY_SYSTEM_TEMPLATE, SUMMARY_USER_TEMPLATE),
+            Self::Question => (QUESTION_SYSTEM_TEMPLATE, QUESTION_USER_TEMPLATE),
+            Self::TemplateReview => (TEMPLATE_REVIEW_SYSTEM_TEMPLATE, TEMPLATE_REVIEW_USER_TEMPLATE),
+        };
+        PromptTemplate {
+            kind: *self,
+            version: BUILTIN_VERSION,
+            system: system.to_string(),
+            user: user.to_string(),
+        }
+    }
+}
+
+/// Version number reported for the built-in templates.
+pub const BUILTIN_VERSION: i64 = 0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptTemplate {
+    pub kind: PromptKind,
+    pub version: i64,
+    pub system: String,
+    pub user: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderedPrompt {
+    pub system: String,
+    pub user: String,
+}
+
+impl PromptTemplate {
+    pub fn render(&self, vars: &HashMap<&str, &str>) -> RenderedPrompt {
+        RenderedPrompt {
+            system: render_template(&self.system, vars),
+            user: render_template(&self.user, vars),
+        }
+    }
+}
+
+/// The active template for every prompt kind, resolved once per provider.
+#[derive(Debug, Clone)]
+pub struct PromptSet {
+    pub extraction: PromptTemplate,
+    pub risk: PromptTemplate,
+    pub comparison: PromptTemplate,
+    pub summary: PromptTemplate,
+    pub question: PromptTemplate,
+    pub template_review: PromptTemplate,
+}
+
+impl Default for PromptSet {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl PromptSet {
+    pub fn builtin() -> Self {
+        Self {
+            extraction: PromptKind::Extraction.builtin(),
+            risk: PromptKind::Risk.builtin(),
+            comparison: PromptKind::Comparison.builtin(),
+            summary: PromptKind::Summary.builtin(),
+            question: PromptKind::Question.builtin(),
+            template_review: PromptKind::TemplateReview.builtin(),
+        }
+    }
+
+    pub fn get(&self, kind: PromptKind) -> &PromptTemplate {
+        match kind {
+            PromptKind::Extraction => &self.extraction,
+            PromptKind::Risk => &self.risk,
+            PromptKind::Comparison => &self.comparison,
+            PromptKind::Summary => &self.summary,
+            PromptKind::Question => &self.question,
+            PromptKind::TemplateReview => &self.template_review,
+        }
+    }
+
+    pub fn set(&mut self, template: PromptTemplate) {
+        match template.kind {
+            PromptKind::Extraction => self.extraction = template,
+            PromptKind::Risk => self.risk = template,
+            PromptKind::Comparison => self.comparison = template,
+            PromptKind::Summary => self.summary = template,
+            PromptKind::Question => self.question = template,
+            PromptKind::TemplateReview => self.template_review = template,
+        }
+    }
+
+    pub fn extraction(&self, text: &str, contract_type: &ContractType) -> RenderedPrompt {
+        self.extraction.render(&HashMap::from([
+            ("contract_type", contract_type.display_name()),
+            ("schema", extraction_schema(contract_type)),
+            ("document_text", text),
+        ]))
+    }
+
+    pub fn risk(&self, extraction_json: &str, contract_type: &ContractType) -> RenderedPrompt {
+        self.risk.render(&HashMap::from([
+            ("contract_type", contract_type.display_name()),
+            ("schema", RISK_ASSESSMENT_SCHEMA),
+            ("extraction_json", extraction_json),
+        ]))
+    }
+
+    /// `changes` lists the redline's changes by id, for the model to classify.
+    pub fn comparison(&self, changes: &str, contract_type: &ContractType) -> RenderedPrompt {
+        self.comparison.render(&HashMap::from([
+            ("contract_type", contract_type.display_name()),
+            ("schema", COMPARISON_SCHEMA),
+            ("changes", changes),
+        ]))
+    }
+
+    pub fn summary(&self, extraction_json: &str, risk_json: &str) -> RenderedPrompt {
+        self.summary.render(&HashMap::from([
+            ("extraction_json", extraction_json),
+            ("risk_json", risk_json),
+        ]))
+    }
+
+    pub fn question(&self, question: &str, excerpts: &str, history: &str) -> RenderedPrompt {
+        self.question.render(&HashMap::from([
+            ("schema", ANSWER_SCHEMA),
+            ("excerpts", excerpts),
+            ("history", history),
+            ("question", question),
+        ]))
+    }
+
+    pub fn template_review(&self, changes: &str, contract_type: &ContractType) -> RenderedPrompt {
+        self.template_review.render(&HashMap::from([
+            ("contract_type", contract_type.display_name()),
+            ("schema", TEMPLATE_REVIEW_SCHEMA),
+            ("changes", changes),
+        ]))
+    }
+}
+
+/// Substitutes `{{name}}` placeholders in a single pass, so variable values
+/// (e.g. document text) are never themselves scanned for placeholders.
+/// Unknown placeholders are left as-is.
+pub fn render_template(template: &str, vars: &HashMap<&str, &str>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        match after.find("}}") {
+            Some(end) => {
+                let name = after[..end].trim();
+                match vars.get(name) {
+                    Some(value) => out.push_str(value),
+                    None => out.push_str(&rest[start..start + 2 + end + 2]),
+                }
+                rest = &after[end + 2..];
+            }
+            None => {
+                out.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Names of all `{{name}}` placeholders in a template, in order of appearance.
+pub fn template_variables(template: &str) -> AppResult<Vec<String>> {
+    let mut names = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let end = after.find("}}").ok_or_else(|| {
+            AppError::Validation("Unterminated '{{' placeholder in prompt template".to_string())
+        })?;
+        names.push(after[..end].trim().to_string());
+        rest = &after[end + 2..];
+    }
+    Ok(names)
+}
+
+/// Rejects templates that reference variables the prompt kind does not supply.
+pub fn validate_template(kind: PromptKind, system: &str, user: &str) -> AppResult<()> {
+    if user.trim().is_empty() {
+        return Err(AppError::Validation("Prompt user template cannot be empty".to_string()));
+    }
+    let allowed = kind.variables();
+    for name in template_variables(system)?.into_iter().chain(template_variables(user)?) {
+        if !allowed.contains(&name.as_str()) {
+            return Err(AppError::Validation(format!(
+                "Unknown variable '{{{{{name}}}}}' in {} prompt. Available: {}",
+                kind.as_str(),
+                allowed.join(", ")
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffOp {
+    Equal,
+    Insert,
+    Delete,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffLine {
+    pub op: DiffOp,
+    pub text: String,
+}
+
+/// Line-level LCS diff between two prompt revisions.
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+
+    // lcs[i][j] = length of the LCS of a[i..] and b[j..]
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
 
-pub fn extraction_system_prompt(contract_type: &ContractType) -> String {
-    format!(
-        "You are a legal document analysis expert specializing in {}. \
-         Extract key clauses and terms from the provided contract text. \
-         You MUST respond with valid JSON only — no markdown, no explanations, no preamble.",
-        contract_type.display_name()
-    )
-}
-
-pub fn extraction_user_prompt(text: &str, contract_type: &ContractType) -> String {
-    let schema = extraction_schema(contract_type);
-    format!(
-        "Analyze the following {} and extract all key clauses.\n\n\
-         RULES:\n\
-         1. Quote exact text from the document — do not paraphrase\n\
-         2. Use null for any clause or field not found in the document\n\
-         3. Respond with ONLY the JSON object below — no other text\n\n\
-         JSON Schema:\n{}\n\n\
-         DOCUMENT TEXT:\n---\n{}\n---",
-        contract_type.display_name(),
-        schema,
-        text
-    )
-}
-
-fn extraction_schema(contract_type: &ContractType) -> &'static str {
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            lines.push(DiffLine { op: DiffOp::Equal, text: a[i].to_string() });
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            lines.push(DiffLine { op: DiffOp::Delete, text: a[i].to_string() });
+            i += 1;
+        } else {
+            lines.push(DiffLine { op: DiffOp::Insert, text: b[j].to_string() });
+            j += 1;
+        }
+    }
+    lines.extend(a[i..].iter().map(|l| DiffLine { op: DiffOp::Delete, text: l.to_string() }));
+    lines.extend(b[j..].iter().map(|l| DiffLine { op: DiffOp::Insert, text: l.to_string() }));
+    lines
+}
+
+pub(crate) fn extraction_schema(contract_type: &ContractType) -> &'static str {
     match contract_type {
         ContractType::Nda => NDA_EXTRACTION_SCHEMA,
         ContractType::ServiceAgreement => SERVICE_AGREEMENT_EXTRACTION_SCHEMA,
@@ -33,70 +327,131 @@ fn extraction_schema(contract_type: &ContractType) -> &'static str {
     }
 }
 
-pub fn risk_system_prompt() -> &'static str {
-    "You are a legal risk assessment expert. Analyze the extracted clauses and identify potential risks. \
-     You MUST respond with valid JSON only — no markdown, no explanations, no preamble."
-}
-
-pub fn risk_user_prompt(extraction_json: &str, contract_type: &ContractType) -> String {
-    format!(
-        "Analyze the following extracted clauses from a {} and provide a risk assessment.\n\n\
-         RULES:\n\
-         1. Score overall risk 0-100 (0=no risk, 100=extreme risk)\n\
-         2. Set risk_level to \"low\" (0-33), \"medium\" (34-66), or \"high\" (67-100)\n\
-         3. Flag specific issues with severity, description, and fix suggestions\n\
-         4. Common risks: missing indemnification cap, one-sided termination, auto-renewal traps, \
-            broad non-compete, unlimited liability, missing governing law\n\
-         5. Respond with ONLY the JSON object below — no other text\n\n\
-         JSON Schema:\n{}\n\n\
-         EXTRACTED CLAUSES:\n---\n{}\n---",
-        contract_type.display_name(),
-        RISK_ASSESSMENT_SCHEMA,
-        extraction_json
-    )
-}
-
-pub fn comparison_system_prompt() -> &'static str {
-    "You are a legal document comparison expert. Compare two contract versions and categorize differences. \
-     You MUST respond with valid JSON only — no markdown, no explanations, no preamble."
-}
-
-pub fn comparison_user_prompt(text_a: &str, text_b: &str, contract_type: &ContractType) -> String {
-    format!(
-        "Compare these two versions of a {} and identify all differences.\n\n\
-         RULES:\n\
-         1. Categorize each difference as \"substantive\" or \"formatting\"\n\
-         2. Rate significance as \"high\", \"medium\", or \"low\"\n\
-         3. Quote exact text from each document\n\
-         4. Respond with ONLY the JSON object below — no other text\n\n\
-         JSON Schema:\n{}\n\n\
-         DOCUMENT A:\n---\n{}\n---\n\n\
-         DOCUMENT B:\n---\n{}\n---",
-        contract_type.display_name(),
-        COMPARISON_SCHEMA,
-        text_a,
-        text_b
-    )
-}
-
-pub fn summary_system_prompt() -> &'static str {
-    "You are a legal document summarizer. Write a concise, client-ready executive summary. \
-     Respond with plain text only — no JSON, no markdown headers."
-}
-
-pub fn summary_user_prompt(extraction_json: &str, risk_json: &str) -> String {
-    format!(
-        "Write a 2-3 paragraph executive summary of this contract review for a client.\n\n\
-         Include:\n\
-         1. Key parties and terms\n\
-         2. Notable clauses and their implications\n\
-         3. Risk highlights and recommended actions\n\n\
-         Keep it professional, concise, and actionable.\n\n\
-         EXTRACTED CLAUSES:\n{}\n\n\
-         RISK ASSESSMENT:\n{}",
-        extraction_json, risk_json
-    )
-}
+const EXTRACTION_SYSTEM_TEMPLATE: &str = "You are a legal document analysis expert specializing in {{contract_type}}. \
+Extract key clauses and terms from the provided contract text. \
+You MUST respond with valid JSON only — no markdown, no explanations, no preamble.";
+
+const EXTRACTION_USER_TEMPLATE: &str = "Analyze the following {{contract_type}} and extract all key clauses.
+
+RULES:
+1. Quote exact text from the document — do not paraphrase
+2. Use null for any clause or field not found in the document
+3. Respond with ONLY the JSON object below — no other text
+
+JSON Schema:
+{{schema}}
+
+DOCUMENT TEXT:
+---
+{{document_text}}
+---";
+
+const RISK_SYSTEM_TEMPLATE: &str = "You are a legal risk assessment expert. Analyze the extracted clauses and identify potential risks. \
+You MUST respond with valid JSON only — no markdown, no explanations, no preamble.";
+
+const RISK_USER_TEMPLATE: &str = "Analyze the following extracted clauses from a {{contract_type}} and provide a risk assessment.
+
+RULES:
+1. Score overall risk 0-100 (0=no risk, 100=extreme risk)
+2. Set risk_level to \"low\" (0-33), \"medium\" (34-66), or \"high\" (67-100)
+3. Flag specific issues with severity, description, and fix suggestions
+4. Common risks: missing indemnification cap, one-sided termination, auto-renewal traps, broad non-compete, unlimited liability, missing governing law
+5. Respond with ONLY the JSON object below — no other text
+
+JSON Schema:
+{{schema}}
+
+EXTRACTED CLAUSES:
+---
+{{extraction_json}}
+---";
+
+const COMPARISON_SYSTEM_TEMPLATE: &str = "You are a legal document comparison expert. Classify and explain the changes between two contract versions. \
+You MUST respond with valid JSON only — no markdown, no explanations, no preamble.";
+
+const COMPARISON_USER_TEMPLATE: &str = "An exact redline of two versions of a {{contract_type}} found the changes listed below. \
+Classify each one.
+
+RULES:
+1. Return one classification per change, using its id (e.g. \"C1\"); do not add changes that are not listed
+2. Categorize each change as \"substantive\" or \"formatting\"; a single changed word such as \"shall\" to \"may\" can be substantive
+3. Rate significance as \"high\", \"medium\", or \"low\"
+4. Explain in one or two sentences what changed and why it matters
+5. Respond with ONLY the JSON object below — no other text
+
+JSON Schema:
+{{schema}}
+
+CHANGES (document A -> document B):
+---
+{{changes}}
+---";
+
+const TEMPLATE_REVIEW_SYSTEM_TEMPLATE: &str = "You are a legal negotiation expert reviewing a counterparty's draft against your firm's standard template. \
+You MUST respond with valid JSON only — no markdown, no explanations, no preamble.";
+
+const TEMPLATE_REVIEW_USER_TEMPLATE: &str = "An exact redline of our standard {{contract_type}} template against a counterparty's draft \
+found the deviations listed below. Assess each one from our side of the negotiation.
+
+RULES:
+1. Return one assessment per deviation, using its id (e.g. \"C1\"); do not add deviations that are not listed
+2. Label each deviation \"acceptable\" (no effect on our position), \"negotiable\" (worse for us but could be conceded) \
+or \"unacceptable\" (must be pushed back)
+3. Categorize each deviation as \"substantive\" or \"formatting\" and rate significance as \"high\", \"medium\", or \"low\"
+4. Explain in one or two sentences how the draft departs from our language and what to ask for
+5. Respond with ONLY the JSON object below — no other text
+
+JSON Schema:
+{{schema}}
+
+DEVIATIONS (our template -> counterparty draft):
+---
+{{changes}}
+---";
+
+const SUMMARY_SYSTEM_TEMPLATE: &str = "You are a legal document summarizer. Write a concise, client-ready executive summary. \
+Respond with plain text only — no JSON, no markdown headers.";
+
+const SUMMARY_USER_TEMPLATE: &str = "Write a 2-3 paragraph executive summary of this contract review for a client.
+
+Include:
+1. Key parties and terms
+2. Notable clauses and their implications
+3. Risk highlights and recommended actions
+
+Keep it professional, concise, and actionable.
+
+EXTRACTED CLAUSES:
+{{extraction_json}}
+
+RISK ASSESSMENT:
+{{risk_json}}";
+
+const QUESTION_SYSTEM_TEMPLATE: &str = "You are a legal assistant answering questions about contracts. \
+Answer only from the numbered excerpts provided; if they do not contain the answer, say so. \
+You MUST respond with valid JSON only — no markdown, no explanations, no preamble.";
+
+const QUESTION_USER_TEMPLATE: &str = "Answer the question using only the excerpts below.
+
+RULES:
+1. Be direct and concise; refer to excerpts by their labels, e.g. [E2]
+2. Support every statement with a citation: the excerpt label and a short quote copied word for word from it
+3. If the excerpts do not answer the question, set answered to false and say what is missing
+4. Respond with ONLY the JSON object below — no other text
+
+JSON Schema:
+{{schema}}
+
+EXCERPTS:
+---
+{{excerpts}}
+---
+
+EARLIER IN THIS CONVERSATION:
+{{history}}
+
+QUESTION:
+{{question}}";
 
 const NDA_EXTRACTION_SCHEMA: &str = r#"{
   "parties": ["Party A name", "Party B name"],
@@ -161,7 +516,7 @@ const LEASE_EXTRACTION_SCHEMA: &str = r#"{
   "contract_type": "lease"
 }"#;
 
-const RISK_ASSESSMENT_SCHEMA: &str = r#"{
+pub(crate) const RISK_ASSESSMENT_SCHEMA: &str = r#"{
   "overall_score": 45,
   "risk_level": "medium",
   "flags": [
@@ -176,16 +531,84 @@ const RISK_ASSESSMENT_SCHEMA: &str = r#"{
   "summary": "2-3 sentence risk overview"
 }"#;
 
-const COMPARISON_SCHEMA: &str = r#"{
-  "differences": [
+pub(crate) const COMPARISON_SCHEMA: &str = r#"{
+  "classifications": [
     {
+      "change_id": "C1",
       "category": "parties|payment|term|liability|indemnification|confidentiality|termination|other",
       "diff_type": "substantive|formatting",
-      "description": "What changed and why it matters",
-      "text_a": "Exact text from document A or null",
-      "text_b": "Exact text from document B or null",
-      "significance": "high|medium|low"
+      "significance": "high|medium|low",
+      "explanation": "What changed and why it matters"
     }
   ],
   "summary": "Overall comparison summary"
 }"#;
+
+pub(crate) const TEMPLATE_REVIEW_SCHEMA: &str = r#"{
+  "classifications": [
+    {
+      "change_id": "C1",
+      "category": "parties|payment|term|liability|indemnification|confidentiality|termination|other",
+      "diff_type": "substantive|formatting",
+      "position": "acceptable|negotiable|unacceptable",
+      "significance": "high|medium|low",
+      "explanation": "How the draft departs from our language and what to ask for"
+    }
+  ],
+  "summary": "Overall assessment of the draft against our template"
+}"#;
+
+pub(crate) const ANSWER_SCHEMA: &str = r#"{
+  "answer": "Direct answer that refers to excerpts like [E1]",
+  "answered": true,
+  "citations": [
+    {
+      "excerpt": "E1",
+      "quote": "Exact words copied from the excerpt"
+    }
+  ]
+}"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_extraction_prompt_renders_all_variables() {
+        let prompt = PromptSet::builtin().extraction("The parties agree...", &ContractType::Nda);
+        assert!(prompt.system.contains("Non-Disclosure Agreement"));
+        assert!(prompt.user.contains("The parties agree..."));
+        assert!(prompt.user.contains("definition_of_confidential_info"));
+        assert!(!prompt.user.contains("{{"));
+    }
+
+    #[test]
+    fn test_render_does_not_expand_placeholders_inside_values() {
+        let vars = HashMap::from([("document_text", "literal {{schema}} in contract")]);
+        let out = render_template("Text: {{document_text}} / {{ schema }}", &vars);
+        assert_eq!(out, "Text: literal {{schema}} in contract / {{ schema }}");
+    }
+
+    #[test]
+    fn test_validate_rejects_unknown_variable() {
+        assert!(validate_template(PromptKind::Summary, "", "{{extraction_json}} {{risk_json}}").is_ok());
+        let err = validate_template(PromptKind::Summary, "", "{{document_text}}").unwrap_err();
+        assert!(err.to_string().contains("document_text"));
+        assert!(validate_template(PromptKind::Risk, "", "{{schema").is_err());
+    }
+
+    #[test]
+    fn test_diff_lines() {
+        let diff = diff_lines("a\nb\nc", "a\nx\nc");
+        let ops: Vec<_> = diff.iter().map(|l| (l.op, l.text.as_str())).collect();
+        assert_eq!(
+            ops,
+            vec![
+                (DiffOp::Equal, "a"),
+                (DiffOp::Delete, "b"),
+                (DiffOp::Insert, "x"),
+                (DiffOp::Equal, "c"),
+            ]
+        );
+    }
+}
@@ -1,34 +1,156 @@
 use async_trait::async_trait;
 
-use crate::error::AppResult;
+use crate::error::{AppError, AppResult};
+use super::parse;
+use super::prompts::{PromptKind, PromptSet, RenderedPrompt};
+use super::redact::Redaction;
+use super::schema::{self, OutputSchema};
 use super::types::*;
 
+/// A single prompt sent to a model. Providers only implement the transport for
+/// this; prompt rendering and response parsing are shared by the task methods.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct CompletionRequest {
+    pub kind: PromptKind,
+    pub system: String,
+    pub user: String,
+    /// The response must be a single JSON object.
+    pub json: bool,
+    /// Schema for the JSON object, enforced natively where the API supports it.
+    pub schema: Option<OutputSchema>,
+    pub max_tokens: i32,
+}
+
+impl CompletionRequest {
+    pub fn json(kind: PromptKind, prompt: RenderedPrompt, max_tokens: i32) -> Self {
+        Self { kind, system: prompt.system, user: prompt.user, json: true, schema: None, max_tokens }
+    }
+
+    pub fn text(kind: PromptKind, prompt: RenderedPrompt, max_tokens: i32) -> Self {
+        Self { kind, system: prompt.system, user: prompt.user, json: false, schema: None, max_tokens }
+    }
+
+    pub fn with_schema(mut self, schema: OutputSchema) -> Self {
+        self.json = true;
+        self.schema = Some(schema);
+        self
+    }
+}
+
 #[async_trait]
 pub trait AiProvider: Send + Sync {
     fn name(&self) -> &str;
 
+    /// Prompt templates this provider renders, including their versions.
+    fn prompts(&self) -> &PromptSet;
+
+    /// The URL prompts are sent to, or `None` when nothing leaves the process.
+    fn endpoint(&self) -> Option<&str> {
+        None
+    }
+
+    /// Checks the provider can serve requests before any work starts, so a
+    /// missing model fails up front instead of partway through a pipeline.
+    async fn preflight(&self) -> AppResult<()> {
+        Ok(())
+    }
+
+    /// Sends one prompt and returns the raw model output. JSON requests must
+    /// return the bare JSON object, with any wrapping already stripped.
+    async fn complete(&self, request: &CompletionRequest) -> AppResult<String>;
+
+    /// Drains the record of what was redacted from prompts since the last call.
+    /// Empty for providers that send text unchanged.
+    fn take_redactions(&self) -> Vec<Redaction> {
+        Vec::new()
+    }
+
+    /// The model [`embed`](Self::embed) uses, or `None` when the provider
+    /// cannot embed text. Vectors from different models are not comparable.
+    fn embedding_model(&self) -> Option<String> {
+        None
+    }
+
+    /// One vector per input text, in order.
+    async fn embed(&self, _texts: &[String]) -> AppResult<Vec<Vec<f32>>> {
+        Err(AppError::AiProvider(format!("The {} provider does not support embeddings", self.name())))
+    }
+
     async fn extract_clauses(
         &self,
         text: &str,
         contract_type: &ContractType,
-    ) -> AppResult<ExtractionResponse>;
+    ) -> AppResult<ExtractionResponse> {
+        let prompt = self.prompts().extraction(text, contract_type);
+        let response = self
+            .complete(
+                &CompletionRequest::json(PromptKind::Extraction, prompt, 4096)
+                    .with_schema(schema::extraction(contract_type)),
+            )
+            .await?;
+        parse::parse_extraction_response(&response)
+    }
 
     async fn score_risk(
         &self,
         extraction: &ExtractionResponse,
         contract_type: &ContractType,
-    ) -> AppResult<RiskAssessmentResponse>;
+    ) -> AppResult<RiskAssessmentResponse> {
+        let extraction_json = serde_json::to_string_pretty(extraction)
+            .map_err(|e| AppError::AiProvider(format!("Failed to serialize extraction: {e}")))?;
+        let prompt = self.prompts().risk(&extraction_json, contract_type);
+        let response = self
+            .complete(&CompletionRequest::json(PromptKind::Risk, prompt, 2048).with_schema(schema::risk()))
+            .await?;
+        parse::parse_risk_response(&response)
+    }
 
-    async fn compare_documents(
-        &self,
-        text_a: &str,
-        text_b: &str,
-        contract_type: &ContractType,
-    ) -> AppResult<ComparisonResponse>;
+    /// Classifies and explains the changes a redline found, listed as
+    /// rendered by `analysis::redline::describe_changes`.
+    async fn classify_changes(&self, changes: &str, contract_type: &ContractType) -> AppResult<ComparisonResponse> {
+        let prompt = self.prompts().comparison(changes, contract_type);
+        let response = self
+            .complete(
+                &CompletionRequest::json(PromptKind::Comparison, prompt, 4096)
+                    .with_schema(schema::comparison()),
+            )
+            .await?;
+        parse::parse_comparison_response(&response)
+    }
+
+    /// Labels each deviation of a draft from one of our templates, listed
+    /// like `classify_changes` with the template as document A.
+    async fn review_deviations(&self, changes: &str, contract_type: &ContractType) -> AppResult<ComparisonResponse> {
+        let prompt = self.prompts().template_review(changes, contract_type);
+        let response = self
+            .complete(
+                &CompletionRequest::json(PromptKind::TemplateReview, prompt, 4096)
+                    .with_schema(schema::template_review()),
+            )
+            .await?;
+        parse::parse_template_review_response(&response)
+    }
+
+    /// Answers a question from numbered excerpts, citing the ones it used.
+    async fn answer_question(&self, question: &str, excerpts: &str, history: &str) -> AppResult<AnswerResponse> {
+        let prompt = self.prompts().question(question, excerpts, history);
+        let response = self
+            .complete(&CompletionRequest::json(PromptKind::Question, prompt, 2048).with_schema(schema::answer()))
+            .await?;
+        parse::parse_answer_response(&response)
+    }
 
     async fn generate_summary(
         &self,
         extraction: &ExtractionResponse,
         risk: &RiskAssessmentResponse,
-    ) -> AppResult<String>;
+    ) -> AppResult<String> {
+        let extraction_json = serde_json::to_string_pretty(extraction)
+            .map_err(|e| AppError::AiProvider(format!("Failed to serialize extraction: {e}")))?;
+        let risk_json = serde_json::to_string_pretty(risk)
+            .map_err(|e| AppError::AiProvider(format!("Failed to serialize risk: {e}")))?;
+        let prompt = self.prompts().summary(&extraction_json, &risk_json);
+        self.complete(&CompletionRequest::text(PromptKind::Summary, prompt, 2048))
+            .await
+    }
 }
@@ -0,0 +1,325 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult};
+use super::prompts::{PromptKind, PromptSet};
+use super::provider::{AiProvider, CompletionRequest};
+
+/// Something the redactor replaced before a prompt left the machine.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Redaction {
+    pub prompt_kind: PromptKind,
+    pub entity_type: String,
+    pub placeholder: String,
+    /// The original with most characters masked, so the log can be reviewed
+    /// without becoming a second copy of the sensitive data.
+    pub masked_value: String,
+    pub occurrences: i64,
+}
+
+struct Rule {
+    entity_type: &'static str,
+    pattern: Regex,
+    /// Capture group holding the entity; 0 for the whole match.
+    group: usize,
+}
+
+impl Rule {
+    fn new(entity_type: &'static str, pattern: &str, group: usize) -> Self {
+        Self {
+            entity_type,
+            pattern: Regex::new(pattern).expect("built-in redaction pattern is valid"),
+            group,
+        }
+    }
+}
+
+fn builtin_rules() -> Vec<Rule> {
+    vec![
+        Rule::new("EMAIL", r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}\b", 0),
+        Rule::new("IBAN", r"\b[A-Z]{2}\d{2}(?: ?[A-Z0-9]{4}){3,7}(?: ?[A-Z0-9]{1,4})?\b", 0),
+        Rule::new(
+            "ACCOUNT_NUMBER",
+            r"(?i)\b(?:account|acct|routing|sort code|swift|bic)(?:\s+(?:no\.?|number|#))?\s*[:#]?\s*([A-Z0-9][A-Z0-9-]{5,33}[A-Z0-9])\b",
+            1,
+        ),
+        Rule::new("ID_NUMBER", r"\b\d{3}-\d{2}-\d{4}\b", 0),
+        Rule::new("PHONE", r"(?:\+\d{1,3}[\s.-]?)?(?:\(\d{3}\)\s?|\b\d{3}[\s.-])\d{3}[\s.-]\d{4}\b", 0),
+        Rule::new(
+            "ADDRESS",
+            r"\b\d{1,6}\s+(?:[A-Z][a-z]+\s+){1,4}(?:Street|St|Avenue|Ave|Road|Rd|Boulevard|Blvd|Lane|Ln|Drive|Dr|Court|Ct|Way|Place|Pl)\b\.?(?:,?\s+(?:Suite|Ste|Apt|Unit|Floor)\s*[\w-]+)?",
+            0,
+        ),
+        Rule::new(
+            "PERSON",
+            r"\b(?:Mr|Mrs|Ms|Miss|Dr|Prof)\.?\s+([A-Z][a-z]+(?:\s+[A-Z][a-z]+)?)",
+            1,
+        ),
+        // Signature blocks and notice details: "By: Jane Doe", "Name: John Q. Smith"
+        Rule::new(
+            "PERSON",
+            r"(?m)^[ \t]*(?:By|Name|Signed|Signature|Attn|Attention)[ \t]*:[ \t]*([A-Z][a-z]+(?:[ \t]+[A-Z]\.)?(?:[ \t]+[A-Z][a-z]+){1,2})[ \t]*$",
+            1,
+        ),
+    ]
+}
+
+/// Pseudonymizes prompt text with stable placeholders such as `[PERSON_1]`.
+/// The same original always maps to the same placeholder for the lifetime of
+/// the redactor, so separate prompts in one pipeline stay consistent.
+pub struct Redactor {
+    rules: Vec<Rule>,
+    placeholders: HashMap<String, String>,
+    originals: HashMap<String, (String, String)>,
+    counters: HashMap<&'static str, usize>,
+}
+
+impl Redactor {
+    /// Built-in entity rules plus custom patterns, one regex per line.
+    pub fn new(custom_patterns: &str) -> AppResult<Self> {
+        let mut rules = builtin_rules();
+        for line in custom_patterns.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let pattern = Regex::new(line)
+                .map_err(|e| AppError::Validation(format!("Invalid redaction pattern {line:?}: {e}")))?;
+            rules.push(Rule { entity_type: "CUSTOM", pattern, group: 0 });
+        }
+        Ok(Self {
+            rules,
+            placeholders: HashMap::new(),
+            originals: HashMap::new(),
+            counters: HashMap::new(),
+        })
+    }
+
+    fn placeholder_for(&mut self, entity_type: &'static str, original: &str) -> String {
+        if let Some(existing) = self.placeholders.get(original) {
+            return existing.clone();
+        }
+        let counter = self.counters.entry(entity_type).or_insert(0);
+        *counter += 1;
+        let placeholder = format!("[{entity_type}_{counter}]");
+        self.placeholders.insert(original.to_string(), placeholder.clone());
+        self.originals
+            .insert(placeholder.clone(), (entity_type.to_string(), original.to_string()));
+        placeholder
+    }
+
+    /// Replaces every detected entity, and every earlier-seen original, in `text`.
+    /// Returns the redacted text and the placeholders used with their counts.
+    pub fn redact(&mut self, text: &str) -> (String, HashMap<String, i64>) {
+        let mut found = Vec::new();
+        for rule in &self.rules {
+            for caps in rule.pattern.captures_iter(text) {
+                if let Some(m) = caps.get(rule.group) {
+                    if !m.as_str().trim().is_empty() {
+                        found.push((rule.entity_type, m.as_str().trim().to_string()));
+                    }
+                }
+            }
+        }
+        for (entity_type, original) in found {
+            self.placeholder_for(entity_type, &original);
+        }
+        if self.placeholders.is_empty() {
+            return (text.to_string(), HashMap::new());
+        }
+
+        // One pass over all known originals, longest first, so a name inside
+        // a longer match is never replaced separately
+        let mut originals: Vec<&String> = self.placeholders.keys().collect();
+        originals.sort_by(|a, b| b.len().cmp(&a.len()).then(a.cmp(b)));
+        let alternation = originals.iter().map(|o| regex::escape(o)).collect::<Vec<_>>().join("|");
+        let matcher = Regex::new(&alternation).expect("escaped literals form a valid pattern");
+
+        let mut counts = HashMap::new();
+        let redacted = matcher.replace_all(text, |caps: &Captures| {
+            let placeholder = self.placeholders[&caps[0]].clone();
+            *counts.entry(placeholder.clone()).or_insert(0) += 1;
+            placeholder
+        });
+        (redacted.into_owned(), counts)
+    }
+
+    /// Puts the originals back. With `json` the originals are escaped so they
+    /// can be restored inside JSON string values.
+    pub fn restore(&self, text: &str, json: bool) -> String {
+        let placeholder = Regex::new(r"\[[A-Z_]+_\d+\]").expect("placeholder pattern is valid");
+        placeholder
+            .replace_all(text, |caps: &Captures| match self.originals.get(&caps[0]) {
+                Some((_, original)) if json => {
+                    let quoted = serde_json::to_string(original).unwrap_or_default();
+                    quoted[1..quoted.len() - 1].to_string()
+                }
+                Some((_, original)) => original.clone(),
+                None => caps[0].to_string(),
+            })
+            .into_owned()
+    }
+
+    fn describe(&self, placeholder: &str) -> Option<(String, String)> {
+        self.originals
+            .get(placeholder)
+            .map(|(entity_type, original)| (entity_type.clone(), mask(original)))
+    }
+}
+
+fn mask(original: &str) -> String {
+    original
+        .split(' ')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => std::iter::once(first).chain(chars.map(|_| '*')).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Wraps a cloud provider so that only pseudonymized text is sent, and
+/// restores the originals in whatever comes back.
+pub struct RedactingProvider {
+    inner: Arc<dyn AiProvider>,
+    redactor: Mutex<Redactor>,
+    log: Mutex<Vec<Redaction>>,
+}
+
+impl RedactingProvider {
+    pub fn new(inner: Arc<dyn AiProvider>, redactor: Redactor) -> Self {
+        Self {
+            inner,
+            redactor: Mutex::new(redactor),
+            log: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl AiProvider for RedactingProvider {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn prompts(&self) -> &PromptSet {
+        self.inner.prompts()
+    }
+
+    fn endpoint(&self) -> Option<&str> {
+        self.inner.endpoint()
+    }
+
+    async fn preflight(&self) -> AppResult<()> {
+        self.inner.preflight().await
+    }
+
+    async fn complete(&self, request: &CompletionRequest) -> AppResult<String> {
+        let redacted = {
+            let mut redactor = self.redactor.lock().expect("redactor lock poisoned");
+            let (system, system_counts) = redactor.redact(&request.system);
+            let (user, user_counts) = redactor.redact(&request.user);
+
+            let mut counts = system_counts;
+            for (placeholder, count) in user_counts {
+                *counts.entry(placeholder).or_insert(0) += count;
+            }
+            let mut entries: Vec<Redaction> = counts
+                .into_iter()
+                .filter_map(|(placeholder, occurrences)| {
+                    let (entity_type, masked_value) = redactor.describe(&placeholder)?;
+                    Some(Redaction {
+                        prompt_kind: request.kind,
+                        entity_type,
+                        placeholder,
+                        masked_value,
+                        occurrences,
+                    })
+                })
+                .collect();
+            entries.sort_by(|a, b| a.placeholder.cmp(&b.placeholder));
+            self.log.lock().expect("redaction log lock poisoned").extend(entries);
+
+            CompletionRequest { system, user, ..request.clone() }
+        };
+
+        let response = self.inner.complete(&redacted).await?;
+        let redactor = self.redactor.lock().expect("redactor lock poisoned");
+        Ok(redactor.restore(&response, request.json))
+    }
+
+    fn take_redactions(&self) -> Vec<Redaction> {
+        std::mem::take(&mut *self.log.lock().expect("redaction log lock poisoned"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::mock::MockProvider;
+    use crate::ai::ContractType;
+
+    const CONTRACT: &str = "This Agreement is between Acme Corp and Mr. John Smith.\n\
+        Notices: john.smith@example.com or (415) 555-0134, 1200 Market Street, Suite 400.\n\
+        Payment to account number 004512339871 or IBAN DE89 3704 0044 0532 0130 00.\n\
+        John Smith acknowledges the terms.\n\
+        By: Jane Doe\n";
+
+    #[test]
+    fn test_redacts_entities_with_stable_placeholders() {
+        let mut redactor = Redactor::new("").unwrap();
+        let (redacted, counts) = redactor.redact(CONTRACT);
+
+        for secret in ["John Smith", "john.smith@example.com", "555-0134", "1200 Market Street", "004512339871", "DE89", "Jane Doe"] {
+            assert!(!redacted.contains(secret), "{secret} leaked: {redacted}");
+        }
+        assert!(redacted.contains("Acme Corp"));
+        assert!(redacted.contains("Mr. [PERSON_1]"));
+        assert_eq!(counts["[PERSON_1]"], 2);
+
+        // A later prompt reuses the same placeholder for the same person
+        let (again, _) = redactor.redact("Signed by John Smith");
+        assert_eq!(again, "Signed by [PERSON_1]");
+    }
+
+    #[test]
+    fn test_restore_escapes_for_json() {
+        let mut redactor = Redactor::new(r#"Project "\w+""#).unwrap();
+        let (redacted, _) = redactor.redact("Codename Project \"Falcon\", contact Dr. Ann Lee-Hart");
+        assert!(redacted.contains("[CUSTOM_1]"));
+
+        let json = format!(r#"{{"text": "{}"}}"#, "[CUSTOM_1] and [PERSON_1] and [UNKNOWN_9]");
+        let restored: serde_json::Value = serde_json::from_str(&redactor.restore(&json, true)).unwrap();
+        assert_eq!(restored["text"], "Project \"Falcon\" and Ann Lee and [UNKNOWN_9]");
+
+        assert!(Redactor::new("(unclosed").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_provider_sends_pseudonymized_text_and_restores_response() {
+        let mock = Arc::new(MockProvider::new().respond(
+            PromptKind::Extraction,
+            r#"{"parties": ["Acme Corp", "[PERSON_1]"], "effective_date": null, "termination_date": null,
+                "clauses": [{"clause_type": "notices", "title": "Notices", "text": "Send to [EMAIL_1]",
+                "section_reference": null, "importance": "low"}], "contract_type": "nda"}"#,
+        ));
+        let provider = RedactingProvider::new(mock.clone(), Redactor::new("").unwrap());
+
+        let extraction = provider.extract_clauses(CONTRACT, &ContractType::Nda).await.unwrap();
+        assert_eq!(extraction.parties, vec!["Acme Corp", "John Smith"]);
+        assert_eq!(extraction.clauses[0].text, "Send to john.smith@example.com");
+
+        let sent = &mock.requests()[0];
+        assert!(!sent.user.contains("John Smith"));
+        assert!(!sent.user.contains("john.smith@example.com"));
+
+        let log = provider.take_redactions();
+        let person = log.iter().find(|r| r.placeholder == "[PERSON_1]").unwrap();
+        assert_eq!(person.entity_type, "PERSON");
+        assert_eq!(person.masked_value, "J*** S****");
+        assert!(provider.take_redactions().is_empty());
+    }
+}
@@ -0,0 +1,293 @@
+#[cfg(any(test, debug_assertions))]
+use std::path::PathBuf;
+use std::sync::{Arc, PoisonError, RwLock};
+
+use rusqlite::Connection;
+use tauri::{AppHandle, Manager};
+
+use crate::config;
+use crate::db::{prompts, settings, Database};
+use crate::secrets::SecretStore;
+use crate::error::{AppError, AppResult};
+use super::policy::{self, DataPolicy};
+use super::prompts::PromptKind;
+use super::{
+    AiProvider, ClaudeProvider, LocalEmbedder, OllamaProvider, OllamaTaskOptions, OpenAiProvider, RedactingProvider,
+    Redactor,
+};
+#[cfg(any(test, debug_assertions))]
+use super::{RecordingProvider, ReplayProvider};
+
+/// The configured provider, built once and reused so HTTP connections are
+/// kept alive between calls. Invalidated whenever settings, secrets or
+/// prompts change.
+#[derive(Default)]
+pub struct ProviderCache(RwLock<Option<Arc<dyn AiProvider>>>);
+
+// A panic while building leaves at worst an empty cache, so a poisoned lock
+// is taken over rather than propagated.
+impl ProviderCache {
+    pub fn invalidate(&self) {
+        *self.0.write().unwrap_or_else(PoisonError::into_inner) = None;
+    }
+
+    fn get_or_build(
+        &self,
+        build: impl FnOnce() -> AppResult<Arc<dyn AiProvider>>,
+    ) -> AppResult<Arc<dyn AiProvider>> {
+        if let Some(provider) = self.0.read().unwrap_or_else(PoisonError::into_inner).as_ref() {
+            return Ok(provider.clone());
+        }
+        let provider = build()?;
+        *self.0.write().unwrap_or_else(PoisonError::into_inner) = Some(provider.clone());
+        Ok(provider)
+    }
+}
+
+/// Builds the provider selected in settings, primed with the active prompt
+/// templates, for work on the given documents. Under a local-only policy
+/// (workspace-wide or on any of the documents), cloud providers and Ollama
+/// servers off this machine are refused.
+///
+/// Cloud providers are wrapped in a [`RedactingProvider`] unless
+/// `redaction_enabled` is "false"; `redaction_patterns` adds custom regexes,
+/// one per line. In debug builds, when `ai_record_dir` is set, every exchange
+/// with the provider is also written there as a fixture that the `replay`
+/// provider can serve offline.
+///
+/// Settings and prompts are read and secrets fetched from the keyring on a
+/// blocking thread, off the async runtime.
+pub async fn create_provider_for(app_handle: &AppHandle, document_ids: &[&str]) -> AppResult<Arc<dyn AiProvider>> {
+    let app_handle = app_handle.clone();
+    let document_ids: Vec<String> = document_ids.iter().map(|id| id.to_string()).collect();
+    tauri::async_runtime::spawn_blocking(move || {
+        let ids: Vec<&str> = document_ids.iter().map(String::as_str).collect();
+        provider_for(
+            &app_handle.state::<Database>().reader(),
+            &app_handle.state::<SecretStore>(),
+            &app_handle.state::<ProviderCache>(),
+            &ids,
+        )
+    })
+    .await
+    .map_err(|e| AppError::Task(e.to_string()))?
+}
+
+fn provider_for(
+    conn: &Connection,
+    secrets: &SecretStore,
+    cache: &ProviderCache,
+    document_ids: &[&str],
+) -> AppResult<Arc<dyn AiProvider>> {
+    let provider_name = config::get(conn, "ai_provider")?.unwrap_or_default();
+    let data_policy = policy::resolve(conn, document_ids)?;
+
+    let is_cloud = matches!(provider_name.as_str(), "claude" | "openai");
+    if is_cloud && data_policy == DataPolicy::LocalOnly {
+        return Err(AppError::PolicyViolation(format!(
+            "local-only documents cannot be processed with the {provider_name} provider; \
+             switch to a local Ollama server on this machine"
+        )));
+    }
+    let provider = cache.get_or_build(|| build_provider(conn, secrets, &provider_name))?;
+    policy::enforce(data_policy, provider.as_ref())?;
+
+    // Redaction logs are per call, so the wrappers are never cached
+    let provider: Arc<dyn AiProvider> = if is_cloud && config::get_bool(conn, "redaction_enabled")? {
+        let patterns = settings::get(conn, "redaction_patterns")?.unwrap_or_default();
+        Arc::new(RedactingProvider::new(provider, Redactor::new(&patterns)?))
+    } else {
+        provider
+    };
+
+    #[cfg(any(test, debug_assertions))]
+    if let Some(dir) = settings::get(conn, "ai_record_dir")?.filter(|dir| !dir.is_empty()) {
+        if provider_name != "replay" {
+            return Ok(Arc::new(RecordingProvider::new(provider, PathBuf::from(dir))));
+        }
+    }
+    Ok(provider)
+}
+
+fn build_provider(conn: &Connection, secrets: &SecretStore, provider_name: &str) -> AppResult<Arc<dyn AiProvider>> {
+    let prompt_set = prompts::load_active_set(conn)?;
+    let provider: Arc<dyn AiProvider> = match provider_name {
+        "ollama" => Arc::new(ollama_provider(conn)?.with_prompts(prompt_set)),
+        "claude" => {
+            let api_key = secrets.get("claude_api_key")?
+                .ok_or_else(|| AppError::Validation("Claude API key not configured".to_string()))?;
+            let model = settings::get(conn, "claude_model")?;
+            Arc::new(ClaudeProvider::new(api_key, model).with_prompts(prompt_set))
+        }
+        "openai" => {
+            let api_key = secrets.get("openai_api_key")?
+                .ok_or_else(|| AppError::Validation("OpenAI API key not configured".to_string()))?;
+            let model = settings::get(conn, "openai_model")?;
+            Arc::new(OpenAiProvider::new(api_key, model).with_prompts(prompt_set))
+        }
+        #[cfg(any(test, debug_assertions))]
+        "replay" => {
+            let dir = settings::get(conn, "ai_replay_dir")?
+                .ok_or_else(|| AppError::Validation("Replay fixture directory not configured".to_string()))?;
+            Arc::new(ReplayProvider::new(PathBuf::from(dir)).with_prompts(prompt_set))
+        }
+        other => return Err(AppError::Validation(format!("Unknown AI provider: {other}"))),
+    };
+    Ok(provider)
+}
+
+/// The provider that embeds clauses for similarity search, chosen by
+/// `embedding_provider` independently of the provider used for analysis.
+/// Callers enforce the data policy, since it depends on the documents involved.
+pub fn create_embedder(conn: &Connection) -> AppResult<Arc<dyn AiProvider>> {
+    match config::get(conn, "embedding_provider")?.unwrap_or_default().as_str() {
+        "ollama" => Ok(Arc::new(ollama_provider(conn)?)),
+        "local" => Ok(Arc::new(LocalEmbedder::new())),
+        other => Err(AppError::Validation(format!("Unknown embedding provider: {other}"))),
+    }
+}
+
+/// The Ollama server and model from settings, whichever provider is selected.
+pub fn ollama_provider(conn: &Connection) -> AppResult<OllamaProvider> {
+    let url = config::get(conn, "ollama_url")?.unwrap_or_default();
+    let model = config::get(conn, "ollama_model")?.unwrap_or_default();
+    let mut provider = OllamaProvider::new(url, model);
+    if let Some(embedding_model) = config::get(conn, "ollama_embedding_model")? {
+        provider = provider.with_embedding_model(embedding_model);
+    }
+    for kind in PromptKind::ALL {
+        provider = provider.with_task_options(kind, ollama_task_options(conn, kind)?);
+    }
+    Ok(provider)
+}
+
+/// Options for one task. Each option is read from `ollama_{task}_{option}`
+/// (e.g. `ollama_extraction_num_ctx`), then `ollama_{option}` for all tasks,
+/// then the built-in default.
+fn ollama_task_options(conn: &Connection, kind: PromptKind) -> AppResult<OllamaTaskOptions> {
+    let lookup = |option: &str| -> AppResult<Option<String>> {
+        let task_key = format!("ollama_{}_{option}", kind.as_str());
+        match settings::get(conn, &task_key)? {
+            Some(value) if !value.trim().is_empty() => Ok(Some(value)),
+            _ => Ok(settings::get(conn, &format!("ollama_{option}"))?.filter(|v| !v.trim().is_empty())),
+        }
+    };
+    let parsed = |option: &str| -> AppResult<Option<f64>> {
+        lookup(option)?
+            .map(|value| {
+                value.trim().parse::<f64>().map_err(|_| {
+                    AppError::Validation(format!("Ollama setting {option} must be a number, got {value:?}"))
+                })
+            })
+            .transpose()
+    };
+
+    let mut options = OllamaTaskOptions::defaults(kind);
+    if let Some(num_ctx) = parsed("num_ctx")? {
+        options.num_ctx = num_ctx as i32;
+    }
+    if let Some(num_predict) = parsed("num_predict")? {
+        options.num_predict = Some(num_predict as i32);
+    }
+    if let Some(temperature) = parsed("temperature")? {
+        options.temperature = temperature;
+    }
+    options.top_p = parsed("top_p")?.or(options.top_p);
+    options.seed = parsed("seed")?.map(|seed| seed as i64).or(options.seed);
+    options.keep_alive = lookup("keep_alive")?.or(options.keep_alive);
+    Ok(options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_only_refuses_cloud_and_remote_endpoints() {
+        let db = Database::in_memory().unwrap();
+        let secrets = SecretStore::in_memory();
+        let cache = ProviderCache::default();
+        {
+            let conn = db.writer();
+            settings::set(&conn, "local_only", "true").unwrap();
+            settings::set(&conn, "ai_provider", "claude").unwrap();
+        }
+        // Refused before the missing API key is even looked at
+        let err = provider_for(&db.reader(), &secrets, &cache, &[]).err().unwrap();
+        assert!(matches!(err, AppError::PolicyViolation(_)));
+
+        {
+            let conn = db.writer();
+            settings::set(&conn, "ai_provider", "ollama").unwrap();
+            settings::set(&conn, "ollama_url", "http://10.0.0.5:11434").unwrap();
+        }
+        assert!(matches!(provider_for(&db.reader(), &secrets, &cache, &[]).err().unwrap(), AppError::PolicyViolation(_)));
+
+        {
+            let conn = db.writer();
+            settings::set(&conn, "ollama_url", "http://127.0.0.1:11434").unwrap();
+        }
+        cache.invalidate();
+        assert_eq!(provider_for(&db.reader(), &secrets, &cache, &[]).unwrap().name(), "ollama");
+    }
+
+    #[test]
+    fn test_provider_cache_reuses_until_invalidated() {
+        let db = Database::in_memory().unwrap();
+        let secrets = SecretStore::in_memory();
+        let cache = ProviderCache::default();
+
+        let first = provider_for(&db.reader(), &secrets, &cache, &[]).unwrap();
+        let second = provider_for(&db.reader(), &secrets, &cache, &[]).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+
+        {
+            let conn = db.writer();
+            settings::set(&conn, "ai_provider", "openai").unwrap();
+        }
+        cache.invalidate();
+        // Rebuilt from the new settings, which now need a key
+        assert!(provider_for(&db.reader(), &secrets, &cache, &[]).is_err());
+        secrets.set("openai_api_key", "sk-test").unwrap();
+        assert_eq!(provider_for(&db.reader(), &secrets, &cache, &[]).unwrap().name(), "openai");
+    }
+
+    #[test]
+    fn test_create_embedder_follows_setting() {
+        let db = Database::in_memory().unwrap();
+        let conn = db.writer();
+        let embedder = create_embedder(&conn).unwrap();
+        assert_eq!(embedder.embedding_model().as_deref(), Some("ollama:nomic-embed-text"));
+
+        settings::set(&conn, "ollama_embedding_model", "mxbai-embed-large").unwrap();
+        assert_eq!(create_embedder(&conn).unwrap().embedding_model().as_deref(), Some("ollama:mxbai-embed-large"));
+
+        settings::set(&conn, "embedding_provider", "local").unwrap();
+        let embedder = create_embedder(&conn).unwrap();
+        assert_eq!(embedder.name(), "local");
+        assert!(embedder.endpoint().is_none());
+    }
+
+    #[test]
+    fn test_ollama_task_options_fall_back_from_task_to_global() {
+        let db = Database::in_memory().unwrap();
+        let conn = db.writer();
+        settings::set(&conn, "ollama_num_ctx", "12000").unwrap();
+        settings::set(&conn, "ollama_extraction_num_ctx", "24000").unwrap();
+        settings::set(&conn, "ollama_seed", "42").unwrap();
+        settings::set(&conn, "ollama_summary_keep_alive", "1h").unwrap();
+
+        let extraction = ollama_task_options(&conn, PromptKind::Extraction).unwrap();
+        assert_eq!(extraction.num_ctx, 24000);
+        assert_eq!(extraction.seed, Some(42));
+        assert_eq!(extraction.temperature, 0.1);
+        assert_eq!(extraction.keep_alive, None);
+
+        let summary = ollama_task_options(&conn, PromptKind::Summary).unwrap();
+        assert_eq!(summary.num_ctx, 12000);
+        assert_eq!(summary.keep_alive.as_deref(), Some("1h"));
+
+        settings::set(&conn, "ollama_risk_temperature", "warm").unwrap();
+        assert!(ollama_task_options(&conn, PromptKind::Risk).is_err());
+    }
+}
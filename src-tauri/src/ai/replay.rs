@@ -0,0 +1,158 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::error::{AppError, AppResult};
+use super::prompts::PromptSet;
+use super::provider::{AiProvider, CompletionRequest};
+
+/// One recorded request/response pair, stored as a JSON fixture file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Exchange {
+    pub provider: String,
+    pub request: CompletionRequest,
+    pub response: String,
+}
+
+/// Fixture file name for a request: the prompt kind plus a digest of the
+/// full request, so any change to the prompt or the input misses the fixture.
+pub fn fixture_name(request: &CompletionRequest) -> AppResult<String> {
+    let canonical = serde_json::to_vec(request)?;
+    let digest = format!("{:x}", Sha256::digest(&canonical));
+    Ok(format!("{}_{}.json", request.kind.as_str(), &digest[..16]))
+}
+
+/// Wraps a real provider and writes every successful exchange to `dir`.
+pub struct RecordingProvider {
+    inner: Arc<dyn AiProvider>,
+    dir: PathBuf,
+}
+
+impl RecordingProvider {
+    pub fn new(inner: Arc<dyn AiProvider>, dir: PathBuf) -> Self {
+        Self { inner, dir }
+    }
+}
+
+#[async_trait]
+impl AiProvider for RecordingProvider {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn prompts(&self) -> &PromptSet {
+        self.inner.prompts()
+    }
+
+    fn endpoint(&self) -> Option<&str> {
+        self.inner.endpoint()
+    }
+
+    async fn preflight(&self) -> AppResult<()> {
+        self.inner.preflight().await
+    }
+
+    fn take_redactions(&self) -> Vec<super::redact::Redaction> {
+        self.inner.take_redactions()
+    }
+
+    async fn complete(&self, request: &CompletionRequest) -> AppResult<String> {
+        let response = self.inner.complete(request).await?;
+
+        let exchange = Exchange {
+            provider: self.inner.name().to_string(),
+            request: request.clone(),
+            response: response.clone(),
+        };
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::write(
+            self.dir.join(fixture_name(request)?),
+            serde_json::to_string_pretty(&exchange)?,
+        )?;
+
+        Ok(response)
+    }
+}
+
+/// Serves responses from fixtures written by [`RecordingProvider`], without
+/// any network access. A request with no matching fixture is an error.
+pub struct ReplayProvider {
+    dir: PathBuf,
+    prompts: PromptSet,
+}
+
+impl ReplayProvider {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            prompts: PromptSet::builtin(),
+        }
+    }
+
+    pub fn with_prompts(mut self, prompts: PromptSet) -> Self {
+        self.prompts = prompts;
+        self
+    }
+
+    fn load(&self, path: &Path) -> AppResult<Exchange> {
+        let contents = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+}
+
+#[async_trait]
+impl AiProvider for ReplayProvider {
+    fn name(&self) -> &str {
+        "replay"
+    }
+
+    fn prompts(&self) -> &PromptSet {
+        &self.prompts
+    }
+
+    async fn complete(&self, request: &CompletionRequest) -> AppResult<String> {
+        let path = self.dir.join(fixture_name(request)?);
+        if !path.exists() {
+            return Err(AppError::AiProvider(format!(
+                "No recorded fixture for this {} request (expected {})",
+                request.kind.as_str(),
+                path.display()
+            )));
+        }
+        Ok(self.load(&path)?.response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::mock::{fixtures, MockProvider};
+    use crate::ai::prompts::PromptKind;
+    use crate::ai::ContractType;
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("ldr-fixtures-{}", uuid::Uuid::new_v4()))
+    }
+
+    #[tokio::test]
+    async fn test_record_then_replay() {
+        let dir = temp_dir();
+        let live = Arc::new(MockProvider::new().respond(PromptKind::Extraction, fixtures::NDA_EXTRACTION));
+
+        let recorder = RecordingProvider::new(live, dir.clone());
+        let recorded = recorder.extract_clauses("contract text", &ContractType::Nda).await.unwrap();
+
+        let replay = ReplayProvider::new(dir.clone());
+        let replayed = replay.extract_clauses("contract text", &ContractType::Nda).await.unwrap();
+        assert_eq!(replayed.parties, recorded.parties);
+        assert_eq!(replayed.clauses.len(), recorded.clauses.len());
+
+        let miss = replay.extract_clauses("different text", &ContractType::Nda).await;
+        assert!(miss.unwrap_err().to_string().contains("No recorded fixture"));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
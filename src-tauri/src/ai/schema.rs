@@ -0,0 +1,263 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use super::prompts::{self, ANSWER_SCHEMA, COMPARISON_SCHEMA, RISK_ASSESSMENT_SCHEMA, TEMPLATE_REVIEW_SCHEMA};
+use super::types::ContractType;
+
+/// A JSON Schema the model's reply must conform to, for providers that can
+/// enforce one (Claude tool input schemas, OpenAI `json_schema` responses).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutputSchema {
+    pub name: String,
+    pub description: String,
+    pub schema: Value,
+}
+
+pub fn extraction(contract_type: &ContractType) -> OutputSchema {
+    OutputSchema {
+        name: "record_extraction".to_string(),
+        description: format!("Record the clauses extracted from a {}.", contract_type.display_name()),
+        schema: from_example(prompts::extraction_schema(contract_type)),
+    }
+}
+
+pub fn risk() -> OutputSchema {
+    OutputSchema {
+        name: "record_risk_assessment".to_string(),
+        description: "Record the risk assessment of the extracted clauses.".to_string(),
+        schema: from_example(RISK_ASSESSMENT_SCHEMA),
+    }
+}
+
+pub fn comparison() -> OutputSchema {
+    OutputSchema {
+        name: "record_comparison".to_string(),
+        description: "Record the classification of each change between two contract versions.".to_string(),
+        schema: from_example(COMPARISON_SCHEMA),
+    }
+}
+
+pub fn template_review() -> OutputSchema {
+    OutputSchema {
+        name: "record_template_review".to_string(),
+        description: "Record the assessment of each deviation from the standard template.".to_string(),
+        schema: from_example(TEMPLATE_REVIEW_SCHEMA),
+    }
+}
+
+pub fn answer() -> OutputSchema {
+    OutputSchema {
+        name: "record_answer".to_string(),
+        description: "Record the answer to a question about the contract, with citations.".to_string(),
+        schema: from_example(ANSWER_SCHEMA),
+    }
+}
+
+/// Derives a strict JSON Schema from one of the annotated example objects
+/// that are also shown to the model in the prompt:
+///
+/// - `"a|b|c"` becomes an enum, and so does a field whose values across the
+///   elements of an array are two or more distinct snake_case identifiers
+///   (e.g. the `clause_type` of each listed clause)
+/// - `"... or null"` becomes a nullable string
+/// - every property is required and no others are allowed, as strict
+///   structured-output modes demand
+pub fn from_example(example: &str) -> Value {
+    let value: Value = serde_json::from_str(example).expect("built-in example schemas are valid JSON");
+    to_schema(&infer(&value))
+}
+
+#[derive(Debug, Clone)]
+enum Shape {
+    Object(Vec<(String, Shape)>),
+    Array(Box<Shape>),
+    Integer,
+    Number,
+    Boolean,
+    Text(TextShape),
+}
+
+#[derive(Debug, Clone, Default)]
+struct TextShape {
+    nullable: bool,
+    /// Any example value that is free text rather than an identifier.
+    free_text: bool,
+    choices: Vec<String>,
+    /// Whether `choices` came from an explicit `a|b|c` annotation.
+    explicit: bool,
+}
+
+fn infer(value: &Value) -> Shape {
+    match value {
+        Value::Object(map) => Shape::Object(map.iter().map(|(k, v)| (k.clone(), infer(v))).collect()),
+        Value::Array(items) => {
+            let item = items
+                .iter()
+                .map(infer)
+                .reduce(merge)
+                .unwrap_or(Shape::Text(TextShape { free_text: true, ..Default::default() }));
+            Shape::Array(Box::new(item))
+        }
+        Value::Number(n) if n.is_i64() || n.is_u64() => Shape::Integer,
+        Value::Number(_) => Shape::Number,
+        Value::Bool(_) => Shape::Boolean,
+        Value::Null => Shape::Text(TextShape { nullable: true, free_text: true, ..Default::default() }),
+        Value::String(s) => Shape::Text(infer_text(s)),
+    }
+}
+
+fn infer_text(s: &str) -> TextShape {
+    if let Some(prefix) = s.strip_suffix(" or null") {
+        return TextShape { nullable: true, ..infer_text(prefix) };
+    }
+    let parts: Vec<&str> = s.split('|').collect();
+    if parts.len() > 1 && parts.iter().all(|p| is_identifier(p)) {
+        return TextShape {
+            choices: parts.iter().map(|p| p.to_string()).collect(),
+            explicit: true,
+            ..Default::default()
+        };
+    }
+    if s == "..." {
+        // Placeholder that only says "same as the first element"
+        return TextShape::default();
+    }
+    if is_identifier(s) {
+        return TextShape { choices: vec![s.to_string()], ..Default::default() };
+    }
+    TextShape { free_text: true, ..Default::default() }
+}
+
+fn is_identifier(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+fn merge(a: Shape, b: Shape) -> Shape {
+    match (a, b) {
+        (Shape::Object(a), Shape::Object(b)) => {
+            let mut fields = a;
+            for (key, shape) in b {
+                match fields.iter().position(|(k, _)| *k == key) {
+                    Some(i) => {
+                        let existing = fields[i].1.clone();
+                        fields[i].1 = merge(existing, shape);
+                    }
+                    None => fields.push((key, shape)),
+                }
+            }
+            Shape::Object(fields)
+        }
+        (Shape::Array(a), Shape::Array(b)) => Shape::Array(Box::new(merge(*a, *b))),
+        (Shape::Text(a), Shape::Text(b)) => {
+            let mut choices = a.choices;
+            for choice in b.choices {
+                if !choices.contains(&choice) {
+                    choices.push(choice);
+                }
+            }
+            Shape::Text(TextShape {
+                nullable: a.nullable || b.nullable,
+                free_text: a.free_text || b.free_text,
+                choices,
+                explicit: a.explicit || b.explicit,
+            })
+        }
+        (a, _) => a,
+    }
+}
+
+fn to_schema(shape: &Shape) -> Value {
+    match shape {
+        Shape::Object(fields) => {
+            let mut properties = Map::new();
+            for (key, field) in fields {
+                properties.insert(key.clone(), to_schema(field));
+            }
+            json!({
+                "type": "object",
+                "properties": properties,
+                "required": fields.iter().map(|(k, _)| k.clone()).collect::<Vec<_>>(),
+                "additionalProperties": false,
+            })
+        }
+        Shape::Array(item) => json!({ "type": "array", "items": to_schema(item) }),
+        Shape::Integer => json!({ "type": "integer" }),
+        Shape::Number => json!({ "type": "number" }),
+        Shape::Boolean => json!({ "type": "boolean" }),
+        Shape::Text(text) => {
+            let is_enum = !text.free_text && (text.explicit || text.choices.len() > 1);
+            match (is_enum, text.nullable) {
+                (true, false) => json!({ "type": "string", "enum": text.choices }),
+                (true, true) => {
+                    let mut choices: Vec<Value> = text.choices.iter().map(|c| json!(c)).collect();
+                    choices.push(Value::Null);
+                    json!({ "type": ["string", "null"], "enum": choices })
+                }
+                (false, false) => json!({ "type": "string" }),
+                (false, true) => json!({ "type": ["string", "null"] }),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nda_extraction_schema() {
+        let schema = extraction(&ContractType::Nda).schema;
+        assert_eq!(schema["type"], "object");
+        assert_eq!(schema["additionalProperties"], false);
+        assert_eq!(schema["properties"]["effective_date"]["type"], json!(["string", "null"]));
+        assert_eq!(schema["properties"]["parties"]["items"]["type"], "string");
+
+        let clause = &schema["properties"]["clauses"]["items"];
+        let clause_types = clause["properties"]["clause_type"]["enum"].as_array().unwrap();
+        assert_eq!(clause_types.len(), 9);
+        assert!(clause_types.contains(&json!("governing_law")));
+        assert_eq!(clause["properties"]["importance"]["enum"], json!(["high", "medium", "low"]));
+        assert_eq!(clause["properties"]["title"]["type"], "string");
+        assert_eq!(clause["required"].as_array().unwrap().len(), 5);
+    }
+
+    #[test]
+    fn test_risk_schema() {
+        let schema = risk().schema;
+        assert_eq!(schema["properties"]["overall_score"]["type"], "integer");
+        // A single example value is not enough to infer the allowed set
+        assert_eq!(schema["properties"]["risk_level"]["type"], "string");
+        let flag = &schema["properties"]["flags"]["items"];
+        assert_eq!(flag["properties"]["severity"]["enum"], json!(["high", "medium", "low"]));
+        assert_eq!(flag["properties"]["clause_reference"]["type"], json!(["string", "null"]));
+    }
+
+    #[test]
+    fn test_comparison_schema_classifies_changes() {
+        let schema = comparison().schema;
+        let classification = &schema["properties"]["classifications"]["items"];
+        assert_eq!(classification["properties"]["change_id"]["type"], "string");
+        assert_eq!(classification["properties"]["diff_type"]["enum"], json!(["substantive", "formatting"]));
+        assert_eq!(classification["required"].as_array().unwrap().len(), 5);
+    }
+
+    #[test]
+    fn test_template_review_schema_labels_positions() {
+        let schema = template_review().schema;
+        let classification = &schema["properties"]["classifications"]["items"];
+        assert_eq!(
+            classification["properties"]["position"]["enum"],
+            json!(["acceptable", "negotiable", "unacceptable"])
+        );
+        assert_eq!(classification["required"].as_array().unwrap().len(), 6);
+    }
+
+    #[test]
+    fn test_answer_schema() {
+        let schema = answer().schema;
+        assert_eq!(schema["properties"]["answered"]["type"], "boolean");
+        let citation = &schema["properties"]["citations"]["items"];
+        assert_eq!(citation["properties"]["excerpt"]["type"], "string");
+        assert_eq!(citation["required"], json!(["excerpt", "quote"]));
+    }
+}
@@ -69,12 +69,38 @@ pub struct RiskFlag {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ComparisonResponse {
-    pub differences: Vec<Difference>,
+    pub classifications: Vec<ChangeClassification>,
     pub summary: String,
 }
 
+/// The model's reading of one change found by the redline.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeClassification {
+    /// Id of the redline change, e.g. `C3`.
+    pub change_id: String,
+    pub category: String,
+    pub diff_type: String,
+    /// `acceptable`, `negotiable` or `unacceptable`; template reviews only.
+    #[serde(default)]
+    pub position: Option<String>,
+    pub significance: String,
+    pub explanation: String,
+}
+
+/// A change between the compared documents, as stored with the comparison.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Difference {
+    /// The redline change this describes; absent from comparisons made
+    /// before redlines were stored.
+    #[serde(default)]
+    pub change_id: Option<String>,
+    /// Clause type of the clauses compared, in clause-aligned comparisons.
+    #[serde(default)]
+    pub clause_type: Option<String>,
+    /// Our position on a deviation from a template: `acceptable`,
+    /// `negotiable` or `unacceptable`.
+    #[serde(default)]
+    pub position: Option<String>,
     pub category: String,
     pub diff_type: String,
     pub description: String,
@@ -82,3 +108,18 @@ pub struct Difference {
     pub text_b: Option<String>,
     pub significance: String,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnswerResponse {
+    pub answer: String,
+    /// False when the excerpts did not contain the answer.
+    pub answered: bool,
+    pub citations: Vec<AnswerCitation>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnswerCitation {
+    /// Label of the excerpt quoted, e.g. `E2`.
+    pub excerpt: String,
+    pub quote: String,
+}
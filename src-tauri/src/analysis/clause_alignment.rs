@@ -0,0 +1,187 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::ai::ExtractedClause;
+
+/// Clauses of the same type in different sections are paired when their
+/// text is at least this similar...
+const SAME_TYPE_THRESHOLD: f64 = 0.2;
+/// ...and clauses the extraction typed differently when they are in the
+/// same section and this similar.
+const SAME_SECTION_THRESHOLD: f64 = 0.4;
+
+/// A clause of document A and its counterpart in B; one side is `None` when
+/// the clause was added or removed.
+pub type ClausePair<'a> = (Option<&'a ExtractedClause>, Option<&'a ExtractedClause>);
+
+fn words(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().any(char::is_alphabetic))
+        .map(str::to_lowercase)
+        .collect()
+}
+
+fn similarity(a: &HashSet<String>, b: &HashSet<String>) -> f64 {
+    let common = a.intersection(b).count();
+    let union = a.len() + b.len() - common;
+    if union == 0 { 0.0 } else { common as f64 / union as f64 }
+}
+
+/// `Section 7.2`, `§ 7.2` and `7.2.` all become `7.2`.
+fn section_key(clause: &ExtractedClause) -> Option<String> {
+    let reference = clause.section_reference.as_deref()?.to_lowercase().replace('§', " ");
+    let key = reference
+        .split_whitespace()
+        .filter(|w| !matches!(*w, "section" | "sec." | "clause" | "article" | "paragraph"))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let key = key.trim_end_matches('.');
+    (!key.is_empty()).then(|| key.to_string())
+}
+
+/// Pairs the clauses of two extractions: first by clause type and section,
+/// then by type and wording, then by section and wording for clauses the
+/// extraction typed differently. Pairs follow B's order, with removed
+/// clauses after the clause that preceded them in A.
+pub fn align_clauses<'a>(a: &'a [ExtractedClause], b: &'a [ExtractedClause]) -> Vec<ClausePair<'a>> {
+    // Clauses the extraction did not find come back with empty text
+    let a: Vec<&ExtractedClause> = a.iter().filter(|c| !c.text.trim().is_empty()).collect();
+    let b: Vec<&ExtractedClause> = b.iter().filter(|c| !c.text.trim().is_empty()).collect();
+    let words_a: Vec<_> = a.iter().map(|c| words(&c.text)).collect();
+    let words_b: Vec<_> = b.iter().map(|c| words(&c.text)).collect();
+    let keys_a: Vec<_> = a.iter().map(|c| section_key(c)).collect();
+    let keys_b: Vec<_> = b.iter().map(|c| section_key(c)).collect();
+
+    let mut match_of_a: Vec<Option<usize>> = vec![None; a.len()];
+    let mut match_of_b: Vec<Option<usize>> = vec![None; b.len()];
+    let pair_best = |accept: &dyn Fn(usize, usize, f64) -> bool,
+                     match_of_a: &mut Vec<Option<usize>>,
+                     match_of_b: &mut Vec<Option<usize>>| {
+        let mut candidates = Vec::new();
+        for i in (0..a.len()).filter(|&i| match_of_a[i].is_none()) {
+            for j in (0..b.len()).filter(|&j| match_of_b[j].is_none()) {
+                let sim = similarity(&words_a[i], &words_b[j]);
+                if accept(i, j, sim) {
+                    candidates.push((sim, i, j));
+                }
+            }
+        }
+        candidates.sort_by(|x, y| y.0.total_cmp(&x.0).then((x.1, x.2).cmp(&(y.1, y.2))));
+        for (_, i, j) in candidates {
+            if match_of_a[i].is_none() && match_of_b[j].is_none() {
+                match_of_a[i] = Some(j);
+                match_of_b[j] = Some(i);
+            }
+        }
+    };
+
+    let mut type_counts: HashMap<(&str, bool), usize> = HashMap::new();
+    for clause in &a {
+        *type_counts.entry((clause.clause_type.as_str(), true)).or_default() += 1;
+    }
+    for clause in &b {
+        *type_counts.entry((clause.clause_type.as_str(), false)).or_default() += 1;
+    }
+    let same_type = |i: usize, j: usize| a[i].clause_type == b[j].clause_type;
+    let same_section = |i: usize, j: usize| keys_a[i].is_some() && keys_a[i] == keys_b[j];
+    let only_of_type = |i: usize| {
+        let clause_type = a[i].clause_type.as_str();
+        type_counts.get(&(clause_type, true)) == Some(&1) && type_counts.get(&(clause_type, false)) == Some(&1)
+    };
+
+    pair_best(&|i, j, _| same_type(i, j) && same_section(i, j), &mut match_of_a, &mut match_of_b);
+    pair_best(
+        &|i, j, sim| same_type(i, j) && (sim >= SAME_TYPE_THRESHOLD || only_of_type(i)),
+        &mut match_of_a,
+        &mut match_of_b,
+    );
+    pair_best(&|i, j, sim| same_section(i, j) && sim >= SAME_SECTION_THRESHOLD, &mut match_of_a, &mut match_of_b);
+
+    // Sorted by (B position, removed from A, A position)
+    type Key = (Option<usize>, bool, usize);
+    let mut ordered: Vec<(Key, ClausePair<'a>)> = Vec::new();
+    for (j, clause) in b.iter().enumerate() {
+        ordered.push(((Some(j), false, 0), (match_of_b[j].map(|i| a[i]), Some(*clause))));
+    }
+    for (i, clause) in a.iter().enumerate().filter(|(i, _)| match_of_a[*i].is_none()) {
+        let anchor = (0..i).rev().find_map(|k| match_of_a[k]);
+        ordered.push(((anchor, true, i), (Some(*clause), None)));
+    }
+    ordered.sort_by_key(|(key, _)| *key);
+    ordered.into_iter().map(|(_, pair)| pair).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clause(clause_type: &str, section: &str, text: &str) -> ExtractedClause {
+        ExtractedClause {
+            clause_type: clause_type.into(),
+            title: clause_type.replace('_', " "),
+            text: text.into(),
+            section_reference: (!section.is_empty()).then(|| section.to_string()),
+            importance: "medium".into(),
+        }
+    }
+
+    fn summary<'a>(pairs: &[ClausePair<'a>]) -> Vec<(Option<&'a str>, Option<&'a str>)> {
+        pairs
+            .iter()
+            .map(|(a, b)| (a.map(|c| c.text.as_str()), b.map(|c| c.text.as_str())))
+            .collect()
+    }
+
+    #[test]
+    fn test_reordered_clauses_pair_by_type() {
+        let a = vec![
+            clause("payment_terms", "Section 3", "Fees are due within thirty days."),
+            clause("confidentiality", "Section 4", "Each party keeps information secret."),
+            clause("warranties", "Section 5", "Supplier warrants the services."),
+            clause("governing_law", "", ""),
+        ];
+        let b = vec![
+            clause("confidentiality", "§ 2", "Each party keeps all information secret."),
+            clause("payment_terms", "3.", "Fees are due within sixty days."),
+            clause("force_majeure", "Section 6", "Neither party is liable for events beyond its control."),
+        ];
+
+        assert_eq!(
+            summary(&align_clauses(&a, &b)),
+            vec![
+                (Some("Each party keeps information secret."), Some("Each party keeps all information secret.")),
+                // Warranties followed confidentiality in A
+                (Some("Supplier warrants the services."), None),
+                (Some("Fees are due within thirty days."), Some("Fees are due within sixty days.")),
+                (None, Some("Neither party is liable for events beyond its control.")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_repeated_types_pair_by_section_then_wording() {
+        let a = vec![
+            clause("indemnification", "Section 8.1", "Supplier indemnifies Customer against third-party IP claims."),
+            clause("indemnification", "Section 8.2", "Customer indemnifies Supplier against misuse of the services."),
+            clause("term_and_termination", "Section 9", "Either party may terminate for material breach."),
+        ];
+        let b = vec![
+            clause("indemnification", "Section 10.1", "Customer indemnifies Supplier against any misuse of the services."),
+            clause("indemnification", "Section 8.1", "Supplier indemnifies Customer against third-party claims."),
+            clause("termination", "Section 9", "Either party may terminate for material breach on notice."),
+        ];
+
+        let pairs = align_clauses(&a, &b);
+        let sections: Vec<_> = pairs
+            .iter()
+            .map(|(a, b)| (a.and_then(|c| c.section_reference.as_deref()), b.and_then(|c| c.section_reference.as_deref())))
+            .collect();
+        assert_eq!(
+            sections,
+            vec![
+                (Some("Section 8.2"), Some("Section 10.1")),
+                (Some("Section 8.1"), Some("Section 8.1")),
+                (Some("Section 9"), Some("Section 9")),
+            ]
+        );
+    }
+}
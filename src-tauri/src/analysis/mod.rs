@@ -1,9 +1,16 @@
 mod risk_rules;
+pub mod clause_alignment;
+pub mod questions;
+pub mod redline;
+pub mod similarity;
 
 use std::sync::Arc;
 use std::time::Instant;
 
-use crate::ai::{AiProvider, ContractType, ExtractionResponse};
+use crate::ai::{policy, AiProvider, ContractType, ExtractionResponse};
+use rusqlite::Connection;
+
+use crate::db::redactions::{self, RedactionSubject};
 use crate::db::{Database, documents, extractions, risk_assessments};
 use crate::error::{AppError, AppResult};
 
@@ -12,54 +19,71 @@ pub async fn run_extraction(
     provider: Arc<dyn AiProvider>,
     document_id: &str,
 ) -> AppResult<extractions::Extraction> {
-    let (raw_text, contract_type_str) = {
-        let conn = db.conn.lock().expect("db lock poisoned");
-        let doc = documents::get_by_id(&conn, document_id)?;
-        let text = doc.raw_text.ok_or_else(|| {
-            AppError::Validation("Document text not yet extracted".to_string())
-        })?;
-        (text, doc.contract_type)
-    };
+    let id = document_id.to_string();
+    let (raw_text, contract_type_str, data_policy) = db
+        .read(move |conn| {
+            let data_policy = policy::resolve(conn, &[&id])?;
+            let doc = documents::get_by_id(conn, &id)?;
+            let text = doc.raw_text.ok_or_else(|| {
+                AppError::Validation("Document text not yet extracted".to_string())
+            })?;
+            Ok((text, doc.contract_type, data_policy))
+        })
+        .await?;
+    policy::enforce(data_policy, provider.as_ref())?;
 
     let contract_type = contract_type_str
         .parse::<ContractType>()
         .map_err(|_| AppError::Validation(format!("Unknown contract type: {contract_type_str}")))?;
 
     // Update status to analyzing
-    {
-        let conn = db.conn.lock().expect("db lock poisoned");
-        documents::update_status(&conn, document_id, "analyzing", None)?;
-    }
+    let id = document_id.to_string();
+    db.write(move |conn| documents::update_status(conn, &id, "analyzing", None)).await?;
 
     let start = Instant::now();
     let extraction = provider.extract_clauses(&raw_text, &contract_type).await;
     let elapsed_ms = start.elapsed().as_millis() as i64;
 
-    match extraction {
-        Ok(result) => {
-            let conn = db.conn.lock().expect("db lock poisoned");
-            let extraction_record = extractions::insert(
-                &conn,
-                &extractions::CreateExtraction {
-                    document_id: document_id.to_string(),
-                    ai_provider: provider.name().to_string(),
-                    ai_model: None,
-                    contract_type: contract_type_str,
-                    extracted_data: serde_json::to_string(&result)
-                        .map_err(AppError::Json)?,
-                    confidence_score: None,
-                    processing_time_ms: Some(elapsed_ms),
-                },
-            )?;
-            documents::update_status(&conn, document_id, "extracted", None)?;
-            Ok(extraction_record)
-        }
-        Err(e) => {
-            let conn = db.conn.lock().expect("db lock poisoned");
-            documents::update_status(&conn, document_id, "error", Some(&e.to_string()))?;
-            Err(e)
+    let id = document_id.to_string();
+    db.write(move |conn| {
+        // The extraction is saved together with the log of what was redacted
+        // to produce it. The log is kept even when the call failed, since the
+        // prompt was still sent, and failing to write it fails the extraction.
+        let tx = conn.unchecked_transaction()?;
+        let saved = record_redactions(&tx, provider.as_ref(), [RedactionSubject::Document(&id)])
+            .and(extraction)
+            .and_then(|result| {
+                extractions::insert(
+                    &tx,
+                    &extractions::CreateExtraction {
+                        document_id: id.clone(),
+                        ai_provider: provider.name().to_string(),
+                        ai_model: None,
+                        contract_type: contract_type_str,
+                        extracted_data: serde_json::to_string(&result)
+                            .map_err(AppError::Json)?,
+                        confidence_score: None,
+                        processing_time_ms: Some(elapsed_ms),
+                        prompt_version: Some(provider.prompts().extraction.version),
+                        data_policy: Some(data_policy.as_str().to_string()),
+                        ai_endpoint: provider.endpoint().map(str::to_string),
+                    },
+                )
+            });
+        match saved {
+            Ok(extraction_record) => {
+                documents::update_status(&tx, &id, "extracted", None)?;
+                tx.commit()?;
+                Ok(extraction_record)
+            }
+            Err(e) => {
+                documents::update_status(&tx, &id, "error", Some(&e.to_string()))?;
+                tx.commit()?;
+                Err(e)
+            }
         }
-    }
+    })
+    .await
 }
 
 pub async fn run_risk_assessment(
@@ -68,11 +92,15 @@ pub async fn run_risk_assessment(
     document_id: &str,
     extraction_id: &str,
 ) -> AppResult<risk_assessments::RiskAssessment> {
-    let (extraction_data, contract_type_str) = {
-        let conn = db.conn.lock().expect("db lock poisoned");
-        let ext = extractions::get_by_id(&conn, extraction_id)?;
-        (ext.extracted_data, ext.contract_type)
-    };
+    let (id, ext_id) = (document_id.to_string(), extraction_id.to_string());
+    let (extraction_data, contract_type_str, data_policy) = db
+        .read(move |conn| {
+            let data_policy = policy::resolve(conn, &[&id])?;
+            let ext = extractions::get_by_id(conn, &ext_id)?;
+            Ok((ext.extracted_data, ext.contract_type, data_policy))
+        })
+        .await?;
+    policy::enforce(data_policy, provider.as_ref())?;
 
     let contract_type = contract_type_str
         .parse::<ContractType>()
@@ -81,7 +109,11 @@ pub async fn run_risk_assessment(
     let extraction: ExtractionResponse = serde_json::from_str(&extraction_data)
         .map_err(|e| AppError::AiProvider(format!("Failed to parse stored extraction: {e}")))?;
 
-    let mut risk_result = provider.score_risk(&extraction, &contract_type).await?;
+    let scored = provider.score_risk(&extraction, &contract_type).await;
+    let (id, logged) = (document_id.to_string(), provider.clone());
+    db.write(move |conn| record_redactions(conn, logged.as_ref(), [RedactionSubject::Document(&id)]))
+        .await?;
+    let mut risk_result = scored?;
 
     // Apply rule-based risk checks
     let rule_flags = risk_rules::apply_rules(&extraction, &contract_type);
@@ -93,24 +125,42 @@ pub async fn run_risk_assessment(
         risk_result.risk_level = "high".to_string();
     }
 
-    let conn = db.conn.lock().expect("db lock poisoned");
-    let ra = risk_assessments::insert(
-        &conn,
-        &risk_assessments::CreateRiskAssessment {
-            document_id: document_id.to_string(),
-            extraction_id: extraction_id.to_string(),
-            overall_score: risk_result.overall_score,
-            risk_level: risk_result.risk_level.clone(),
-            flags: serde_json::to_string(&risk_result.flags)
-                .map_err(AppError::Json)?,
-            summary: Some(risk_result.summary),
-            ai_provider: provider.name().to_string(),
-        },
-    )?;
-
-    documents::update_status(&conn, document_id, "analyzed", None)?;
-
-    Ok(ra)
+    let create = risk_assessments::CreateRiskAssessment {
+        document_id: document_id.to_string(),
+        extraction_id: extraction_id.to_string(),
+        overall_score: risk_result.overall_score,
+        risk_level: risk_result.risk_level.clone(),
+        flags: serde_json::to_string(&risk_result.flags)
+            .map_err(AppError::Json)?,
+        summary: Some(risk_result.summary),
+        ai_provider: provider.name().to_string(),
+        prompt_version: Some(provider.prompts().risk.version),
+        data_policy: Some(data_policy.as_str().to_string()),
+        ai_endpoint: provider.endpoint().map(str::to_string),
+    };
+    db.write(move |conn| {
+        let ra = risk_assessments::insert(conn, &create)?;
+        documents::update_status(conn, &create.document_id, "analyzed", None)?;
+        Ok(ra)
+    })
+    .await
+}
+
+/// Logs what the provider redacted from the prompts it just sent, if
+/// anything, against each subject the prompts were about.
+pub(crate) fn record_redactions<'a>(
+    conn: &Connection,
+    provider: &dyn AiProvider,
+    subjects: impl IntoIterator<Item = RedactionSubject<'a>>,
+) -> AppResult<()> {
+    let redacted = provider.take_redactions();
+    if redacted.is_empty() {
+        return Ok(());
+    }
+    for subject in subjects {
+        redactions::insert_all(conn, subject, provider.name(), &redacted)?;
+    }
+    Ok(())
 }
 
 pub async fn run_full_analysis(
@@ -118,7 +168,197 @@ pub async fn run_full_analysis(
     provider: Arc<dyn AiProvider>,
     document_id: &str,
 ) -> AppResult<(extractions::Extraction, risk_assessments::RiskAssessment)> {
+    // Refuse to start rather than leave the document half-analyzed; the
+    // policy check comes first since preflight may itself reach the endpoint
+    let id = document_id.to_string();
+    let data_policy = db.read(move |conn| policy::resolve(conn, &[&id])).await?;
+    policy::enforce(data_policy, provider.as_ref())?;
+    provider.preflight().await?;
     let extraction = run_extraction(db, provider.clone(), document_id).await?;
     let risk = run_risk_assessment(db, provider, document_id, &extraction.id).await?;
     Ok((extraction, risk))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::mock::{fixtures, MockProvider};
+    use crate::ai::prompts::{PromptKind, BUILTIN_VERSION};
+    use crate::db::documents::CreateDocument;
+    use crate::db::prompts;
+
+    fn insert_doc(db: &Database, text: Option<&str>) -> String {
+        let conn = db.writer();
+        let doc = documents::insert(&conn, &CreateDocument {
+            filename: "nda.pdf".into(),
+            original_path: "/tmp/nda.pdf".into(),
+            stored_path: "/data/nda.pdf".into(),
+            file_hash: "hash".into(),
+            file_size: 10,
+            contract_type: "nda".into(),
+        }).unwrap();
+        if let Some(text) = text {
+            documents::update_text(&conn, &doc.id, text, 1).unwrap();
+        }
+        doc.id
+    }
+
+    #[tokio::test]
+    async fn test_run_full_analysis() {
+        let db = Database::in_memory().unwrap();
+        let doc_id = insert_doc(&db, Some("Mutual NDA between Acme and Globex."));
+        let mock = Arc::new(
+            MockProvider::new()
+                .respond(PromptKind::Extraction, fixtures::NDA_EXTRACTION)
+                .respond(PromptKind::Risk, fixtures::RISK),
+        );
+
+        let (extraction, risk) = run_full_analysis(&db, mock.clone(), &doc_id).await.unwrap();
+
+        assert_eq!(extraction.ai_provider, "mock");
+        assert_eq!(extraction.prompt_version, Some(BUILTIN_VERSION));
+        assert_eq!(risk.extraction_id, extraction.id);
+
+        // The NDA fixture has no exclusions clause, so the rule engine adds a
+        // high-severity flag and lifts the model's medium score.
+        let flags: Vec<crate::ai::RiskFlag> = serde_json::from_str(&risk.flags).unwrap();
+        assert!(flags.iter().any(|f| f.description.contains("indefinitely")));
+        assert!(flags.iter().any(|f| f.category == "confidentiality" && f.severity == "high"));
+        assert_eq!(risk.risk_level, "high");
+        assert_eq!(risk.overall_score, 67);
+
+        let conn = db.writer();
+        assert_eq!(documents::get_by_id(&conn, &doc_id).unwrap().processing_status, "analyzed");
+
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].user.contains("Mutual NDA between Acme and Globex."));
+    }
+
+    #[tokio::test]
+    async fn test_run_full_analysis_logs_redactions() {
+        let db = Database::in_memory().unwrap();
+        let doc_id = insert_doc(&db, Some("Mutual NDA. Notices to legal@acme.example, attn Ms. Dana Reyes."));
+        let mock = Arc::new(
+            MockProvider::new()
+                .respond(PromptKind::Extraction, fixtures::NDA_EXTRACTION)
+                .respond(PromptKind::Risk, fixtures::RISK),
+        );
+        let provider = Arc::new(crate::ai::RedactingProvider::new(
+            mock.clone(),
+            crate::ai::Redactor::new("").unwrap(),
+        ));
+
+        run_full_analysis(&db, provider, &doc_id).await.unwrap();
+        assert!(!mock.requests()[0].user.contains("legal@acme.example"));
+
+        let conn = db.writer();
+        let log = redactions::list_by_document(&conn, &doc_id).unwrap();
+        let types: Vec<&str> = log.iter().map(|r| r.entity_type.as_str()).collect();
+        assert!(types.contains(&"EMAIL"));
+        assert!(types.contains(&"PERSON"));
+        assert!(log.iter().all(|r| r.ai_provider == "mock" && r.prompt_kind == "extraction"));
+    }
+
+    #[tokio::test]
+    async fn test_run_extraction_fails_when_redactions_cannot_be_logged() {
+        let db = Database::in_memory().unwrap();
+        let doc_id = insert_doc(&db, Some("Notices to legal@acme.example."));
+        db.writer().execute_batch("DROP TABLE redaction_log").unwrap();
+        let provider = Arc::new(crate::ai::RedactingProvider::new(
+            Arc::new(MockProvider::new().respond(PromptKind::Extraction, fixtures::NDA_EXTRACTION)),
+            crate::ai::Redactor::new("").unwrap(),
+        ));
+
+        assert!(run_extraction(&db, provider, &doc_id).await.is_err());
+        let conn = db.writer();
+        assert!(extractions::list_by_document(&conn, &doc_id).unwrap().is_empty());
+        assert_eq!(documents::get_by_id(&conn, &doc_id).unwrap().processing_status, "error");
+    }
+
+    #[tokio::test]
+    async fn test_local_only_document_is_refused_and_policy_recorded() {
+        let db = Database::in_memory().unwrap();
+        let doc_id = insert_doc(&db, Some("Confidential settlement terms."));
+        {
+            let conn = db.writer();
+            documents::set_local_only(&conn, &doc_id, true).unwrap();
+        }
+
+        let cloud = Arc::new(crate::ai::ClaudeProvider::new("key".into(), None));
+        let err = run_full_analysis(&db, cloud, &doc_id).await.unwrap_err();
+        assert!(matches!(err, AppError::PolicyViolation(_)));
+
+        let mock = Arc::new(
+            MockProvider::new()
+                .respond(PromptKind::Extraction, fixtures::NDA_EXTRACTION)
+                .respond(PromptKind::Risk, fixtures::RISK),
+        );
+        let (extraction, risk) = run_full_analysis(&db, mock, &doc_id).await.unwrap();
+        assert_eq!(extraction.data_policy.as_deref(), Some("local_only"));
+        assert_eq!(extraction.ai_endpoint, None);
+        assert_eq!(risk.data_policy.as_deref(), Some("local_only"));
+    }
+
+    #[tokio::test]
+    async fn test_run_full_analysis_refuses_unavailable_provider() {
+        let db = Database::in_memory().unwrap();
+        let doc_id = insert_doc(&db, Some("text"));
+        let mock = Arc::new(
+            MockProvider::new()
+                .respond(PromptKind::Extraction, fixtures::NDA_EXTRACTION)
+                .unavailable("Ollama model 'llama3' is not installed"),
+        );
+
+        let err = run_full_analysis(&db, mock.clone(), &doc_id).await.unwrap_err();
+        assert!(err.to_string().contains("not installed"));
+        assert!(mock.requests().is_empty());
+
+        let conn = db.writer();
+        let doc = documents::get_by_id(&conn, &doc_id).unwrap();
+        assert_eq!(doc.processing_status, "extracted");
+    }
+
+    #[tokio::test]
+    async fn test_run_full_analysis_records_provider_error() {
+        let db = Database::in_memory().unwrap();
+        let doc_id = insert_doc(&db, Some("text"));
+        let mock = Arc::new(MockProvider::new().fail(PromptKind::Extraction, "model unavailable"));
+
+        assert!(run_full_analysis(&db, mock, &doc_id).await.is_err());
+
+        let conn = db.writer();
+        let doc = documents::get_by_id(&conn, &doc_id).unwrap();
+        assert_eq!(doc.processing_status, "error");
+        assert!(doc.error_message.unwrap().contains("model unavailable"));
+    }
+
+    #[tokio::test]
+    async fn test_run_extraction_uses_active_prompt_version() {
+        let db = Database::in_memory().unwrap();
+        let doc_id = insert_doc(&db, Some("text"));
+        let prompt_set = {
+            let conn = db.writer();
+            prompts::create_version(&conn, PromptKind::Extraction, "Custom {{contract_type}}", "{{document_text}}", None).unwrap();
+            prompts::load_active_set(&conn).unwrap()
+        };
+        let mock = Arc::new(
+            MockProvider::new()
+                .with_prompts(prompt_set)
+                .respond(PromptKind::Extraction, fixtures::NDA_EXTRACTION),
+        );
+
+        let extraction = run_extraction(&db, mock.clone(), &doc_id).await.unwrap();
+
+        assert_eq!(extraction.prompt_version, Some(1));
+        assert_eq!(mock.requests()[0].system, "Custom Non-Disclosure Agreement");
+    }
+
+    #[tokio::test]
+    async fn test_run_extraction_requires_text() {
+        let db = Database::in_memory().unwrap();
+        let doc_id = insert_doc(&db, None);
+        let mock = Arc::new(MockProvider::new());
+        assert!(run_extraction(&db, mock, &doc_id).await.is_err());
+    }
+}
@@ -0,0 +1,607 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use serde::Serialize;
+
+use crate::ai::{policy, AiProvider, AnswerCitation};
+use crate::analysis::record_redactions;
+use crate::db::audit::{self, NewAuditEvent};
+use crate::db::conversations::{self, Citation, Conversation, ConversationMessage, CreateMessage};
+use crate::db::redactions::RedactionSubject;
+use crate::db::{documents, Database};
+use crate::error::{AppError, AppResult};
+
+/// Sections are merged up to about this many bytes...
+const CHUNK_TARGET: usize = 1200;
+/// ...and longer paragraphs are split near this size.
+const CHUNK_MAX: usize = 2000;
+/// Excerpts sent with each question.
+const EXCERPT_COUNT: usize = 6;
+/// Earlier messages included so follow-up questions make sense.
+const HISTORY_MESSAGES: usize = 6;
+const TITLE_MAX_CHARS: usize = 80;
+
+/// Words that carry no meaning for retrieval.
+const STOPWORDS: &[&str] = &[
+    "a", "about", "an", "and", "any", "are", "as", "at", "be", "by", "can", "do", "does", "for", "from", "has",
+    "have", "how", "if", "in", "is", "it", "its", "of", "on", "or", "shall", "that", "the", "there", "this",
+    "to", "what", "when", "where", "which", "who", "will", "with",
+];
+
+/// One question and its answer, as stored in the conversation.
+#[derive(Debug, Serialize)]
+pub struct Answer {
+    pub conversation: Conversation,
+    pub question: ConversationMessage,
+    pub answer: ConversationMessage,
+    /// False when the model found no answer in the excerpts.
+    pub answered: bool,
+}
+
+struct Source {
+    id: String,
+    filename: String,
+    text: String,
+}
+
+/// A section of a document, by byte offsets into its text.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Chunk {
+    source: usize,
+    /// Position within its document, used to interleave documents.
+    ordinal: usize,
+    start: usize,
+    end: usize,
+}
+
+/// Paragraphs (runs of non-blank lines) as byte ranges, with surrounding
+/// whitespace trimmed.
+fn paragraphs(text: &str) -> Vec<(usize, usize)> {
+    let mut paragraphs = Vec::new();
+    let mut current: Option<(usize, usize)> = None;
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            paragraphs.extend(current.take());
+        } else {
+            let start = offset + (line.len() - line.trim_start().len());
+            let end = offset + line.trim_end().len();
+            current = Some(current.map_or((start, end), |(s, _)| (s, end)));
+        }
+        offset += line.len();
+    }
+    paragraphs.extend(current);
+    paragraphs
+}
+
+/// The last whitespace boundary at or before `limit`, so a split never
+/// breaks a word or a character.
+fn split_point(text: &str, start: usize, limit: usize) -> usize {
+    let mut limit = limit;
+    while !text.is_char_boundary(limit) {
+        limit -= 1;
+    }
+    text[start..limit]
+        .rfind(char::is_whitespace)
+        .filter(|i| *i > 0)
+        .map_or(limit, |i| start + i)
+}
+
+/// Splits a document into sections of roughly [`CHUNK_TARGET`] bytes along
+/// paragraph breaks, splitting paragraphs over [`CHUNK_MAX`].
+fn chunk(text: &str, source: usize) -> Vec<Chunk> {
+    let mut pieces = Vec::new();
+    for (mut start, end) in paragraphs(text) {
+        while end - start > CHUNK_MAX {
+            let split = split_point(text, start, start + CHUNK_MAX);
+            pieces.push((start, split));
+            start = split + (text[split..].len() - text[split..].trim_start().len());
+        }
+        pieces.push((start, end));
+    }
+
+    let mut chunks: Vec<Chunk> = Vec::new();
+    for (start, end) in pieces {
+        match chunks.last_mut() {
+            Some(last) if end - last.start <= CHUNK_TARGET => last.end = end,
+            _ => chunks.push(Chunk { source, ordinal: chunks.len(), start, end }),
+        }
+    }
+    chunks
+}
+
+/// Lowercased words without stopwords, with common suffixes stripped so
+/// "terminates" matches "termination".
+fn terms(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.len() > 1 && !STOPWORDS.contains(word))
+        .map(|word| {
+            ["ation", "ing", "ed", "es", "s"]
+                .iter()
+                .find_map(|suffix| word.strip_suffix(suffix).filter(|stem| stem.len() >= 4))
+                .unwrap_or(word)
+                .to_string()
+        })
+        .collect()
+}
+
+/// The chunks most relevant to the question by BM25, in reading order.
+/// Falls back to the opening sections when no term matches.
+fn retrieve(sources: &[Source], chunks: &[Chunk], question: &str, count: usize) -> Vec<Chunk> {
+    const K1: f64 = 1.2;
+    const B: f64 = 0.75;
+
+    let query: HashSet<String> = terms(question).into_iter().collect();
+    let chunk_terms: Vec<Vec<String>> = chunks
+        .iter()
+        .map(|c| terms(&sources[c.source].text[c.start..c.end]))
+        .collect();
+    let total = chunks.len() as f64;
+    let average = chunk_terms.iter().map(Vec::len).sum::<usize>() as f64 / total.max(1.0);
+
+    let mut scored: Vec<(f64, Chunk)> = chunks
+        .iter()
+        .zip(&chunk_terms)
+        .map(|(chunk, words)| {
+            let score = query
+                .iter()
+                .map(|term| {
+                    let frequency = words.iter().filter(|w| *w == term).count() as f64;
+                    if frequency == 0.0 {
+                        return 0.0;
+                    }
+                    let containing = chunk_terms.iter().filter(|ws| ws.contains(term)).count() as f64;
+                    let idf = ((total - containing + 0.5) / (containing + 0.5) + 1.0).ln();
+                    let length = words.len() as f64 / average.max(1.0);
+                    idf * frequency * (K1 + 1.0) / (frequency + K1 * (1.0 - B + B * length))
+                })
+                .sum();
+            (score, *chunk)
+        })
+        .filter(|(score, _)| *score > 0.0)
+        .collect();
+
+    let mut selected: Vec<Chunk> = if scored.is_empty() {
+        let mut opening = chunks.to_vec();
+        opening.sort_by_key(|c| (c.ordinal, c.source));
+        opening
+    } else {
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+        scored.into_iter().map(|(_, chunk)| chunk).collect()
+    };
+    selected.truncate(count);
+    selected.sort_by_key(|c| (c.source, c.start));
+    selected
+}
+
+fn excerpt_block(sources: &[Source], excerpts: &[Chunk]) -> String {
+    excerpts
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            let source = &sources[c.source];
+            format!("[E{}] {}\n{}", i + 1, source.filename, &source.text[c.start..c.end])
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn history_block(messages: &[ConversationMessage]) -> String {
+    let recent = &messages[messages.len().saturating_sub(HISTORY_MESSAGES)..];
+    if recent.is_empty() {
+        return "(none)".to_string();
+    }
+    recent
+        .iter()
+        .map(|m| {
+            let speaker = if m.role == "user" { "User" } else { "Assistant" };
+            format!("{speaker}: {}", m.content)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Text folded for quote matching: lowercase, single spaces, and plain
+/// quotes and dashes, with each character's byte range in the original.
+struct Folded {
+    chars: Vec<char>,
+    spans: Vec<(usize, usize)>,
+}
+
+fn fold(text: &str) -> Folded {
+    let mut folded = Folded { chars: Vec::new(), spans: Vec::new() };
+    for (i, c) in text.char_indices() {
+        let end = i + c.len_utf8();
+        let c = match c {
+            '\u{2018}' | '\u{2019}' => '\'',
+            '\u{201C}' | '\u{201D}' => '"',
+            '\u{2010}'..='\u{2015}' => '-',
+            c if c.is_whitespace() => ' ',
+            c => c.to_lowercase().next().unwrap_or(c),
+        };
+        if c == ' ' && matches!(folded.chars.last(), None | Some(' ')) {
+            continue;
+        }
+        folded.chars.push(c);
+        folded.spans.push((i, end));
+    }
+    folded
+}
+
+/// Byte range of `quote` in `text`, ignoring case, whitespace and
+/// typographic differences. An elided quote ("the term ... shall survive")
+/// matches its parts in order and covers everything between them.
+fn locate(text: &str, quote: &str) -> Option<(usize, usize)> {
+    let haystack = fold(text);
+    let segments: Vec<Vec<char>> = quote
+        .replace("...", "\u{2026}")
+        .split('\u{2026}')
+        .map(|segment| {
+            let mut chars = fold(segment).chars;
+            while chars.last() == Some(&' ') {
+                chars.pop();
+            }
+            chars
+        })
+        .filter(|chars| !chars.is_empty())
+        .collect();
+
+    let mut from = 0;
+    let mut first = None;
+    for segment in &segments {
+        let found = haystack.chars.get(from..)?
+            .windows(segment.len())
+            .position(|window| window == segment.as_slice())?
+            + from;
+        first.get_or_insert(found);
+        from = found + segment.len();
+    }
+    Some((haystack.spans[first?].0, haystack.spans[from - 1].1))
+}
+
+fn char_offset(text: &str, byte: usize) -> usize {
+    text[..byte].chars().count()
+}
+
+/// Finds each quote in the excerpt it names, then anywhere in that document
+/// (or any document for an unknown label). Unmatched quotes fall back to the
+/// whole excerpt, unverified; quotes from unknown excerpts are dropped.
+fn resolve_citations(sources: &[Source], excerpts: &[Chunk], cited: &[AnswerCitation]) -> Vec<Citation> {
+    let mut citations: Vec<Citation> = Vec::new();
+    for citation in cited {
+        let label = citation.excerpt.trim().trim_start_matches('[').trim_end_matches(']').to_uppercase();
+        let excerpt = label
+            .strip_prefix('E')
+            .and_then(|n| n.parse::<usize>().ok())
+            .and_then(|n| excerpts.get(n.checked_sub(1)?));
+
+        let found = match excerpt {
+            Some(c) => {
+                let text = &sources[c.source].text;
+                locate(&text[c.start..c.end], &citation.quote)
+                    .map(|(s, e)| (c.source, c.start + s, c.start + e))
+                    .or_else(|| locate(text, &citation.quote).map(|(s, e)| (c.source, s, e)))
+            }
+            None => sources
+                .iter()
+                .enumerate()
+                .find_map(|(i, source)| locate(&source.text, &citation.quote).map(|(s, e)| (i, s, e))),
+        };
+        let (source, start, end, verified) = match (found, excerpt) {
+            (Some((source, start, end)), _) => (source, start, end, true),
+            (None, Some(c)) => (c.source, c.start, c.end, false),
+            (None, None) => continue,
+        };
+
+        let text = &sources[source].text;
+        let resolved = Citation {
+            document_id: sources[source].id.clone(),
+            excerpt: label,
+            text: text[start..end].to_string(),
+            start: char_offset(text, start),
+            end: char_offset(text, end),
+            verified,
+        };
+        let duplicate = citations
+            .iter()
+            .any(|c| c.document_id == resolved.document_id && c.start == resolved.start && c.end == resolved.end);
+        if !duplicate {
+            citations.push(resolved);
+        }
+    }
+    citations
+}
+
+fn title_for(question: &str) -> String {
+    let mut title: String = question.chars().take(TITLE_MAX_CHARS).collect();
+    if question.chars().count() > TITLE_MAX_CHARS {
+        title.push('…');
+    }
+    title
+}
+
+/// Answers a question about the documents from their most relevant sections
+/// and saves both to the conversation, audited in the same transaction,
+/// starting a new one when
+/// `conversation_id` is `None`. A continued conversation keeps its own
+/// documents and `document_ids` is ignored.
+pub async fn ask(
+    db: &Database,
+    provider: Arc<dyn AiProvider>,
+    conversation_id: Option<&str>,
+    document_ids: &[String],
+    question: &str,
+) -> AppResult<Answer> {
+    let question = question.trim();
+    if question.is_empty() {
+        return Err(AppError::Validation("Enter a question".to_string()));
+    }
+
+    let (conversation_id, document_ids) = (conversation_id.map(str::to_string), document_ids.to_vec());
+    let (existing, sources, history, data_policy) = db
+        .read(move |conn| {
+            let (existing, document_ids, history) = match conversation_id {
+                Some(id) => {
+                    let conversation = conversations::get_by_id(conn, &id)?;
+                    let history = conversations::list_messages(conn, &id)?;
+                    let ids = conversation.document_ids.clone();
+                    (Some(conversation), ids, history)
+                }
+                None => (None, document_ids, Vec::new()),
+            };
+            if document_ids.is_empty() {
+                return Err(AppError::Validation("Choose at least one document to ask about".to_string()));
+            }
+
+            let mut sources = Vec::new();
+            for id in &document_ids {
+                let doc = documents::get_by_id(conn, id)?;
+                if doc.deleted_at.is_some() {
+                    return Err(AppError::Validation(format!("{} is in the trash", doc.filename)));
+                }
+                let text = doc.raw_text.filter(|t| !t.trim().is_empty()).ok_or_else(|| {
+                    AppError::Validation(format!("Text has not been extracted from {}", doc.filename))
+                })?;
+                sources.push(Source { id: doc.id, filename: doc.filename, text });
+            }
+            let ids: Vec<&str> = document_ids.iter().map(String::as_str).collect();
+            let data_policy = policy::resolve(conn, &ids)?;
+            Ok((existing, sources, history, data_policy))
+        })
+        .await?;
+    policy::enforce(data_policy, provider.as_ref())?;
+
+    let chunks: Vec<Chunk> = sources.iter().enumerate().flat_map(|(i, s)| chunk(&s.text, i)).collect();
+    let excerpts = retrieve(&sources, &chunks, question, EXCERPT_COUNT);
+    let response = provider
+        .answer_question(question, &excerpt_block(&sources, &excerpts), &history_block(&history))
+        .await;
+    let citations = response
+        .as_ref()
+        .map(|r| resolve_citations(&sources, &excerpts, &r.citations))
+        .unwrap_or_default();
+
+    let question = question.to_string();
+    db.write(move |conn| {
+        // The redaction log is kept even when the call failed, since the
+        // prompt was still sent
+        let tx = conn.unchecked_transaction()?;
+        record_redactions(&tx, provider.as_ref(), sources.iter().map(|s| RedactionSubject::Document(&s.id)))?;
+        let response = match response {
+            Ok(response) => response,
+            Err(e) => {
+                tx.commit()?;
+                return Err(e);
+            }
+        };
+
+        let conversation = match existing {
+            Some(conversation) => conversation,
+            None => {
+                let ids: Vec<String> = sources.iter().map(|s| s.id.clone()).collect();
+                conversations::create(&tx, &title_for(&question), &ids)?
+            }
+        };
+        let question = conversations::add_message(&tx, &CreateMessage {
+            conversation_id: &conversation.id,
+            role: "user",
+            content: &question,
+            citations: &[],
+            ai_provider: None,
+            prompt_version: None,
+            data_policy: None,
+            ai_endpoint: None,
+        })?;
+        let answer = conversations::add_message(&tx, &CreateMessage {
+            conversation_id: &conversation.id,
+            role: "assistant",
+            content: &response.answer,
+            citations: &citations,
+            ai_provider: Some(provider.name()),
+            prompt_version: Some(provider.prompts().question.version),
+            data_policy: Some(data_policy.as_str()),
+            ai_endpoint: provider.endpoint(),
+        })?;
+        let conversation = conversations::get_by_id(&tx, &conversation.id)?;
+
+        // The question itself stays out of the audit log, like document text
+        audit::record(
+            &tx,
+            NewAuditEvent::new("question_answered")
+                .subject("conversation", &conversation.id)
+                .details(serde_json::json!({
+                    "document_ids": conversation.document_ids,
+                    "citations": answer.citations.len(),
+                    "ai_provider": answer.ai_provider,
+                })),
+        )?;
+        tx.commit()?;
+        Ok(Answer { conversation, question, answer, answered: response.answered })
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::mock::MockProvider;
+    use crate::ai::prompts::{PromptKind, BUILTIN_VERSION};
+    use crate::ai::OllamaProvider;
+    use crate::db::documents::CreateDocument;
+
+    const MSA: &str = "MASTER SERVICES AGREEMENT\n\n\
+        1. Services. Supplier will provide the services described in each statement of work.\n\n\
+        2. Fees. Customer shall pay all invoices within thirty (30) days of receipt.\n\n\
+        3. Termination. Either party may terminate this Agreement if the other party \
+        materially breaches it and fails to cure the breach within \u{201C}thirty (30) days\u{201D} after written notice.\n\n\
+        4. Governing Law. This Agreement is governed by the laws of the State of Delaware.";
+
+    fn source(text: &str) -> Source {
+        Source { id: "doc".into(), filename: "msa.pdf".into(), text: text.into() }
+    }
+
+    fn insert_doc(db: &Database, filename: &str, text: &str) -> String {
+        let conn = db.writer();
+        let doc = documents::insert(&conn, &CreateDocument {
+            filename: filename.into(),
+            original_path: format!("/tmp/{filename}"),
+            stored_path: format!("/data/{filename}"),
+            file_hash: filename.into(),
+            file_size: 10,
+            contract_type: "service_agreement".into(),
+        }).unwrap();
+        documents::update_text(&conn, &doc.id, text, 1).unwrap();
+        doc.id
+    }
+
+    #[test]
+    fn test_chunks_follow_paragraphs() {
+        let long = format!("{}\n\n{}\n\n{}", "alpha ".repeat(100), "beta ".repeat(100), "gamma ".repeat(500));
+        let chunks = chunk(&long, 0);
+        for c in &chunks {
+            let text = &long[c.start..c.end];
+            assert_eq!(text, text.trim());
+            assert!(text.len() <= CHUNK_MAX);
+        }
+        // alpha and beta merge; gamma is split
+        assert!(long[chunks[0].start..chunks[0].end].contains("beta"));
+        assert!(chunks.len() >= 3);
+        assert!(chunk("   \n\n ", 0).is_empty());
+    }
+
+    #[test]
+    fn test_retrieve_prefers_matching_sections() {
+        let sources = vec![source(&MSA.replace("\n\n", &format!("\n\n{}\n\n", "filler ".repeat(200))))];
+        let chunks = chunk(&sources[0].text, 0);
+        assert!(chunks.len() > 3);
+
+        let top = retrieve(&sources, &chunks, "How do we terminate for breach?", 1);
+        assert!(sources[0].text[top[0].start..top[0].end].contains("materially breaches"));
+
+        let opening = retrieve(&sources, &chunks, "zzz", 2);
+        assert_eq!(opening, chunks[..2].to_vec());
+    }
+
+    #[test]
+    fn test_locate_tolerates_formatting() {
+        let (start, end) = locate(MSA, "fails to cure the breach within \"thirty (30) days\"").unwrap();
+        assert!(MSA[start..end].starts_with("fails to cure"));
+        assert!(MSA[start..end].ends_with('\u{201D}'));
+
+        let (start, end) = locate(MSA, "either PARTY may terminate ... after written notice").unwrap();
+        assert!(MSA[start..end].starts_with("Either party"));
+        assert!(MSA[start..end].ends_with("written notice"));
+
+        assert!(locate(MSA, "laws of the State of New York").is_none());
+        assert!(locate(MSA, " ... ").is_none());
+    }
+
+    #[test]
+    fn test_citations_resolve_to_character_offsets() {
+        let text = format!("Préambule.\n\n{MSA}");
+        let sources = vec![source(&text)];
+        let excerpts = chunk(&text, 0);
+        let cited = vec![
+            AnswerCitation { excerpt: "[E1]".into(), quote: "governed by the laws of the State of Delaware".into() },
+            AnswerCitation { excerpt: "E1".into(), quote: "something the document never says".into() },
+            AnswerCitation { excerpt: "E9".into(), quote: "nowhere".into() },
+        ];
+
+        let citations = resolve_citations(&sources, &excerpts, &cited);
+        assert_eq!(citations.len(), 2);
+        let chars: Vec<char> = text.chars().collect();
+        let quoted: String = chars[citations[0].start..citations[0].end].iter().collect();
+        assert_eq!(quoted, "governed by the laws of the State of Delaware");
+        assert_eq!(citations[0].text, quoted);
+        assert!(citations[0].verified);
+        assert!(!citations[1].verified);
+        assert_eq!(citations[1].text, text);
+    }
+
+    #[tokio::test]
+    async fn test_ask_answers_and_keeps_the_conversation() {
+        let db = Database::in_memory().unwrap();
+        let doc = insert_doc(&db, "msa.pdf", MSA);
+        let mock = Arc::new(
+            MockProvider::new()
+                .respond(
+                    PromptKind::Question,
+                    r#"{"answer": "Thirty days after written notice [E1].", "answered": true,
+                        "citations": [{"excerpt": "E1", "quote": "fails to cure the breach within \"thirty (30) days\" after written notice"}]}"#,
+                )
+                .respond(PromptKind::Question, r#"{"answer": "Delaware law [E1].", "answered": true, "citations": []}"#),
+        );
+
+        let first = ask(&db, mock.clone(), None, std::slice::from_ref(&doc), "What is the cure period?").await.unwrap();
+        assert_eq!(first.conversation.title, "What is the cure period?");
+        assert_eq!(first.conversation.document_ids, vec![doc.clone()]);
+        assert_eq!(first.answer.prompt_version, Some(BUILTIN_VERSION));
+        assert_eq!(first.answer.data_policy.as_deref(), Some("standard"));
+        let citation = &first.answer.citations[0];
+        assert!(citation.verified);
+        assert!(citation.text.ends_with("after written notice"));
+
+        let second = ask(&db, mock.clone(), Some(&first.conversation.id), &[], "And which law governs?").await.unwrap();
+        assert_eq!(second.conversation.id, first.conversation.id);
+        let requests = mock.requests();
+        assert!(requests[0].user.contains("[E1] msa.pdf"));
+        assert!(requests[0].user.contains("(none)"));
+        assert!(requests[1].user.contains("User: What is the cure period?"));
+
+        let conn = db.writer();
+        let messages = conversations::list_messages(&conn, &first.conversation.id).unwrap();
+        assert_eq!(messages.len(), 4);
+        assert_eq!(conversations::list_by_document(&conn, &doc).unwrap().len(), 1);
+        let answered: Vec<_> =
+            audit::list(&conn, None, None).unwrap().into_iter().filter(|e| e.action == "question_answered").collect();
+        assert_eq!(answered.len(), 2);
+        assert!(answered.iter().all(|e| e.subject_id.as_deref() == Some(first.conversation.id.as_str())));
+        assert!(!answered[0].details.contains("cure period"));
+    }
+
+    #[tokio::test]
+    async fn test_ask_refuses_unusable_documents() {
+        let db = Database::in_memory().unwrap();
+        let mock = Arc::new(MockProvider::new());
+        assert!(matches!(ask(&db, mock.clone(), None, &[], "Anything?").await, Err(AppError::Validation(_))));
+
+        let doc = insert_doc(&db, "nda.pdf", "Confidential settlement terms.");
+        assert!(matches!(ask(&db, mock.clone(), None, std::slice::from_ref(&doc), "  ").await, Err(AppError::Validation(_))));
+
+        documents::set_local_only(&db.writer(), &doc, true).unwrap();
+        let remote: Arc<dyn AiProvider> = Arc::new(OllamaProvider::new("http://10.0.0.5:11434".into(), "llama3".into()));
+        assert!(matches!(
+            ask(&db, remote, None, std::slice::from_ref(&doc), "Who are the parties?").await,
+            Err(AppError::PolicyViolation(_))
+        ));
+
+        documents::soft_delete(&db.writer(), &doc).unwrap();
+        assert!(matches!(
+            ask(&db, mock.clone(), None, &[doc], "Who are the parties?").await,
+            Err(AppError::Validation(_))
+        ));
+        assert!(mock.requests().is_empty());
+    }
+}
@@ -0,0 +1,973 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ai::prompts::DiffOp;
+
+/// Sections are paired when at least this share of their words is common.
+const ALIGN_THRESHOLD: f64 = 0.3;
+/// A deleted run and an inserted run with the same text and at least this
+/// many words are one move rather than two changes.
+const MIN_MOVE_WORDS: usize = 5;
+/// Beyond this many edits a section is shown as replaced outright.
+const MAX_EDITS: usize = 1000;
+/// Replacements of up to this many tokens a side also get a character diff.
+const CHAR_DIFF_MAX_TOKENS: usize = 3;
+/// Unchanged tokens quoted on either side of a change.
+const CONTEXT_TOKENS: usize = 6;
+const LABEL_MAX_CHARS: usize = 60;
+/// Longer changed text is shortened in the prompt; the redline keeps it all.
+const PROMPT_TEXT_MAX_CHARS: usize = 600;
+/// Label of the text before the first heading.
+const PREAMBLE: &str = "Preamble";
+
+/// An exact, word-level redline of document B against document A.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Redline {
+    pub sections: Vec<SectionDiff>,
+    pub changes: Vec<Change>,
+    pub stats: RedlineStats,
+}
+
+impl Redline {
+    /// The changes marked in one section, in order.
+    pub fn changes_in(&self, section: usize) -> Vec<&Change> {
+        let ids: HashSet<&str> =
+            self.sections[section].segments.iter().filter_map(|s| s.change_id.as_deref()).collect();
+        self.changes.iter().filter(|c| ids.contains(c.id.as_str())).collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SectionStatus {
+    Unchanged,
+    Modified,
+    Added,
+    Removed,
+    Moved,
+}
+
+/// A section of B paired with its counterpart in A, in B's order; removed
+/// sections appear where they were in A.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SectionDiff {
+    /// The heading in each version; `None` for the text before the first
+    /// heading and on the side the section is missing from.
+    pub heading_a: Option<String>,
+    pub heading_b: Option<String>,
+    pub status: SectionStatus,
+    pub segments: Vec<Segment>,
+}
+
+/// A run of text that is in both versions, or only in one.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Segment {
+    pub op: DiffOp,
+    pub text: String,
+    /// The change the run belongs to; `None` for unchanged text.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub change_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Insert,
+    Delete,
+    Replace,
+    Move,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Change {
+    /// `C1`, `C2`, ... in document order; the model refers to changes by it.
+    pub id: String,
+    pub kind: ChangeKind,
+    /// Label of the section the change is in.
+    pub section: String,
+    /// For moves, the section the text came from.
+    pub moved_from: Option<String>,
+    /// The changed text in each version; both `None` when a whole section moved.
+    pub text_a: Option<String>,
+    pub text_b: Option<String>,
+    /// Unchanged text just before and after the change.
+    pub context_before: String,
+    pub context_after: String,
+    /// Character-level diff of short replacements, e.g. `$10,000` to `$100,000`.
+    pub char_diff: Option<Vec<Segment>>,
+}
+
+impl Change {
+    /// Plain description, used until the change is explained.
+    pub fn description(&self) -> String {
+        let quote = |text: &Option<String>| format!("\u{201C}{}\u{201D}", shorten(text.as_deref().unwrap_or(""), 80));
+        match self.kind {
+            ChangeKind::Insert => format!("Inserted {} in {}", quote(&self.text_b), self.section),
+            ChangeKind::Delete => format!("Deleted {} from {}", quote(&self.text_a), self.section),
+            ChangeKind::Replace => format!("Replaced {} with {} in {}", quote(&self.text_a), quote(&self.text_b), self.section),
+            ChangeKind::Move if self.text_b.is_none() => format!("Moved section {}", self.section),
+            ChangeKind::Move => format!(
+                "Moved {} from {} to {}",
+                quote(&self.text_b),
+                self.moved_from.as_deref().unwrap_or(PREAMBLE),
+                self.section
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct RedlineStats {
+    pub insertions: usize,
+    pub deletions: usize,
+    pub replacements: usize,
+    pub moves: usize,
+}
+
+/// A word or a punctuation mark. Whitespace only records whether one came
+/// before, so reflowed lines compare equal.
+#[derive(Debug, Clone, Copy)]
+struct Token<'a> {
+    text: &'a str,
+    spaced: bool,
+}
+
+fn is_word(token: &Token<'_>) -> bool {
+    token.text.chars().any(char::is_alphanumeric)
+}
+
+/// Splits leading and trailing punctuation off each word, so `years.` and
+/// `years` share the word; punctuation inside a word (`7.2`, `10,000`,
+/// `Supplier's`) stays.
+fn tokenize(text: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    for word in text.split_whitespace() {
+        let core_start = word.find(char::is_alphanumeric).unwrap_or(word.len());
+        let core_end = word
+            .char_indices()
+            .rev()
+            .find(|(_, c)| c.is_alphanumeric())
+            .map_or(core_start, |(i, c)| i + c.len_utf8());
+        let mut spaced = true;
+        for (i, c) in word[..core_start].char_indices() {
+            tokens.push(Token { text: &word[i..i + c.len_utf8()], spaced });
+            spaced = false;
+        }
+        if core_end > core_start {
+            tokens.push(Token { text: &word[core_start..core_end], spaced });
+            spaced = false;
+        }
+        for (i, c) in word[core_end..].char_indices() {
+            let start = core_end + i;
+            tokens.push(Token { text: &word[start..start + c.len_utf8()], spaced });
+            spaced = false;
+        }
+    }
+    tokens
+}
+
+/// Appends tokens with their spacing; `started` tracks whether anything
+/// precedes them in the section.
+fn render_into(out: &mut String, tokens: &[Token<'_>], started: bool) {
+    for (i, token) in tokens.iter().enumerate() {
+        if token.spaced && (started || i > 0) {
+            out.push(' ');
+        }
+        out.push_str(token.text);
+    }
+}
+
+fn render(tokens: &[Token<'_>]) -> String {
+    let mut out = String::new();
+    render_into(&mut out, tokens, false);
+    out
+}
+
+fn shorten(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(max_chars).collect();
+    let cut = cut.rsplit_once(' ').map_or(cut.as_str(), |(head, _)| head);
+    format!("{cut}\u{2026}")
+}
+
+struct Section<'a> {
+    label: Option<String>,
+    tokens: Vec<Token<'a>>,
+    words: HashSet<String>,
+}
+
+impl Section<'_> {
+    fn label(&self) -> &str {
+        self.label.as_deref().unwrap_or(PREAMBLE)
+    }
+}
+
+/// Numbered clauses (`1.`, `7.2`, `12)`), `Article`/`Section`/... headings
+/// and short all-caps lines.
+fn is_heading(line: &str) -> bool {
+    let Some(first) = line.split_whitespace().next() else {
+        return false;
+    };
+    let number = first.trim_end_matches(['.', ')']);
+    if !number.is_empty() && number.split('.').all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit())) {
+        return line[first.len()..].trim_start().starts_with(char::is_uppercase);
+    }
+    if line.chars().count() > 80 {
+        return false;
+    }
+    let keyword = ["article", "section", "clause", "schedule", "exhibit", "annex", "appendix"]
+        .contains(&first.to_lowercase().as_str());
+    let numbered = line
+        .split_whitespace()
+        .nth(1)
+        .is_some_and(|w| w.starts_with(|c: char| c.is_ascii_digit() || "IVXLC".contains(c)));
+    (keyword && numbered) || (line.chars().filter(|c| c.is_alphabetic()).count() >= 4 && !line.chars().any(char::is_lowercase))
+}
+
+fn split_sections(text: &str) -> Vec<Section<'_>> {
+    let mut sections = vec![Section { label: None, tokens: Vec::new(), words: HashSet::new() }];
+    for line in text.lines() {
+        let line = line.trim();
+        if is_heading(line) {
+            sections.push(Section { label: Some(shorten(line, LABEL_MAX_CHARS)), tokens: Vec::new(), words: HashSet::new() });
+        }
+        let section = sections.last_mut().expect("there is always a section");
+        section.tokens.extend(tokenize(line));
+    }
+    if sections.len() > 1 && sections[0].tokens.is_empty() {
+        sections.remove(0);
+    }
+    for section in &mut sections {
+        // Clause numbers change whenever a section is added, so only words count
+        section.words = section
+            .tokens
+            .iter()
+            .filter(|t| t.text.chars().any(char::is_alphabetic))
+            .map(|t| t.text.to_lowercase())
+            .collect();
+    }
+    sections
+}
+
+/// Share of distinct words the two sections have in common.
+fn similarity(a: &Section<'_>, b: &Section<'_>) -> f64 {
+    if a.words.is_empty() || b.words.is_empty() {
+        return if a.words.is_empty() && b.words.is_empty() { 1.0 } else { 0.0 };
+    }
+    let common = a.words.intersection(&b.words).count();
+    common as f64 / (a.words.len() + b.words.len() - common) as f64
+}
+
+/// How each entry of the aligned section list pairs A and B.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Pairing {
+    Both(usize, usize),
+    Moved(usize, usize),
+    OnlyA(usize),
+    OnlyB(usize),
+}
+
+/// Pairs sections in order, maximizing total similarity, then pairs what is
+/// left over across positions as moves.
+fn align(a: &[Section<'_>], b: &[Section<'_>]) -> Vec<Pairing> {
+    let sim: Vec<Vec<f64>> = a.iter().map(|sa| b.iter().map(|sb| similarity(sa, sb)).collect()).collect();
+    let pairable = |i: usize, j: usize| {
+        (a.len() == 1 && b.len() == 1)
+            || sim[i][j] >= ALIGN_THRESHOLD
+            || (a[i].label.is_some() && a[i].label == b[j].label)
+    };
+
+    // best[i][j] = highest total similarity aligning a[i..] with b[j..]
+    let mut best = vec![vec![0.0f64; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            let mut score = best[i + 1][j].max(best[i][j + 1]);
+            if pairable(i, j) {
+                score = score.max(best[i + 1][j + 1] + sim[i][j] + 1.0);
+            }
+            best[i][j] = score;
+        }
+    }
+
+    let mut pairings = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if pairable(i, j) && best[i][j] == best[i + 1][j + 1] + sim[i][j] + 1.0 {
+            pairings.push(Pairing::Both(i, j));
+            i += 1;
+            j += 1;
+        } else if best[i + 1][j] >= best[i][j + 1] {
+            pairings.push(Pairing::OnlyA(i));
+            i += 1;
+        } else {
+            pairings.push(Pairing::OnlyB(j));
+            j += 1;
+        }
+    }
+    pairings.extend((i..a.len()).map(Pairing::OnlyA));
+    pairings.extend((j..b.len()).map(Pairing::OnlyB));
+
+    // The moved-from entries are dropped once every move is found, so
+    // positions stay valid while searching
+    let mut consumed = vec![false; pairings.len()];
+    for entry in 0..pairings.len() {
+        let Pairing::OnlyB(j) = pairings[entry] else { continue };
+        let source = pairings
+            .iter()
+            .enumerate()
+            .filter_map(|(at, p)| match p {
+                Pairing::OnlyA(i) if !consumed[at] && !a[*i].words.is_empty() && sim[*i][j] >= ALIGN_THRESHOLD => {
+                    Some((at, *i))
+                }
+                _ => None,
+            })
+            .max_by(|x, y| sim[x.1][j].total_cmp(&sim[y.1][j]));
+        if let Some((at, i)) = source {
+            pairings[entry] = Pairing::Moved(i, j);
+            consumed[at] = true;
+        }
+    }
+    pairings.into_iter().zip(consumed).filter(|(_, consumed)| !consumed).map(|(p, _)| p).collect()
+}
+
+/// Shortest edit script between two sequences (Myers), or `None` when it
+/// needs more than `MAX_EDITS` edits.
+fn myers<T: PartialEq>(a: &[T], b: &[T]) -> Option<Vec<DiffOp>> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = a.len() + b.len();
+    let offset = max as isize + 1;
+    let mut v = vec![0isize; 2 * max + 3];
+    // trace[d] holds the furthest x on diagonals -d..=d before step d
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+    for d in 0..=max.min(MAX_EDITS) as isize {
+        trace.push(v[(offset - d) as usize..=(offset + d) as usize].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let at = (offset + k) as usize;
+            let mut x = if k == -d || (k != d && v[at - 1] < v[at + 1]) { v[at + 1] } else { v[at - 1] + 1 };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[at] = x;
+            if x >= n && y >= m {
+                return Some(backtrack(&trace, n, m));
+            }
+        }
+    }
+    None
+}
+
+fn backtrack(trace: &[Vec<isize>], n: isize, m: isize) -> Vec<DiffOp> {
+    let mut ops = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for d in (1..trace.len() as isize).rev() {
+        let v = &trace[d as usize];
+        let furthest = |k: isize| v[(k + d) as usize];
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && furthest(k - 1) < furthest(k + 1)) { k + 1 } else { k - 1 };
+        let prev_x = furthest(prev_k);
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            ops.push(DiffOp::Equal);
+            x -= 1;
+            y -= 1;
+        }
+        ops.push(if prev_k == k + 1 { DiffOp::Insert } else { DiffOp::Delete });
+        x = prev_x;
+        y = prev_y;
+    }
+    ops.extend((0..x.min(y)).map(|_| DiffOp::Equal));
+    ops.reverse();
+    ops
+}
+
+/// Edit script with the common prefix and suffix taken off first, since
+/// versions of a contract are mostly the same.
+fn diff<T: PartialEq>(a: &[T], b: &[T]) -> Vec<DiffOp> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..].iter().rev().zip(b[prefix..].iter().rev()).take_while(|(x, y)| x == y).count();
+    let (a_mid, b_mid) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    let mut ops = vec![DiffOp::Equal; prefix];
+    match myers(a_mid, b_mid) {
+        Some(mid) => ops.extend(mid),
+        None => {
+            ops.resize(ops.len() + a_mid.len(), DiffOp::Delete);
+            ops.resize(ops.len() + b_mid.len(), DiffOp::Insert);
+        }
+    }
+    ops.resize(ops.len() + suffix, DiffOp::Equal);
+    ops
+}
+
+/// Moves each pure insertion or deletion as far right as it can go, so a
+/// sentence added after `secret.` reads `Late ... monthly.` rather than
+/// `. Late ... monthly`.
+fn slide_right(ops: &mut [DiffOp], a: &[&str], b: &[&str]) {
+    let (mut i, mut j, mut at) = (0, 0, 0);
+    while at < ops.len() {
+        let op = ops[at];
+        if op == DiffOp::Equal {
+            (i, j, at) = (i + 1, j + 1, at + 1);
+            continue;
+        }
+        let end = at + ops[at..].iter().take_while(|o| **o == op).count();
+        let (tokens, start) = if op == DiffOp::Insert { (b, j) } else { (a, i) };
+        let pure = at == 0 || ops[at - 1] == DiffOp::Equal;
+        if pure && end < ops.len() && ops[end] == DiffOp::Equal && tokens[start] == tokens[start + end - at] {
+            ops[at] = DiffOp::Equal;
+            ops[end] = op;
+            continue;
+        }
+        match op {
+            DiffOp::Insert => j += end - at,
+            _ => i += end - at,
+        }
+        at = end;
+    }
+}
+
+/// Character diff of a short replacement, when enough of it is shared to
+/// be worth showing.
+fn char_diff(text_a: &str, text_b: &str) -> Option<Vec<Segment>> {
+    let a: Vec<char> = text_a.chars().collect();
+    let b: Vec<char> = text_b.chars().collect();
+    let ops = myers(&a, &b)?;
+    let shared = ops.iter().filter(|op| **op == DiffOp::Equal).count();
+    if shared * 2 < a.len().min(b.len()) || shared == 0 {
+        return None;
+    }
+
+    let mut segments: Vec<Segment> = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    for op in ops {
+        let c = match op {
+            DiffOp::Equal | DiffOp::Delete => a[i],
+            DiffOp::Insert => b[j],
+        };
+        match op {
+            DiffOp::Equal => (i, j) = (i + 1, j + 1),
+            DiffOp::Delete => i += 1,
+            DiffOp::Insert => j += 1,
+        }
+        match segments.last_mut() {
+            Some(last) if last.op == op => last.text.push(c),
+            _ => segments.push(Segment { op, text: c.to_string(), change_id: None }),
+        }
+    }
+    Some(segments)
+}
+
+struct Hunk<'a> {
+    section: usize,
+    deleted: Vec<Token<'a>>,
+    inserted: Vec<Token<'a>>,
+    before: String,
+    after: String,
+    /// The hunk at the other end of a move.
+    partner: Option<usize>,
+    id: Option<String>,
+}
+
+impl Hunk<'_> {
+    fn words(tokens: &[Token<'_>]) -> usize {
+        tokens.iter().filter(|t| is_word(t)).count()
+    }
+}
+
+enum Piece<'a> {
+    Same(Vec<Token<'a>>),
+    Changed(usize),
+}
+
+struct Aligned<'a> {
+    heading_a: Option<String>,
+    heading_b: Option<String>,
+    status: SectionStatus,
+    pieces: Vec<Piece<'a>>,
+    /// For sections moved whole, the section they came from.
+    moved_from: Option<String>,
+}
+
+fn context(tokens: &[Token<'_>], before: bool) -> String {
+    if before {
+        render(&tokens[tokens.len().saturating_sub(CONTEXT_TOKENS)..])
+    } else {
+        render(&tokens[..tokens.len().min(CONTEXT_TOKENS)])
+    }
+}
+
+/// Diffs one pair of sections into pieces, adding its changes to `hunks`.
+fn diff_section<'a>(
+    section: usize,
+    a: &[Token<'a>],
+    b: &[Token<'a>],
+    hunks: &mut Vec<Hunk<'a>>,
+) -> Vec<Piece<'a>> {
+    let keys_a: Vec<&str> = a.iter().map(|t| t.text).collect();
+    let keys_b: Vec<&str> = b.iter().map(|t| t.text).collect();
+    // Context comes from B where the section exists there
+    let (context_tokens, on_b) = if b.is_empty() { (a, false) } else { (b, true) };
+
+    let mut pieces = Vec::new();
+    let mut same = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    let mut ops = diff(&keys_a, &keys_b);
+    slide_right(&mut ops, &keys_a, &keys_b);
+    let mut at = 0;
+    while at < ops.len() {
+        if ops[at] == DiffOp::Equal {
+            same.push(b[j]);
+            (i, j, at) = (i + 1, j + 1, at + 1);
+            continue;
+        }
+        if !same.is_empty() {
+            pieces.push(Piece::Same(std::mem::take(&mut same)));
+        }
+        let (start_a, start_b) = (i, j);
+        while at < ops.len() && ops[at] != DiffOp::Equal {
+            match ops[at] {
+                DiffOp::Delete => i += 1,
+                _ => j += 1,
+            }
+            at += 1;
+        }
+        let (start, end) = if on_b { (start_b, j) } else { (start_a, i) };
+        hunks.push(Hunk {
+            section,
+            deleted: a[start_a..i].to_vec(),
+            inserted: b[start_b..j].to_vec(),
+            before: context(&context_tokens[..start], true),
+            after: context(&context_tokens[end..], false),
+            partner: None,
+            id: None,
+        });
+        pieces.push(Piece::Changed(hunks.len() - 1));
+    }
+    if !same.is_empty() {
+        pieces.push(Piece::Same(same));
+    }
+    pieces
+}
+
+/// Pairs a pure deletion with a pure insertion of the same text.
+fn pair_moves(hunks: &mut [Hunk<'_>]) {
+    fn key<'a>(tokens: &[Token<'a>]) -> Vec<&'a str> {
+        tokens.iter().map(|t| t.text).collect()
+    }
+    for d in 0..hunks.len() {
+        let hunk = &hunks[d];
+        if !hunk.inserted.is_empty() || hunk.partner.is_some() || Hunk::words(&hunk.deleted) < MIN_MOVE_WORDS {
+            continue;
+        }
+        let text = key(&hunk.deleted);
+        let found = (0..hunks.len()).find(|&i| {
+            hunks[i].deleted.is_empty() && hunks[i].partner.is_none() && key(&hunks[i].inserted) == text
+        });
+        if let Some(i) = found {
+            hunks[d].partner = Some(i);
+            hunks[i].partner = Some(d);
+        }
+    }
+}
+
+/// Compares two versions of a contract: sections are aligned by content,
+/// then diffed word by word, and moved text is recognized as such.
+pub fn redline(text_a: &str, text_b: &str) -> Redline {
+    let sections_a = split_sections(text_a);
+    let sections_b = split_sections(text_b);
+    let pairings = align(&sections_a, &sections_b);
+    assemble(&sections_a, &sections_b, pairings)
+}
+
+/// A passage compared on its own, such as one extracted clause.
+pub struct Passage<'a> {
+    pub label: String,
+    pub text: &'a str,
+}
+
+/// Redline of passages the caller has already paired, one section per pair
+/// in the order given. Text moved between passages is still found.
+pub fn redline_pairs(pairs: &[(Option<Passage<'_>>, Option<Passage<'_>>)]) -> Redline {
+    fn section<'a>(p: &Passage<'a>) -> Section<'a> {
+        Section { label: Some(p.label.clone()), tokens: tokenize(p.text), words: HashSet::new() }
+    }
+    let (mut sections_a, mut sections_b, mut pairings) = (Vec::new(), Vec::new(), Vec::new());
+    for (a, b) in pairs {
+        let i = a.as_ref().map(|p| {
+            sections_a.push(section(p));
+            sections_a.len() - 1
+        });
+        let j = b.as_ref().map(|p| {
+            sections_b.push(section(p));
+            sections_b.len() - 1
+        });
+        pairings.push(match (i, j) {
+            (Some(i), Some(j)) => Pairing::Both(i, j),
+            (Some(i), None) => Pairing::OnlyA(i),
+            (None, Some(j)) => Pairing::OnlyB(j),
+            (None, None) => continue,
+        });
+    }
+    assemble(&sections_a, &sections_b, pairings)
+}
+
+fn assemble(sections_a: &[Section<'_>], sections_b: &[Section<'_>], pairings: Vec<Pairing>) -> Redline {
+    let mut hunks = Vec::new();
+    let mut aligned = Vec::new();
+    for (index, pairing) in pairings.into_iter().enumerate() {
+        let (a, b, moved) = match pairing {
+            Pairing::Both(i, j) => (Some(&sections_a[i]), Some(&sections_b[j]), false),
+            Pairing::Moved(i, j) => (Some(&sections_a[i]), Some(&sections_b[j]), true),
+            Pairing::OnlyA(i) => (Some(&sections_a[i]), None, false),
+            Pairing::OnlyB(j) => (None, Some(&sections_b[j]), false),
+        };
+        let before = hunks.len();
+        let pieces = diff_section(
+            index,
+            a.map_or(&[][..], |s| &s.tokens),
+            b.map_or(&[][..], |s| &s.tokens),
+            &mut hunks,
+        );
+        let status = match (a, b) {
+            _ if moved => SectionStatus::Moved,
+            (Some(_), None) => SectionStatus::Removed,
+            (None, Some(_)) => SectionStatus::Added,
+            _ if hunks.len() > before => SectionStatus::Modified,
+            _ => SectionStatus::Unchanged,
+        };
+        aligned.push(Aligned {
+            heading_a: a.and_then(|s| s.label.clone()),
+            heading_b: b.and_then(|s| s.label.clone()),
+            status,
+            pieces,
+            moved_from: a.filter(|_| moved).map(|s| s.label().to_string()),
+        });
+    }
+    pair_moves(&mut hunks);
+
+    let label = |section: &Aligned<'_>| {
+        section.heading_b.clone().or_else(|| section.heading_a.clone()).unwrap_or_else(|| PREAMBLE.to_string())
+    };
+    let mut changes = Vec::new();
+    let mut sections = Vec::new();
+    for section in &aligned {
+        if let Some(from) = &section.moved_from {
+            let id = format!("C{}", changes.len() + 1);
+            changes.push(Change {
+                id,
+                kind: ChangeKind::Move,
+                section: label(section),
+                moved_from: Some(from.clone()),
+                text_a: None,
+                text_b: None,
+                context_before: String::new(),
+                context_after: String::new(),
+                char_diff: None,
+            });
+        }
+
+        let mut segments = Vec::new();
+        let mut started = false;
+        for piece in &section.pieces {
+            match piece {
+                Piece::Same(tokens) => {
+                    let mut text = String::new();
+                    render_into(&mut text, tokens, started);
+                    segments.push(Segment { op: DiffOp::Equal, text, change_id: None });
+                    started = true;
+                }
+                Piece::Changed(h) => {
+                    let h = *h;
+                    let id = match hunks[h].id.clone() {
+                        Some(id) => id,
+                        None => {
+                            let id = format!("C{}", changes.len() + 1);
+                            changes.push(change(&id, &hunks, h, &aligned, label));
+                            hunks[h].id = Some(id.clone());
+                            if let Some(partner) = hunks[h].partner {
+                                hunks[partner].id = Some(id.clone());
+                            }
+                            id
+                        }
+                    };
+                    for (op, tokens) in [(DiffOp::Delete, &hunks[h].deleted), (DiffOp::Insert, &hunks[h].inserted)] {
+                        if tokens.is_empty() {
+                            continue;
+                        }
+                        let mut text = String::new();
+                        render_into(&mut text, tokens, started);
+                        segments.push(Segment { op, text, change_id: Some(id.clone()) });
+                    }
+                    started = true;
+                }
+            }
+        }
+        sections.push(SectionDiff {
+            heading_a: section.heading_a.clone(),
+            heading_b: section.heading_b.clone(),
+            status: section.status,
+            segments,
+        });
+    }
+
+    let count = |kind: ChangeKind| changes.iter().filter(|c| c.kind == kind).count();
+    let stats = RedlineStats {
+        insertions: count(ChangeKind::Insert),
+        deletions: count(ChangeKind::Delete),
+        replacements: count(ChangeKind::Replace),
+        moves: count(ChangeKind::Move),
+    };
+    Redline { sections, changes, stats }
+}
+
+fn change<'a>(
+    id: &str,
+    hunks: &[Hunk<'a>],
+    h: usize,
+    aligned: &[Aligned<'a>],
+    label: impl Fn(&Aligned<'a>) -> String,
+) -> Change {
+    let hunk = &hunks[h];
+    let text_a = (!hunk.deleted.is_empty()).then(|| render(&hunk.deleted));
+    let text_b = (!hunk.inserted.is_empty()).then(|| render(&hunk.inserted));
+    if let Some(partner) = hunk.partner {
+        // Described from where the text ended up
+        let (from, to) = if hunk.inserted.is_empty() { (hunk, &hunks[partner]) } else { (&hunks[partner], hunk) };
+        let text = render(&to.inserted);
+        return Change {
+            id: id.to_string(),
+            kind: ChangeKind::Move,
+            section: label(&aligned[to.section]),
+            moved_from: Some(label(&aligned[from.section])),
+            text_a: Some(text.clone()),
+            text_b: Some(text),
+            context_before: to.before.clone(),
+            context_after: to.after.clone(),
+            char_diff: None,
+        };
+    }
+    let kind = match (&text_a, &text_b) {
+        (Some(_), Some(_)) => ChangeKind::Replace,
+        (Some(_), None) => ChangeKind::Delete,
+        _ => ChangeKind::Insert,
+    };
+    let char_diff = match (&text_a, &text_b) {
+        (Some(a), Some(b))
+            if hunk.deleted.len() <= CHAR_DIFF_MAX_TOKENS && hunk.inserted.len() <= CHAR_DIFF_MAX_TOKENS =>
+        {
+            char_diff(a, b)
+        }
+        _ => None,
+    };
+    Change {
+        id: id.to_string(),
+        kind,
+        section: label(&aligned[hunk.section]),
+        moved_from: None,
+        text_a,
+        text_b,
+        context_before: hunk.before.clone(),
+        context_after: hunk.after.clone(),
+        char_diff,
+    }
+}
+
+/// The changes as listed in the comparison prompt.
+pub fn describe_changes<'a>(changes: impl IntoIterator<Item = &'a Change>) -> String {
+    let quote = |text: &Option<String>| format!("\"{}\"", shorten(text.as_deref().unwrap_or(""), PROMPT_TEXT_MAX_CHARS));
+    let mut out = String::new();
+    for change in changes {
+        let line = match change.kind {
+            ChangeKind::Insert => format!("inserted in \"{}\": {}", change.section, quote(&change.text_b)),
+            ChangeKind::Delete => format!("deleted from \"{}\": {}", change.section, quote(&change.text_a)),
+            ChangeKind::Replace => format!(
+                "replaced in \"{}\": {} -> {}",
+                change.section,
+                quote(&change.text_a),
+                quote(&change.text_b)
+            ),
+            ChangeKind::Move if change.text_b.is_none() => format!(
+                "section moved: \"{}\" is now \"{}\"",
+                change.moved_from.as_deref().unwrap_or(PREAMBLE),
+                change.section
+            ),
+            ChangeKind::Move => format!(
+                "moved from \"{}\" to \"{}\": {}",
+                change.moved_from.as_deref().unwrap_or(PREAMBLE),
+                change.section,
+                quote(&change.text_b)
+            ),
+        };
+        out.push_str(&format!("[{}] {line}\n", change.id));
+        if !change.context_before.is_empty() || !change.context_after.is_empty() {
+            out.push_str(&format!("    context: ...{} [here] {}...\n", change.context_before, change.context_after));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_of(section: &SectionDiff, op: DiffOp) -> String {
+        section.segments.iter().filter(|s| s.op == op).map(|s| s.text.as_str()).collect()
+    }
+
+    #[test]
+    fn test_single_word_change_is_found() {
+        let a = "7. Termination\nThe Supplier shall terminate this Agreement on thirty days' notice.";
+        let b = "7. Termination\nThe Supplier may terminate this Agreement\non thirty  days' notice.";
+        let redline = redline(a, b);
+
+        assert_eq!(redline.changes.len(), 1);
+        let change = &redline.changes[0];
+        assert_eq!((change.id.as_str(), change.kind), ("C1", ChangeKind::Replace));
+        assert_eq!((change.text_a.as_deref(), change.text_b.as_deref()), (Some("shall"), Some("may")));
+        assert_eq!(change.section, "7. Termination");
+        assert!(change.context_before.ends_with("The Supplier"));
+        assert!(change.context_after.starts_with("terminate this"));
+        assert_eq!(change.char_diff, None);
+        assert_eq!(redline.stats.replacements, 1);
+
+        let section = &redline.sections[0];
+        assert_eq!(section.status, SectionStatus::Modified);
+        assert_eq!(text_of(section, DiffOp::Delete), " shall");
+        assert_eq!(text_of(section, DiffOp::Insert), " may");
+        assert_eq!(
+            section.segments.iter().filter(|s| s.op != DiffOp::Insert).map(|s| s.text.as_str()).collect::<String>(),
+            "7. Termination The Supplier shall terminate this Agreement on thirty days' notice."
+        );
+    }
+
+    #[test]
+    fn test_identical_text_has_no_changes() {
+        let redline = redline("Term: two\nyears.", "Term:  two years.");
+        assert!(redline.changes.is_empty());
+        assert_eq!(redline.sections.len(), 1);
+        assert_eq!(redline.sections[0].status, SectionStatus::Unchanged);
+        assert_eq!(redline.sections[0].heading_a, None);
+    }
+
+    #[test]
+    fn test_sections_are_aligned_by_content() {
+        let a = "MASTER SERVICES AGREEMENT\n\
+            1. Services. Supplier will provide the services in each statement of work.\n\
+            2. Fees. Customer shall pay all invoices within thirty days of receipt.\n\
+            3. Governing Law. This Agreement is governed by the laws of Delaware.";
+        let b = "MASTER SERVICES AGREEMENT\n\
+            1. Services. Supplier will provide the services in each statement of work.\n\
+            2. Fees. Customer shall pay all undisputed invoices within sixty days of receipt.\n\
+            3. Audit. Customer may audit Supplier's records once a year.\n\
+            4. Governing Law. This Agreement is governed by the laws of Delaware.";
+        let redline = redline(a, b);
+
+        let statuses: Vec<_> = redline.sections.iter().map(|s| s.status).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                SectionStatus::Unchanged,
+                SectionStatus::Unchanged,
+                SectionStatus::Modified,
+                SectionStatus::Added,
+                SectionStatus::Modified,
+            ]
+        );
+        let summary: Vec<_> = redline
+            .changes
+            .iter()
+            .map(|c| (c.kind, c.text_a.as_deref(), c.text_b.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (ChangeKind::Insert, None, Some("undisputed")),
+                (ChangeKind::Replace, Some("thirty"), Some("sixty")),
+                (ChangeKind::Insert, None, Some("3. Audit. Customer may audit Supplier's records once a year.")),
+                (ChangeKind::Replace, Some("3"), Some("4")),
+            ]
+        );
+        assert_eq!(redline.changes[2].section, "3. Audit. Customer may audit Supplier's records once a year.");
+        assert_eq!(redline.sections[4].heading_a.as_deref(), Some("3. Governing Law. This Agreement is governed by the laws of\u{2026}"));
+    }
+
+    #[test]
+    fn test_moves_are_detected() {
+        let a = "1. Payment\nInvoices are due in thirty days. Late amounts accrue interest at one percent monthly.\n\
+            2. Confidentiality\nEach party keeps the other's information secret.\n\
+            3. Notices\nNotices must be in writing.";
+        let b = "1. Payment\nInvoices are due in thirty days.\n\
+            2. Notices\nNotices must be in writing.\n\
+            3. Confidentiality\nEach party keeps the other's information secret. Late amounts accrue interest at one percent monthly.";
+        let redline = redline(a, b);
+
+        let moves: Vec<_> = redline.changes.iter().filter(|c| c.kind == ChangeKind::Move).collect();
+        assert_eq!(moves.len(), 2, "{:#?}", redline.changes);
+        assert!(redline.sections.iter().any(|s| s.status == SectionStatus::Moved));
+        let sentence = moves.iter().find(|c| c.text_b.as_deref().is_some_and(|t| t.starts_with("Late amounts"))).unwrap();
+        assert_eq!(sentence.moved_from.as_deref(), Some("1. Payment"));
+        assert_eq!(sentence.section, "3. Confidentiality");
+        // Both ends of the moved sentence point at the one change
+        let marked = redline
+            .sections
+            .iter()
+            .flat_map(|s| &s.segments)
+            .filter(|s| s.change_id.as_deref() == Some(sentence.id.as_str()))
+            .map(|s| s.op)
+            .collect::<Vec<_>>();
+        assert_eq!(marked, vec![DiffOp::Delete, DiffOp::Insert]);
+        assert_eq!(redline.stats.moves, 2);
+    }
+
+    #[test]
+    fn test_moved_section_followed_by_another() {
+        let a = "1. Payment\nInvoices are due in thirty days.\n\
+            2. Confidentiality\nEach party keeps the other's information secret.\n\
+            3. Notices\nNotices must be in writing and delivered by hand.\n\
+            4. Governing Law\nThis Agreement is governed by the laws of Delaware.";
+        let b = "1. Payment\nInvoices are due in thirty days.\n\
+            2. Notices\nNotices must be in writing and delivered by hand.\n\
+            3. Confidentiality\nEach party keeps the other's information secret.\n\
+            4. Governing Law\nThis Agreement is governed by the laws of Delaware.";
+        let redline = redline(a, b);
+
+        let statuses: Vec<_> = redline.sections.iter().map(|s| s.status).collect();
+        assert_eq!(statuses.len(), 4, "{statuses:?}");
+        assert_eq!(statuses.iter().filter(|s| **s == SectionStatus::Moved).count(), 1);
+        assert!(!statuses.contains(&SectionStatus::Added) && !statuses.contains(&SectionStatus::Removed));
+        assert_eq!(redline.sections[3].heading_b.as_deref(), Some("4. Governing Law"));
+        assert_eq!(redline.stats.moves, 1);
+    }
+
+    #[test]
+    fn test_short_replacements_get_a_character_diff() {
+        let redline = redline("The cap is $10,000 per claim.", "The cap is $100,000 per claim.");
+        let change = &redline.changes[0];
+        assert_eq!((change.text_a.as_deref(), change.text_b.as_deref()), (Some("10,000"), Some("100,000")));
+        let chars: Vec<_> = change.char_diff.as_ref().unwrap().iter().map(|s| (s.op, s.text.as_str())).collect();
+        assert_eq!(chars, vec![(DiffOp::Equal, "10"), (DiffOp::Insert, "0"), (DiffOp::Equal, ",000")]);
+    }
+
+    #[test]
+    fn test_myers_edit_script() {
+        let ops = diff(&["a", "b", "c", "a", "b", "b", "a"], &["c", "b", "a", "b", "a", "c"]);
+        assert_eq!(ops.iter().filter(|op| **op != DiffOp::Insert).count(), 7);
+        assert_eq!(ops.iter().filter(|op| **op != DiffOp::Delete).count(), 6);
+        assert_eq!(ops.iter().filter(|op| **op != DiffOp::Equal).count(), 5);
+    }
+
+    #[test]
+    fn test_describe_changes_for_the_prompt() {
+        let redline = redline("Term: two years.", "Term: three years.");
+        assert_eq!(
+            describe_changes(&redline.changes),
+            "[C1] replaced in \"Preamble\": \"two\" -> \"three\"\n    context: ...Term: [here] years....\n"
+        );
+        assert_eq!(redline.changes[0].description(), "Replaced \u{201C}two\u{201D} with \u{201C}three\u{201D} in Preamble");
+    }
+}
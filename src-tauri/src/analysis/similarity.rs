@@ -0,0 +1,217 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::ai::{policy, AiProvider};
+use crate::db::clauses::{self, Clause, SimilarClause};
+use crate::db::Database;
+use crate::error::{AppError, AppResult};
+
+/// Clauses sent to the embedder per request.
+const BATCH_SIZE: usize = 32;
+
+/// What to find similar clauses for.
+pub enum SimilarTo {
+    Clause(i64),
+    Text(String),
+}
+
+fn embedding_model(embedder: &dyn AiProvider) -> AppResult<String> {
+    embedder
+        .embedding_model()
+        .ok_or_else(|| AppError::AiProvider(format!("The {} provider does not support embeddings", embedder.name())))
+}
+
+/// The title carries the clause type in the reader's words, so it is
+/// embedded along with the text.
+fn embedding_input(clause: &Clause) -> String {
+    format!("{}\n{}", clause.title, clause.text)
+}
+
+async fn embed_one(embedder: &dyn AiProvider, text: String) -> AppResult<Vec<f32>> {
+    embedder
+        .embed(&[text])
+        .await?
+        .pop()
+        .ok_or_else(|| AppError::AiProvider("The embedder returned no vector".to_string()))
+}
+
+/// Embeds every current clause that has no vector from the embedder's model
+/// yet and returns how many were embedded. Clauses of local-only documents
+/// are skipped when the embedder would send them off this machine.
+pub async fn index_clauses(db: &Database, embedder: Arc<dyn AiProvider>) -> AppResult<usize> {
+    let model = embedding_model(embedder.as_ref())?;
+    let (checked, unembedded) = (embedder.clone(), model.clone());
+    let pending = db
+        .read(move |conn| {
+            policy::enforce(policy::resolve(conn, &[])?, checked.as_ref())?;
+            let mut allowed: HashMap<String, bool> = HashMap::new();
+            let mut pending = Vec::new();
+            for clause in clauses::list_unembedded(conn, &unembedded)? {
+                let ok = match allowed.get(&clause.document_id) {
+                    Some(ok) => *ok,
+                    None => {
+                        let data_policy = policy::resolve(conn, &[&clause.document_id])?;
+                        let ok = policy::enforce(data_policy, checked.as_ref()).is_ok();
+                        allowed.insert(clause.document_id.clone(), ok);
+                        ok
+                    }
+                };
+                if ok {
+                    pending.push(clause);
+                }
+            }
+            Ok(pending)
+        })
+        .await?;
+
+    for batch in pending.chunks(BATCH_SIZE) {
+        let texts: Vec<String> = batch.iter().map(embedding_input).collect();
+        let vectors = embedder.embed(&texts).await?;
+        let ids: Vec<i64> = batch.iter().map(|clause| clause.id).collect();
+        let model = model.clone();
+        db.write(move |conn| {
+            for (id, vector) in ids.into_iter().zip(&vectors) {
+                clauses::set_embedding(conn, id, &model, vector)?;
+            }
+            Ok(())
+        })
+        .await?;
+    }
+    Ok(pending.len())
+}
+
+/// The `limit` current clauses across all documents closest to a clause or a
+/// free-text query. Clauses not yet embedded are indexed first.
+pub async fn find_similar(
+    db: &Database,
+    embedder: Arc<dyn AiProvider>,
+    target: SimilarTo,
+    limit: usize,
+) -> AppResult<Vec<SimilarClause>> {
+    let model = embedding_model(embedder.as_ref())?;
+    index_clauses(db, embedder.clone()).await?;
+
+    let (vector, exclude) = match target {
+        SimilarTo::Clause(id) => {
+            let (checked, model) = (embedder.clone(), model.clone());
+            let (clause, stored) = db
+                .read(move |conn| {
+                    let clause = clauses::get_by_id(conn, id)?;
+                    policy::enforce(policy::resolve(conn, &[&clause.document_id])?, checked.as_ref())?;
+                    let stored = clauses::get_embedding(conn, id, &model)?;
+                    Ok((clause, stored))
+                })
+                .await?;
+            // Clauses from superseded extractions are not indexed
+            let vector = match stored {
+                Some(vector) => vector,
+                None => embed_one(embedder.as_ref(), embedding_input(&clause)).await?,
+            };
+            (vector, Some(id))
+        }
+        SimilarTo::Text(text) => {
+            let text = text.trim().to_string();
+            if text.is_empty() {
+                return Err(AppError::Validation("Enter text to find similar clauses".to_string()));
+            }
+            (embed_one(embedder.as_ref(), text).await?, None)
+        }
+    };
+
+    db.read(move |conn| clauses::nearest(conn, &model, &vector, exclude, limit)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::{LocalEmbedder, OllamaProvider};
+    use crate::db::documents::{self, CreateDocument};
+    use crate::db::extractions::{self, CreateExtraction};
+
+    fn analyzed_doc(db: &Database, filename: &str, clauses: &[(&str, &str)]) -> String {
+        let conn = db.writer();
+        let doc = documents::insert(&conn, &CreateDocument {
+            filename: filename.into(),
+            original_path: format!("/tmp/{filename}"),
+            stored_path: format!("/data/{filename}"),
+            file_hash: filename.into(),
+            file_size: 10,
+            contract_type: "service_agreement".into(),
+        }).unwrap();
+        let clauses: Vec<_> = clauses
+            .iter()
+            .map(|(title, text)| serde_json::json!({ "clause_type": "other", "title": title, "text": text, "importance": "medium" }))
+            .collect();
+        extractions::insert(&conn, &CreateExtraction {
+            document_id: doc.id.clone(),
+            ai_provider: "mock".into(),
+            ai_model: None,
+            contract_type: "service_agreement".into(),
+            extracted_data: serde_json::json!({ "parties": [], "clauses": clauses }).to_string(),
+            confidence_score: None,
+            processing_time_ms: None,
+            prompt_version: None,
+            data_policy: None,
+            ai_endpoint: None,
+        }).unwrap();
+        doc.id
+    }
+
+    fn clause_id(db: &Database, document_id: &str, title: &str) -> i64 {
+        db.writer()
+            .query_row(
+                "SELECT id FROM clauses WHERE document_id = ?1 AND title = ?2",
+                rusqlite::params![document_id, title],
+                |row| row.get(0),
+            )
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_find_similar_across_documents() {
+        let db = Database::in_memory().unwrap();
+        let msa = analyzed_doc(&db, "msa.pdf", &[
+            ("Indemnification", "Supplier shall indemnify and hold harmless the Customer."),
+            ("Governing Law", "Governed by the laws of the State of Delaware."),
+        ]);
+        let sow = analyzed_doc(&db, "sow.pdf", &[
+            ("Indemnity", "The Customer shall be indemnified and held harmless by the Supplier."),
+            ("Payment", "Invoices are payable within thirty days."),
+        ]);
+        let embedder: Arc<dyn AiProvider> = Arc::new(LocalEmbedder::new());
+
+        assert_eq!(index_clauses(&db, embedder.clone()).await.unwrap(), 4);
+        assert_eq!(index_clauses(&db, embedder.clone()).await.unwrap(), 0);
+
+        let source = clause_id(&db, &msa, "Indemnification");
+        let similar = find_similar(&db, embedder.clone(), SimilarTo::Clause(source), 3).await.unwrap();
+        assert_eq!(similar.len(), 3);
+        assert!(similar.iter().all(|s| s.clause_id != source));
+        assert_eq!(similar[0].clause_id, clause_id(&db, &sow, "Indemnity"));
+        assert!(similar[0].score > similar[1].score);
+
+        let similar = find_similar(&db, embedder.clone(), SimilarTo::Text("delaware law".into()), 1).await.unwrap();
+        assert_eq!(similar[0].title, "Governing Law");
+
+        assert!(matches!(
+            find_similar(&db, embedder, SimilarTo::Text("  ".into()), 1).await,
+            Err(AppError::Validation(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_local_only_clauses_are_not_sent_to_remote_embedder() {
+        let db = Database::in_memory().unwrap();
+        let doc = analyzed_doc(&db, "nda.pdf", &[("Confidentiality", "Keep it secret.")]);
+        documents::set_local_only(&db.writer(), &doc, true).unwrap();
+        // Never contacted: the only clause is skipped before any request
+        let remote: Arc<dyn AiProvider> = Arc::new(OllamaProvider::new("http://10.0.0.5:11434".into(), "llama3".into()));
+
+        assert_eq!(index_clauses(&db, remote.clone()).await.unwrap(), 0);
+        let source = clause_id(&db, &doc, "Confidentiality");
+        assert!(matches!(
+            find_similar(&db, remote, SimilarTo::Clause(source), 5).await,
+            Err(AppError::PolicyViolation(_))
+        ));
+    }
+}
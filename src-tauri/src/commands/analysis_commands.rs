@@ -1,48 +1,44 @@
-use std::sync::Arc;
 use tauri::State;
 
-use crate::ai::{OllamaProvider, ClaudeProvider, OpenAiProvider, AiProvider};
-use crate::analysis;
+use crate::ai::{create_embedder, create_provider_for};
+use crate::analysis::{self, similarity};
+use crate::db::audit::{self, NewAuditEvent};
 use crate::db::Database;
-use crate::db::{extractions, risk_assessments, settings};
-use crate::error::{AppError, AppResult};
-
-fn create_provider(db: &Database) -> AppResult<Arc<dyn AiProvider>> {
-    let conn = db.conn.lock().expect("db lock poisoned");
-    let provider_name = settings::get(&conn, "ai_provider")?
-        .unwrap_or_else(|| "ollama".to_string());
-
-    match provider_name.as_str() {
-        "ollama" => {
-            let url = settings::get(&conn, "ollama_url")?
-                .unwrap_or_else(|| "http://localhost:11434".to_string());
-            let model = settings::get(&conn, "ollama_model")?
-                .unwrap_or_else(|| "llama3".to_string());
-            Ok(Arc::new(OllamaProvider::new(url, model)))
-        }
-        "claude" => {
-            let api_key = settings::get(&conn, "claude_api_key")?
-                .ok_or_else(|| AppError::Validation("Claude API key not configured".to_string()))?;
-            let model = settings::get(&conn, "claude_model")?;
-            Ok(Arc::new(ClaudeProvider::new(api_key, model)))
-        }
-        "openai" => {
-            let api_key = settings::get(&conn, "openai_api_key")?
-                .ok_or_else(|| AppError::Validation("OpenAI API key not configured".to_string()))?;
-            let model = settings::get(&conn, "openai_model")?;
-            Ok(Arc::new(OpenAiProvider::new(api_key, model)))
-        }
-        other => Err(AppError::Validation(format!("Unknown AI provider: {other}"))),
-    }
-}
+use crate::db::{extractions, redactions, risk_assessments};
+use crate::error::AppResult;
 
 #[tauri::command]
 pub async fn analyze_document(
     db: State<'_, Database>,
+    app_handle: tauri::AppHandle,
     document_id: String,
 ) -> AppResult<AnalysisResult> {
-    let provider = create_provider(&db)?;
+    let provider = create_provider_for(&app_handle, &[&document_id]).await?;
     let (extraction, risk) = analysis::run_full_analysis(&db, provider, &document_id).await?;
+    let details = serde_json::json!({
+        "extraction_id": extraction.id,
+        "ai_provider": risk.ai_provider,
+        "risk_level": risk.risk_level,
+        "overall_score": risk.overall_score,
+    });
+    let risk_id = risk.id.clone();
+    db.write(move |conn| {
+        audit::record(
+            conn,
+            NewAuditEvent::new("document_analyzed")
+                .document(&document_id)
+                .subject("risk_assessment", &risk_id)
+                .details(details),
+        )
+    })
+    .await?;
+
+    // Similar-clause search indexes lazily, so a missing embedding model
+    // must not fail the analysis
+    let embedder = db.read(create_embedder).await;
+    if let Err(e) = async { similarity::index_clauses(&db, embedder?).await }.await {
+        log::warn!("Failed to embed clauses of {}: {e}", extraction.document_id);
+    }
 
     let extraction_data: serde_json::Value = serde_json::from_str(&extraction.extracted_data)
         .unwrap_or(serde_json::Value::Null);
@@ -65,8 +61,7 @@ pub async fn get_extractions(
     db: State<'_, Database>,
     document_id: String,
 ) -> AppResult<Vec<extractions::Extraction>> {
-    let conn = db.conn.lock().expect("db lock poisoned");
-    extractions::list_by_document(&conn, &document_id)
+    db.read(move |conn| extractions::list_by_document(conn, &document_id)).await
 }
 
 #[tauri::command]
@@ -74,8 +69,16 @@ pub async fn get_risk_assessments(
     db: State<'_, Database>,
     document_id: String,
 ) -> AppResult<Vec<risk_assessments::RiskAssessment>> {
-    let conn = db.conn.lock().expect("db lock poisoned");
-    risk_assessments::get_by_document(&conn, &document_id)
+    db.read(move |conn| risk_assessments::get_by_document(conn, &document_id)).await
+}
+
+/// What was pseudonymized before this document's text went to a cloud provider.
+#[tauri::command]
+pub async fn get_redaction_log(
+    db: State<'_, Database>,
+    document_id: String,
+) -> AppResult<Vec<redactions::RedactionLogEntry>> {
+    db.read(move |conn| redactions::list_by_document(conn, &document_id)).await
 }
 
 #[derive(serde::Serialize)]
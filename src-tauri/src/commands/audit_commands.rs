@@ -0,0 +1,86 @@
+use serde::Serialize;
+use tauri::{Manager, State};
+
+use crate::db::audit::{self, AuditEvent, AuditVerification, NewAuditEvent};
+use crate::db::Database;
+use crate::encryption::Encryption;
+use crate::error::{AppError, AppResult};
+
+/// Re-hashes the whole audit chain and reports the first broken event.
+#[tauri::command]
+pub async fn verify_audit_log(db: State<'_, Database>) -> AppResult<AuditVerification> {
+    db.read(audit::verify).await
+}
+
+#[tauri::command]
+pub async fn get_audit_events(
+    db: State<'_, Database>,
+    document_id: Option<String>,
+    matter_id: Option<String>,
+) -> AppResult<Vec<AuditEvent>> {
+    db.read(move |conn| audit::list(conn, document_id.as_deref(), matter_id.as_deref())).await
+}
+
+/// A self-contained audit trail. Every event carries its hash inputs, so a
+/// reviewer can recompute each hash and the links between consecutive events.
+#[derive(Serialize)]
+pub struct AuditExport {
+    pub document_id: Option<String>,
+    pub matter_id: Option<String>,
+    pub verification: AuditVerification,
+    pub events: Vec<AuditEvent>,
+}
+
+/// Writes the audit trail of one document or one matter to the exports
+/// folder, encrypted when the workspace is, and returns the file path. The
+/// export is itself audited.
+#[tauri::command]
+pub async fn export_audit_log(
+    db: State<'_, Database>,
+    document_id: Option<String>,
+    matter_id: Option<String>,
+    app_handle: tauri::AppHandle,
+) -> AppResult<String> {
+    let (scope, scope_id) = match (&document_id, &matter_id) {
+        (Some(id), None) => ("document", id.clone()),
+        (None, Some(id)) => ("matter", id.clone()),
+        _ => {
+            return Err(AppError::Validation(
+                "Export the audit log of either a document or a matter".to_string(),
+            ))
+        }
+    };
+    let exports_dir = super::app_data_dir(&app_handle)?.join("exports");
+
+    db.write(move |conn| {
+        let export = AuditExport {
+            verification: audit::verify(conn)?,
+            events: audit::list(conn, document_id.as_deref(), matter_id.as_deref())?,
+            document_id,
+            matter_id,
+        };
+        if export.events.is_empty() {
+            return Err(AppError::NotFound(format!("No audit events for {scope} {scope_id}")));
+        }
+
+        std::fs::create_dir_all(&exports_dir)?;
+        let last_seq = export.events.last().map(|e| e.seq).unwrap_or_default();
+        let export_path = app_handle.state::<Encryption>().write_file(
+            &exports_dir.join(format!("audit_{scope}_{}_{last_seq}.json", &scope_id[..scope_id.len().min(8)])),
+            &serde_json::to_vec_pretty(&export)?,
+        )?;
+
+        let event = NewAuditEvent::new("audit_log_exported").details(serde_json::json!({
+            "events": export.events.len(),
+            "export_path": export_path.to_string_lossy(),
+        }));
+        let event = match scope {
+            "document" => event.document(&scope_id),
+            _ => event.matter(&scope_id),
+        };
+        audit::record(conn, event)?;
+
+        Ok(export_path.to_string_lossy().to_string())
+    })
+    .await
+}
@@ -1,79 +1,765 @@
+use std::collections::HashMap;
+use serde::Deserialize;
 use std::sync::Arc;
 use tauri::State;
 
-use crate::ai::{AiProvider, ContractType, OllamaProvider, ClaudeProvider, OpenAiProvider};
+use crate::ai::policy::DataPolicy;
+use crate::ai::prompts::PromptKind;
+use crate::ai::{
+    create_provider_for, policy, AiProvider, ChangeClassification, ComparisonResponse, ContractType, Difference,
+    ExtractedClause,
+};
+use crate::analysis;
+use crate::analysis::clause_alignment;
+use crate::analysis::redline::{self, Change, Passage, Redline, SectionStatus};
+use crate::db::redactions::RedactionSubject;
+use crate::db::audit::{self, NewAuditEvent};
 use crate::db::Database;
-use crate::db::{comparisons, documents, settings};
+use crate::db::tags::{self, TagSubject};
+use crate::db::{comparisons, documents, extractions, templates};
 use crate::error::{AppError, AppResult};
 
-fn create_provider(db: &Database) -> AppResult<Arc<dyn AiProvider>> {
-    let conn = db.conn.lock().expect("db lock poisoned");
-    let provider_name = settings::get(&conn, "ai_provider")?
-        .unwrap_or_else(|| "ollama".to_string());
-
-    match provider_name.as_str() {
-        "ollama" => {
-            let url = settings::get(&conn, "ollama_url")?
-                .unwrap_or_else(|| "http://localhost:11434".to_string());
-            let model = settings::get(&conn, "ollama_model")?
-                .unwrap_or_else(|| "llama3".to_string());
-            Ok(Arc::new(OllamaProvider::new(url, model)))
-        }
-        "claude" => {
-            let api_key = settings::get(&conn, "claude_api_key")?
-                .ok_or_else(|| AppError::Validation("Claude API key not configured".to_string()))?;
-            let model = settings::get(&conn, "claude_model")?;
-            Ok(Arc::new(ClaudeProvider::new(api_key, model)))
-        }
-        "openai" => {
-            let api_key = settings::get(&conn, "openai_api_key")?
-                .ok_or_else(|| AppError::Validation("OpenAI API key not configured".to_string()))?;
-            let model = settings::get(&conn, "openai_model")?;
-            Ok(Arc::new(OpenAiProvider::new(api_key, model)))
-        }
-        other => Err(AppError::Validation(format!("Unknown AI provider: {other}"))),
-    }
+#[tauri::command]
+pub async fn compare_documents(
+    db: State<'_, Database>,
+    app_handle: tauri::AppHandle,
+    document_a_id: String,
+    document_b_id: String,
+) -> AppResult<comparisons::Comparison> {
+    let provider = create_provider_for(&app_handle, &[&document_a_id, &document_b_id]).await?;
+    run_comparison(&db, provider, &document_a_id, &document_b_id).await
 }
 
 #[tauri::command]
-pub async fn compare_documents(
+pub async fn compare_clauses(
     db: State<'_, Database>,
+    app_handle: tauri::AppHandle,
     document_a_id: String,
     document_b_id: String,
 ) -> AppResult<comparisons::Comparison> {
-    let (text_a, text_b, contract_type_str) = {
-        let conn = db.conn.lock().expect("db lock poisoned");
-        let doc_a = documents::get_by_id(&conn, &document_a_id)?;
-        let doc_b = documents::get_by_id(&conn, &document_b_id)?;
+    let provider = create_provider_for(&app_handle, &[&document_a_id, &document_b_id]).await?;
+    run_clause_comparison(&db, provider, &document_a_id, &document_b_id).await
+}
 
-        let text_a = doc_a.raw_text.ok_or_else(|| {
-            AppError::Validation("Document A has no extracted text".to_string())
-        })?;
-        let text_b = doc_b.raw_text.ok_or_else(|| {
-            AppError::Validation("Document B has no extracted text".to_string())
-        })?;
+#[tauri::command]
+pub async fn compare_to_template(
+    db: State<'_, Database>,
+    app_handle: tauri::AppHandle,
+    document_id: String,
+    template_id: String,
+) -> AppResult<comparisons::Comparison> {
+    let provider = create_provider_for(&app_handle, &[&document_id]).await?;
+    run_template_comparison(&db, provider, &document_id, &template_id).await
+}
+
+#[tauri::command]
+pub async fn list_comparisons(
+    db: State<'_, Database>,
+    filter: comparisons::ComparisonFilter,
+) -> AppResult<Vec<comparisons::Comparison>> {
+    db.read(move |conn| comparisons::list(conn, &filter)).await
+}
+
+#[tauri::command]
+pub async fn get_comparison(db: State<'_, Database>, comparison_id: String) -> AppResult<comparisons::Comparison> {
+    db.read(move |conn| comparisons::get_by_id(conn, &comparison_id)).await
+}
+
+/// Deletes a comparison and its reports. Comparisons under litigation hold
+/// are kept.
+#[tauri::command]
+pub async fn delete_comparison(db: State<'_, Database>, comparison_id: String) -> AppResult<()> {
+    db.write(move |conn| {
+        let comparison = comparisons::get_by_id(conn, &comparison_id)?;
+        comparisons::delete(conn, &comparison_id)?;
+        audit::record(
+            conn,
+            NewAuditEvent::new("comparison_deleted")
+                .document(&comparison.document_a_id)
+                .subject("comparison", &comparison_id)
+                .details(serde_json::json!({ "comparison_type": comparison.comparison_type })),
+        )?;
+        Ok(())
+    })
+    .await
+}
+
+/// Runs a comparison again with the current documents, template and
+/// prompts. The earlier result is kept in the history.
+#[tauri::command]
+pub async fn rerun_comparison(
+    db: State<'_, Database>,
+    app_handle: tauri::AppHandle,
+    comparison_id: String,
+) -> AppResult<comparisons::Comparison> {
+    let previous = db.read(move |conn| comparisons::get_by_id(conn, &comparison_id)).await?;
+    let document_ids: Vec<&str> =
+        std::iter::once(previous.document_a_id.as_str()).chain(previous.document_b_id.as_deref()).collect();
+    let provider = create_provider_for(&app_handle, &document_ids).await?;
+    rerun(&db, provider, previous).await
+}
+
+/// The new comparison is filed in the earlier one's matter with its tags.
+pub(crate) async fn rerun(
+    db: &Database,
+    provider: Arc<dyn AiProvider>,
+    previous: comparisons::Comparison,
+) -> AppResult<comparisons::Comparison> {
+    let document_a_id = &previous.document_a_id;
+    let comparison = match (previous.comparison_type.as_str(), &previous.document_b_id, &previous.template_id) {
+        ("document_vs_document", Some(document_b_id), _) => {
+            run_comparison(db, provider, document_a_id, document_b_id).await?
+        }
+        ("clause_aligned", Some(document_b_id), _) => {
+            run_clause_comparison(db, provider, document_a_id, document_b_id).await?
+        }
+        ("document_vs_template", _, Some(template_id)) => {
+            run_template_comparison(db, provider, document_a_id, template_id).await?
+        }
+        ("document_vs_template", _, None) => {
+            return Err(AppError::Validation(format!(
+                "The template of comparison {} has been deleted",
+                previous.id
+            )))
+        }
+        (comparison_type, _, _) => {
+            return Err(AppError::Validation(format!("Cannot re-run a {comparison_type} comparison")))
+        }
+    };
+
+    db.write(move |conn| {
+        comparisons::set_matter(conn, &comparison.id, previous.matter_id.as_deref())?;
+        tags::set_tags(conn, TagSubject::Comparison(&comparison.id), &previous.tags)?;
+        audit::record(
+            conn,
+            NewAuditEvent::new("comparison_rerun")
+                .document(&comparison.document_a_id)
+                .subject("comparison", &comparison.id)
+                .details(serde_json::json!({ "previous_comparison_id": previous.id })),
+        )?;
+        comparisons::get_by_id(conn, &comparison.id)
+    })
+    .await
+}
+
+pub(crate) async fn run_comparison(
+    db: &Database,
+    provider: Arc<dyn AiProvider>,
+    document_a_id: &str,
+    document_b_id: &str,
+) -> AppResult<comparisons::Comparison> {
+    let (a, b) = (document_a_id.to_string(), document_b_id.to_string());
+    let (text_a, text_b, contract_type_str, data_policy) = db
+        .read(move |conn| {
+            let data_policy = policy::resolve(conn, &[&a, &b])?;
+            let doc_a = documents::get_by_id(conn, &a)?;
+            let doc_b = documents::get_by_id(conn, &b)?;
+
+            let text_a = doc_a.raw_text.ok_or_else(|| {
+                AppError::Validation("Document A has no extracted text".to_string())
+            })?;
+            let text_b = doc_b.raw_text.ok_or_else(|| {
+                AppError::Validation("Document B has no extracted text".to_string())
+            })?;
+
+            Ok((text_a, text_b, doc_a.contract_type, data_policy))
+        })
+        .await?;
+    policy::enforce(data_policy, provider.as_ref())?;
 
-        (text_a, text_b, doc_a.contract_type)
+    let contract_type = contract_type_str
+        .parse::<ContractType>()
+        .map_err(|_| AppError::Validation(format!("Unknown contract type: {contract_type_str}")))?;
+
+    // The redline finds every change; the model only classifies and explains them
+    let redline = redline::redline(&text_a, &text_b);
+    let (differences, summary, provider_used) = if redline.changes.is_empty() {
+        (Vec::new(), "No changes were found between the documents.".to_string(), false)
+    } else {
+        let result =
+            classify(db, &provider, PromptKind::Comparison, document_a_id, &redline.changes, &contract_type)
+                .await?;
+        (differences_from(&redline.changes, result.classifications, None), result.summary, true)
     };
 
+    save(db, provider, Outcome {
+        document_a_id: document_a_id.to_string(),
+        document_b_id: Some(document_b_id.to_string()),
+        template_id: None,
+        comparison_type: "document_vs_document",
+        differences,
+        redline,
+        summary,
+        data_policy: provider_used.then_some(data_policy),
+    })
+    .await
+}
+
+/// Compares the clauses of the documents' latest extractions, paired by
+/// clause type and section, so reordered agreements line up. Each modified
+/// pair is explained by the model on its own.
+pub(crate) async fn run_clause_comparison(
+    db: &Database,
+    provider: Arc<dyn AiProvider>,
+    document_a_id: &str,
+    document_b_id: &str,
+) -> AppResult<comparisons::Comparison> {
+    let (a, b) = (document_a_id.to_string(), document_b_id.to_string());
+    let (extraction_a, extraction_b, contract_type_str, data_policy) = db
+        .read(move |conn| {
+            let data_policy = policy::resolve(conn, &[&a, &b])?;
+            let doc_a = documents::get_by_id(conn, &a)?;
+            documents::get_by_id(conn, &b)?;
+
+            let latest = |document_id: &str, label: &str| -> AppResult<StoredExtraction> {
+                let extraction = extractions::list_by_document(conn, document_id)?
+                    .into_iter()
+                    .next()
+                    .ok_or_else(|| AppError::Validation(format!("Document {label} has not been analyzed")))?;
+                serde_json::from_str(&extraction.extracted_data)
+                    .map_err(|e| AppError::AiProvider(format!("Failed to parse stored extraction: {e}")))
+            };
+            Ok((latest(&a, "A")?, latest(&b, "B")?, doc_a.contract_type, data_policy))
+        })
+        .await?;
+    policy::enforce(data_policy, provider.as_ref())?;
+
     let contract_type = contract_type_str
         .parse::<ContractType>()
         .map_err(|_| AppError::Validation(format!("Unknown contract type: {contract_type_str}")))?;
 
-    let provider = create_provider(&db)?;
-    let result = provider.compare_documents(&text_a, &text_b, &contract_type).await?;
+    let pairs = clause_alignment::align_clauses(&extraction_a.clauses, &extraction_b.clauses);
+    fn passage(clause: &ExtractedClause) -> Passage<'_> {
+        Passage { label: clause_label(clause), text: &clause.text }
+    }
+    let redline = redline::redline_pairs(
+        &pairs.iter().map(|(a, b)| (a.map(passage), b.map(passage))).collect::<Vec<_>>(),
+    );
 
-    let differences_json = serde_json::to_string(&result.differences)
-        .map_err(AppError::Json)?;
+    let mut differences = Vec::new();
+    for (index, (clause_a, clause_b)) in pairs.iter().enumerate() {
+        let status = redline.sections[index].status;
+        if status == SectionStatus::Unchanged {
+            continue;
+        }
+        let clause = clause_b.or(*clause_a).expect("aligned pairs have a clause");
+        let mut difference = Difference {
+            change_id: None,
+            clause_type: Some(clause.clause_type.clone()),
+            position: None,
+            category: "other".to_string(),
+            diff_type: "substantive".to_string(),
+            description: String::new(),
+            text_a: clause_a.map(|c| c.text.clone()),
+            text_b: clause_b.map(|c| c.text.clone()),
+            significance: clause.importance.clone(),
+        };
+        match status {
+            SectionStatus::Added => difference.description = format!("Clause added: {}", clause_label(clause)),
+            SectionStatus::Removed => difference.description = format!("Clause removed: {}", clause_label(clause)),
+            _ => {
+                let changes: Vec<Change> = redline.changes_in(index).into_iter().cloned().collect();
+                let result =
+                    classify(db, &provider, PromptKind::Comparison, document_a_id, &changes, &contract_type)
+                        .await?;
+                explain_pair(&mut difference, result);
+            }
+        }
+        differences.push(difference);
+    }
 
-    let conn = db.conn.lock().expect("db lock poisoned");
-    comparisons::insert(&conn, &comparisons::CreateComparison {
-        document_a_id: &document_a_id,
-        document_b_id: Some(&document_b_id),
+    let count = |status| redline.sections.iter().filter(|s| s.status == status).count();
+    let summary = format!(
+        "{} clauses modified, {} added and {} removed.",
+        count(SectionStatus::Modified),
+        count(SectionStatus::Added),
+        count(SectionStatus::Removed)
+    );
+    let provider_used = count(SectionStatus::Modified) > 0;
+    save(db, provider, Outcome {
+        document_a_id: document_a_id.to_string(),
+        document_b_id: Some(document_b_id.to_string()),
         template_id: None,
-        comparison_type: "document_vs_document",
-        differences: &differences_json,
-        summary: Some(&result.summary),
-        ai_provider: Some(provider.name()),
+        comparison_type: "clause_aligned",
+        differences,
+        redline,
+        summary,
+        data_policy: provider_used.then_some(data_policy),
     })
+    .await
+}
+
+/// Compares a counterparty's draft against one of our templates. The
+/// template is the redline's document A, so every change is a deviation
+/// from our standard language, and the model labels each one acceptable,
+/// negotiable or unacceptable.
+pub(crate) async fn run_template_comparison(
+    db: &Database,
+    provider: Arc<dyn AiProvider>,
+    document_id: &str,
+    template_id: &str,
+) -> AppResult<comparisons::Comparison> {
+    let (id, template_id) = (document_id.to_string(), template_id.to_string());
+    let (template, text, data_policy) = db
+        .read(move |conn| {
+            let data_policy = policy::resolve(conn, &[&id])?;
+            let template = templates::get_by_id(conn, &template_id)?;
+            let text = documents::get_by_id(conn, &id)?
+                .raw_text
+                .ok_or_else(|| AppError::Validation("Document has no extracted text".to_string()))?;
+            Ok((template, text, data_policy))
+        })
+        .await?;
+    policy::enforce(data_policy, provider.as_ref())?;
+
+    let contract_type = template
+        .contract_type
+        .parse::<ContractType>()
+        .map_err(|_| AppError::Validation(format!("Unknown contract type: {}", template.contract_type)))?;
+
+    let redline = redline::redline(&template.raw_text, &text);
+    let (differences, summary, provider_used) = if redline.changes.is_empty() {
+        (Vec::new(), format!("The document matches the {} template.", template.name), false)
+    } else {
+        let result =
+            classify(db, &provider, PromptKind::TemplateReview, document_id, &redline.changes, &contract_type)
+                .await?;
+        (differences_from(&redline.changes, result.classifications, Some("negotiable")), result.summary, true)
+    };
+
+    save(db, provider, Outcome {
+        document_a_id: document_id.to_string(),
+        document_b_id: None,
+        template_id: Some(template.id),
+        comparison_type: "document_vs_template",
+        differences,
+        redline,
+        summary,
+        data_policy: provider_used.then_some(data_policy),
+    })
+    .await
+}
+
+/// The part of a stored extraction a clause comparison needs.
+#[derive(Deserialize)]
+struct StoredExtraction {
+    #[serde(default)]
+    clauses: Vec<ExtractedClause>,
+}
+
+fn clause_label(clause: &ExtractedClause) -> String {
+    match &clause.section_reference {
+        Some(section) => format!("{} ({section})", clause.title),
+        None => clause.title.clone(),
+    }
+}
+
+/// Has the model classify and explain redline changes, or assess them as
+/// deviations from a template for `PromptKind::TemplateReview`. Redactions
+/// are logged against document A if the call fails; otherwise they are
+/// logged with the saved comparison.
+async fn classify(
+    db: &Database,
+    provider: &Arc<dyn AiProvider>,
+    kind: PromptKind,
+    document_a_id: &str,
+    changes: &[Change],
+    contract_type: &ContractType,
+) -> AppResult<ComparisonResponse> {
+    let listed = redline::describe_changes(changes);
+    let result = match kind {
+        PromptKind::TemplateReview => provider.review_deviations(&listed, contract_type).await,
+        _ => provider.classify_changes(&listed, contract_type).await,
+    };
+    match result {
+        Ok(result) => Ok(result),
+        Err(e) => {
+            let (provider, id) = (provider.clone(), document_a_id.to_string());
+            db.write(move |conn| {
+                analysis::record_redactions(conn, provider.as_ref(), [RedactionSubject::Document(&id)])
+            })
+            .await?;
+            Err(e)
+        }
+    }
+}
+
+fn significance_rank(significance: &str) -> u8 {
+    match significance {
+        "high" => 2,
+        "medium" => 1,
+        _ => 0,
+    }
+}
+
+/// Sums up a clause pair's classified changes: the pair is as significant as
+/// its most significant change, and the model's summary explains it.
+fn explain_pair(difference: &mut Difference, result: ComparisonResponse) {
+    if let Some(top) = result.classifications.iter().max_by_key(|c| significance_rank(&c.significance)) {
+        difference.category = top.category.clone();
+        difference.significance = top.significance.clone();
+    }
+    if !result.classifications.is_empty() && result.classifications.iter().all(|c| c.diff_type == "formatting") {
+        difference.diff_type = "formatting".to_string();
+    }
+    difference.description = result.summary;
+}
+
+/// One difference per redline change, explained by the model where it
+/// classified the change and described plainly where it did not. Changes
+/// the model gave no position get `default_position`.
+fn differences_from(
+    changes: &[Change],
+    classifications: Vec<ChangeClassification>,
+    default_position: Option<&str>,
+) -> Vec<Difference> {
+    let mut classified: HashMap<String, ChangeClassification> =
+        classifications.into_iter().map(|c| (c.change_id.clone(), c)).collect();
+    changes
+        .iter()
+        .map(|change| {
+            let classification = classified.remove(&change.id);
+            let field = |get: fn(&ChangeClassification) -> &String, default: &str| {
+                classification.as_ref().map_or_else(|| default.to_string(), |c| get(c).clone())
+            };
+            let explanation = field(|c| &c.explanation, "");
+            Difference {
+                change_id: Some(change.id.clone()),
+                clause_type: None,
+                position: classification
+                    .as_ref()
+                    .and_then(|c| c.position.clone())
+                    .or_else(|| default_position.map(str::to_string)),
+                category: field(|c| &c.category, "other"),
+                diff_type: field(|c| &c.diff_type, "substantive"),
+                description: if explanation.trim().is_empty() { change.description() } else { explanation },
+                text_a: change.text_a.clone(),
+                text_b: change.text_b.clone(),
+                significance: field(|c| &c.significance, "medium"),
+            }
+        })
+        .collect()
+}
+
+/// A finished comparison, ready to store.
+struct Outcome {
+    document_a_id: String,
+    document_b_id: Option<String>,
+    template_id: Option<String>,
+    comparison_type: &'static str,
+    differences: Vec<Difference>,
+    redline: Redline,
+    summary: String,
+    /// The policy the provider was called under; `None` when it was not needed.
+    data_policy: Option<DataPolicy>,
+}
+
+async fn save(db: &Database, provider: Arc<dyn AiProvider>, outcome: Outcome) -> AppResult<comparisons::Comparison> {
+    let differences_json = serde_json::to_string(&outcome.differences)
+        .map_err(AppError::Json)?;
+    let redline_json = serde_json::to_string(&outcome.redline).map_err(AppError::Json)?;
+
+    db.write(move |conn| {
+        let provider_used = outcome.data_policy.is_some();
+        let comparison = comparisons::insert(conn, &comparisons::CreateComparison {
+            document_a_id: &outcome.document_a_id,
+            document_b_id: outcome.document_b_id.as_deref(),
+            template_id: outcome.template_id.as_deref(),
+            comparison_type: outcome.comparison_type,
+            differences: &differences_json,
+            redline: Some(&redline_json),
+            summary: Some(&outcome.summary),
+            ai_provider: provider_used.then(|| provider.name()),
+            data_policy: outcome.data_policy.map(|p| p.as_str()),
+            ai_endpoint: provider.endpoint().filter(|_| provider_used),
+        })?;
+        analysis::record_redactions(conn, provider.as_ref(), [RedactionSubject::Comparison(&comparison.id)])?;
+        audit::record(
+            conn,
+            NewAuditEvent::new("documents_compared")
+                .document(&outcome.document_a_id)
+                .subject("comparison", &comparison.id)
+                .details(serde_json::json!({
+                    "document_b_id": comparison.document_b_id,
+                    "template_id": comparison.template_id,
+                    "comparison_type": comparison.comparison_type,
+                    "ai_provider": comparison.ai_provider,
+                    "changes": outcome.redline.changes.len(),
+                })),
+        )?;
+        Ok(comparison)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::mock::{fixtures, MockProvider};
+    use crate::ai::prompts::PromptKind;
+    use crate::db::documents::CreateDocument;
+
+    fn insert_doc_with_text(db: &Database, text: &str) -> String {
+        let conn = db.writer();
+        let doc = documents::insert(&conn, &CreateDocument {
+            filename: "nda.pdf".into(),
+            original_path: "/tmp/nda.pdf".into(),
+            stored_path: "/data/nda.pdf".into(),
+            file_hash: "hash".into(),
+            file_size: 10,
+            contract_type: "nda".into(),
+        }).unwrap();
+        documents::update_text(&conn, &doc.id, text, 1).unwrap();
+        doc.id
+    }
+
+    #[tokio::test]
+    async fn test_run_comparison_stores_differences() {
+        let db = Database::in_memory().unwrap();
+        let a = insert_doc_with_text(&db, "Term: two years.");
+        let b = insert_doc_with_text(&db, "Term: three years.");
+        let mock = Arc::new(MockProvider::new().respond(PromptKind::Comparison, fixtures::COMPARISON));
+
+        let comparison = run_comparison(&db, mock.clone(), &a, &b).await.unwrap();
+
+        assert_eq!(comparison.comparison_type, "document_vs_document");
+        assert_eq!(comparison.ai_provider.as_deref(), Some("mock"));
+        let differences: Vec<crate::ai::Difference> = serde_json::from_str(&comparison.differences).unwrap();
+        assert_eq!(differences.len(), 1);
+        assert_eq!(differences[0].change_id.as_deref(), Some("C1"));
+        assert_eq!(differences[0].description, "Term extended from two to three years");
+        assert_eq!(differences[0].text_b.as_deref(), Some("three"));
+
+        let redline: analysis::redline::Redline = serde_json::from_str(comparison.redline.as_deref().unwrap()).unwrap();
+        assert_eq!(redline.stats.replacements, 1);
+
+        let request = &mock.requests()[0];
+        assert!(request.user.contains("[C1] replaced in \"Preamble\": \"two\" -> \"three\""));
+    }
+
+    #[tokio::test]
+    async fn test_run_comparison_sends_only_the_changes() {
+        let db = Database::in_memory().unwrap();
+        let clause = "The Receiving Party shall hold the Confidential Information in strict confidence.";
+        let a = insert_doc_with_text(&db, &format!("1. Confidentiality\n{clause}\n2. Remedies\nThe Discloser shall be entitled to injunctive relief."));
+        let b = insert_doc_with_text(&db, &format!("1. Confidentiality\n{clause}\n2. Remedies\nThe Discloser may be entitled to injunctive relief. Fees are capped."));
+        // The model classifies C1 only; C2 is still kept
+        let mock = Arc::new(MockProvider::new().respond(PromptKind::Comparison, fixtures::COMPARISON));
+
+        let comparison = run_comparison(&db, mock.clone(), &a, &b).await.unwrap();
+
+        let user = &mock.requests()[0].user;
+        assert!(!user.contains(clause));
+        assert!(user.contains("[C1] replaced in \"2. Remedies\": \"shall\" -> \"may\""));
+        let differences: Vec<crate::ai::Difference> = serde_json::from_str(&comparison.differences).unwrap();
+        assert_eq!(differences.len(), 2);
+        assert_eq!(differences[1].change_id.as_deref(), Some("C2"));
+        assert_eq!(differences[1].description, "Inserted \u{201C}Fees are capped.\u{201D} in 2. Remedies");
+        assert_eq!((differences[1].significance.as_str(), differences[1].category.as_str()), ("medium", "other"));
+    }
+
+    #[tokio::test]
+    async fn test_identical_documents_skip_the_model() {
+        let db = Database::in_memory().unwrap();
+        let a = insert_doc_with_text(&db, "Term: two\nyears.");
+        let b = insert_doc_with_text(&db, "Term: two years.");
+        let mock = Arc::new(MockProvider::new());
+
+        let comparison = run_comparison(&db, mock.clone(), &a, &b).await.unwrap();
+
+        assert!(mock.requests().is_empty());
+        assert_eq!(comparison.differences, "[]");
+        assert_eq!(comparison.ai_provider, None);
+    }
+
+    #[tokio::test]
+    async fn test_run_comparison_requires_text() {
+        let db = Database::in_memory().unwrap();
+        let a = insert_doc_with_text(&db, "Term: two years.");
+        let b = {
+            let conn = db.writer();
+            documents::insert(&conn, &CreateDocument {
+                filename: "blank.pdf".into(),
+                original_path: "/tmp/blank.pdf".into(),
+                stored_path: "/data/blank.pdf".into(),
+                file_hash: "hash2".into(),
+                file_size: 10,
+                contract_type: "nda".into(),
+            }).unwrap().id
+        };
+        let mock = Arc::new(MockProvider::new());
+        assert!(run_comparison(&db, mock.clone(), &a, &b).await.is_err());
+        assert!(mock.requests().is_empty());
+    }
+
+    fn insert_extraction(db: &Database, document_id: &str, clauses: serde_json::Value) {
+        let conn = db.writer();
+        extractions::insert(&conn, &extractions::CreateExtraction {
+            document_id: document_id.into(),
+            ai_provider: "mock".into(),
+            ai_model: None,
+            contract_type: "nda".into(),
+            extracted_data: serde_json::json!({ "parties": [], "clauses": clauses }).to_string(),
+            confidence_score: None,
+            processing_time_ms: None,
+            prompt_version: None,
+            data_policy: None,
+            ai_endpoint: None,
+        }).unwrap();
+    }
+
+    fn clause(clause_type: &str, section: &str, text: &str) -> serde_json::Value {
+        serde_json::json!({
+            "clause_type": clause_type,
+            "title": clause_type.replace('_', " "),
+            "text": text,
+            "section_reference": section,
+            "importance": "high",
+        })
+    }
+
+    #[tokio::test]
+    async fn test_clause_comparison_explains_each_modified_pair() {
+        let db = Database::in_memory().unwrap();
+        let a = insert_doc_with_text(&db, "unused");
+        let b = insert_doc_with_text(&db, "unused");
+        let confidentiality = "The Receiving Party shall hold the Confidential Information in strict confidence.";
+        insert_extraction(&db, &a, serde_json::json!([
+            clause("confidentiality", "Section 2", confidentiality),
+            clause("term", "Section 5", "Term: two years."),
+            clause("non_solicitation", "Section 6", "Neither party shall solicit the other's employees."),
+        ]));
+        // Reordered, with one clause replaced by another
+        insert_extraction(&db, &b, serde_json::json!([
+            clause("term", "Section 3", "Term: three years."),
+            clause("confidentiality", "Section 4", confidentiality),
+            clause("governing_law", "Section 7", "This Agreement is governed by the laws of Delaware."),
+        ]));
+        let mock = Arc::new(MockProvider::new().respond(PromptKind::Comparison, fixtures::COMPARISON));
+
+        let comparison = run_clause_comparison(&db, mock.clone(), &a, &b).await.unwrap();
+
+        assert_eq!(comparison.comparison_type, "clause_aligned");
+        assert_eq!(comparison.summary.as_deref(), Some("1 clauses modified, 1 added and 1 removed."));
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].user.contains("\"two\" -> \"three\""));
+        assert!(!requests[0].user.contains(confidentiality));
+
+        let differences: Vec<Difference> = serde_json::from_str(&comparison.differences).unwrap();
+        let described: Vec<_> = differences
+            .iter()
+            .map(|d| (d.clause_type.as_deref().unwrap(), d.description.as_str(), d.significance.as_str()))
+            .collect();
+        assert_eq!(
+            described,
+            vec![
+                ("term", "The term was extended.", "high"),
+                // Non-solicitation followed confidentiality in A
+                ("non_solicitation", "Clause removed: non solicitation (Section 6)", "high"),
+                ("governing_law", "Clause added: governing law (Section 7)", "high"),
+            ]
+        );
+        assert_eq!(differences[0].category, "term");
+    }
+
+    #[tokio::test]
+    async fn test_clause_comparison_requires_extractions() {
+        let db = Database::in_memory().unwrap();
+        let a = insert_doc_with_text(&db, "Term: two years.");
+        let b = insert_doc_with_text(&db, "Term: three years.");
+        insert_extraction(&db, &a, serde_json::json!([clause("term", "", "Term: two years.")]));
+        let mock = Arc::new(MockProvider::new());
+
+        let err = run_clause_comparison(&db, mock.clone(), &a, &b).await.unwrap_err();
+
+        assert!(matches!(err, AppError::Validation(ref m) if m == "Document B has not been analyzed"));
+        assert!(mock.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_template_comparison_labels_deviations() {
+        let db = Database::in_memory().unwrap();
+        let template = {
+            let conn = db.writer();
+            templates::insert(&conn, "Standard NDA", "nda", None, "Term: two years.").unwrap()
+        };
+        let draft = insert_doc_with_text(&db, "Term: three years.");
+        let mock = Arc::new(MockProvider::new().respond(PromptKind::TemplateReview, fixtures::TEMPLATE_REVIEW));
+
+        let comparison = run_template_comparison(&db, mock.clone(), &draft, &template.id).await.unwrap();
+
+        assert_eq!(comparison.comparison_type, "document_vs_template");
+        assert_eq!(comparison.template_id.as_deref(), Some(template.id.as_str()));
+        assert_eq!(comparison.document_b_id, None);
+        let differences: Vec<Difference> = serde_json::from_str(&comparison.differences).unwrap();
+        assert_eq!(differences[0].position.as_deref(), Some("negotiable"));
+        // The template is the original the draft deviates from
+        assert_eq!((differences[0].text_a.as_deref(), differences[0].text_b.as_deref()), (Some("two"), Some("three")));
+
+        let request = &mock.requests()[0];
+        assert_eq!(request.kind, PromptKind::TemplateReview);
+        assert!(request.user.contains("[C1] replaced in \"Preamble\": \"two\" -> \"three\""));
+    }
+
+    #[tokio::test]
+    async fn test_template_deviation_the_model_skipped_is_negotiable() {
+        let db = Database::in_memory().unwrap();
+        let template = {
+            let conn = db.writer();
+            templates::insert(&conn, "Standard NDA", "nda", None, "Term: two years.").unwrap()
+        };
+        let draft = insert_doc_with_text(&db, "Term: three years.");
+        let mock = Arc::new(MockProvider::new().respond(
+            PromptKind::TemplateReview,
+            r#"{"classifications": [], "summary": "The term differs from the template."}"#,
+        ));
+
+        let comparison = run_template_comparison(&db, mock, &draft, &template.id).await.unwrap();
+
+        let differences: Vec<Difference> = serde_json::from_str(&comparison.differences).unwrap();
+        assert_eq!(differences.len(), 1);
+        assert_eq!(differences[0].change_id.as_deref(), Some("C1"));
+        assert_eq!(differences[0].position.as_deref(), Some("negotiable"));
+    }
+
+    #[tokio::test]
+    async fn test_template_comparison_requires_the_template() {
+        let db = Database::in_memory().unwrap();
+        let draft = insert_doc_with_text(&db, "Term: three years.");
+        let mock = Arc::new(MockProvider::new());
+
+        let err = run_template_comparison(&db, mock.clone(), &draft, "missing").await.unwrap_err();
+
+        assert!(matches!(err, AppError::NotFound(_)));
+        assert!(mock.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_rerun_keeps_history_matter_and_tags() {
+        let db = Database::in_memory().unwrap();
+        let a = insert_doc_with_text(&db, "Term: two years.");
+        let b = insert_doc_with_text(&db, "Term: three years.");
+        let mock = Arc::new(MockProvider::new().respond(PromptKind::Comparison, fixtures::COMPARISON));
+        let first = run_comparison(&db, mock.clone(), &a, &b).await.unwrap();
+        {
+            let conn = db.writer();
+            let matter = crate::db::matters::insert(&conn, &crate::db::matters::MatterInput::named("Acme NDA")).unwrap();
+            comparisons::set_matter(&conn, &first.id, Some(&matter.id)).unwrap();
+            tags::set_tags(&conn, TagSubject::Comparison(&first.id), &["Round 2".to_string()]).unwrap();
+        }
+        let first = comparisons::get_by_id(&db.reader(), &first.id).unwrap();
+
+        let second = rerun(&db, mock.clone(), first.clone()).await.unwrap();
+
+        assert_ne!(second.id, first.id);
+        assert_eq!(second.matter_id, first.matter_id);
+        assert_eq!(second.tags, vec!["Round 2".to_string()]);
+        let history = {
+            let conn = db.reader();
+            let filter = comparisons::ComparisonFilter { document_id: Some(b.clone()), ..Default::default() };
+            comparisons::list(&conn, &filter).unwrap()
+        };
+        assert_eq!(history.iter().map(|c| c.id.as_str()).collect::<Vec<_>>(), vec![second.id.as_str(), first.id.as_str()]);
+    }
 }
@@ -1,11 +1,45 @@
 use std::path::PathBuf;
 use tauri::{Manager, State};
+use tauri_plugin_dialog::DialogExt;
 
+use serde_json::json;
+
+use crate::config;
+use crate::db::audit::{self, NewAuditEvent};
 use crate::db::Database;
-use crate::db::documents::{self, CreateDocument, Document, DocumentStats};
-use crate::documents::{compute_file_hash, get_file_size};
-use crate::documents::pdf;
-use crate::error::AppResult;
+use crate::db::documents::{self, CreateDocument, Document, DocumentFilter, DocumentPage, DocumentQuery, DocumentStats};
+use crate::documents::upload::{self, UploadGrants};
+use crate::documents::{hash_bytes, pdf};
+use crate::encryption::Encryption;
+use crate::error::{AppError, AppResult};
+use crate::retention::{self, RetentionReport};
+
+/// Shows the native file picker. The chosen file is granted for one upload;
+/// the webview never gets to name an arbitrary path itself.
+#[tauri::command]
+pub async fn pick_document_file(
+    app_handle: tauri::AppHandle,
+    grants: State<'_, UploadGrants>,
+) -> AppResult<Option<String>> {
+    // The dialog blocks its thread until the user closes it
+    let picked = tauri::async_runtime::spawn_blocking(move || {
+        app_handle
+            .dialog()
+            .file()
+            .set_title("Choose a contract")
+            .add_filter("PDF", &["pdf"])
+            .blocking_pick_file()
+    })
+    .await
+    .map_err(|e| AppError::Task(e.to_string()))?;
+    let Some(picked) = picked else {
+        return Ok(None);
+    };
+    let path = picked
+        .into_path()
+        .map_err(|e| AppError::Validation(format!("Unsupported file location: {e}")))?;
+    Ok(Some(grants.grant(&path)?.to_string_lossy().to_string()))
+}
 
 #[tauri::command]
 pub async fn upload_document(
@@ -14,69 +48,100 @@ pub async fn upload_document(
     contract_type: String,
     app_handle: tauri::AppHandle,
 ) -> AppResult<Document> {
-    let source = PathBuf::from(&file_path);
-
-    let filename = source
-        .file_name()
-        .map(|n| n.to_string_lossy().to_string())
-        .unwrap_or_else(|| "unknown.pdf".to_string());
-
-    let file_hash = compute_file_hash(&source)?;
-    let file_size = get_file_size(&source)?;
-
-    // Store document in app data directory
-    let app_data = app_handle
-        .path()
-        .app_data_dir()
-        .expect("failed to get app data dir");
-    let docs_dir = app_data.join("documents");
-    std::fs::create_dir_all(&docs_dir)?;
-
-    let stored_name = format!("{}_{}", &file_hash[..8], &filename);
-    let stored_path = docs_dir.join(&stored_name);
-    std::fs::copy(&source, &stored_path)?;
-
-    let conn = db.conn.lock().expect("db lock poisoned");
-    let doc = documents::insert(
-        &conn,
-        &CreateDocument {
-            filename,
-            original_path: file_path,
-            stored_path: stored_path.to_string_lossy().to_string(),
-            file_hash,
-            file_size,
-            contract_type,
-        },
-    )?;
-
-    Ok(doc)
+    let max_mb: u64 = db
+        .read(|conn| Ok(config::get(conn, "upload_max_mb")?.unwrap_or_default().parse().unwrap_or(50)))
+        .await?;
+    let docs_dir = super::app_data_dir(&app_handle)?.join("documents");
+
+    // Reading, hashing and encrypting a large PDF takes a while; keep it off
+    // the async runtime
+    let (id, source, file_hash, stored_path) = tauri::async_runtime::spawn_blocking(move || {
+        let source =
+            upload::validate_source(&file_path, &app_handle.state::<UploadGrants>(), max_mb * 1024 * 1024)?;
+        let file_hash = hash_bytes(&source.bytes);
+        std::fs::create_dir_all(&docs_dir)?;
+
+        // Named after the new document, so identical uploads never share a file
+        let id = uuid::Uuid::new_v4().to_string();
+        let stored_name = format!("{id}_{}", upload::sanitize_filename(&source.filename));
+        let stored_path = app_handle.state::<Encryption>().write_file(&docs_dir.join(stored_name), &source.bytes)?;
+        Ok::<_, AppError>((id, source, file_hash, stored_path))
+    })
+    .await
+    .map_err(|e| AppError::Task(e.to_string()))??;
+    let file_size = source.bytes.len() as i64;
+
+    db.write(move |conn| {
+        let doc = documents::insert_with_id(
+            conn,
+            &id,
+            &CreateDocument {
+                filename: source.filename,
+                original_path: source.path.to_string_lossy().to_string(),
+                stored_path: stored_path.to_string_lossy().to_string(),
+                file_hash,
+                file_size,
+                contract_type,
+            },
+        )?;
+        audit::record(
+            conn,
+            NewAuditEvent::new("document_uploaded").document(&doc.id).details(json!({
+                "filename": doc.filename,
+                "file_hash": doc.file_hash,
+                "file_size": doc.file_size,
+                "contract_type": doc.contract_type,
+            })),
+        )?;
+
+        Ok(doc)
+    })
+    .await
 }
 
 #[tauri::command]
 pub async fn extract_document_text(
     db: State<'_, Database>,
+    encryption: State<'_, Encryption>,
     document_id: String,
 ) -> AppResult<Document> {
-    let stored_path = {
-        let conn = db.conn.lock().expect("db lock poisoned");
-        let doc = documents::get_by_id(&conn, &document_id)?;
-        doc.stored_path
-    };
+    let id = document_id.clone();
+    let stored_path = db.read(move |conn| Ok(documents::get_by_id(conn, &id)?.stored_path)).await?;
 
-    let result = pdf::extract_text(&PathBuf::from(&stored_path));
+    // Parsing a large PDF takes a while; keep it off the async runtime
+    let result = match encryption.read_file(&PathBuf::from(&stored_path)) {
+        Ok(bytes) => tauri::async_runtime::spawn_blocking(move || pdf::extract_text_from_bytes(&bytes, &stored_path))
+            .await
+            .map_err(|e| AppError::Task(e.to_string()))?,
+        Err(e) => Err(e),
+    };
 
-    let conn = db.conn.lock().expect("db lock poisoned");
-    match result {
-        Ok(extraction) => {
-            documents::update_text(&conn, &document_id, &extraction.text, extraction.page_count)?;
-        }
-        Err(e) => {
-            documents::update_status(&conn, &document_id, "error", Some(&e.to_string()))?;
-            return Err(e);
+    db.write(move |conn| {
+        match result {
+            Ok(extraction) => {
+                documents::update_text(conn, &document_id, &extraction.text, extraction.page_count)?;
+                audit::record(
+                    conn,
+                    NewAuditEvent::new("text_extracted")
+                        .document(&document_id)
+                        .details(json!({ "page_count": extraction.page_count, "characters": extraction.text.len() })),
+                )?;
+            }
+            Err(e) => {
+                documents::update_status(conn, &document_id, "error", Some(&e.to_string()))?;
+                audit::record(
+                    conn,
+                    NewAuditEvent::new("text_extraction_failed")
+                        .document(&document_id)
+                        .details(json!({ "error": e.to_string() })),
+                )?;
+                return Err(e);
+            }
         }
-    }
 
-    documents::get_by_id(&conn, &document_id)
+        documents::get_by_id(conn, &document_id)
+    })
+    .await
 }
 
 #[tauri::command]
@@ -84,35 +149,125 @@ pub async fn get_document(
     db: State<'_, Database>,
     document_id: String,
 ) -> AppResult<Document> {
-    let conn = db.conn.lock().expect("db lock poisoned");
-    documents::get_by_id(&conn, &document_id)
+    db.read(move |conn| documents::get_by_id(conn, &document_id)).await
 }
 
+/// A page of document summaries; `get_document` returns the full text.
 #[tauri::command]
-pub async fn list_documents(db: State<'_, Database>) -> AppResult<Vec<Document>> {
-    let conn = db.conn.lock().expect("db lock poisoned");
-    documents::list_all(&conn)
+pub async fn list_documents(db: State<'_, Database>, query: Option<DocumentQuery>) -> AppResult<DocumentPage> {
+    let query = query.unwrap_or_default();
+    db.read(move |conn| documents::list(conn, &query)).await
 }
 
+/// Moves a document to the trash. It can be restored until it is purged.
 #[tauri::command]
 pub async fn delete_document(
     db: State<'_, Database>,
     document_id: String,
 ) -> AppResult<()> {
-    let conn = db.conn.lock().expect("db lock poisoned");
-    let doc = documents::get_by_id(&conn, &document_id)?;
+    db.write(move |conn| {
+        documents::soft_delete(conn, &document_id)?;
+        audit::record(conn, NewAuditEvent::new("document_trashed").document(&document_id))?;
+        Ok(())
+    })
+    .await
+}
 
-    // Remove stored file
-    let stored = PathBuf::from(&doc.stored_path);
-    if stored.exists() {
-        std::fs::remove_file(&stored)?;
-    }
+#[tauri::command]
+pub async fn list_trash(db: State<'_, Database>) -> AppResult<Vec<Document>> {
+    db.read(documents::list_trash).await
+}
 
-    documents::delete(&conn, &document_id)
+#[tauri::command]
+pub async fn restore_document(
+    db: State<'_, Database>,
+    document_id: String,
+) -> AppResult<Document> {
+    db.write(move |conn| {
+        documents::restore(conn, &document_id)?;
+        audit::record(conn, NewAuditEvent::new("document_restored").document(&document_id))?;
+        documents::get_by_id(conn, &document_id)
+    })
+    .await
 }
 
+/// Permanently deletes a trashed document, its file and its analyses.
 #[tauri::command]
-pub async fn get_document_stats(db: State<'_, Database>) -> AppResult<DocumentStats> {
-    let conn = db.conn.lock().expect("db lock poisoned");
-    documents::get_stats(&conn)
+pub async fn purge_document(
+    db: State<'_, Database>,
+    document_id: String,
+) -> AppResult<()> {
+    db.write(move |conn| {
+        let doc = documents::get_by_id(conn, &document_id)?;
+        if doc.deleted_at.is_none() {
+            return Err(AppError::Validation("Move the document to the trash before purging it".to_string()));
+        }
+        let tx = conn.unchecked_transaction()?;
+        let stored = retention::purge(&tx, &doc, "user")?;
+        tx.commit()?;
+        retention::remove_files(stored.as_slice());
+        Ok(())
+    })
+    .await
+}
+
+/// Places or lifts a litigation hold. Held documents cannot be deleted,
+/// purged or have their text removed by retention.
+#[tauri::command]
+pub async fn set_document_legal_hold(
+    db: State<'_, Database>,
+    document_id: String,
+    hold: bool,
+    reason: Option<String>,
+) -> AppResult<Document> {
+    db.write(move |conn| {
+        documents::set_legal_hold(conn, &document_id, hold, reason.as_deref())?;
+        audit::record(
+            conn,
+            NewAuditEvent::new(if hold { "legal_hold_placed" } else { "legal_hold_lifted" })
+                .document(&document_id)
+                .details(json!({ "reason": reason })),
+        )?;
+        documents::get_by_id(conn, &document_id)
+    })
+    .await
+}
+
+/// Runs the retention policies now instead of waiting for the next start.
+#[tauri::command]
+pub async fn apply_retention(db: State<'_, Database>) -> AppResult<RetentionReport> {
+    let db = db.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || retention::apply(&db))
+        .await
+        .map_err(|e| AppError::Task(e.to_string()))?
+}
+
+/// Marks a document as local-only: it can then only be processed by a model
+/// running on this machine.
+#[tauri::command]
+pub async fn set_document_local_only(
+    db: State<'_, Database>,
+    document_id: String,
+    local_only: bool,
+) -> AppResult<Document> {
+    db.write(move |conn| {
+        documents::set_local_only(conn, &document_id, local_only)?;
+        audit::record(
+            conn,
+            NewAuditEvent::new("document_policy_changed")
+                .document(&document_id)
+                .details(json!({ "local_only": local_only })),
+        )?;
+        documents::get_by_id(conn, &document_id)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn get_document_stats(
+    db: State<'_, Database>,
+    filter: Option<DocumentFilter>,
+) -> AppResult<DocumentStats> {
+    let filter = filter.unwrap_or_default();
+    db.read(move |conn| documents::get_stats(conn, &filter)).await
 }
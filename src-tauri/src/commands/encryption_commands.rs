@@ -0,0 +1,77 @@
+use tauri::{AppHandle, Manager, State};
+
+use crate::db::audit::{self, NewAuditEvent};
+use crate::db::{Database, DB_FILE};
+use crate::encryption::{Encryption, EncryptionStatus};
+use crate::error::{AppError, AppResult};
+use crate::retention;
+use crate::secrets::{self, SecretStore};
+
+#[tauri::command]
+pub async fn get_encryption_status(encryption: State<'_, Encryption>) -> AppResult<EncryptionStatus> {
+    Ok(encryption.status())
+}
+
+/// Opens an encrypted workspace. Until this succeeds no command that needs
+/// the database can run.
+#[tauri::command]
+pub async fn unlock_workspace(
+    app: AppHandle,
+    encryption: State<'_, Encryption>,
+    passphrase: String,
+) -> AppResult<EncryptionStatus> {
+    if encryption.is_unlocked() {
+        return Ok(encryption.status());
+    }
+    // Key derivation and the startup retention pass both block
+    let handle = app.clone();
+    let database = blocking(move || {
+        let db_path = super::app_data_dir(&handle)?.join(DB_FILE);
+        let database = handle.state::<Encryption>().unlock(&passphrase, &db_path)?;
+        secrets::migrate_plaintext(&database.writer(), &handle.state::<SecretStore>())?;
+        if let Err(e) = retention::apply(&database) {
+            log::error!("Failed to apply retention policies: {e}");
+        }
+        Ok(database)
+    })
+    .await?;
+    app.manage(database);
+    Ok(encryption.status())
+}
+
+/// Encrypts the current workspace: the database and every stored document.
+/// The passphrase is then required at every start.
+#[tauri::command]
+pub async fn enable_encryption(
+    app: AppHandle,
+    db: State<'_, Database>,
+    encryption: State<'_, Encryption>,
+    passphrase: String,
+) -> AppResult<EncryptionStatus> {
+    let database = db.inner().clone();
+    blocking(move || app.state::<Encryption>().enable(&database, &passphrase)).await?;
+    db.write(|conn| audit::record(conn, NewAuditEvent::new("encryption_enabled"))).await?;
+    Ok(encryption.status())
+}
+
+/// Re-encrypts the workspace under a new key and passphrase.
+#[tauri::command]
+pub async fn rotate_encryption_key(
+    app: AppHandle,
+    db: State<'_, Database>,
+    encryption: State<'_, Encryption>,
+    current_passphrase: String,
+    new_passphrase: String,
+) -> AppResult<EncryptionStatus> {
+    let database = db.inner().clone();
+    blocking(move || app.state::<Encryption>().rotate(&database, &current_passphrase, &new_passphrase)).await?;
+    db.write(|conn| audit::record(conn, NewAuditEvent::new("encryption_key_rotated"))).await?;
+    Ok(encryption.status())
+}
+
+/// Runs key derivation, rekeying and file re-encryption off the async runtime.
+async fn blocking<T: Send + 'static>(f: impl FnOnce() -> AppResult<T> + Send + 'static) -> AppResult<T> {
+    tauri::async_runtime::spawn_blocking(f)
+        .await
+        .map_err(|e| AppError::Task(e.to_string()))?
+}
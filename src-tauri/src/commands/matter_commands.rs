@@ -0,0 +1,159 @@
+use tauri::State;
+
+use crate::db::audit::{self, NewAuditEvent};
+use crate::db::comparisons::{self, Comparison};
+use crate::db::matters::{self, Matter, MatterInput};
+use crate::db::reports::{self, Report};
+use crate::db::Database;
+use crate::db::documents::{self, Document};
+use crate::error::AppResult;
+
+#[tauri::command]
+pub async fn create_matter(db: State<'_, Database>, matter: MatterInput) -> AppResult<Matter> {
+    db.write(move |conn| {
+        let matter = matters::insert(conn, &matter)?;
+        audit::record(
+            conn,
+            NewAuditEvent::new("matter_created").matter(&matter.id).details(serde_json::json!({
+                "name": matter.name,
+                "client": matter.client,
+                "matter_number": matter.matter_number,
+            })),
+        )?;
+        Ok(matter)
+    })
+    .await
+}
+
+/// Updates a matter's name, client, number and responsible attorney.
+#[tauri::command]
+pub async fn update_matter(db: State<'_, Database>, matter_id: String, matter: MatterInput) -> AppResult<Matter> {
+    db.write(move |conn| {
+        let matter = matters::update(conn, &matter_id, &matter)?;
+        audit::record(
+            conn,
+            NewAuditEvent::new("matter_updated").matter(&matter.id).details(serde_json::json!({
+                "name": matter.name,
+                "client": matter.client,
+                "matter_number": matter.matter_number,
+                "responsible_attorney": matter.responsible_attorney,
+            })),
+        )?;
+        Ok(matter)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn list_matters(db: State<'_, Database>) -> AppResult<Vec<Matter>> {
+    db.read(matters::list_all).await
+}
+
+/// Client names in use across matters, for filtering.
+#[tauri::command]
+pub async fn list_clients(db: State<'_, Database>) -> AppResult<Vec<String>> {
+    db.read(matters::list_clients).await
+}
+
+/// Closes a matter, starting the retention period for its documents' text.
+#[tauri::command]
+pub async fn close_matter(db: State<'_, Database>, matter_id: String) -> AppResult<Matter> {
+    db.write(move |conn| {
+        matters::set_closed(conn, &matter_id, true)?;
+        audit::record(conn, NewAuditEvent::new("matter_closed").matter(&matter_id))?;
+        matters::get_by_id(conn, &matter_id)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn reopen_matter(db: State<'_, Database>, matter_id: String) -> AppResult<Matter> {
+    db.write(move |conn| {
+        matters::set_closed(conn, &matter_id, false)?;
+        audit::record(conn, NewAuditEvent::new("matter_reopened").matter(&matter_id))?;
+        matters::get_by_id(conn, &matter_id)
+    })
+    .await
+}
+
+/// Places or lifts a litigation hold on every document in the matter.
+#[tauri::command]
+pub async fn set_matter_legal_hold(
+    db: State<'_, Database>,
+    matter_id: String,
+    hold: bool,
+) -> AppResult<Matter> {
+    db.write(move |conn| {
+        matters::set_legal_hold(conn, &matter_id, hold)?;
+        let action = if hold { "legal_hold_placed" } else { "legal_hold_lifted" };
+        audit::record(conn, NewAuditEvent::new(action).matter(&matter_id))?;
+        matters::get_by_id(conn, &matter_id)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn set_document_matter(
+    db: State<'_, Database>,
+    document_id: String,
+    matter_id: Option<String>,
+) -> AppResult<Document> {
+    db.write(move |conn| {
+        if let Some(matter_id) = &matter_id {
+            matters::get_by_id(conn, matter_id)?;
+        }
+        documents::set_matter(conn, &document_id, matter_id.as_deref())?;
+        audit::record(
+            conn,
+            NewAuditEvent::new("document_matter_changed")
+                .document(&document_id)
+                .details(serde_json::json!({ "matter_id": matter_id })),
+        )?;
+        documents::get_by_id(conn, &document_id)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn set_comparison_matter(
+    db: State<'_, Database>,
+    comparison_id: String,
+    matter_id: Option<String>,
+) -> AppResult<Comparison> {
+    db.write(move |conn| {
+        if let Some(matter_id) = &matter_id {
+            matters::get_by_id(conn, matter_id)?;
+        }
+        comparisons::set_matter(conn, &comparison_id, matter_id.as_deref())?;
+        audit::record(
+            conn,
+            NewAuditEvent::new("comparison_matter_changed")
+                .subject("comparison", &comparison_id)
+                .details(serde_json::json!({ "matter_id": matter_id })),
+        )?;
+        comparisons::get_by_id(conn, &comparison_id)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn set_report_matter(
+    db: State<'_, Database>,
+    report_id: String,
+    matter_id: Option<String>,
+) -> AppResult<Report> {
+    db.write(move |conn| {
+        if let Some(matter_id) = &matter_id {
+            matters::get_by_id(conn, matter_id)?;
+        }
+        reports::set_matter(conn, &report_id, matter_id.as_deref())?;
+        audit::record(
+            conn,
+            NewAuditEvent::new("report_matter_changed")
+                .subject("report", &report_id)
+                .details(serde_json::json!({ "matter_id": matter_id })),
+        )?;
+        reports::get_by_id(conn, &report_id)
+    })
+    .await
+}
@@ -4,3 +4,27 @@ pub(crate) mod analysis_commands;
 pub(crate) mod comparison_commands;
 pub(crate) mod template_commands;
 pub(crate) mod report_commands;
+pub(crate) mod prompt_commands;
+pub(crate) mod ollama_commands;
+pub(crate) mod secret_commands;
+pub(crate) mod encryption_commands;
+pub(crate) mod audit_commands;
+pub(crate) mod matter_commands;
+pub(crate) mod tag_commands;
+pub(crate) mod version_commands;
+pub(crate) mod search_commands;
+pub(crate) mod question_commands;
+
+use std::path::PathBuf;
+
+use tauri::{AppHandle, Manager};
+
+use crate::error::{AppError, AppResult};
+
+/// The app's data folder; an error rather than a panic when the platform
+/// cannot provide one.
+pub(crate) fn app_data_dir(app: &AppHandle) -> AppResult<PathBuf> {
+    app.path()
+        .app_data_dir()
+        .map_err(|e| AppError::Io(std::io::Error::other(format!("Cannot locate the app data folder: {e}"))))
+}
@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use tauri::{AppHandle, Emitter, State};
+
+use crate::ai::{ollama_provider, policy, AiProvider, OllamaHealth, OllamaModel, OllamaProvider, PullProgress};
+use crate::db::Database;
+use crate::error::{AppError, AppResult};
+
+/// Event emitted with a [`PullProgress`] payload whenever a pull advances.
+pub const PULL_PROGRESS_EVENT: &str = "ollama-pull-progress";
+
+/// Pulls started from the app, keyed by model name. Finished and failed pulls
+/// stay listed until the same model is pulled again.
+#[derive(Default, Clone)]
+pub struct OllamaPulls(Arc<Mutex<HashMap<String, PullProgress>>>);
+
+impl OllamaPulls {
+    fn update(&self, progress: &PullProgress) {
+        self.0
+            .lock()
+            .expect("pull state lock poisoned")
+            .insert(progress.model.clone(), progress.clone());
+    }
+
+    fn is_running(&self, model: &str) -> bool {
+        self.0
+            .lock()
+            .expect("pull state lock poisoned")
+            .get(model)
+            .is_some_and(|p| !p.done)
+    }
+
+    fn snapshot(&self) -> Vec<PullProgress> {
+        let mut pulls: Vec<_> = self.0.lock().expect("pull state lock poisoned").values().cloned().collect();
+        pulls.sort_by(|a, b| a.model.cmp(&b.model));
+        pulls
+    }
+}
+
+/// The configured Ollama server, refused under the workspace local-only policy
+/// when it is not on this machine.
+async fn configured_ollama(db: &Database) -> AppResult<OllamaProvider> {
+    db.read(|conn| {
+        let ollama = ollama_provider(conn)?;
+        policy::enforce(policy::resolve(conn, &[])?, &ollama)?;
+        Ok(ollama)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn check_ollama_health(db: State<'_, Database>) -> AppResult<OllamaHealth> {
+    let ollama = configured_ollama(&db).await?;
+    Ok(ollama.health().await)
+}
+
+#[tauri::command]
+pub async fn list_ollama_models(db: State<'_, Database>) -> AppResult<Vec<OllamaModel>> {
+    let ollama = configured_ollama(&db).await?;
+    ollama.list_models().await
+}
+
+/// Starts pulling a model in the background and returns immediately. Progress
+/// is emitted as [`PULL_PROGRESS_EVENT`] and can be polled with `get_ollama_pulls`.
+#[tauri::command]
+pub async fn pull_ollama_model(
+    app: AppHandle,
+    db: State<'_, Database>,
+    pulls: State<'_, OllamaPulls>,
+    model: Option<String>,
+) -> AppResult<PullProgress> {
+    let ollama = configured_ollama(&db).await?;
+    let model = model.unwrap_or_else(|| ollama.model().to_string());
+    if pulls.is_running(&model) {
+        return Err(AppError::Validation(format!("A pull of {model} is already in progress")));
+    }
+
+    let started = PullProgress::new(&model);
+    pulls.update(&started);
+
+    let pulls = pulls.inner().clone();
+    tauri::async_runtime::spawn(async move {
+        let report = |progress: &PullProgress| {
+            pulls.update(progress);
+            let _ = app.emit(PULL_PROGRESS_EVENT, progress.clone());
+        };
+        if let Err(e) = ollama.pull(&model, report).await {
+            log::warn!("Ollama pull of {model} failed: {e}");
+            // Connection failures never reach the progress callback
+            let mut failed = PullProgress::new(&model);
+            failed.status = "failed".to_string();
+            failed.error = Some(e.to_string());
+            failed.done = true;
+            pulls.update(&failed);
+            let _ = app.emit(PULL_PROGRESS_EVENT, failed);
+        }
+    });
+
+    Ok(started)
+}
+
+#[tauri::command]
+pub async fn get_ollama_pulls(pulls: State<'_, OllamaPulls>) -> AppResult<Vec<PullProgress>> {
+    Ok(pulls.snapshot())
+}
+
+/// Loads the configured model into memory ahead of a batch of analyses.
+#[tauri::command]
+pub async fn warm_ollama_model(
+    db: State<'_, Database>,
+    keep_alive: Option<String>,
+) -> AppResult<()> {
+    let ollama = configured_ollama(&db).await?;
+    ollama.preflight().await?;
+    ollama.warm(keep_alive.as_deref().unwrap_or("10m")).await
+}
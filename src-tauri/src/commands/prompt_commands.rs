@@ -0,0 +1,123 @@
+use serde::Serialize;
+use tauri::State;
+
+use crate::ai::prompts::{self, DiffLine, PromptKind};
+use crate::ai::ProviderCache;
+use crate::db::audit::{self, NewAuditEvent};
+use crate::db::Database;
+use crate::db::prompts::{self as prompt_store, PromptVersion};
+use crate::error::AppResult;
+
+#[derive(Serialize)]
+pub struct PromptSummary {
+    pub prompt_kind: PromptKind,
+    pub active_version: i64,
+    pub latest_version: i64,
+    pub variables: Vec<&'static str>,
+}
+
+#[derive(Serialize)]
+pub struct PromptDiff {
+    pub prompt_kind: PromptKind,
+    pub from_version: i64,
+    pub to_version: i64,
+    pub system: Vec<DiffLine>,
+    pub user: Vec<DiffLine>,
+}
+
+#[tauri::command]
+pub async fn list_prompts(db: State<'_, Database>) -> AppResult<Vec<PromptSummary>> {
+    db.read(move |conn| {
+        PromptKind::ALL
+            .iter()
+            .map(|kind| {
+                let versions = prompt_store::list_versions(conn, *kind)?;
+                Ok(PromptSummary {
+                    prompt_kind: *kind,
+                    active_version: prompt_store::active(conn, *kind)?.version,
+                    latest_version: versions.first().map(|v| v.version).unwrap_or_default(),
+                    variables: kind.variables().to_vec(),
+                })
+            })
+            .collect()
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn get_prompt_history(
+    db: State<'_, Database>,
+    prompt_kind: String,
+) -> AppResult<Vec<PromptVersion>> {
+    let kind: PromptKind = prompt_kind.parse()?;
+    db.read(move |conn| prompt_store::list_versions(conn, kind)).await
+}
+
+#[tauri::command]
+pub async fn update_prompt(
+    db: State<'_, Database>,
+    cache: State<'_, ProviderCache>,
+    prompt_kind: String,
+    system_template: String,
+    user_template: String,
+    notes: Option<String>,
+) -> AppResult<PromptVersion> {
+    let kind: PromptKind = prompt_kind.parse()?;
+    let version = db.write(move |conn| {
+        let version = prompt_store::create_version(conn, kind, &system_template, &user_template, notes.as_deref())?;
+        audit::record(
+            conn,
+            NewAuditEvent::new("prompt_updated")
+                .details(serde_json::json!({ "prompt_kind": kind.as_str(), "version": version.version })),
+        )?;
+        Ok(version)
+    })
+    .await?;
+    // Providers are primed with the active templates
+    cache.invalidate();
+    Ok(version)
+}
+
+#[tauri::command]
+pub async fn diff_prompt_versions(
+    db: State<'_, Database>,
+    prompt_kind: String,
+    from_version: i64,
+    to_version: i64,
+) -> AppResult<PromptDiff> {
+    let kind: PromptKind = prompt_kind.parse()?;
+    db.read(move |conn| {
+        let from = prompt_store::get_version(conn, kind, from_version)?;
+        let to = prompt_store::get_version(conn, kind, to_version)?;
+        Ok(PromptDiff {
+            prompt_kind: kind,
+            from_version,
+            to_version,
+            system: prompts::diff_lines(&from.system_template, &to.system_template),
+            user: prompts::diff_lines(&from.user_template, &to.user_template),
+        })
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn rollback_prompt(
+    db: State<'_, Database>,
+    cache: State<'_, ProviderCache>,
+    prompt_kind: String,
+    version: i64,
+) -> AppResult<PromptVersion> {
+    let kind: PromptKind = prompt_kind.parse()?;
+    let active = db.write(move |conn| {
+        let active = prompt_store::activate(conn, kind, version)?;
+        audit::record(
+            conn,
+            NewAuditEvent::new("prompt_rolled_back")
+                .details(serde_json::json!({ "prompt_kind": kind.as_str(), "version": version })),
+        )?;
+        Ok(active)
+    })
+    .await?;
+    cache.invalidate();
+    Ok(active)
+}
@@ -0,0 +1,49 @@
+use tauri::State;
+
+use crate::ai::create_provider_for;
+use crate::analysis::questions::{self, Answer};
+use crate::db::conversations::{self, Conversation, ConversationMessage};
+use crate::db::Database;
+use crate::error::AppResult;
+
+/// Asks a question about one or more documents. Starts a new conversation
+/// unless `conversation_id` is given, in which case its documents are used.
+#[tauri::command]
+pub async fn ask_question(
+    db: State<'_, Database>,
+    app_handle: tauri::AppHandle,
+    document_ids: Vec<String>,
+    conversation_id: Option<String>,
+    question: String,
+) -> AppResult<Answer> {
+    let document_ids = match conversation_id.clone() {
+        Some(id) => db.read(move |conn| Ok(conversations::get_by_id(conn, &id)?.document_ids)).await?,
+        None => document_ids,
+    };
+    let ids: Vec<&str> = document_ids.iter().map(String::as_str).collect();
+    let provider = create_provider_for(&app_handle, &ids).await?;
+    questions::ask(&db, provider, conversation_id.as_deref(), &document_ids, &question).await
+}
+
+/// Conversations about a document, most recently active first.
+#[tauri::command]
+pub async fn list_conversations(db: State<'_, Database>, document_id: String) -> AppResult<Vec<Conversation>> {
+    db.read(move |conn| conversations::list_by_document(conn, &document_id)).await
+}
+
+#[tauri::command]
+pub async fn get_conversation_messages(
+    db: State<'_, Database>,
+    conversation_id: String,
+) -> AppResult<Vec<ConversationMessage>> {
+    db.read(move |conn| {
+        conversations::get_by_id(conn, &conversation_id)?;
+        conversations::list_messages(conn, &conversation_id)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn delete_conversation(db: State<'_, Database>, conversation_id: String) -> AppResult<()> {
+    db.write(move |conn| conversations::delete(conn, &conversation_id)).await
+}
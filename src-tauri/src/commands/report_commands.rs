@@ -1,59 +1,84 @@
 use std::sync::Arc;
 use tauri::{Manager, State};
 
-use crate::ai::{AiProvider, ExtractionResponse, RiskAssessmentResponse, OllamaProvider, ClaudeProvider, OpenAiProvider};
+use rusqlite::Connection;
+
+use crate::ai::prompts::DiffOp;
+use crate::ai::{
+    create_provider_for, policy, AiProvider, Difference, ExtractionResponse, RiskAssessmentResponse,
+};
+use crate::analysis;
+use crate::analysis::redline::{Redline, SectionStatus};
+use crate::db::redactions::RedactionSubject;
+use crate::db::audit::{self, NewAuditEvent};
 use crate::db::Database;
-use crate::db::{extractions, reports, risk_assessments, settings};
+use crate::db::comparisons::{self, Comparison};
+use crate::db::{documents, extractions, reports, risk_assessments, templates};
+use crate::encryption::Encryption;
 use crate::error::{AppError, AppResult};
 
-fn create_provider(db: &Database) -> AppResult<Arc<dyn AiProvider>> {
-    let conn = db.conn.lock().expect("db lock poisoned");
-    let provider_name = settings::get(&conn, "ai_provider")?
-        .unwrap_or_else(|| "ollama".to_string());
-
-    match provider_name.as_str() {
-        "ollama" => {
-            let url = settings::get(&conn, "ollama_url")?
-                .unwrap_or_else(|| "http://localhost:11434".to_string());
-            let model = settings::get(&conn, "ollama_model")?
-                .unwrap_or_else(|| "llama3".to_string());
-            Ok(Arc::new(OllamaProvider::new(url, model)))
-        }
-        "claude" => {
-            let api_key = settings::get(&conn, "claude_api_key")?
-                .ok_or_else(|| AppError::Validation("Claude API key not configured".to_string()))?;
-            let model = settings::get(&conn, "claude_model")?;
-            Ok(Arc::new(ClaudeProvider::new(api_key, model)))
-        }
-        "openai" => {
-            let api_key = settings::get(&conn, "openai_api_key")?
-                .ok_or_else(|| AppError::Validation("OpenAI API key not configured".to_string()))?;
-            let model = settings::get(&conn, "openai_model")?;
-            Ok(Arc::new(OpenAiProvider::new(api_key, model)))
-        }
-        other => Err(AppError::Validation(format!("Unknown AI provider: {other}"))),
-    }
-}
-
 #[tauri::command]
 pub async fn generate_report(
     db: State<'_, Database>,
     document_id: String,
     app_handle: tauri::AppHandle,
+) -> AppResult<reports::Report> {
+    let provider = create_provider_for(&app_handle, &[&document_id]).await?;
+    let report = create_report(&db, provider, &document_id).await?;
+    let export_path = export(&app_handle, &report).await?;
+
+    db.write(move |conn| {
+        audit::record(
+            conn,
+            NewAuditEvent::new("report_generated")
+                .document(&document_id)
+                .subject("report", &report.id)
+                .details(serde_json::json!({
+                    "report_type": report.report_type,
+                    "export_path": export_path.to_string_lossy(),
+                })),
+        )?;
+
+        Ok(report)
+    })
+    .await
+}
+
+/// Also exports the report as a text file in the app's reports folder,
+/// encrypted when the workspace is.
+async fn export(app_handle: &tauri::AppHandle, report: &reports::Report) -> AppResult<std::path::PathBuf> {
+    let reports_dir = super::app_data_dir(app_handle)?.join("reports");
+    let filename = format!("report_{}.txt", &report.id[..8]);
+    let (handle, content) = (app_handle.clone(), report.content.clone());
+    tauri::async_runtime::spawn_blocking(move || {
+        std::fs::create_dir_all(&reports_dir)?;
+        handle.state::<Encryption>().write_file(&reports_dir.join(filename), content.as_bytes())
+    })
+    .await
+    .map_err(|e| AppError::Task(e.to_string()))?
+}
+
+pub(crate) async fn create_report(
+    db: &Database,
+    provider: Arc<dyn AiProvider>,
+    document_id: &str,
 ) -> AppResult<reports::Report> {
     // Get latest extraction and risk assessment
-    let (extraction_data, risk_data) = {
-        let conn = db.conn.lock().expect("db lock poisoned");
-        let exts = extractions::list_by_document(&conn, &document_id)?;
-        let ext = exts.first().ok_or_else(|| {
-            AppError::NotFound("No extraction found. Run analysis first.".to_string())
-        })?;
-        let risks = risk_assessments::get_by_document(&conn, &document_id)?;
-        let risk = risks.first().ok_or_else(|| {
-            AppError::NotFound("No risk assessment found. Run analysis first.".to_string())
-        })?;
-        (ext.extracted_data.clone(), risk.clone())
-    };
+    let (id, checked) = (document_id.to_string(), provider.clone());
+    let (extraction_data, risk_data) = db
+        .read(move |conn| {
+            policy::enforce(policy::resolve(conn, &[&id])?, checked.as_ref())?;
+            let exts = extractions::list_by_document(conn, &id)?;
+            let ext = exts.first().ok_or_else(|| {
+                AppError::NotFound("No extraction found. Run analysis first.".to_string())
+            })?;
+            let risks = risk_assessments::get_by_document(conn, &id)?;
+            let risk = risks.first().ok_or_else(|| {
+                AppError::NotFound("No risk assessment found. Run analysis first.".to_string())
+            })?;
+            Ok((ext.extracted_data.clone(), risk.clone()))
+        })
+        .await?;
 
     let extraction: ExtractionResponse = serde_json::from_str(&extraction_data)
         .map_err(|e| AppError::AiProvider(format!("Failed to parse extraction: {e}")))?;
@@ -68,26 +93,18 @@ pub async fn generate_report(
     };
 
     // Generate AI summary
-    let provider = create_provider(&db)?;
-    let summary = provider.generate_summary(&extraction, &risk_response).await?;
+    let summary = provider.generate_summary(&extraction, &risk_response).await;
+    let (id, logged) = (document_id.to_string(), provider.clone());
+    db.write(move |conn| analysis::record_redactions(conn, logged.as_ref(), [RedactionSubject::Document(&id)]))
+        .await?;
+    let summary = summary?;
 
     // Build report content
     let report_content = build_report_content(&extraction, &risk_response, &summary);
 
     // Save report
-    let conn = db.conn.lock().expect("db lock poisoned");
-    let report = reports::insert(&conn, &document_id, "full_analysis", &report_content, "text")?;
-
-    // Also export as text file
-    let app_data = app_handle.path().app_data_dir()
-        .expect("failed to get app data dir");
-    let reports_dir = app_data.join("reports");
-    std::fs::create_dir_all(&reports_dir)?;
-    let filename = format!("report_{}.txt", &report.id[..8]);
-    let export_path = reports_dir.join(&filename);
-    std::fs::write(&export_path, &report_content)?;
-
-    Ok(report)
+    let document_id = document_id.to_string();
+    db.write(move |conn| reports::insert(conn, &document_id, "full_analysis", &report_content, "text")).await
 }
 
 fn build_report_content(
@@ -167,11 +184,323 @@ fn build_report_content(
     content
 }
 
+/// Reports on a stored comparison: `comparison_redline` marks up the text,
+/// `comparison_summary` tabulates the significant changes.
+#[tauri::command]
+pub async fn generate_comparison_report(
+    db: State<'_, Database>,
+    comparison_id: String,
+    report_type: String,
+    app_handle: tauri::AppHandle,
+) -> AppResult<reports::Report> {
+    let report = db.write(move |conn| create_comparison_report(conn, &comparison_id, &report_type)).await?;
+    let export_path = export(&app_handle, &report).await?;
+
+    db.write(move |conn| {
+        audit::record(
+            conn,
+            NewAuditEvent::new("report_generated")
+                .document(&report.document_id)
+                .subject("report", &report.id)
+                .details(serde_json::json!({
+                    "report_type": report.report_type,
+                    "comparison_id": report.comparison_id,
+                    "export_path": export_path.to_string_lossy(),
+                })),
+        )?;
+
+        Ok(report)
+    })
+    .await
+}
+
+pub(crate) fn create_comparison_report(
+    conn: &Connection,
+    comparison_id: &str,
+    report_type: &str,
+) -> AppResult<reports::Report> {
+    let comparison = comparisons::get_by_id(conn, comparison_id)?;
+    let (original, revised) = compared_names(conn, &comparison)?;
+
+    let content = match report_type {
+        "comparison_redline" => {
+            let redline: Redline = serde_json::from_str(comparison.redline.as_deref().ok_or_else(|| {
+                AppError::Validation(format!(
+                    "Comparison {comparison_id} was made before redlines were stored; re-run it first"
+                ))
+            })?)
+            .map_err(AppError::Json)?;
+            build_redline_report(&comparison, &original, &revised, &redline)
+        }
+        "comparison_summary" => {
+            let differences: Vec<Difference> = serde_json::from_str(&comparison.differences).map_err(AppError::Json)?;
+            build_summary_report(&comparison, &original, &revised, &differences)
+        }
+        other => return Err(AppError::Validation(format!("Unknown comparison report type: {other}"))),
+    };
+
+    reports::insert_for_comparison(conn, comparison_id, report_type, &content, "text")
+}
+
+/// What the comparison's redline went from and to.
+fn compared_names(conn: &Connection, comparison: &Comparison) -> AppResult<(String, String)> {
+    let draft = documents::get_by_id(conn, &comparison.document_a_id)?.filename;
+    if comparison.comparison_type == "document_vs_template" {
+        let template = match &comparison.template_id {
+            Some(id) => templates::get_by_id(conn, id)?.name,
+            None => "(deleted)".to_string(),
+        };
+        return Ok((format!("Template: {template}"), format!("Draft:    {draft}")));
+    }
+    let other = match &comparison.document_b_id {
+        Some(id) => documents::get_by_id(conn, id)?.filename,
+        None => "(none)".to_string(),
+    };
+    Ok((format!("Original: {draft}"), format!("Revised:  {other}")))
+}
+
+fn comparison_heading(content: &mut String, title: &str, comparison: &Comparison, original: &str, revised: &str) {
+    content.push_str("═══════════════════════════════════════════════════\n");
+    content.push_str(&format!("        {title}\n"));
+    content.push_str("═══════════════════════════════════════════════════\n\n");
+    content.push_str(&format!("{original}\n{revised}\n"));
+    content.push_str(&format!("Compared: {} ({})\n\n", comparison.created_at, comparison.comparison_type));
+    if let Some(summary) = &comparison.summary {
+        content.push_str("SUMMARY\n");
+        content.push_str("───────\n");
+        content.push_str(summary);
+        content.push_str("\n\n");
+    }
+}
+
+fn comparison_footer(content: &mut String) {
+    content.push_str("\n═══════════════════════════════════════════════════\n");
+    content.push_str("Generated by Legal Document Review Assistant\n");
+}
+
+/// Splits text into lines of at most `width` characters, breaking at spaces
+/// where it can.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let mut word = word.to_string();
+        while word.chars().count() > width {
+            let head: String = word.chars().take(width).collect();
+            word = word.chars().skip(width).collect();
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            lines.push(head);
+        }
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(&word);
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// The whole compared text, with deletions marked `[-like this-]` and
+/// insertions `{+like this+}`.
+fn build_redline_report(comparison: &Comparison, original: &str, revised: &str, redline: &Redline) -> String {
+    let mut content = String::new();
+    comparison_heading(&mut content, "COMPARISON REDLINE", comparison, original, revised);
+
+    let stats = &redline.stats;
+    content.push_str(&format!(
+        "Changes: {} insertions, {} deletions, {} replacements, {} moves\n",
+        stats.insertions, stats.deletions, stats.replacements, stats.moves
+    ));
+    content.push_str("Deletions are marked [-like this-], insertions {+like this+}\n");
+
+    for section in &redline.sections {
+        let heading = section.heading_b.as_deref().or(section.heading_a.as_deref()).unwrap_or("Preamble");
+        let status = match section.status {
+            SectionStatus::Unchanged => String::new(),
+            SectionStatus::Modified => " [MODIFIED]".to_string(),
+            SectionStatus::Added => " [ADDED]".to_string(),
+            SectionStatus::Removed => " [REMOVED]".to_string(),
+            SectionStatus::Moved => format!(" [MOVED from {}]", section.heading_a.as_deref().unwrap_or("Preamble")),
+        };
+        content.push_str(&format!("\n── {heading}{status}\n"));
+
+        let mut text = String::new();
+        for segment in &section.segments {
+            let marked = segment.text.trim_start();
+            text.push_str(&segment.text[..segment.text.len() - marked.len()]);
+            match segment.op {
+                DiffOp::Equal => text.push_str(marked),
+                DiffOp::Delete => text.push_str(&format!("[-{marked}-]")),
+                DiffOp::Insert => text.push_str(&format!("{{+{marked}+}}")),
+            }
+        }
+        for line in wrap(&text, 76) {
+            content.push_str(&format!("  {line}\n"));
+        }
+    }
+
+    comparison_footer(&mut content);
+    content
+}
+
+/// A table of the substantive changes of medium or high significance.
+fn build_summary_report(comparison: &Comparison, original: &str, revised: &str, differences: &[Difference]) -> String {
+    let mut content = String::new();
+    comparison_heading(&mut content, "COMPARISON SUMMARY", comparison, original, revised);
+
+    let significant: Vec<&Difference> = differences
+        .iter()
+        .filter(|d| d.significance != "low" && d.diff_type != "formatting")
+        .collect();
+    content.push_str(&format!(
+        "SIGNIFICANT CHANGES ({} of {})\n",
+        significant.len(),
+        differences.len()
+    ));
+    content.push_str("───────────────────\n");
+    if significant.is_empty() {
+        content.push_str("No significant changes.\n");
+        comparison_footer(&mut content);
+        return content;
+    }
+
+    let with_position = significant.iter().any(|d| d.position.is_some());
+    let mut header = format!("{:<6} {:<18} {:<7}", "ID", "Category", "Level");
+    if with_position {
+        header.push_str(&format!(" {:<12}", "Position"));
+    }
+    let description_width = 76 - header.chars().count();
+    header.push_str(" Change");
+    content.push_str(&format!("{header}\n{}\n", "─".repeat(78)));
+
+    for (index, difference) in significant.iter().enumerate() {
+        let id = difference.change_id.clone().unwrap_or_else(|| format!("{}", index + 1));
+        let category = difference.clause_type.as_deref().unwrap_or(&difference.category).replace('_', " ");
+        let mut row = format!("{:<6} {:<18} {:<7}", id, wrap(&category, 18)[0], difference.significance.to_uppercase());
+        if with_position {
+            row.push_str(&format!(" {:<12}", difference.position.as_deref().unwrap_or("").to_uppercase()));
+        }
+        let indent = " ".repeat(row.chars().count() + 1);
+        for (i, line) in wrap(&difference.description, description_width).iter().enumerate() {
+            if i == 0 {
+                content.push_str(&format!("{row} {line}\n"));
+            } else {
+                content.push_str(&format!("{indent}{line}\n"));
+            }
+        }
+    }
+
+    comparison_footer(&mut content);
+    content
+}
+
+#[tauri::command]
+pub async fn get_comparison_reports(
+    db: State<'_, Database>,
+    comparison_id: String,
+) -> AppResult<Vec<reports::Report>> {
+    db.read(move |conn| reports::list_by_comparison(conn, &comparison_id)).await
+}
+
 #[tauri::command]
 pub async fn get_reports(
     db: State<'_, Database>,
     document_id: String,
 ) -> AppResult<Vec<reports::Report>> {
-    let conn = db.conn.lock().expect("db lock poisoned");
-    reports::list_by_document(&conn, &document_id)
+    db.read(move |conn| reports::list_by_document(conn, &document_id)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::mock::{fixtures, MockProvider};
+    use crate::ai::prompts::PromptKind;
+    use crate::analysis;
+    use crate::db::documents::{self, CreateDocument};
+
+    #[tokio::test]
+    async fn test_create_report_after_analysis() {
+        let db = Database::in_memory().unwrap();
+        let doc_id = {
+            let conn = db.writer();
+            let doc = documents::insert(&conn, &CreateDocument {
+                filename: "nda.pdf".into(),
+                original_path: "/tmp/nda.pdf".into(),
+                stored_path: "/data/nda.pdf".into(),
+                file_hash: "hash".into(),
+                file_size: 10,
+                contract_type: "nda".into(),
+            }).unwrap();
+            documents::update_text(&conn, &doc.id, "Mutual NDA between Acme and Globex.", 1).unwrap();
+            doc.id
+        };
+        let mock = Arc::new(
+            MockProvider::new()
+                .respond(PromptKind::Extraction, fixtures::NDA_EXTRACTION)
+                .respond(PromptKind::Risk, fixtures::RISK)
+                .respond(PromptKind::Summary, fixtures::SUMMARY),
+        );
+
+        assert!(create_report(&db, mock.clone(), &doc_id).await.is_err());
+
+        analysis::run_full_analysis(&db, mock.clone(), &doc_id).await.unwrap();
+        let report = create_report(&db, mock, &doc_id).await.unwrap();
+
+        assert_eq!(report.report_type, "full_analysis");
+        assert!(report.content.contains(fixtures::SUMMARY));
+        assert!(report.content.contains("Acme Corp"));
+        assert!(report.content.contains("[GOVERNING_LAW] Governing Law"));
+    }
+
+    #[tokio::test]
+    async fn test_comparison_reports() {
+        let db = Database::in_memory().unwrap();
+        let insert = |filename: &str, text: &str| {
+            let conn = db.writer();
+            let doc = documents::insert(&conn, &CreateDocument {
+                filename: filename.into(),
+                original_path: format!("/tmp/{filename}"),
+                stored_path: format!("/data/{filename}"),
+                file_hash: filename.into(),
+                file_size: 10,
+                contract_type: "nda".into(),
+            }).unwrap();
+            documents::update_text(&conn, &doc.id, text, 1).unwrap();
+            doc.id
+        };
+        let a = insert("nda_v1.pdf", "Term: two years.");
+        let b = insert("nda_v2.pdf", "Term: three years.");
+        let mock = Arc::new(MockProvider::new().respond(PromptKind::Comparison, fixtures::COMPARISON));
+        let comparison = crate::commands::comparison_commands::run_comparison(&db, mock, &a, &b).await.unwrap();
+
+        let conn = db.writer();
+        let redline = create_comparison_report(&conn, &comparison.id, "comparison_redline").unwrap();
+        assert_eq!(redline.comparison_id.as_deref(), Some(comparison.id.as_str()));
+        assert_eq!(redline.document_id, a);
+        assert!(redline.content.contains("Original: nda_v1.pdf\nRevised:  nda_v2.pdf"));
+        assert!(redline.content.contains("  Term: [-two-] {+three+} years.\n"));
+
+        let summary = create_comparison_report(&conn, &comparison.id, "comparison_summary").unwrap();
+        assert!(summary.content.contains("SIGNIFICANT CHANGES (1 of 1)"));
+        assert!(summary.content.contains("C1     term               HIGH    Term extended from two to three years"));
+        assert!(create_comparison_report(&conn, &comparison.id, "comparison_memo").is_err());
+
+        assert_eq!(reports::list_by_comparison(&conn, &comparison.id).unwrap().len(), 2);
+        comparisons::delete(&conn, &comparison.id).unwrap();
+        assert!(reports::list_by_document(&conn, &a).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(wrap("one two three", 7), vec!["one two", "three"]);
+        assert_eq!(wrap("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+        assert_eq!(wrap("", 4), vec![""]);
+    }
 }
@@ -0,0 +1,40 @@
+use tauri::State;
+
+use crate::ai::create_embedder;
+use crate::analysis::similarity::{self, SimilarTo};
+use crate::db::clauses::SimilarClause;
+use crate::db::search::{self, SearchHit, SearchQuery};
+use crate::db::Database;
+use crate::error::{AppError, AppResult};
+
+/// Full-text search over document text and extracted clauses.
+#[tauri::command]
+pub async fn search(db: State<'_, Database>, query: SearchQuery) -> AppResult<Vec<SearchHit>> {
+    db.read(move |conn| search::search(conn, &query)).await
+}
+
+/// The clauses across all documents closest in meaning to a clause or to
+/// free text, with their cosine scores.
+#[tauri::command]
+pub async fn find_similar_clauses(
+    db: State<'_, Database>,
+    clause_id: Option<i64>,
+    query: Option<String>,
+    limit: Option<usize>,
+) -> AppResult<Vec<SimilarClause>> {
+    let target = match (clause_id, query) {
+        (Some(id), None) => SimilarTo::Clause(id),
+        (None, Some(text)) => SimilarTo::Text(text),
+        _ => return Err(AppError::Validation("Give either a clause or a text query".to_string())),
+    };
+    let embedder = db.read(create_embedder).await?;
+    similarity::find_similar(&db, embedder, target, limit.unwrap_or(10).clamp(1, 100)).await
+}
+
+/// Embeds clauses that have no vector from the configured embedding model,
+/// e.g. after switching models. Returns how many were embedded.
+#[tauri::command]
+pub async fn index_clause_embeddings(db: State<'_, Database>) -> AppResult<usize> {
+    let embedder = db.read(create_embedder).await?;
+    similarity::index_clauses(&db, embedder).await
+}
@@ -0,0 +1,83 @@
+use tauri::{AppHandle, Manager, State};
+
+use crate::ai::ProviderCache;
+use crate::db::audit::{self, NewAuditEvent};
+use crate::db::Database;
+use crate::error::{AppError, AppResult};
+use crate::secrets::{self, SecretStore, SecretsStatus};
+use super::settings_commands::notify_settings_changed;
+
+/// Which backend holds the secrets, whether it is locked, and which keys are
+/// set. Secret values are never returned to the frontend.
+#[tauri::command]
+pub async fn get_secrets_status(secrets: State<'_, SecretStore>) -> AppResult<SecretsStatus> {
+    Ok(secrets.status())
+}
+
+/// Unlocks the encrypted vault (creating it on first use) and moves any
+/// plaintext keys still in the settings table into it.
+#[tauri::command]
+pub async fn unlock_secrets(
+    app: AppHandle,
+    db: State<'_, Database>,
+    secrets: State<'_, SecretStore>,
+    cache: State<'_, ProviderCache>,
+    passphrase: String,
+) -> AppResult<SecretsStatus> {
+    // Deriving the vault key is deliberately slow
+    let (handle, database) = (app.clone(), db.inner().clone());
+    tauri::async_runtime::spawn_blocking(move || {
+        let secrets = handle.state::<SecretStore>();
+        secrets.unlock(&passphrase)?;
+        secrets::migrate_plaintext(&database.writer(), &secrets)
+    })
+    .await
+    .map_err(|e| AppError::Task(e.to_string()))??;
+    let keys = secrets::SECRET_KEYS.iter().map(|key| key.to_string()).collect();
+    notify_settings_changed(&app, &cache, keys);
+    Ok(secrets.status())
+}
+
+/// Records which secret changed; never its value.
+async fn record_secret_change(db: &Database, action: &'static str, key: String) -> AppResult<()> {
+    db.write(move |conn| {
+        audit::record(conn, NewAuditEvent::new(action).details(serde_json::json!({ "key": key })))?;
+        Ok(())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn set_secret(
+    app: AppHandle,
+    db: State<'_, Database>,
+    secrets: State<'_, SecretStore>,
+    cache: State<'_, ProviderCache>,
+    key: String,
+    value: String,
+) -> AppResult<()> {
+    secrets.set(&key, &value)?;
+    let action = if value.trim().is_empty() { "secret_deleted" } else { "secret_set" };
+    record_secret_change(&db, action, key.clone()).await?;
+    notify_settings_changed(&app, &cache, vec![key]);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn has_secret(secrets: State<'_, SecretStore>, key: String) -> AppResult<bool> {
+    secrets.is_set(&key)
+}
+
+#[tauri::command]
+pub async fn delete_secret(
+    app: AppHandle,
+    db: State<'_, Database>,
+    secrets: State<'_, SecretStore>,
+    cache: State<'_, ProviderCache>,
+    key: String,
+) -> AppResult<()> {
+    secrets.delete(&key)?;
+    record_secret_change(&db, "secret_deleted", key.clone()).await?;
+    notify_settings_changed(&app, &cache, vec![key]);
+    Ok(())
+}
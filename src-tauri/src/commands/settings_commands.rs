@@ -1,24 +1,65 @@
-use tauri::State;
+use tauri::{AppHandle, Emitter, State};
 
+use crate::ai::ProviderCache;
+use crate::config::{self, SettingsChanged, SettingsSection, SETTINGS_CHANGED_EVENT};
+use crate::db::audit::{self, NewAuditEvent};
 use crate::db::Database;
-use crate::db::settings;
-use crate::error::AppResult;
+use crate::error::{AppError, AppResult};
+use crate::secrets;
 
+/// Credentials go through the secret commands and are never readable here.
+fn reject_secret_key(key: &str) -> AppResult<()> {
+    if secrets::is_secret_key(key) {
+        return Err(AppError::Validation(format!(
+            "{key} is a secret; use set_secret / has_secret instead"
+        )));
+    }
+    Ok(())
+}
+
+/// Drops the cached provider so the next call is built from the new
+/// configuration, and tells the frontend which keys changed.
+pub(crate) fn notify_settings_changed(app: &AppHandle, cache: &ProviderCache, keys: Vec<String>) {
+    cache.invalidate();
+    if let Err(e) = app.emit(SETTINGS_CHANGED_EVENT, SettingsChanged { keys }) {
+        log::warn!("Failed to emit settings change: {e}");
+    }
+}
+
+/// The stored value, or the setting's default when unset.
 #[tauri::command]
 pub async fn get_setting(
     db: State<'_, Database>,
     key: String,
 ) -> AppResult<Option<String>> {
-    let conn = db.conn.lock().expect("db lock poisoned");
-    settings::get(&conn, &key)
+    reject_secret_key(&key)?;
+    db.read(move |conn| config::get(conn, &key)).await
 }
 
+/// Validates and stores a setting. An empty value restores the default.
 #[tauri::command]
 pub async fn set_setting(
+    app: AppHandle,
     db: State<'_, Database>,
+    cache: State<'_, ProviderCache>,
     key: String,
     value: String,
 ) -> AppResult<()> {
-    let conn = db.conn.lock().expect("db lock poisoned");
-    settings::set(&conn, &key, &value)
+    reject_secret_key(&key)?;
+    let changed = key.clone();
+    db.write(move |conn| {
+        config::set(conn, &key, &value)?;
+        audit::record(
+            conn,
+            NewAuditEvent::new("setting_changed").details(serde_json::json!({ "key": key, "value": value })),
+        )
+    })
+    .await?;
+    notify_settings_changed(&app, &cache, vec![changed]);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_all_settings(db: State<'_, Database>) -> AppResult<Vec<SettingsSection>> {
+    db.read(config::all).await
 }
@@ -0,0 +1,81 @@
+use tauri::State;
+
+use crate::db::audit::{self, NewAuditEvent};
+use crate::db::tags::{self, Tag, TagSubject};
+use crate::db::Database;
+use crate::error::AppResult;
+
+/// Every tag with how many documents, comparisons and reports carry it.
+#[tauri::command]
+pub async fn list_tags(db: State<'_, Database>) -> AppResult<Vec<Tag>> {
+    db.read(tags::list_all).await
+}
+
+/// Replaces a document's tags, creating any that do not exist yet.
+#[tauri::command]
+pub async fn set_document_tags(
+    db: State<'_, Database>,
+    document_id: String,
+    tags: Vec<String>,
+) -> AppResult<Vec<String>> {
+    db.write(move |conn| {
+        let tags = tags::set_tags(conn, TagSubject::Document(&document_id), &tags)?;
+        audit::record(
+            conn,
+            NewAuditEvent::new("document_tags_changed")
+                .document(&document_id)
+                .details(serde_json::json!({ "tags": tags })),
+        )?;
+        Ok(tags)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn set_comparison_tags(
+    db: State<'_, Database>,
+    comparison_id: String,
+    tags: Vec<String>,
+) -> AppResult<Vec<String>> {
+    db.write(move |conn| {
+        let tags = tags::set_tags(conn, TagSubject::Comparison(&comparison_id), &tags)?;
+        audit::record(
+            conn,
+            NewAuditEvent::new("comparison_tags_changed")
+                .subject("comparison", &comparison_id)
+                .details(serde_json::json!({ "tags": tags })),
+        )?;
+        Ok(tags)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn set_report_tags(
+    db: State<'_, Database>,
+    report_id: String,
+    tags: Vec<String>,
+) -> AppResult<Vec<String>> {
+    db.write(move |conn| {
+        let tags = tags::set_tags(conn, TagSubject::Report(&report_id), &tags)?;
+        audit::record(
+            conn,
+            NewAuditEvent::new("report_tags_changed")
+                .subject("report", &report_id)
+                .details(serde_json::json!({ "tags": tags })),
+        )?;
+        Ok(tags)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn rename_tag(db: State<'_, Database>, tag_id: String, name: String) -> AppResult<()> {
+    db.write(move |conn| tags::rename(conn, &tag_id, &name)).await
+}
+
+/// Deletes a tag and removes it from everything it is on.
+#[tauri::command]
+pub async fn delete_tag(db: State<'_, Database>, tag_id: String) -> AppResult<()> {
+    db.write(move |conn| tags::delete(conn, &tag_id)).await
+}
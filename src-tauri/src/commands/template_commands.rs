@@ -1,5 +1,6 @@
 use tauri::State;
 
+use crate::db::audit::{self, NewAuditEvent};
 use crate::db::Database;
 use crate::db::templates::{self, Template};
 use crate::error::AppResult;
@@ -12,14 +13,22 @@ pub async fn create_template(
     description: Option<String>,
     raw_text: String,
 ) -> AppResult<Template> {
-    let conn = db.conn.lock().expect("db lock poisoned");
-    templates::insert(&conn, &name, &contract_type, description.as_deref(), &raw_text)
+    db.write(move |conn| {
+        let template = templates::insert(conn, &name, &contract_type, description.as_deref(), &raw_text)?;
+        audit::record(
+            conn,
+            NewAuditEvent::new("template_created")
+                .subject("template", &template.id)
+                .details(serde_json::json!({ "name": template.name, "contract_type": template.contract_type })),
+        )?;
+        Ok(template)
+    })
+    .await
 }
 
 #[tauri::command]
 pub async fn list_templates(db: State<'_, Database>) -> AppResult<Vec<Template>> {
-    let conn = db.conn.lock().expect("db lock poisoned");
-    templates::list_all(&conn)
+    db.read(templates::list_all).await
 }
 
 #[tauri::command]
@@ -27,6 +36,10 @@ pub async fn delete_template(
     db: State<'_, Database>,
     template_id: String,
 ) -> AppResult<()> {
-    let conn = db.conn.lock().expect("db lock poisoned");
-    templates::delete(&conn, &template_id)
+    db.write(move |conn| {
+        templates::delete(conn, &template_id)?;
+        audit::record(conn, NewAuditEvent::new("template_deleted").subject("template", &template_id))?;
+        Ok(())
+    })
+    .await
 }
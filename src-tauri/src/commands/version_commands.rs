@@ -0,0 +1,97 @@
+use tauri::State;
+
+use crate::ai::create_provider_for;
+use crate::commands::comparison_commands::run_comparison;
+use crate::db::audit::{self, NewAuditEvent};
+use crate::db::comparisons::Comparison;
+use crate::db::documents::{self, Document};
+use crate::db::versions::{self, DocumentVersion};
+use crate::db::Database;
+use crate::error::{AppError, AppResult};
+
+#[derive(Debug, serde::Serialize)]
+pub struct VersionAdded {
+    pub document: Document,
+    /// The comparison against the previous version, when it could be run.
+    pub comparison: Option<Comparison>,
+    /// Why the comparison was not run; the version is added regardless.
+    pub comparison_error: Option<String>,
+}
+
+/// Adds an uploaded document as the newest version of another document's
+/// family, then compares it with the version before it.
+#[tauri::command]
+pub async fn add_document_version(
+    db: State<'_, Database>,
+    app_handle: tauri::AppHandle,
+    version_of: String,
+    document_id: String,
+    label: Option<String>,
+) -> AppResult<VersionAdded> {
+    let id = document_id.clone();
+    let document = db
+        .write(move |conn| {
+            let document = versions::add_version(conn, &version_of, &id, label.as_deref())?;
+            audit::record(
+                conn,
+                NewAuditEvent::new("document_version_added").document(&document.id).details(serde_json::json!({
+                    "family_id": document.family_id,
+                    "version_number": document.version_number,
+                    "version_label": document.version_label,
+                })),
+            )?;
+            Ok(document)
+        })
+        .await?;
+
+    let (comparison, comparison_error) = match compare_with_previous(&db, &app_handle, &document_id).await {
+        Ok(comparison) => (Some(comparison), None),
+        Err(e) => (None, Some(e.to_string())),
+    };
+    Ok(VersionAdded { document, comparison, comparison_error })
+}
+
+/// The document's versions, oldest first, each with its comparison against
+/// the previous one.
+#[tauri::command]
+pub async fn get_document_versions(db: State<'_, Database>, document_id: String) -> AppResult<Vec<DocumentVersion>> {
+    db.read(move |conn| versions::history(conn, &document_id)).await
+}
+
+/// Compares a version with the one before it, e.g. after the automatic
+/// comparison failed or the text was extracted later.
+#[tauri::command]
+pub async fn compare_with_previous_version(
+    db: State<'_, Database>,
+    app_handle: tauri::AppHandle,
+    document_id: String,
+) -> AppResult<Comparison> {
+    compare_with_previous(&db, &app_handle, &document_id).await
+}
+
+#[tauri::command]
+pub async fn set_version_label(
+    db: State<'_, Database>,
+    document_id: String,
+    label: Option<String>,
+) -> AppResult<Document> {
+    db.write(move |conn| {
+        versions::set_label(conn, &document_id, label.as_deref())?;
+        documents::get_by_id(conn, &document_id)
+    })
+    .await
+}
+
+async fn compare_with_previous(
+    db: &Database,
+    app_handle: &tauri::AppHandle,
+    document_id: &str,
+) -> AppResult<Comparison> {
+    let id = document_id.to_string();
+    let previous = db
+        .read(move |conn| versions::previous(conn, &id))
+        .await?
+        .ok_or_else(|| AppError::Validation("This is the first version; there is nothing to compare".to_string()))?;
+    let provider = create_provider_for(app_handle, &[&previous, document_id]).await?;
+    run_comparison(db, provider, &previous, document_id).await
+}
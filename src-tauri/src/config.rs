@@ -0,0 +1,479 @@
+use rusqlite::Connection;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::ai::prompts::PromptKind;
+use crate::db::settings;
+use crate::error::{AppError, AppResult};
+
+/// Emitted with a [`SettingsChanged`] payload whenever settings or secrets change.
+pub const SETTINGS_CHANGED_EVENT: &str = "settings-changed";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SettingsChanged {
+    pub keys: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SettingGroup {
+    Ai,
+    Ollama,
+    Cloud,
+    Privacy,
+    Documents,
+    Developer,
+}
+
+impl SettingGroup {
+    pub const ALL: [SettingGroup; 6] =
+        [Self::Ai, Self::Ollama, Self::Cloud, Self::Privacy, Self::Documents, Self::Developer];
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SettingType {
+    Choice { options: &'static [&'static str] },
+    Boolean,
+    Integer { min: i64, max: i64 },
+    Number { min: f64, max: f64 },
+    Url,
+    ModelName,
+    /// An Ollama keep-alive such as `5m`, `1h`, `0` or `-1`.
+    Duration,
+    /// One regular expression per line.
+    Patterns,
+    #[cfg_attr(not(debug_assertions), allow(dead_code))]
+    Path,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct SettingDef {
+    #[serde(skip)]
+    pub key: &'static str,
+    pub group: SettingGroup,
+    #[serde(flatten)]
+    pub value_type: SettingType,
+    pub default: Option<&'static str>,
+    pub description: &'static str,
+}
+
+/// Ollama options that can also be set per task as `ollama_{task}_{option}`.
+const OLLAMA_TASK_OPTIONS: [&str; 6] = ["num_ctx", "num_predict", "temperature", "top_p", "seed", "keep_alive"];
+
+/// The `replay` provider serves recorded fixtures and exists in debug builds only.
+#[cfg(any(test, debug_assertions))]
+const AI_PROVIDERS: &[&str] = &["ollama", "claude", "openai", "replay"];
+#[cfg(not(any(test, debug_assertions)))]
+const AI_PROVIDERS: &[&str] = &["ollama", "claude", "openai"];
+
+/// Every setting the app reads. API keys are not here; they live in the
+/// secret store.
+pub const SETTINGS: &[SettingDef] = &[
+    SettingDef {
+        key: "ai_provider",
+        group: SettingGroup::Ai,
+        value_type: SettingType::Choice { options: AI_PROVIDERS },
+        default: Some("ollama"),
+        description: "Provider used for extraction, risk scoring, comparison and summaries",
+    },
+    SettingDef {
+        key: "embedding_provider",
+        group: SettingGroup::Ai,
+        value_type: SettingType::Choice { options: &["ollama", "local"] },
+        default: Some("ollama"),
+        description: "Embeds clauses for similar-clause search; local needs no model but only matches shared wording",
+    },
+    SettingDef {
+        key: "ollama_url",
+        group: SettingGroup::Ollama,
+        value_type: SettingType::Url,
+        default: Some("http://localhost:11434"),
+        description: "Base URL of the Ollama server",
+    },
+    SettingDef {
+        key: "ollama_model",
+        group: SettingGroup::Ollama,
+        value_type: SettingType::ModelName,
+        default: Some("llama3"),
+        description: "Ollama model name, e.g. llama3 or llama3.1:8b",
+    },
+    SettingDef {
+        key: "ollama_embedding_model",
+        group: SettingGroup::Ollama,
+        value_type: SettingType::ModelName,
+        default: Some("nomic-embed-text"),
+        description: "Ollama model used for clause embeddings",
+    },
+    SettingDef {
+        key: "ollama_num_ctx",
+        group: SettingGroup::Ollama,
+        value_type: SettingType::Integer { min: 512, max: 1_048_576 },
+        default: None,
+        description: "Context window in tokens; empty sizes it per task",
+    },
+    SettingDef {
+        key: "ollama_num_predict",
+        group: SettingGroup::Ollama,
+        value_type: SettingType::Integer { min: 1, max: 1_048_576 },
+        default: None,
+        description: "Maximum tokens to generate; empty uses each task's limit",
+    },
+    SettingDef {
+        key: "ollama_temperature",
+        group: SettingGroup::Ollama,
+        value_type: SettingType::Number { min: 0.0, max: 2.0 },
+        default: None,
+        description: "Sampling temperature; empty uses each task's default",
+    },
+    SettingDef {
+        key: "ollama_top_p",
+        group: SettingGroup::Ollama,
+        value_type: SettingType::Number { min: 0.0, max: 1.0 },
+        default: None,
+        description: "Nucleus sampling cutoff",
+    },
+    SettingDef {
+        key: "ollama_seed",
+        group: SettingGroup::Ollama,
+        value_type: SettingType::Integer { min: 0, max: u32::MAX as i64 },
+        default: None,
+        description: "Fixed seed for reproducible output",
+    },
+    SettingDef {
+        key: "ollama_keep_alive",
+        group: SettingGroup::Ollama,
+        value_type: SettingType::Duration,
+        default: None,
+        description: "How long Ollama keeps the model loaded, e.g. 5m, 1h or -1",
+    },
+    SettingDef {
+        key: "claude_model",
+        group: SettingGroup::Cloud,
+        value_type: SettingType::ModelName,
+        default: None,
+        description: "Claude model; empty uses the provider default",
+    },
+    SettingDef {
+        key: "openai_model",
+        group: SettingGroup::Cloud,
+        value_type: SettingType::ModelName,
+        default: None,
+        description: "OpenAI model; empty uses the provider default",
+    },
+    SettingDef {
+        key: "local_only",
+        group: SettingGroup::Privacy,
+        value_type: SettingType::Boolean,
+        default: Some("false"),
+        description: "Never send documents off this machine",
+    },
+    SettingDef {
+        key: "redaction_enabled",
+        group: SettingGroup::Privacy,
+        value_type: SettingType::Boolean,
+        default: Some("true"),
+        description: "Replace personal data with placeholders before calling cloud providers",
+    },
+    SettingDef {
+        key: "redaction_patterns",
+        group: SettingGroup::Privacy,
+        value_type: SettingType::Patterns,
+        default: None,
+        description: "Extra regular expressions to redact, one per line",
+    },
+    SettingDef {
+        key: "upload_max_mb",
+        group: SettingGroup::Documents,
+        value_type: SettingType::Integer { min: 1, max: 500 },
+        default: Some("50"),
+        description: "Largest PDF that can be uploaded, in megabytes",
+    },
+    SettingDef {
+        key: "trash_retention_days",
+        group: SettingGroup::Documents,
+        value_type: SettingType::Integer { min: 0, max: 3650 },
+        default: Some("30"),
+        description: "Days a deleted document stays in the trash before it is purged; 0 keeps it until purged by hand",
+    },
+    SettingDef {
+        key: "closed_matter_text_retention_days",
+        group: SettingGroup::Documents,
+        value_type: SettingType::Integer { min: 0, max: 3650 },
+        default: None,
+        description: "Days after a matter closes before the text of its documents is deleted: the stored file, extracted text, clauses, redlines, reports and conversations. Parties, dates and risk scores are kept; unset keeps everything",
+    },
+    #[cfg(any(test, debug_assertions))]
+    SettingDef {
+        key: "ai_record_dir",
+        group: SettingGroup::Developer,
+        value_type: SettingType::Path,
+        default: None,
+        description: "Record every provider exchange as a replay fixture in this directory",
+    },
+    #[cfg(any(test, debug_assertions))]
+    SettingDef {
+        key: "ai_replay_dir",
+        group: SettingGroup::Developer,
+        value_type: SettingType::Path,
+        default: None,
+        description: "Fixture directory served by the replay provider",
+    },
+];
+
+/// The definition for a key, including per-task Ollama overrides such as
+/// `ollama_extraction_num_ctx`, which share the definition of `ollama_num_ctx`.
+pub fn definition(key: &str) -> Option<SettingDef> {
+    if let Some(def) = SETTINGS.iter().find(|def| def.key == key) {
+        return Some(*def);
+    }
+    let option = task_override_option(key)?;
+    let base = SETTINGS.iter().find(|def| def.key.strip_prefix("ollama_") == Some(option))?;
+    Some(SettingDef { key: base.key, default: None, ..*base })
+}
+
+fn task_override_option(key: &str) -> Option<&'static str> {
+    let rest = key.strip_prefix("ollama_")?;
+    PromptKind::ALL.iter().find_map(|kind| {
+        let option = rest.strip_prefix(kind.as_str())?.strip_prefix('_')?;
+        OLLAMA_TASK_OPTIONS.iter().copied().find(|known| *known == option)
+    })
+}
+
+/// Checks a value against the key's definition and returns it normalized.
+/// An empty value means "unset" and is always allowed.
+pub fn validate(key: &str, value: &str) -> AppResult<String> {
+    let def = definition(key).ok_or_else(|| AppError::Validation(format!("Unknown setting: {key}")))?;
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(String::new());
+    }
+    let invalid = |reason: String| AppError::Validation(format!("Invalid value for {key}: {reason}"));
+
+    match def.value_type {
+        SettingType::Choice { options } => {
+            let choice = value.to_ascii_lowercase();
+            if options.contains(&choice.as_str()) {
+                Ok(choice)
+            } else {
+                Err(invalid(format!("expected one of {}, got {value:?}", options.join(", "))))
+            }
+        }
+        SettingType::Boolean => match value.to_ascii_lowercase().as_str() {
+            "true" => Ok("true".to_string()),
+            "false" => Ok("false".to_string()),
+            _ => Err(invalid(format!("expected true or false, got {value:?}"))),
+        },
+        SettingType::Integer { min, max } => {
+            let n: i64 = value.parse().map_err(|_| invalid(format!("expected a whole number, got {value:?}")))?;
+            if (min..=max).contains(&n) {
+                Ok(n.to_string())
+            } else {
+                Err(invalid(format!("must be between {min} and {max}")))
+            }
+        }
+        SettingType::Number { min, max } => {
+            let n: f64 = value.parse().map_err(|_| invalid(format!("expected a number, got {value:?}")))?;
+            if n.is_finite() && (min..=max).contains(&n) {
+                Ok(value.to_string())
+            } else {
+                Err(invalid(format!("must be between {min} and {max}")))
+            }
+        }
+        SettingType::Url => {
+            let url = reqwest::Url::parse(value).map_err(|e| invalid(format!("{e}")))?;
+            if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none() {
+                return Err(invalid("expected an http:// or https:// URL".to_string()));
+            }
+            Ok(value.trim_end_matches('/').to_string())
+        }
+        SettingType::ModelName => {
+            let valid = value
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-' | ':' | '/'));
+            if valid {
+                Ok(value.to_string())
+            } else {
+                Err(invalid(format!("{value:?} is not a valid model name")))
+            }
+        }
+        SettingType::Duration => {
+            let number = value.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+            let unit = &value[number.len()..];
+            if number.parse::<f64>().is_ok() && matches!(unit, "" | "ms" | "s" | "m" | "h") {
+                Ok(value.to_string())
+            } else {
+                Err(invalid(format!("expected a duration such as 5m, 1h or -1, got {value:?}")))
+            }
+        }
+        SettingType::Patterns => {
+            for line in value.lines().map(str::trim).filter(|l| !l.is_empty()) {
+                regex::Regex::new(line).map_err(|e| invalid(format!("{line:?}: {e}")))?;
+            }
+            Ok(value.to_string())
+        }
+        SettingType::Path => {
+            if std::path::Path::new(value).is_absolute() {
+                Ok(value.to_string())
+            } else {
+                Err(invalid("expected an absolute path".to_string()))
+            }
+        }
+    }
+}
+
+/// Validates and stores a setting; an empty value removes it so the default
+/// applies again. Returns the stored value.
+pub fn set(conn: &Connection, key: &str, value: &str) -> AppResult<Option<String>> {
+    let value = validate(key, value)?;
+    if value.is_empty() {
+        settings::delete(conn, key)?;
+        Ok(None)
+    } else {
+        settings::set(conn, key, &value)?;
+        Ok(Some(value))
+    }
+}
+
+/// The stored value, or the default when unset or empty.
+pub fn get(conn: &Connection, key: &str) -> AppResult<Option<String>> {
+    let default = definition(key).and_then(|def| def.default);
+    Ok(settings::get(conn, key)?
+        .filter(|value| !value.trim().is_empty())
+        .or_else(|| default.map(str::to_string)))
+}
+
+pub fn get_bool(conn: &Connection, key: &str) -> AppResult<bool> {
+    Ok(get(conn, key)?.is_some_and(|value| value.eq_ignore_ascii_case("true")))
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SettingEntry {
+    #[serde(flatten)]
+    pub definition: SettingDef,
+    /// The key as stored; differs from `definition.key` for per-task overrides.
+    pub key: String,
+    pub value: Value,
+    pub is_default: bool,
+    /// Set when a stored value no longer passes validation.
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SettingsSection {
+    pub group: SettingGroup,
+    pub settings: Vec<SettingEntry>,
+}
+
+/// All settings with their effective values, grouped by subsystem. Per-task
+/// Ollama overrides are listed only when set.
+pub fn all(conn: &Connection) -> AppResult<Vec<SettingsSection>> {
+    let mut keys: Vec<String> = SETTINGS.iter().map(|def| def.key.to_string()).collect();
+    for kind in PromptKind::ALL {
+        for option in OLLAMA_TASK_OPTIONS {
+            let key = format!("ollama_{}_{option}", kind.as_str());
+            if settings::get(conn, &key)?.is_some() {
+                keys.push(key);
+            }
+        }
+    }
+
+    let mut sections: Vec<SettingsSection> = SettingGroup::ALL
+        .iter()
+        .map(|group| SettingsSection { group: *group, settings: Vec::new() })
+        .collect();
+    for key in keys {
+        let def = definition(&key).expect("listed keys are defined");
+        let stored = settings::get(conn, &key)?.filter(|value| !value.trim().is_empty());
+        let error = stored.as_deref().and_then(|value| validate(&key, value).err()).map(|e| e.to_string());
+        let value = stored.as_deref().or(def.default).map(|raw| typed_value(def.value_type, raw)).unwrap_or(Value::Null);
+        let section = sections.iter_mut().find(|s| s.group == def.group).expect("every group has a section");
+        section.settings.push(SettingEntry {
+            definition: def,
+            is_default: stored.is_none(),
+            key,
+            value,
+            error,
+        });
+    }
+    // Developer settings are compiled out of release builds
+    sections.retain(|section| !section.settings.is_empty());
+    Ok(sections)
+}
+
+fn typed_value(value_type: SettingType, raw: &str) -> Value {
+    let parsed = match value_type {
+        SettingType::Boolean => raw.parse::<bool>().ok().map(Value::from),
+        SettingType::Integer { .. } => raw.parse::<i64>().ok().map(Value::from),
+        SettingType::Number { .. } => raw.parse::<f64>().ok().map(Value::from),
+        _ => None,
+    };
+    parsed.unwrap_or_else(|| Value::from(raw))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+
+    #[test]
+    fn test_validate_by_type() {
+        assert_eq!(validate("ai_provider", " Claude ").unwrap(), "claude");
+        assert!(validate("ai_provider", "gemini").is_err());
+        assert_eq!(validate("ollama_url", "http://localhost:11434/").unwrap(), "http://localhost:11434");
+        assert!(validate("ollama_url", "localhost:11434").is_err());
+        assert!(validate("ollama_url", "ftp://host").is_err());
+        assert!(validate("ollama_model", "llama3.1:8b").is_ok());
+        assert!(validate("ollama_model", "llama 3").is_err());
+        assert!(validate("ollama_num_ctx", "100").is_err());
+        assert!(validate("ollama_num_ctx", "8k").is_err());
+        assert_eq!(validate("ollama_num_ctx", "").unwrap(), "");
+        assert!(validate("ollama_temperature", "0.2").is_ok());
+        assert!(validate("ollama_top_p", "1.5").is_err());
+        assert!(validate("ollama_keep_alive", "10m").is_ok());
+        assert!(validate("ollama_keep_alive", "-1").is_ok());
+        assert!(validate("ollama_keep_alive", "forever").is_err());
+        assert_eq!(validate("local_only", "TRUE").unwrap(), "true");
+        assert!(validate("redaction_patterns", "MATTER-\\d+\n(unclosed").is_err());
+        assert!(validate("upload_max_mb", "0").is_err());
+        assert!(validate("olama_url", "http://localhost").is_err());
+        assert!(validate("claude_api_key", "sk-ant").is_err());
+    }
+
+    #[test]
+    fn test_task_overrides_share_base_definition() {
+        assert!(validate("ollama_extraction_num_ctx", "24000").is_ok());
+        assert!(validate("ollama_risk_temperature", "warm").is_err());
+        assert!(definition("ollama_extraction_model").is_none());
+        assert!(definition("ollama_drafting_num_ctx").is_none());
+    }
+
+    #[test]
+    fn test_set_get_and_group_all() {
+        let db = Database::in_memory().unwrap();
+        let conn = db.writer();
+
+        assert_eq!(get(&conn, "ollama_model").unwrap().as_deref(), Some("llama3"));
+        assert!(get_bool(&conn, "redaction_enabled").unwrap());
+        set(&conn, "ollama_model", "mistral").unwrap();
+        set(&conn, "ollama_extraction_num_ctx", "24000").unwrap();
+        assert!(set(&conn, "ollama_url", "not a url").is_err());
+        // Legacy rows written before validation are reported, not hidden
+        settings::set(&conn, "ollama_num_ctx", "lots").unwrap();
+
+        let sections = all(&conn).unwrap();
+        let ollama = sections.iter().find(|s| s.group == SettingGroup::Ollama).unwrap();
+        let entry = |key: &str| ollama.settings.iter().find(|e| e.key == key).unwrap();
+        assert_eq!(entry("ollama_model").value, Value::from("mistral"));
+        assert!(!entry("ollama_model").is_default);
+        assert!(entry("ollama_url").is_default);
+        assert_eq!(entry("ollama_extraction_num_ctx").value, Value::from(24000));
+        assert!(entry("ollama_num_ctx").error.is_some());
+
+        let privacy = sections.iter().find(|s| s.group == SettingGroup::Privacy).unwrap();
+        assert_eq!(privacy.settings[0].value, Value::Bool(false));
+
+        set(&conn, "ollama_model", "").unwrap();
+        assert_eq!(get(&conn, "ollama_model").unwrap().as_deref(), Some("llama3"));
+    }
+}
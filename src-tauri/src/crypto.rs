@@ -0,0 +1,103 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use argon2::password_hash::SaltString;
+use argon2::Argon2;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use crate::error::{AppError, AppResult};
+
+pub const NONCE_LEN: usize = 12;
+
+pub type Key = Zeroizing<[u8; 32]>;
+
+/// An AES-256-GCM ciphertext with its nonce, base64-encoded for JSON files.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SealedValue {
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+pub fn generate_salt() -> String {
+    SaltString::generate(&mut OsRng).to_string()
+}
+
+pub fn generate_key() -> Key {
+    let mut key = Zeroizing::new([0u8; 32]);
+    key.copy_from_slice(&Aes256Gcm::generate_key(&mut OsRng));
+    key
+}
+
+/// Derives a 256-bit key from a passphrase with Argon2id.
+pub fn derive_key(passphrase: &str, salt: &str) -> AppResult<Key> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt.as_bytes(), key.as_mut())
+        .map_err(|e| AppError::Validation(format!("Failed to derive key: {e}")))?;
+    Ok(key)
+}
+
+/// Encrypts `plaintext`, authenticating `aad` with it. Returns nonce || ciphertext.
+pub fn encrypt(key: &[u8; 32], aad: &[u8], plaintext: &[u8]) -> AppResult<Vec<u8>> {
+    let cipher = Aes256Gcm::new(key.into());
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, Payload { msg: plaintext, aad })
+        .map_err(|_| AppError::Validation("Encryption failed".to_string()))?;
+    let mut out = nonce.to_vec();
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+/// Reverses [`encrypt`]. Fails if the key is wrong or the data was altered.
+pub fn decrypt(key: &[u8; 32], aad: &[u8], data: &[u8]) -> AppResult<Zeroizing<Vec<u8>>> {
+    if data.len() < NONCE_LEN {
+        return Err(AppError::Validation("Encrypted data is truncated".to_string()));
+    }
+    let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+    Aes256Gcm::new(key.into())
+        .decrypt(Nonce::from_slice(nonce), Payload { msg: ciphertext, aad })
+        .map(Zeroizing::new)
+        .map_err(|_| AppError::Validation("Decryption failed: wrong key or corrupt data".to_string()))
+}
+
+/// Encrypts a value for storage in a JSON file; `label` is authenticated so
+/// a value cannot be moved to another entry.
+pub fn seal(key: &[u8; 32], label: &str, plaintext: &[u8]) -> AppResult<SealedValue> {
+    let sealed = encrypt(key, label.as_bytes(), plaintext)?;
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    Ok(SealedValue {
+        nonce: BASE64.encode(nonce),
+        ciphertext: BASE64.encode(ciphertext),
+    })
+}
+
+pub fn open(key: &[u8; 32], label: &str, sealed: &SealedValue) -> AppResult<Zeroizing<Vec<u8>>> {
+    let corrupt = || AppError::Validation("Encrypted value is corrupt".to_string());
+    let mut data = BASE64.decode(&sealed.nonce).map_err(|_| corrupt())?;
+    if data.len() != NONCE_LEN {
+        return Err(corrupt());
+    }
+    data.extend(BASE64.decode(&sealed.ciphertext).map_err(|_| corrupt())?);
+    decrypt(key, label.as_bytes(), &data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_open_is_bound_to_key_and_label() {
+        let key = generate_key();
+        let sealed = seal(&key, "a", b"secret").unwrap();
+        assert_eq!(open(&key, "a", &sealed).unwrap().as_slice(), b"secret");
+        assert!(open(&key, "b", &sealed).is_err());
+        assert!(open(&generate_key(), "a", &sealed).is_err());
+
+        let salt = generate_salt();
+        assert_eq!(*derive_key("pass phrase", &salt).unwrap(), *derive_key("pass phrase", &salt).unwrap());
+        assert_ne!(*derive_key("pass phrase", &salt).unwrap(), *derive_key("other", &salt).unwrap());
+    }
+}
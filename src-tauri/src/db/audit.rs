@@ -0,0 +1,297 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::error::AppResult;
+
+/// `prev_hash` of the first event in the chain.
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEvent {
+    pub seq: i64,
+    pub id: String,
+    pub occurred_at: String,
+    pub actor: String,
+    pub action: String,
+    pub document_id: Option<String>,
+    pub matter_id: Option<String>,
+    pub subject_type: Option<String>,
+    pub subject_id: Option<String>,
+    /// JSON object with action-specific details.
+    pub details: String,
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+impl AuditEvent {
+    /// SHA-256 over every field and the previous hash. Fields are length
+    /// prefixed so no two different events serialize the same way.
+    pub fn compute_hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        let seq = self.seq.to_string();
+        let fields = [
+            Some(self.prev_hash.as_str()),
+            Some(seq.as_str()),
+            Some(self.id.as_str()),
+            Some(self.occurred_at.as_str()),
+            Some(self.actor.as_str()),
+            Some(self.action.as_str()),
+            self.document_id.as_deref(),
+            self.matter_id.as_deref(),
+            self.subject_type.as_deref(),
+            self.subject_id.as_deref(),
+            Some(self.details.as_str()),
+        ];
+        for field in fields {
+            match field {
+                Some(value) => hasher.update(format!("{}:{value};", value.len())),
+                None => hasher.update("-;"),
+            }
+        }
+        format!("{:x}", hasher.finalize())
+    }
+}
+
+/// An event to append. Built with [`NewAuditEvent::new`] and the chained setters.
+#[derive(Debug, Clone, Default)]
+pub struct NewAuditEvent<'a> {
+    pub action: &'a str,
+    pub document_id: Option<&'a str>,
+    pub matter_id: Option<&'a str>,
+    pub subject_type: Option<&'a str>,
+    pub subject_id: Option<&'a str>,
+    pub details: serde_json::Value,
+}
+
+impl<'a> NewAuditEvent<'a> {
+    pub fn new(action: &'a str) -> Self {
+        Self {
+            action,
+            details: serde_json::json!({}),
+            ..Default::default()
+        }
+    }
+
+    pub fn document(mut self, document_id: &'a str) -> Self {
+        self.document_id = Some(document_id);
+        self
+    }
+
+    pub fn matter(mut self, matter_id: &'a str) -> Self {
+        self.matter_id = Some(matter_id);
+        self
+    }
+
+    pub fn subject(mut self, subject_type: &'a str, subject_id: &'a str) -> Self {
+        self.subject_type = Some(subject_type);
+        self.subject_id = Some(subject_id);
+        self
+    }
+
+    pub fn details(mut self, details: serde_json::Value) -> Self {
+        self.details = details;
+        self
+    }
+}
+
+/// The OS account running the app; the closest thing to a user identity a
+/// single-user desktop app has.
+pub fn current_actor() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+const SELECT_COLUMNS: &str = "seq, id, occurred_at, actor, action, document_id, matter_id,
+        subject_type, subject_id, details, prev_hash, hash";
+
+fn map_row(row: &rusqlite::Row) -> rusqlite::Result<AuditEvent> {
+    Ok(AuditEvent {
+        seq: row.get(0)?,
+        id: row.get(1)?,
+        occurred_at: row.get(2)?,
+        actor: row.get(3)?,
+        action: row.get(4)?,
+        document_id: row.get(5)?,
+        matter_id: row.get(6)?,
+        subject_type: row.get(7)?,
+        subject_id: row.get(8)?,
+        details: row.get(9)?,
+        prev_hash: row.get(10)?,
+        hash: row.get(11)?,
+    })
+}
+
+/// Appends an event, chained to the last one.
+pub fn record(conn: &Connection, event: NewAuditEvent) -> AppResult<AuditEvent> {
+    let last: Option<(i64, String)> = conn
+        .query_row("SELECT seq, hash FROM audit_events ORDER BY seq DESC LIMIT 1", [], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })
+        .optional()?;
+    let (last_seq, prev_hash) = last.unwrap_or((0, GENESIS_HASH.to_string()));
+    // Events about a document belong to its matter too, so per-matter exports
+    // are complete
+    let matter_id = match (event.matter_id, event.document_id) {
+        (Some(matter_id), _) => Some(matter_id.to_string()),
+        (None, Some(document_id)) => conn
+            .query_row("SELECT matter_id FROM documents WHERE id = ?1", params![document_id], |row| row.get(0))
+            .optional()?
+            .flatten(),
+        (None, None) => None,
+    };
+    let occurred_at: String = conn.query_row("SELECT strftime('%Y-%m-%dT%H:%M:%fZ', 'now')", [], |row| row.get(0))?;
+
+    let mut entry = AuditEvent {
+        seq: last_seq + 1,
+        id: uuid::Uuid::new_v4().to_string(),
+        occurred_at,
+        actor: current_actor(),
+        action: event.action.to_string(),
+        document_id: event.document_id.map(str::to_string),
+        matter_id,
+        subject_type: event.subject_type.map(str::to_string),
+        subject_id: event.subject_id.map(str::to_string),
+        details: event.details.to_string(),
+        prev_hash,
+        hash: String::new(),
+    };
+    entry.hash = entry.compute_hash();
+
+    conn.execute(
+        "INSERT INTO audit_events (seq, id, occurred_at, actor, action, document_id, matter_id,
+                                   subject_type, subject_id, details, prev_hash, hash)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        params![
+            entry.seq, entry.id, entry.occurred_at, entry.actor, entry.action, entry.document_id,
+            entry.matter_id, entry.subject_type, entry.subject_id, entry.details, entry.prev_hash, entry.hash
+        ],
+    )?;
+    Ok(entry)
+}
+
+/// Events for one document or one matter, oldest first; all events when
+/// neither is given.
+pub fn list(conn: &Connection, document_id: Option<&str>, matter_id: Option<&str>) -> AppResult<Vec<AuditEvent>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {SELECT_COLUMNS} FROM audit_events
+         WHERE (?1 IS NULL OR document_id = ?1) AND (?2 IS NULL OR matter_id = ?2)
+         ORDER BY seq"
+    ))?;
+    let events = stmt
+        .query_map(params![document_id, matter_id], map_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(events)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditVerification {
+    pub valid: bool,
+    pub events_checked: i64,
+    /// Hash of the last event. Recording it elsewhere also makes truncation
+    /// of the log detectable.
+    pub head_hash: Option<String>,
+    pub first_invalid_seq: Option<i64>,
+    pub problem: Option<String>,
+}
+
+/// Walks the whole chain, recomputing every hash. Detects edited, inserted,
+/// reordered and deleted events.
+pub fn verify(conn: &Connection) -> AppResult<AuditVerification> {
+    let events = list(conn, None, None)?;
+    let mut prev_hash = GENESIS_HASH.to_string();
+    let mut checked = 0;
+    for (expected_seq, event) in (1..).zip(&events) {
+        let problem = if event.seq != expected_seq {
+            Some(format!("expected event #{expected_seq}, found #{}; events are missing", event.seq))
+        } else if event.prev_hash != prev_hash {
+            Some("does not link to the previous event".to_string())
+        } else if event.compute_hash() != event.hash {
+            Some("contents do not match its hash; the event was modified".to_string())
+        } else {
+            None
+        };
+        if let Some(problem) = problem {
+            return Ok(AuditVerification {
+                valid: false,
+                events_checked: checked,
+                head_hash: events.last().map(|e| e.hash.clone()),
+                first_invalid_seq: Some(event.seq),
+                problem: Some(format!("Event #{}: {problem}", event.seq)),
+            });
+        }
+        prev_hash = event.hash.clone();
+        checked += 1;
+    }
+    Ok(AuditVerification {
+        valid: true,
+        events_checked: checked,
+        head_hash: events.last().map(|e| e.hash.clone()),
+        first_invalid_seq: None,
+        problem: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+    use serde_json::json;
+
+    fn seed(conn: &Connection) {
+        record(conn, NewAuditEvent::new("document_uploaded").document("doc-1").details(json!({"filename": "nda.pdf"}))).unwrap();
+        record(conn, NewAuditEvent::new("setting_changed").details(json!({"key": "ai_provider"}))).unwrap();
+        record(conn, NewAuditEvent::new("document_analyzed").document("doc-1").subject("extraction", "ext-1")).unwrap();
+    }
+
+    #[test]
+    fn test_events_are_chained_and_filterable() {
+        let db = Database::in_memory().unwrap();
+        let conn = db.writer();
+        seed(&conn);
+
+        let all = list(&conn, None, None).unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0].prev_hash, GENESIS_HASH);
+        assert_eq!(all[1].prev_hash, all[0].hash);
+        assert_eq!(list(&conn, Some("doc-1"), None).unwrap().len(), 2);
+
+        let verification = verify(&conn).unwrap();
+        assert!(verification.valid);
+        assert_eq!(verification.events_checked, 3);
+        assert_eq!(verification.head_hash.as_deref(), Some(all[2].hash.as_str()));
+    }
+
+    #[test]
+    fn test_log_is_append_only() {
+        let db = Database::in_memory().unwrap();
+        let conn = db.writer();
+        seed(&conn);
+        assert!(conn.execute("UPDATE audit_events SET actor = 'someone else'", []).is_err());
+        assert!(conn.execute("DELETE FROM audit_events WHERE seq = 2", []).is_err());
+    }
+
+    #[test]
+    fn test_verify_detects_tampering() {
+        let db = Database::in_memory().unwrap();
+        let conn = db.writer();
+        seed(&conn);
+
+        // Someone with raw file access drops the guard and edits an event
+        conn.execute_batch("DROP TRIGGER audit_events_no_update; DROP TRIGGER audit_events_no_delete;").unwrap();
+        conn.execute("UPDATE audit_events SET details = '{}' WHERE seq = 1", []).unwrap();
+        let verification = verify(&conn).unwrap();
+        assert!(!verification.valid);
+        assert_eq!(verification.first_invalid_seq, Some(1));
+
+        let db = Database::in_memory().unwrap();
+        let conn = db.writer();
+        seed(&conn);
+        conn.execute_batch("DROP TRIGGER audit_events_no_delete;").unwrap();
+        conn.execute("DELETE FROM audit_events WHERE seq = 2", []).unwrap();
+        let verification = verify(&conn).unwrap();
+        assert_eq!(verification.first_invalid_seq, Some(3));
+        assert!(verification.problem.unwrap().contains("missing"));
+    }
+}
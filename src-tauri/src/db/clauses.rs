@@ -0,0 +1,255 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+
+use crate::ai::cosine;
+use crate::error::{AppError, AppResult};
+
+/// A clause from an extraction, copied out of its JSON by trigger.
+#[derive(Debug, Clone, Serialize)]
+pub struct Clause {
+    pub id: i64,
+    pub extraction_id: String,
+    pub document_id: String,
+    pub clause_index: i64,
+    pub clause_type: String,
+    pub title: String,
+    pub text: String,
+    pub section_reference: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SimilarClause {
+    pub clause_id: i64,
+    pub document_id: String,
+    pub filename: String,
+    pub contract_type: String,
+    pub clause_type: String,
+    pub title: String,
+    pub text: String,
+    pub section_reference: Option<String>,
+    /// Cosine similarity to the query, from -1 to 1.
+    pub score: f32,
+}
+
+const SELECT_COLUMNS: &str =
+    "c.id, c.extraction_id, c.document_id, c.clause_index, c.clause_type, c.title, c.text, c.section_reference";
+
+/// Clauses from each document's latest extraction, on documents not in the
+/// trash. Older extractions keep their clauses for history only.
+pub(crate) const CURRENT: &str = "c.extraction_id = (SELECT e.id FROM extractions e WHERE e.document_id = c.document_id
+                                  ORDER BY e.created_at DESC, e.rowid DESC LIMIT 1)
+        AND EXISTS (SELECT 1 FROM documents d WHERE d.id = c.document_id AND d.deleted_at IS NULL)";
+
+fn map_row(row: &rusqlite::Row) -> rusqlite::Result<Clause> {
+    Ok(Clause {
+        id: row.get(0)?,
+        extraction_id: row.get(1)?,
+        document_id: row.get(2)?,
+        clause_index: row.get(3)?,
+        clause_type: row.get(4)?,
+        title: row.get(5)?,
+        text: row.get(6)?,
+        section_reference: row.get(7)?,
+    })
+}
+
+pub fn get_by_id(conn: &Connection, id: i64) -> AppResult<Clause> {
+    conn.query_row(&format!("SELECT {SELECT_COLUMNS} FROM clauses c WHERE c.id = ?1"), params![id], map_row)
+        .map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => AppError::NotFound(format!("Clause {id} not found")),
+            other => AppError::Database(other),
+        })
+}
+
+/// Deletes the document's clauses along with their search entries and
+/// embeddings.
+pub fn delete_by_document(conn: &Connection, document_id: &str) -> AppResult<()> {
+    conn.execute("DELETE FROM clauses WHERE document_id = ?1", params![document_id])?;
+    Ok(())
+}
+
+/// Current clauses with no embedding from `model`.
+pub fn list_unembedded(conn: &Connection, model: &str) -> AppResult<Vec<Clause>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {SELECT_COLUMNS} FROM clauses c
+         WHERE {CURRENT}
+           AND NOT EXISTS (SELECT 1 FROM clause_embeddings ce WHERE ce.clause_id = c.id AND ce.model = ?1)
+         ORDER BY c.document_id, c.clause_index"
+    ))?;
+    let clauses = stmt.query_map(params![model], map_row)?.collect::<Result<Vec<_>, _>>()?;
+    Ok(clauses)
+}
+
+fn to_blob(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+fn from_blob(blob: &[u8]) -> Vec<f32> {
+    blob.chunks_exact(4)
+        .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .collect()
+}
+
+/// Stores a clause's embedding, replacing one from any other model.
+pub fn set_embedding(conn: &Connection, clause_id: i64, model: &str, vector: &[f32]) -> AppResult<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO clause_embeddings (clause_id, model, dimensions, vector) VALUES (?1, ?2, ?3, ?4)",
+        params![clause_id, model, vector.len() as i64, to_blob(vector)],
+    )?;
+    Ok(())
+}
+
+pub fn get_embedding(conn: &Connection, clause_id: i64, model: &str) -> AppResult<Option<Vec<f32>>> {
+    let blob: Option<Vec<u8>> = conn
+        .query_row(
+            "SELECT vector FROM clause_embeddings WHERE clause_id = ?1 AND model = ?2",
+            params![clause_id, model],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(blob.map(|blob| from_blob(&blob)))
+}
+
+/// The current clauses whose `model` embeddings are closest to `vector` by
+/// cosine similarity, best first. A brute-force scan, which is fast enough
+/// for a single portfolio.
+pub fn nearest(
+    conn: &Connection,
+    model: &str,
+    vector: &[f32],
+    exclude: Option<i64>,
+    limit: usize,
+) -> AppResult<Vec<SimilarClause>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT ce.clause_id, ce.vector FROM clause_embeddings ce JOIN clauses c ON c.id = ce.clause_id
+         WHERE ce.model = ?1 AND {CURRENT}"
+    ))?;
+    let mut scored = stmt
+        .query_map(params![model], |row| {
+            let id: i64 = row.get(0)?;
+            let blob: Vec<u8> = row.get(1)?;
+            Ok((id, cosine(vector, &from_blob(&blob))))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    scored.retain(|(id, _)| Some(*id) != exclude);
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+    scored.truncate(limit);
+
+    let mut details = conn.prepare(
+        "SELECT c.document_id, d.filename, d.contract_type, c.clause_type, c.title, c.text, c.section_reference
+         FROM clauses c JOIN documents d ON d.id = c.document_id WHERE c.id = ?1",
+    )?;
+    scored
+        .into_iter()
+        .map(|(clause_id, score)| {
+            let similar = details.query_row(params![clause_id], |row| {
+                Ok(SimilarClause {
+                    clause_id,
+                    document_id: row.get(0)?,
+                    filename: row.get(1)?,
+                    contract_type: row.get(2)?,
+                    clause_type: row.get(3)?,
+                    title: row.get(4)?,
+                    text: row.get(5)?,
+                    section_reference: row.get(6)?,
+                    score,
+                })
+            })?;
+            Ok(similar)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::documents::{self, CreateDocument};
+    use crate::db::extractions::{self, CreateExtraction};
+    use crate::db::Database;
+
+    fn insert_doc(conn: &Connection, filename: &str) -> String {
+        documents::insert(conn, &CreateDocument {
+            filename: filename.into(),
+            original_path: format!("/tmp/{filename}"),
+            stored_path: format!("/data/{filename}"),
+            file_hash: filename.into(),
+            file_size: 10,
+            contract_type: "nda".into(),
+        }).unwrap().id
+    }
+
+    fn extract(conn: &Connection, doc_id: &str, texts: &[&str]) -> Vec<i64> {
+        let clauses: Vec<_> = texts
+            .iter()
+            .map(|text| serde_json::json!({ "clause_type": "other", "title": "Clause", "text": text, "importance": "low" }))
+            .collect();
+        let ext = extractions::insert(conn, &CreateExtraction {
+            document_id: doc_id.into(),
+            ai_provider: "mock".into(),
+            ai_model: None,
+            contract_type: "nda".into(),
+            extracted_data: serde_json::json!({ "parties": [], "clauses": clauses }).to_string(),
+            confidence_score: None,
+            processing_time_ms: None,
+            prompt_version: None,
+            data_policy: None,
+            ai_endpoint: None,
+        }).unwrap();
+        let mut stmt = conn.prepare("SELECT id FROM clauses WHERE extraction_id = ?1 ORDER BY clause_index").unwrap();
+        let ids = stmt.query_map(params![ext.id], |row| row.get(0)).unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+        ids
+    }
+
+    #[test]
+    fn test_clauses_follow_extractions() {
+        let db = Database::in_memory().unwrap();
+        let conn = db.writer();
+        let doc = insert_doc(&conn, "nda.pdf");
+        let ids = extract(&conn, &doc, &["First.", "Second."]);
+        let clause = get_by_id(&conn, ids[1]).unwrap();
+        assert_eq!(clause.text, "Second.");
+        assert_eq!(clause.clause_index, 1);
+        assert!(matches!(get_by_id(&conn, 9999), Err(AppError::NotFound(_))));
+
+        set_embedding(&conn, ids[0], "m", &[1.0, 0.0]).unwrap();
+        assert_eq!(get_embedding(&conn, ids[0], "m").unwrap(), Some(vec![1.0, 0.0]));
+        assert_eq!(get_embedding(&conn, ids[0], "other").unwrap(), None);
+        let unembedded: Vec<i64> = list_unembedded(&conn, "m").unwrap().iter().map(|c| c.id).collect();
+        assert_eq!(unembedded, vec![ids[1]]);
+
+        // A newer extraction replaces the current clauses
+        conn.execute("UPDATE extractions SET created_at = datetime('now', '-1 day')", []).unwrap();
+        let newer = extract(&conn, &doc, &["Third."]);
+        let unembedded: Vec<i64> = list_unembedded(&conn, "m").unwrap().iter().map(|c| c.id).collect();
+        assert_eq!(unembedded, newer);
+
+        documents::soft_delete(&conn, &doc).unwrap();
+        assert!(list_unembedded(&conn, "m").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_nearest_ranks_by_cosine() {
+        let db = Database::in_memory().unwrap();
+        let conn = db.writer();
+        let a = insert_doc(&conn, "a.pdf");
+        let b = insert_doc(&conn, "b.pdf");
+        let ids_a = extract(&conn, &a, &["x", "y"]);
+        let ids_b = extract(&conn, &b, &["z"]);
+        set_embedding(&conn, ids_a[0], "m", &[1.0, 0.0]).unwrap();
+        set_embedding(&conn, ids_a[1], "m", &[0.0, 1.0]).unwrap();
+        set_embedding(&conn, ids_b[0], "m", &[0.8, 0.6]).unwrap();
+
+        let hits = nearest(&conn, "m", &[1.0, 0.0], Some(ids_a[0]), 10).unwrap();
+        assert_eq!(hits.iter().map(|h| h.clause_id).collect::<Vec<_>>(), vec![ids_b[0], ids_a[1]]);
+        assert!((hits[0].score - 0.8).abs() < 1e-6);
+        assert_eq!(hits[0].filename, "b.pdf");
+
+        assert_eq!(nearest(&conn, "m", &[1.0, 0.0], None, 1).unwrap()[0].clause_id, ids_a[0]);
+        assert!(nearest(&conn, "other", &[1.0, 0.0], None, 10).unwrap().is_empty());
+
+        // Embeddings go with their clause
+        documents::delete(&conn, &b).unwrap();
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM clause_embeddings", [], |r| r.get(0)).unwrap();
+        assert_eq!(count, 2);
+    }
+}
@@ -1,6 +1,8 @@
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 
+use crate::db::{documents, tags};
 use crate::error::{AppError, AppResult};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -11,8 +13,16 @@ pub struct Comparison {
     pub template_id: Option<String>,
     pub comparison_type: String,
     pub differences: String,
+    /// The word-level redline the differences were found in, as JSON;
+    /// `None` for comparisons made before redlines were stored.
+    pub redline: Option<String>,
     pub summary: Option<String>,
     pub ai_provider: Option<String>,
+    pub data_policy: Option<String>,
+    pub ai_endpoint: Option<String>,
+    pub matter_id: Option<String>,
+    /// Tag names, sorted.
+    pub tags: Vec<String>,
     pub created_at: String,
 }
 
@@ -22,15 +32,53 @@ pub struct CreateComparison<'a> {
     pub template_id: Option<&'a str>,
     pub comparison_type: &'a str,
     pub differences: &'a str,
+    pub redline: Option<&'a str>,
     pub summary: Option<&'a str>,
     pub ai_provider: Option<&'a str>,
+    pub data_policy: Option<&'a str>,
+    pub ai_endpoint: Option<&'a str>,
 }
 
+const SELECT_COLUMNS: &str = "id, document_a_id, document_b_id, template_id, comparison_type, differences, summary,
+        ai_provider, data_policy, ai_endpoint, matter_id,
+        (SELECT json_group_array(name) FROM (SELECT t.name FROM comparison_tags ct JOIN tags t ON t.id = ct.tag_id
+         WHERE ct.comparison_id = comparisons.id ORDER BY t.name COLLATE NOCASE)),
+        created_at, redline";
+
+fn map_row(row: &rusqlite::Row) -> rusqlite::Result<Comparison> {
+    Ok(Comparison {
+        id: row.get(0)?,
+        document_a_id: row.get(1)?,
+        document_b_id: row.get(2)?,
+        template_id: row.get(3)?,
+        comparison_type: row.get(4)?,
+        differences: row.get(5)?,
+        summary: row.get(6)?,
+        ai_provider: row.get(7)?,
+        data_policy: row.get(8)?,
+        ai_endpoint: row.get(9)?,
+        matter_id: row.get(10)?,
+        tags: tags::parse_names(11, &row.get::<_, String>(11)?)?,
+        created_at: row.get(12)?,
+        redline: row.get(13)?,
+    })
+}
+
+/// Narrows comparison listings; unset fields match everything.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ComparisonFilter {
+    /// Matches either compared document.
+    pub document_id: Option<String>,
+    pub matter_id: Option<String>,
+    pub tag: Option<String>,
+}
+
+/// The comparison starts out in the first document's matter.
 pub fn insert(conn: &Connection, comparison: &CreateComparison<'_>) -> AppResult<Comparison> {
     let id = uuid::Uuid::new_v4().to_string();
     conn.execute(
-        "INSERT INTO comparisons (id, document_a_id, document_b_id, template_id, comparison_type, differences, summary, ai_provider)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        "INSERT INTO comparisons (id, document_a_id, document_b_id, template_id, comparison_type, differences, redline, summary, ai_provider, data_policy, ai_endpoint, matter_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, (SELECT matter_id FROM documents WHERE id = ?2))",
         params![
             id,
             comparison.document_a_id,
@@ -38,34 +86,172 @@ pub fn insert(conn: &Connection, comparison: &CreateComparison<'_>) -> AppResult
             comparison.template_id,
             comparison.comparison_type,
             comparison.differences,
+            comparison.redline,
             comparison.summary,
-            comparison.ai_provider
+            comparison.ai_provider,
+            comparison.data_policy,
+            comparison.ai_endpoint
         ],
     )?;
     get_by_id(conn, &id)
 }
 
-fn get_by_id(conn: &Connection, id: &str) -> AppResult<Comparison> {
-    conn.query_row(
-        "SELECT id, document_a_id, document_b_id, template_id, comparison_type, differences, summary, ai_provider, created_at
-         FROM comparisons WHERE id = ?1",
-        params![id],
-        |row| {
-            Ok(Comparison {
-                id: row.get(0)?,
-                document_a_id: row.get(1)?,
-                document_b_id: row.get(2)?,
-                template_id: row.get(3)?,
-                comparison_type: row.get(4)?,
-                differences: row.get(5)?,
-                summary: row.get(6)?,
-                ai_provider: row.get(7)?,
-                created_at: row.get(8)?,
+pub fn get_by_id(conn: &Connection, id: &str) -> AppResult<Comparison> {
+    conn.query_row(&format!("SELECT {SELECT_COLUMNS} FROM comparisons WHERE id = ?1"), params![id], map_row)
+        .map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => AppError::NotFound(format!("Comparison {id} not found")),
+            other => AppError::Database(other),
+        })
+}
+
+/// The most recent comparison of document A against document B.
+pub fn latest_between(conn: &Connection, document_a_id: &str, document_b_id: &str) -> AppResult<Option<Comparison>> {
+    let comparison = conn
+        .query_row(
+            &format!(
+                "SELECT {SELECT_COLUMNS} FROM comparisons WHERE document_a_id = ?1 AND document_b_id = ?2
+                 ORDER BY created_at DESC, rowid DESC LIMIT 1"
+            ),
+            params![document_a_id, document_b_id],
+            map_row,
+        )
+        .optional()?;
+    Ok(comparison)
+}
+
+pub fn set_matter(conn: &Connection, id: &str, matter_id: Option<&str>) -> AppResult<()> {
+    let rows = conn.execute("UPDATE comparisons SET matter_id = ?1 WHERE id = ?2", params![matter_id, id])?;
+    if rows == 0 {
+        return Err(AppError::NotFound(format!("Comparison {id} not found")));
+    }
+    Ok(())
+}
+
+/// Newest first.
+pub fn list(conn: &Connection, filter: &ComparisonFilter) -> AppResult<Vec<Comparison>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {SELECT_COLUMNS} FROM comparisons
+         WHERE (?1 IS NULL OR document_a_id = ?1 OR document_b_id = ?1)
+           AND (?2 IS NULL OR matter_id = ?2)
+           AND (?3 IS NULL OR EXISTS (SELECT 1 FROM comparison_tags ct JOIN tags t ON t.id = ct.tag_id
+                                      WHERE ct.comparison_id = comparisons.id AND t.name = ?3))
+         ORDER BY created_at DESC, rowid DESC"
+    ))?;
+    let results = stmt
+        .query_map(params![filter.document_id, filter.matter_id, filter.tag], map_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(results)
+}
+
+/// Drops the contract text kept in comparisons with the document: the
+/// redline and the quoted text of each difference. Descriptions and
+/// summaries are kept.
+pub fn clear_text(conn: &Connection, document_id: &str) -> AppResult<()> {
+    let mut stmt =
+        conn.prepare("SELECT id, differences FROM comparisons WHERE document_a_id = ?1 OR document_b_id = ?1")?;
+    let rows = stmt
+        .query_map(params![document_id], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+        .collect::<Result<Vec<_>, _>>()?;
+    for (id, differences) in rows {
+        let mut differences: Value = serde_json::from_str(&differences).unwrap_or_else(|_| json!([]));
+        if let Some(items) = differences.as_array_mut() {
+            for item in items.iter_mut().filter_map(Value::as_object_mut) {
+                for key in ["text_a", "text_b"] {
+                    if let Some(text) = item.get_mut(key) {
+                        *text = Value::Null;
+                    }
+                }
+            }
+        }
+        conn.execute(
+            "UPDATE comparisons SET redline = NULL, differences = ?1 WHERE id = ?2",
+            params![differences.to_string(), id],
+        )?;
+    }
+    Ok(())
+}
+
+/// Removes the comparison with its reports, tags and redaction log. A
+/// comparison whose matter or documents are under litigation hold is kept.
+pub fn delete(conn: &Connection, id: &str) -> AppResult<()> {
+    let comparison = get_by_id(conn, id)?;
+    let mut held = match &comparison.matter_id {
+        Some(matter_id) => {
+            conn.query_row("SELECT legal_hold FROM matters WHERE id = ?1", params![matter_id], |row| row.get(0))?
+        }
+        None => false,
+    };
+    for document_id in std::iter::once(&comparison.document_a_id).chain(&comparison.document_b_id) {
+        held = held || documents::is_on_hold(conn, document_id)?;
+    }
+    if held {
+        return Err(AppError::Validation(format!(
+            "Comparison {id} is under litigation hold and cannot be deleted"
+        )));
+    }
+    conn.execute("DELETE FROM comparisons WHERE id = ?1", params![id])?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::documents::CreateDocument;
+    use crate::db::{matters, Database};
+
+    fn insert_comparison(conn: &Connection, a: &str, b: &str) -> Comparison {
+        insert(conn, &CreateComparison {
+            document_a_id: a,
+            document_b_id: Some(b),
+            template_id: None,
+            comparison_type: "document_vs_document",
+            differences: "[]",
+            redline: None,
+            summary: None,
+            ai_provider: None,
+            data_policy: None,
+            ai_endpoint: None,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_list_filters_and_held_comparisons_are_kept() {
+        let db = Database::in_memory().unwrap();
+        let conn = db.writer();
+        let doc = |name: &str| {
+            documents::insert(&conn, &CreateDocument {
+                filename: name.into(),
+                original_path: format!("/tmp/{name}"),
+                stored_path: format!("/data/{name}"),
+                file_hash: name.into(),
+                file_size: 10,
+                contract_type: "nda".into(),
             })
-        },
-    )
-    .map_err(|e| match e {
-        rusqlite::Error::QueryReturnedNoRows => AppError::NotFound(format!("Comparison {id} not found")),
-        other => AppError::Database(other),
-    })
+            .unwrap()
+            .id
+        };
+        let (a, b, c) = (doc("a.pdf"), doc("b.pdf"), doc("c.pdf"));
+        let ab = insert_comparison(&conn, &a, &b);
+        let bc = insert_comparison(&conn, &b, &c);
+        let matter = matters::insert(&conn, &matters::MatterInput::named("Acme NDA")).unwrap();
+        set_matter(&conn, &bc.id, Some(&matter.id)).unwrap();
+        tags::set_tags(&conn, tags::TagSubject::Comparison(&ab.id), &["Signed".to_string()]).unwrap();
+
+        let ids = |filter: ComparisonFilter| -> Vec<String> {
+            list(&conn, &filter).unwrap().into_iter().map(|c| c.id).collect()
+        };
+        assert_eq!(ids(ComparisonFilter { document_id: Some(b.clone()), ..Default::default() }), vec![bc.id.clone(), ab.id.clone()]);
+        assert_eq!(ids(ComparisonFilter { document_id: Some(c.clone()), ..Default::default() }), vec![bc.id.clone()]);
+        assert_eq!(ids(ComparisonFilter { matter_id: Some(matter.id.clone()), ..Default::default() }), vec![bc.id.clone()]);
+        assert_eq!(ids(ComparisonFilter { tag: Some("signed".to_string()), ..Default::default() }), vec![ab.id.clone()]);
+
+        matters::set_legal_hold(&conn, &matter.id, true).unwrap();
+        assert!(matches!(delete(&conn, &bc.id), Err(AppError::Validation(_))));
+        documents::set_legal_hold(&conn, &a, true, None).unwrap();
+        assert!(matches!(delete(&conn, &ab.id), Err(AppError::Validation(_))));
+        documents::set_legal_hold(&conn, &a, false, None).unwrap();
+        delete(&conn, &ab.id).unwrap();
+        assert!(matches!(get_by_id(&conn, &ab.id), Err(AppError::NotFound(_))));
+    }
 }
@@ -0,0 +1,270 @@
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult};
+
+/// A question-and-answer thread about one or more documents.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Conversation {
+    pub id: String,
+    pub title: String,
+    pub document_ids: Vec<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationMessage {
+    pub id: String,
+    pub conversation_id: String,
+    /// `user` or `assistant`.
+    pub role: String,
+    pub content: String,
+    /// Empty for questions.
+    pub citations: Vec<Citation>,
+    pub ai_provider: Option<String>,
+    pub prompt_version: Option<i64>,
+    pub data_policy: Option<String>,
+    pub ai_endpoint: Option<String>,
+    pub created_at: String,
+}
+
+/// A passage an answer relies on, located in the document's text.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Citation {
+    pub document_id: String,
+    /// Label of the excerpt the model quoted, e.g. `E2`.
+    pub excerpt: String,
+    /// The cited text exactly as it appears in the document.
+    pub text: String,
+    /// Character offsets into the document's raw text.
+    pub start: usize,
+    pub end: usize,
+    /// False when the model's quote could not be found, in which case the
+    /// span covers the whole excerpt it named.
+    pub verified: bool,
+}
+
+pub struct CreateMessage<'a> {
+    pub conversation_id: &'a str,
+    pub role: &'a str,
+    pub content: &'a str,
+    pub citations: &'a [Citation],
+    pub ai_provider: Option<&'a str>,
+    pub prompt_version: Option<i64>,
+    pub data_policy: Option<&'a str>,
+    pub ai_endpoint: Option<&'a str>,
+}
+
+const MESSAGE_COLUMNS: &str =
+    "id, conversation_id, role, content, citations, ai_provider, prompt_version, data_policy, ai_endpoint, created_at";
+
+fn map_message(row: &rusqlite::Row) -> rusqlite::Result<ConversationMessage> {
+    let citations: String = row.get(4)?;
+    Ok(ConversationMessage {
+        id: row.get(0)?,
+        conversation_id: row.get(1)?,
+        role: row.get(2)?,
+        content: row.get(3)?,
+        citations: serde_json::from_str(&citations).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(4, rusqlite::types::Type::Text, Box::new(e))
+        })?,
+        ai_provider: row.get(5)?,
+        prompt_version: row.get(6)?,
+        data_policy: row.get(7)?,
+        ai_endpoint: row.get(8)?,
+        created_at: row.get(9)?,
+    })
+}
+
+fn document_ids(conn: &Connection, conversation_id: &str) -> AppResult<Vec<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT document_id FROM conversation_documents WHERE conversation_id = ?1 ORDER BY rowid",
+    )?;
+    let ids = stmt.query_map(params![conversation_id], |row| row.get(0))?.collect::<Result<Vec<_>, _>>()?;
+    Ok(ids)
+}
+
+pub fn create(conn: &Connection, title: &str, document_ids: &[String]) -> AppResult<Conversation> {
+    if document_ids.is_empty() {
+        return Err(AppError::Validation("A conversation needs at least one document".to_string()));
+    }
+    let id = uuid::Uuid::new_v4().to_string();
+    // Joins the caller's transaction when there is one
+    let tx = conn.is_autocommit().then(|| conn.unchecked_transaction()).transpose()?;
+    conn.execute("INSERT INTO conversations (id, title) VALUES (?1, ?2)", params![id, title])?;
+    for document_id in document_ids {
+        conn.execute(
+            "INSERT OR IGNORE INTO conversation_documents (conversation_id, document_id) VALUES (?1, ?2)",
+            params![id, document_id],
+        )?;
+    }
+    if let Some(tx) = tx {
+        tx.commit()?;
+    }
+    get_by_id(conn, &id)
+}
+
+pub fn get_by_id(conn: &Connection, id: &str) -> AppResult<Conversation> {
+    let (title, created_at, updated_at) = conn
+        .query_row(
+            "SELECT title, created_at, updated_at FROM conversations WHERE id = ?1",
+            params![id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => AppError::NotFound(format!("Conversation {id} not found")),
+            other => AppError::Database(other),
+        })?;
+    Ok(Conversation { id: id.to_string(), title, document_ids: document_ids(conn, id)?, created_at, updated_at })
+}
+
+/// Conversations that include the document, most recently active first.
+pub fn list_by_document(conn: &Connection, document_id: &str) -> AppResult<Vec<Conversation>> {
+    let mut stmt = conn.prepare(
+        "SELECT c.id FROM conversations c
+         JOIN conversation_documents cd ON cd.conversation_id = c.id
+         WHERE cd.document_id = ?1
+         ORDER BY c.updated_at DESC, c.rowid DESC",
+    )?;
+    let ids: Vec<String> = stmt.query_map(params![document_id], |row| row.get(0))?.collect::<Result<_, _>>()?;
+    ids.iter().map(|id| get_by_id(conn, id)).collect()
+}
+
+pub fn add_message(conn: &Connection, message: &CreateMessage) -> AppResult<ConversationMessage> {
+    let id = uuid::Uuid::new_v4().to_string();
+    let citations = serde_json::to_string(message.citations)?;
+    conn.execute(
+        "INSERT INTO conversation_messages (id, conversation_id, role, content, citations, ai_provider, prompt_version, data_policy, ai_endpoint)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            id,
+            message.conversation_id,
+            message.role,
+            message.content,
+            citations,
+            message.ai_provider,
+            message.prompt_version,
+            message.data_policy,
+            message.ai_endpoint,
+        ],
+    )?;
+    conn.execute(
+        "UPDATE conversations SET updated_at = datetime('now') WHERE id = ?1",
+        params![message.conversation_id],
+    )?;
+    let message = conn.query_row(
+        &format!("SELECT {MESSAGE_COLUMNS} FROM conversation_messages WHERE id = ?1"),
+        params![id],
+        map_message,
+    )?;
+    Ok(message)
+}
+
+/// Messages in the order they were written.
+pub fn list_messages(conn: &Connection, conversation_id: &str) -> AppResult<Vec<ConversationMessage>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {MESSAGE_COLUMNS} FROM conversation_messages WHERE conversation_id = ?1 ORDER BY created_at, rowid"
+    ))?;
+    let messages = stmt.query_map(params![conversation_id], map_message)?.collect::<Result<Vec<_>, _>>()?;
+    Ok(messages)
+}
+
+/// Deletes every conversation that includes the document, messages and
+/// all, since the questions and answers quote it.
+pub fn delete_by_document(conn: &Connection, document_id: &str) -> AppResult<()> {
+    conn.execute(
+        "DELETE FROM conversations
+         WHERE id IN (SELECT conversation_id FROM conversation_documents WHERE document_id = ?1)",
+        params![document_id],
+    )?;
+    Ok(())
+}
+
+pub fn delete(conn: &Connection, id: &str) -> AppResult<()> {
+    let rows = conn.execute("DELETE FROM conversations WHERE id = ?1", params![id])?;
+    if rows == 0 {
+        return Err(AppError::NotFound(format!("Conversation {id} not found")));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::documents::{self, CreateDocument};
+    use crate::db::Database;
+
+    fn insert_doc(conn: &Connection, filename: &str) -> String {
+        documents::insert(conn, &CreateDocument {
+            filename: filename.into(),
+            original_path: format!("/tmp/{filename}"),
+            stored_path: format!("/data/{filename}"),
+            file_hash: filename.into(),
+            file_size: 10,
+            contract_type: "nda".into(),
+        }).unwrap().id
+    }
+
+    fn message<'a>(conversation_id: &'a str, role: &'a str, content: &'a str, citations: &'a [Citation]) -> CreateMessage<'a> {
+        CreateMessage {
+            conversation_id,
+            role,
+            content,
+            citations,
+            ai_provider: None,
+            prompt_version: None,
+            data_policy: None,
+            ai_endpoint: None,
+        }
+    }
+
+    #[test]
+    fn test_conversation_round_trip() {
+        let db = Database::in_memory().unwrap();
+        let conn = db.writer();
+        let nda = insert_doc(&conn, "nda.pdf");
+        let msa = insert_doc(&conn, "msa.pdf");
+        let conversation = create(&conn, "Cure period?", &[nda.clone(), msa.clone()]).unwrap();
+        assert_eq!(conversation.document_ids, vec![nda.clone(), msa.clone()]);
+        assert!(create(&conn, "Nothing", &[]).is_err());
+
+        let citation = Citation {
+            document_id: nda.clone(),
+            excerpt: "E1".into(),
+            text: "thirty (30) days".into(),
+            start: 10,
+            end: 26,
+            verified: true,
+        };
+        add_message(&conn, &message(&conversation.id, "user", "What is the cure period?", &[])).unwrap();
+        add_message(&conn, &message(&conversation.id, "assistant", "Thirty days [E1].", std::slice::from_ref(&citation))).unwrap();
+        assert!(add_message(&conn, &message(&conversation.id, "system", "nope", &[])).is_err());
+
+        let messages = list_messages(&conn, &conversation.id).unwrap();
+        assert_eq!(messages.iter().map(|m| m.role.as_str()).collect::<Vec<_>>(), vec!["user", "assistant"]);
+        assert_eq!(messages[1].citations, vec![citation]);
+
+        assert_eq!(list_by_document(&conn, &msa).unwrap().len(), 1);
+        delete(&conn, &conversation.id).unwrap();
+        assert!(list_messages(&conn, &conversation.id).unwrap().is_empty());
+        assert!(matches!(get_by_id(&conn, &conversation.id), Err(AppError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_conversation_goes_with_its_last_document() {
+        let db = Database::in_memory().unwrap();
+        let conn = db.writer();
+        let nda = insert_doc(&conn, "nda.pdf");
+        let msa = insert_doc(&conn, "msa.pdf");
+        let shared = create(&conn, "Both", &[nda.clone(), msa.clone()]).unwrap();
+        let single = create(&conn, "One", std::slice::from_ref(&nda)).unwrap();
+        add_message(&conn, &message(&single.id, "user", "Question", &[])).unwrap();
+
+        documents::delete(&conn, &nda).unwrap();
+        assert!(matches!(get_by_id(&conn, &single.id), Err(AppError::NotFound(_))));
+        assert_eq!(get_by_id(&conn, &shared.id).unwrap().document_ids, vec![msa]);
+        let orphaned: i64 = conn.query_row("SELECT COUNT(*) FROM conversation_messages", [], |r| r.get(0)).unwrap();
+        assert_eq!(orphaned, 0);
+    }
+}
@@ -1,6 +1,10 @@
-use rusqlite::{params, Connection};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD as CURSOR_ENCODING;
+use base64::Engine;
+use rusqlite::types::Value;
+use rusqlite::{params, Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
 
+use crate::db::tags;
 use crate::error::{AppError, AppResult};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,6 +22,261 @@ pub struct Document {
     pub error_message: Option<String>,
     pub created_at: String,
     pub updated_at: String,
+    /// Must never be sent to a cloud provider or non-loopback endpoint.
+    pub local_only: bool,
+    pub matter_id: Option<String>,
+    /// Set while the document is in the trash.
+    pub deleted_at: Option<String>,
+    pub legal_hold: bool,
+    pub legal_hold_reason: Option<String>,
+    /// Set once retention removed the extracted text.
+    pub raw_text_removed_at: Option<String>,
+    /// Tag names, sorted.
+    pub tags: Vec<String>,
+    /// Shared by every version of the same contract; the first version's id.
+    pub family_id: String,
+    /// 1 for the first version in the family.
+    pub version_number: i64,
+    /// What this draft is, e.g. "Counterparty draft" or "Executed".
+    pub version_label: Option<String>,
+}
+
+const SELECT_COLUMNS: &str = "id, filename, original_path, stored_path, file_hash, file_size, contract_type,
+        raw_text, page_count, processing_status, error_message, created_at, updated_at, local_only,
+        matter_id, deleted_at, legal_hold, legal_hold_reason, raw_text_removed_at,
+        (SELECT json_group_array(name) FROM (SELECT t.name FROM document_tags dt JOIN tags t ON t.id = dt.tag_id
+         WHERE dt.document_id = documents.id ORDER BY t.name COLLATE NOCASE)),
+        family_id, version_number, version_label";
+
+/// The latest risk assessment's `overall_score` or `risk_level`.
+macro_rules! latest_risk {
+    ($column:literal) => {
+        concat!(
+            "(SELECT r.", $column, " FROM risk_assessments r WHERE r.document_id = documents.id
+              ORDER BY r.created_at DESC, r.rowid DESC LIMIT 1)"
+        )
+    };
+}
+
+/// Restricts `documents` to a [`DocumentFilter`] bound as ?1..?6.
+const FILTER: &str = concat!(
+    "(?1 IS NULL OR matter_id = ?1)
+        AND (?2 IS NULL OR matter_id IN (SELECT id FROM matters WHERE client = ?2 COLLATE NOCASE))
+        AND (?3 IS NULL OR EXISTS (SELECT 1 FROM document_tags dt JOIN tags t ON t.id = dt.tag_id
+                                   WHERE dt.document_id = documents.id AND t.name = ?3))
+        AND (?4 IS NULL OR contract_type = ?4)
+        AND (?5 IS NULL OR processing_status = ?5)
+        AND (?6 IS NULL OR ",
+    latest_risk!("risk_level"),
+    " = ?6)"
+);
+
+/// Columns for [`DocumentSummary`]; everything but the text and storage paths.
+pub(crate) const SUMMARY_COLUMNS: &str = concat!(
+    "id, filename, contract_type, file_size, page_count, processing_status, error_message,
+        created_at, updated_at, local_only, matter_id, legal_hold, raw_text IS NOT NULL,
+        (SELECT json_group_array(name) FROM (SELECT t.name FROM document_tags dt JOIN tags t ON t.id = dt.tag_id
+         WHERE dt.document_id = documents.id ORDER BY t.name COLLATE NOCASE)), ",
+    latest_risk!("overall_score"),
+    ", ",
+    latest_risk!("risk_level"),
+    ", family_id, version_number, version_label"
+);
+
+const DEFAULT_PAGE_SIZE: u32 = 50;
+const MAX_PAGE_SIZE: u32 = 200;
+
+/// True when the document or its matter is under litigation hold.
+const ON_HOLD: &str = "(legal_hold = 1 OR EXISTS
+        (SELECT 1 FROM matters m WHERE m.id = documents.matter_id AND m.legal_hold = 1))";
+
+fn map_row(row: &rusqlite::Row) -> rusqlite::Result<Document> {
+    Ok(Document {
+        id: row.get(0)?,
+        filename: row.get(1)?,
+        original_path: row.get(2)?,
+        stored_path: row.get(3)?,
+        file_hash: row.get(4)?,
+        file_size: row.get(5)?,
+        contract_type: row.get(6)?,
+        raw_text: row.get(7)?,
+        page_count: row.get(8)?,
+        processing_status: row.get(9)?,
+        error_message: row.get(10)?,
+        created_at: row.get(11)?,
+        updated_at: row.get(12)?,
+        local_only: row.get(13)?,
+        matter_id: row.get(14)?,
+        deleted_at: row.get(15)?,
+        legal_hold: row.get(16)?,
+        legal_hold_reason: row.get(17)?,
+        raw_text_removed_at: row.get(18)?,
+        tags: tags::parse_names(19, &row.get::<_, String>(19)?)?,
+        family_id: row.get(20)?,
+        version_number: row.get(21)?,
+        version_label: row.get(22)?,
+    })
+}
+
+/// A document without its text, for listings. Fetch the full document with
+/// [`get_by_id`].
+#[derive(Debug, Clone, Serialize)]
+pub struct DocumentSummary {
+    pub id: String,
+    pub filename: String,
+    pub contract_type: String,
+    pub file_size: i64,
+    pub page_count: Option<i32>,
+    pub processing_status: String,
+    pub error_message: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+    pub local_only: bool,
+    pub matter_id: Option<String>,
+    pub legal_hold: bool,
+    /// Whether text has been extracted and not removed by retention.
+    pub has_text: bool,
+    pub tags: Vec<String>,
+    /// From the latest risk assessment, if any.
+    pub risk_score: Option<i64>,
+    pub risk_level: Option<String>,
+    pub family_id: String,
+    pub version_number: i64,
+    pub version_label: Option<String>,
+}
+
+pub(crate) fn map_summary(row: &rusqlite::Row) -> rusqlite::Result<DocumentSummary> {
+    Ok(DocumentSummary {
+        id: row.get(0)?,
+        filename: row.get(1)?,
+        contract_type: row.get(2)?,
+        file_size: row.get(3)?,
+        page_count: row.get(4)?,
+        processing_status: row.get(5)?,
+        error_message: row.get(6)?,
+        created_at: row.get(7)?,
+        updated_at: row.get(8)?,
+        local_only: row.get(9)?,
+        matter_id: row.get(10)?,
+        legal_hold: row.get(11)?,
+        has_text: row.get(12)?,
+        tags: tags::parse_names(13, &row.get::<_, String>(13)?)?,
+        risk_score: row.get(14)?,
+        risk_level: row.get(15)?,
+        family_id: row.get(16)?,
+        version_number: row.get(17)?,
+        version_label: row.get(18)?,
+    })
+}
+
+/// Narrows document listings and stats; unset fields match everything.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DocumentFilter {
+    pub matter_id: Option<String>,
+    /// Matched case-insensitively against the document's matter.
+    pub client: Option<String>,
+    pub tag: Option<String>,
+    pub contract_type: Option<String>,
+    /// A `processing_status`.
+    pub status: Option<String>,
+    /// Matched against the latest risk assessment.
+    pub risk_level: Option<String>,
+}
+
+impl DocumentFilter {
+    fn params(&self) -> [&Option<String>; 6] {
+        [&self.matter_id, &self.client, &self.tag, &self.contract_type, &self.status, &self.risk_level]
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DocumentSort {
+    #[default]
+    CreatedAt,
+    Filename,
+    /// Unscored documents sort below every score.
+    RiskScore,
+    Status,
+}
+
+impl DocumentSort {
+    fn key(self) -> &'static str {
+        match self {
+            Self::CreatedAt => "created_at",
+            Self::Filename => "lower(filename)",
+            Self::RiskScore => concat!("COALESCE(", latest_risk!("overall_score"), ", -1)"),
+            Self::Status => "processing_status",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortDirection {
+    Asc,
+    #[default]
+    Desc,
+}
+
+/// One page of a document listing.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DocumentQuery {
+    #[serde(flatten)]
+    pub filter: DocumentFilter,
+    #[serde(default)]
+    pub sort: DocumentSort,
+    #[serde(default)]
+    pub direction: SortDirection,
+    /// `next_cursor` from the previous page; `None` for the first.
+    pub cursor: Option<String>,
+    /// Defaults to 50, at most 200.
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DocumentPage {
+    pub items: Vec<DocumentSummary>,
+    /// Pass back as `cursor` to get the next page; `None` on the last one.
+    pub next_cursor: Option<String>,
+}
+
+/// Where a page ended: the sort it belongs to and the last row's sort key
+/// and id, which break ties.
+#[derive(Serialize, Deserialize)]
+struct Cursor {
+    sort: DocumentSort,
+    direction: SortDirection,
+    key: serde_json::Value,
+    id: String,
+}
+
+impl Cursor {
+    fn encode(&self) -> AppResult<String> {
+        Ok(CURSOR_ENCODING.encode(serde_json::to_vec(self)?))
+    }
+
+    fn decode(cursor: &str, query: &DocumentQuery) -> AppResult<Self> {
+        let invalid = || AppError::Validation("Invalid page cursor".to_string());
+        let bytes = CURSOR_ENCODING.decode(cursor).map_err(|_| invalid())?;
+        let cursor: Self = serde_json::from_slice(&bytes).map_err(|_| invalid())?;
+        if cursor.sort != query.sort || cursor.direction != query.direction {
+            return Err(AppError::Validation("The page cursor is for a different sort order".to_string()));
+        }
+        Ok(cursor)
+    }
+
+    /// The sort key as a value SQLite compares the same way as the column.
+    fn key_value(&self) -> AppResult<Value> {
+        match &self.key {
+            serde_json::Value::String(s) => Ok(Value::Text(s.clone())),
+            serde_json::Value::Number(n) => n
+                .as_i64()
+                .map(Value::Integer)
+                .ok_or_else(|| AppError::Validation("Invalid page cursor".to_string())),
+            _ => Err(AppError::Validation("Invalid page cursor".to_string())),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -30,39 +289,27 @@ pub struct CreateDocument {
     pub contract_type: String,
 }
 
+/// Uploads name their stored file after the document, so they pick the id
+/// first and use [`insert_with_id`].
+#[cfg(test)]
 pub fn insert(conn: &Connection, doc: &CreateDocument) -> AppResult<Document> {
-    let id = uuid::Uuid::new_v4().to_string();
+    insert_with_id(conn, &uuid::Uuid::new_v4().to_string(), doc)
+}
+
+pub fn insert_with_id(conn: &Connection, id: &str, doc: &CreateDocument) -> AppResult<Document> {
     conn.execute(
-        "INSERT INTO documents (id, filename, original_path, stored_path, file_hash, file_size, contract_type)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        "INSERT INTO documents (id, filename, original_path, stored_path, file_hash, file_size, contract_type, family_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?1)",
         params![id, doc.filename, doc.original_path, doc.stored_path, doc.file_hash, doc.file_size, doc.contract_type],
     )?;
-    get_by_id(conn, &id)
+    get_by_id(conn, id)
 }
 
 pub fn get_by_id(conn: &Connection, id: &str) -> AppResult<Document> {
     conn.query_row(
-        "SELECT id, filename, original_path, stored_path, file_hash, file_size, contract_type,
-                raw_text, page_count, processing_status, error_message, created_at, updated_at
-         FROM documents WHERE id = ?1",
+        &format!("SELECT {SELECT_COLUMNS} FROM documents WHERE id = ?1"),
         params![id],
-        |row| {
-            Ok(Document {
-                id: row.get(0)?,
-                filename: row.get(1)?,
-                original_path: row.get(2)?,
-                stored_path: row.get(3)?,
-                file_hash: row.get(4)?,
-                file_size: row.get(5)?,
-                contract_type: row.get(6)?,
-                raw_text: row.get(7)?,
-                page_count: row.get(8)?,
-                processing_status: row.get(9)?,
-                error_message: row.get(10)?,
-                created_at: row.get(11)?,
-                updated_at: row.get(12)?,
-            })
-        },
+        map_row,
     )
     .map_err(|e| match e {
         rusqlite::Error::QueryReturnedNoRows => AppError::NotFound(format!("Document {id} not found")),
@@ -70,31 +317,69 @@ pub fn get_by_id(conn: &Connection, id: &str) -> AppResult<Document> {
     })
 }
 
+/// Documents not in the trash, newest first, with their text. Listings
+/// should use [`list`].
 pub fn list_all(conn: &Connection) -> AppResult<Vec<Document>> {
-    let mut stmt = conn.prepare(
-        "SELECT id, filename, original_path, stored_path, file_hash, file_size, contract_type,
-                raw_text, page_count, processing_status, error_message, created_at, updated_at
-         FROM documents ORDER BY created_at DESC",
-    )?;
-    let docs = stmt
-        .query_map([], |row| {
-            Ok(Document {
-                id: row.get(0)?,
-                filename: row.get(1)?,
-                original_path: row.get(2)?,
-                stored_path: row.get(3)?,
-                file_hash: row.get(4)?,
-                file_size: row.get(5)?,
-                contract_type: row.get(6)?,
-                raw_text: row.get(7)?,
-                page_count: row.get(8)?,
-                processing_status: row.get(9)?,
-                error_message: row.get(10)?,
-                created_at: row.get(11)?,
-                updated_at: row.get(12)?,
-            })
-        })?
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {SELECT_COLUMNS} FROM documents WHERE deleted_at IS NULL ORDER BY created_at DESC"
+    ))?;
+    let docs = stmt.query_map([], map_row)?.collect::<Result<Vec<_>, _>>()?;
+    Ok(docs)
+}
+
+/// A page of summaries of documents not in the trash that match the query.
+/// Pages are keyed on the last row's sort key and id, so rows added or
+/// removed between calls do not shift later pages.
+pub fn list(conn: &Connection, query: &DocumentQuery) -> AppResult<DocumentPage> {
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let (key_value, cursor_id) = match &query.cursor {
+        Some(cursor) => {
+            let cursor = Cursor::decode(cursor, query)?;
+            (Some(cursor.key_value()?), Some(cursor.id))
+        }
+        None => (None, None),
+    };
+    let key = query.sort.key();
+    let (order, after) = match query.direction {
+        SortDirection::Asc => ("ASC", ">"),
+        SortDirection::Desc => ("DESC", "<"),
+    };
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {SUMMARY_COLUMNS}, {key} FROM documents
+         WHERE deleted_at IS NULL AND {FILTER}
+           AND (?7 IS NULL OR ({key}, id) {after} (?7, ?8))
+         ORDER BY {key} {order}, id {order}
+         LIMIT ?9"
+    ))?;
+    let [matter_id, client, tag, contract_type, status, risk_level] = query.filter.params();
+    let mut rows = stmt
+        .query_map(
+            params![matter_id, client, tag, contract_type, status, risk_level, key_value, cursor_id, limit + 1],
+            |row| Ok((map_summary(row)?, row.get::<_, Value>(19)?)),
+        )?
         .collect::<Result<Vec<_>, _>>()?;
+
+    let next_cursor = if rows.len() > limit as usize {
+        rows.truncate(limit as usize);
+        let (last, key) = rows.last().expect("page is not empty");
+        let key = match key {
+            Value::Integer(n) => serde_json::Value::from(*n),
+            Value::Text(s) => serde_json::Value::from(s.as_str()),
+            _ => serde_json::Value::Null,
+        };
+        Some(Cursor { sort: query.sort, direction: query.direction, key, id: last.id.clone() }.encode()?)
+    } else {
+        None
+    };
+    Ok(DocumentPage { items: rows.into_iter().map(|(summary, _)| summary).collect(), next_cursor })
+}
+
+/// Documents in the trash, most recently deleted first.
+pub fn list_trash(conn: &Connection) -> AppResult<Vec<Document>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {SELECT_COLUMNS} FROM documents WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC"
+    ))?;
+    let docs = stmt.query_map([], map_row)?.collect::<Result<Vec<_>, _>>()?;
     Ok(docs)
 }
 
@@ -123,7 +408,96 @@ pub fn update_status(conn: &Connection, id: &str, status: &str, error: Option<&s
     Ok(())
 }
 
+pub fn set_local_only(conn: &Connection, id: &str, local_only: bool) -> AppResult<()> {
+    let rows = conn.execute(
+        "UPDATE documents SET local_only = ?1, updated_at = datetime('now') WHERE id = ?2",
+        params![local_only, id],
+    )?;
+    if rows == 0 {
+        return Err(AppError::NotFound(format!("Document {id} not found")));
+    }
+    Ok(())
+}
+
+pub fn set_stored_path(conn: &Connection, id: &str, stored_path: &str) -> AppResult<()> {
+    let rows = conn.execute(
+        "UPDATE documents SET stored_path = ?1, updated_at = datetime('now') WHERE id = ?2",
+        params![stored_path, id],
+    )?;
+    if rows == 0 {
+        return Err(AppError::NotFound(format!("Document {id} not found")));
+    }
+    Ok(())
+}
+
+pub fn is_on_hold(conn: &Connection, id: &str) -> AppResult<bool> {
+    let held: Option<bool> = conn
+        .query_row(&format!("SELECT {ON_HOLD} FROM documents WHERE id = ?1"), params![id], |row| row.get(0))
+        .optional()?;
+    held.ok_or_else(|| AppError::NotFound(format!("Document {id} not found")))
+}
+
+fn ensure_not_on_hold(conn: &Connection, id: &str) -> AppResult<()> {
+    if is_on_hold(conn, id)? {
+        return Err(AppError::Validation(format!(
+            "Document {id} is under litigation hold and cannot be deleted"
+        )));
+    }
+    Ok(())
+}
+
+pub fn set_legal_hold(conn: &Connection, id: &str, hold: bool, reason: Option<&str>) -> AppResult<()> {
+    let rows = conn.execute(
+        "UPDATE documents SET legal_hold = ?1, legal_hold_reason = ?2, updated_at = datetime('now') WHERE id = ?3",
+        params![hold, reason.filter(|_| hold), id],
+    )?;
+    if rows == 0 {
+        return Err(AppError::NotFound(format!("Document {id} not found")));
+    }
+    Ok(())
+}
+
+pub fn set_matter(conn: &Connection, id: &str, matter_id: Option<&str>) -> AppResult<()> {
+    let rows = conn.execute(
+        "UPDATE documents SET matter_id = ?1, updated_at = datetime('now') WHERE id = ?2",
+        params![matter_id, id],
+    )?;
+    if rows == 0 {
+        return Err(AppError::NotFound(format!("Document {id} not found")));
+    }
+    Ok(())
+}
+
+/// Moves a document to the trash. Its file and analyses are kept until it
+/// is purged.
+pub fn soft_delete(conn: &Connection, id: &str) -> AppResult<()> {
+    ensure_not_on_hold(conn, id)?;
+    let rows = conn.execute(
+        "UPDATE documents SET deleted_at = datetime('now'), updated_at = datetime('now')
+         WHERE id = ?1 AND deleted_at IS NULL",
+        params![id],
+    )?;
+    if rows == 0 {
+        return Err(AppError::NotFound(format!("Document {id} not found")));
+    }
+    Ok(())
+}
+
+pub fn restore(conn: &Connection, id: &str) -> AppResult<()> {
+    let rows = conn.execute(
+        "UPDATE documents SET deleted_at = NULL, updated_at = datetime('now')
+         WHERE id = ?1 AND deleted_at IS NOT NULL",
+        params![id],
+    )?;
+    if rows == 0 {
+        return Err(AppError::NotFound(format!("Document {id} is not in the trash")));
+    }
+    Ok(())
+}
+
+/// Permanently removes the row; extractions, assessments and reports cascade.
 pub fn delete(conn: &Connection, id: &str) -> AppResult<()> {
+    ensure_not_on_hold(conn, id)?;
     let rows = conn.execute("DELETE FROM documents WHERE id = ?1", params![id])?;
     if rows == 0 {
         return Err(AppError::NotFound(format!("Document {id} not found")));
@@ -131,29 +505,77 @@ pub fn delete(conn: &Connection, id: &str) -> AppResult<()> {
     Ok(())
 }
 
-pub fn get_stats(conn: &Connection) -> AppResult<DocumentStats> {
-    let total: i64 = conn.query_row("SELECT COUNT(*) FROM documents", [], |row| row.get(0))?;
-    let analyzed: i64 = conn.query_row(
-        "SELECT COUNT(*) FROM documents WHERE processing_status = 'analyzed'",
-        [],
+/// Whether a document other than `except_id` has its stored file at
+/// `stored_path`.
+pub fn stored_path_in_use(conn: &Connection, stored_path: &str, except_id: &str) -> AppResult<bool> {
+    let in_use = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM documents WHERE stored_path = ?1 AND id != ?2)",
+        params![stored_path, except_id],
         |row| row.get(0),
     )?;
-    let pending: i64 = conn.query_row(
-        "SELECT COUNT(*) FROM documents WHERE processing_status IN ('pending', 'extracted')",
-        [],
-        |row| row.get(0),
+    Ok(in_use)
+}
+
+/// Trashed documents deleted at least `days` ago and not under hold.
+pub fn expired_trash(conn: &Connection, days: i64) -> AppResult<Vec<Document>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {SELECT_COLUMNS} FROM documents
+         WHERE deleted_at IS NOT NULL AND deleted_at <= datetime('now', ?1) AND NOT {ON_HOLD}"
+    ))?;
+    let docs = stmt
+        .query_map(params![format!("-{days} days")], map_row)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(docs)
+}
+
+/// Documents whose text has not been removed, whose matter closed at least
+/// `days` ago and that are not under hold.
+pub fn expired_raw_text(conn: &Connection, days: i64) -> AppResult<Vec<String>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT id FROM documents
+         WHERE raw_text_removed_at IS NULL AND NOT {ON_HOLD}
+           AND matter_id IN (SELECT id FROM matters WHERE status = 'closed' AND closed_at <= datetime('now', ?1))"
+    ))?;
+    let ids = stmt
+        .query_map(params![format!("-{days} days")], |row| row.get(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(ids)
+}
+
+/// Clears the document's own copy of its text and search entry. The copies
+/// other tables keep are removed by [`crate::retention::remove_text`].
+pub fn clear_raw_text(conn: &Connection, id: &str) -> AppResult<()> {
+    let rows = conn.execute(
+        "UPDATE documents SET raw_text = NULL, raw_text_removed_at = datetime('now'), updated_at = datetime('now')
+         WHERE id = ?1",
+        params![id],
     )?;
-    let failed: i64 = conn.query_row(
-        "SELECT COUNT(*) FROM documents WHERE processing_status = 'error'",
-        [],
-        |row| row.get(0),
+    if rows == 0 {
+        return Err(AppError::NotFound(format!("Document {id} not found")));
+    }
+    Ok(())
+}
+
+pub fn get_stats(conn: &Connection, filter: &DocumentFilter) -> AppResult<DocumentStats> {
+    let stats = conn.query_row(
+        &format!(
+            "SELECT COUNT(*),
+                    COUNT(*) FILTER (WHERE processing_status = 'analyzed'),
+                    COUNT(*) FILTER (WHERE processing_status IN ('pending', 'extracted')),
+                    COUNT(*) FILTER (WHERE processing_status = 'error')
+             FROM documents WHERE deleted_at IS NULL AND {FILTER}"
+        ),
+        filter.params(),
+        |row| {
+            Ok(DocumentStats {
+                total: row.get(0)?,
+                analyzed: row.get(1)?,
+                pending: row.get(2)?,
+                failed: row.get(3)?,
+            })
+        },
     )?;
-    Ok(DocumentStats {
-        total,
-        analyzed,
-        pending,
-        failed,
-    })
+    Ok(stats)
 }
 
 #[derive(Debug, Serialize)]
@@ -187,7 +609,7 @@ mod tests {
     #[test]
     fn test_insert_and_get() {
         let db = test_db();
-        let conn = db.conn.lock().unwrap();
+        let conn = db.writer();
         let doc = insert(&conn, &sample_create()).unwrap();
         assert_eq!(doc.filename, "test-nda.pdf");
         assert_eq!(doc.processing_status, "pending");
@@ -199,7 +621,7 @@ mod tests {
     #[test]
     fn test_list_all() {
         let db = test_db();
-        let conn = db.conn.lock().unwrap();
+        let conn = db.writer();
         insert(&conn, &sample_create()).unwrap();
         insert(&conn, &CreateDocument {
             filename: "lease.pdf".to_string(),
@@ -213,7 +635,7 @@ mod tests {
     #[test]
     fn test_update_text() {
         let db = test_db();
-        let conn = db.conn.lock().unwrap();
+        let conn = db.writer();
         let doc = insert(&conn, &sample_create()).unwrap();
 
         update_text(&conn, &doc.id, "Extracted text content", 3).unwrap();
@@ -226,7 +648,7 @@ mod tests {
     #[test]
     fn test_update_status() {
         let db = test_db();
-        let conn = db.conn.lock().unwrap();
+        let conn = db.writer();
         let doc = insert(&conn, &sample_create()).unwrap();
 
         update_status(&conn, &doc.id, "error", Some("PDF corrupted")).unwrap();
@@ -235,29 +657,194 @@ mod tests {
         assert_eq!(updated.error_message.as_deref(), Some("PDF corrupted"));
     }
 
+    #[test]
+    fn test_set_local_only() {
+        let db = test_db();
+        let conn = db.writer();
+        let doc = insert(&conn, &sample_create()).unwrap();
+        assert!(!doc.local_only);
+
+        set_local_only(&conn, &doc.id, true).unwrap();
+        assert!(get_by_id(&conn, &doc.id).unwrap().local_only);
+        assert!(set_local_only(&conn, "nonexistent", true).is_err());
+    }
+
     #[test]
     fn test_delete() {
         let db = test_db();
-        let conn = db.conn.lock().unwrap();
+        let conn = db.writer();
         let doc = insert(&conn, &sample_create()).unwrap();
         delete(&conn, &doc.id).unwrap();
         assert!(get_by_id(&conn, &doc.id).is_err());
     }
 
+    #[test]
+    fn test_soft_delete_and_restore() {
+        let db = test_db();
+        let conn = db.writer();
+        let doc = insert(&conn, &sample_create()).unwrap();
+
+        soft_delete(&conn, &doc.id).unwrap();
+        assert!(list_all(&conn).unwrap().is_empty());
+        assert_eq!(list_trash(&conn).unwrap().len(), 1);
+        assert_eq!(get_stats(&conn, &DocumentFilter::default()).unwrap().total, 0);
+
+        restore(&conn, &doc.id).unwrap();
+        assert_eq!(list_all(&conn).unwrap().len(), 1);
+        assert!(restore(&conn, &doc.id).is_err());
+    }
+
+    #[test]
+    fn test_legal_hold_blocks_deletion() {
+        let db = test_db();
+        let conn = db.writer();
+        let doc = insert(&conn, &sample_create()).unwrap();
+
+        set_legal_hold(&conn, &doc.id, true, Some("Smith v. Jones")).unwrap();
+        assert!(soft_delete(&conn, &doc.id).is_err());
+        assert!(delete(&conn, &doc.id).is_err());
+
+        set_legal_hold(&conn, &doc.id, false, None).unwrap();
+        let matter = crate::db::matters::insert(&conn, &crate::db::matters::MatterInput::named("Smith v. Jones")).unwrap();
+        set_matter(&conn, &doc.id, Some(&matter.id)).unwrap();
+        crate::db::matters::set_legal_hold(&conn, &matter.id, true).unwrap();
+        assert!(is_on_hold(&conn, &doc.id).unwrap());
+        assert!(soft_delete(&conn, &doc.id).is_err());
+    }
+
     #[test]
     fn test_get_stats() {
         let db = test_db();
-        let conn = db.conn.lock().unwrap();
+        let conn = db.writer();
         insert(&conn, &sample_create()).unwrap();
-        let stats = get_stats(&conn).unwrap();
+        let stats = get_stats(&conn, &DocumentFilter::default()).unwrap();
         assert_eq!(stats.total, 1);
         assert_eq!(stats.pending, 1);
     }
 
+    #[test]
+    fn test_filter_by_matter_client_and_tag() {
+        let db = test_db();
+        let conn = db.writer();
+        let nda = insert(&conn, &sample_create()).unwrap();
+        let lease = insert(&conn, &CreateDocument { filename: "lease.pdf".to_string(), ..sample_create() }).unwrap();
+        let matter = crate::db::matters::insert(&conn, &crate::db::matters::MatterInput {
+            client: Some("Acme Corp".to_string()),
+            ..crate::db::matters::MatterInput::named("Acme NDA")
+        }).unwrap();
+        set_matter(&conn, &nda.id, Some(&matter.id)).unwrap();
+        tags::set_tags(&conn, tags::TagSubject::Document(&lease.id), &["Renewal".to_string()]).unwrap();
+
+        let ids = |filter: DocumentFilter| {
+            let page = list(&conn, &DocumentQuery { filter, ..Default::default() }).unwrap();
+            page.items.into_iter().map(|d| d.id).collect::<Vec<_>>()
+        };
+        assert_eq!(ids(DocumentFilter { matter_id: Some(matter.id.clone()), ..Default::default() }), vec![nda.id.clone()]);
+        assert_eq!(ids(DocumentFilter { client: Some("acme corp".to_string()), ..Default::default() }), vec![nda.id.clone()]);
+        assert_eq!(ids(DocumentFilter { tag: Some("renewal".to_string()), ..Default::default() }), vec![lease.id.clone()]);
+        assert_eq!(ids(DocumentFilter::default()).len(), 2);
+
+        let stats = get_stats(&conn, &DocumentFilter { tag: Some("Renewal".to_string()), ..Default::default() }).unwrap();
+        assert_eq!(stats.total, 1);
+    }
+
+    #[test]
+    fn test_list_pages_through_every_sort() {
+        let db = test_db();
+        let conn = db.writer();
+        for (i, name) in ["b.pdf", "C.pdf", "a.pdf", "e.pdf", "d.pdf"].iter().enumerate() {
+            let doc = insert(&conn, &CreateDocument { filename: name.to_string(), ..sample_create() }).unwrap();
+            // Same timestamp for all but one, so ties fall back to the id
+            conn.execute(
+                "UPDATE documents SET created_at = ?1, raw_text = 'full text' WHERE id = ?2",
+                params![if i == 4 { "2024-02-01 00:00:00" } else { "2024-01-01 00:00:00" }, doc.id],
+            )
+            .unwrap();
+        }
+
+        let mut all = Vec::new();
+        for sort in [DocumentSort::CreatedAt, DocumentSort::Filename, DocumentSort::RiskScore, DocumentSort::Status] {
+            for direction in [SortDirection::Asc, SortDirection::Desc] {
+                let mut query = DocumentQuery { sort, direction, limit: Some(2), ..Default::default() };
+                let mut names = Vec::new();
+                loop {
+                    let page = list(&conn, &query).unwrap();
+                    assert!(page.items.len() <= 2);
+                    names.extend(page.items.into_iter().map(|d| d.filename));
+                    match page.next_cursor {
+                        Some(cursor) => query.cursor = Some(cursor),
+                        None => break,
+                    }
+                }
+                assert_eq!(names.len(), 5, "{sort:?} {direction:?} returned {names:?}");
+                all.push(names);
+            }
+        }
+        assert_eq!(all[2], vec!["a.pdf", "b.pdf", "C.pdf", "d.pdf", "e.pdf"]);
+        assert_eq!(all[3], vec!["e.pdf", "d.pdf", "C.pdf", "b.pdf", "a.pdf"]);
+        assert_eq!(all[1][0], "d.pdf");
+
+        let page = list(&conn, &DocumentQuery { limit: Some(1), ..Default::default() }).unwrap();
+        assert!(page.items[0].has_text);
+        let mismatched = DocumentQuery { sort: DocumentSort::Filename, cursor: page.next_cursor, ..Default::default() };
+        assert!(matches!(list(&conn, &mismatched), Err(AppError::Validation(_))));
+        let garbage = DocumentQuery { cursor: Some("not a cursor".to_string()), ..Default::default() };
+        assert!(matches!(list(&conn, &garbage), Err(AppError::Validation(_))));
+    }
+
+    #[test]
+    fn test_list_sorts_and_filters_by_risk() {
+        let db = test_db();
+        let conn = db.writer();
+        let low = insert(&conn, &CreateDocument { filename: "low.pdf".to_string(), ..sample_create() }).unwrap();
+        let high = insert(&conn, &CreateDocument { filename: "high.pdf".to_string(), ..sample_create() }).unwrap();
+        insert(&conn, &CreateDocument { filename: "unscored.pdf".to_string(), ..sample_create() }).unwrap();
+        for (doc, score, level) in [(&low, 20, "low"), (&high, 85, "high")] {
+            let extraction = crate::db::extractions::insert(&conn, &crate::db::extractions::CreateExtraction {
+                document_id: doc.id.clone(),
+                ai_provider: "mock".to_string(),
+                ai_model: None,
+                contract_type: "nda".to_string(),
+                extracted_data: "{}".to_string(),
+                confidence_score: None,
+                processing_time_ms: None,
+                prompt_version: None,
+                data_policy: None,
+                ai_endpoint: None,
+            })
+            .unwrap();
+            crate::db::risk_assessments::insert(&conn, &crate::db::risk_assessments::CreateRiskAssessment {
+                document_id: doc.id.clone(),
+                extraction_id: extraction.id,
+                overall_score: score,
+                risk_level: level.to_string(),
+                flags: "[]".to_string(),
+                summary: None,
+                ai_provider: "mock".to_string(),
+                prompt_version: None,
+                data_policy: None,
+                ai_endpoint: None,
+            })
+            .unwrap();
+        }
+
+        let page = list(&conn, &DocumentQuery { sort: DocumentSort::RiskScore, ..Default::default() }).unwrap();
+        let names = page.items.iter().map(|d| d.filename.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["high.pdf", "low.pdf", "unscored.pdf"]);
+        assert_eq!(page.items[0].risk_score, Some(85));
+        assert_eq!(page.items[2].risk_level, None);
+
+        let filter = DocumentFilter { risk_level: Some("low".to_string()), ..Default::default() };
+        let page = list(&conn, &DocumentQuery { filter: filter.clone(), ..Default::default() }).unwrap();
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].id, low.id);
+        assert_eq!(get_stats(&conn, &filter).unwrap().total, 1);
+    }
+
     #[test]
     fn test_not_found() {
         let db = test_db();
-        let conn = db.conn.lock().unwrap();
+        let conn = db.writer();
         let result = get_by_id(&conn, "nonexistent");
         assert!(result.is_err());
     }
@@ -1,5 +1,6 @@
 use rusqlite::{params, Connection};
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 
 use crate::error::{AppError, AppResult};
 
@@ -13,6 +14,11 @@ pub struct Extraction {
     pub extracted_data: String,
     pub confidence_score: Option<f64>,
     pub processing_time_ms: Option<i64>,
+    pub prompt_version: Option<i64>,
+    /// Data-handling policy in force when the provider was called.
+    pub data_policy: Option<String>,
+    /// Where the document text was sent; `None` when nothing left the process.
+    pub ai_endpoint: Option<String>,
     pub created_at: String,
 }
 
@@ -24,21 +30,24 @@ pub struct CreateExtraction {
     pub extracted_data: String,
     pub confidence_score: Option<f64>,
     pub processing_time_ms: Option<i64>,
+    pub prompt_version: Option<i64>,
+    pub data_policy: Option<String>,
+    pub ai_endpoint: Option<String>,
 }
 
 pub fn insert(conn: &Connection, ext: &CreateExtraction) -> AppResult<Extraction> {
     let id = uuid::Uuid::new_v4().to_string();
     conn.execute(
-        "INSERT INTO extractions (id, document_id, ai_provider, ai_model, contract_type, extracted_data, confidence_score, processing_time_ms)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
-        params![id, ext.document_id, ext.ai_provider, ext.ai_model, ext.contract_type, ext.extracted_data, ext.confidence_score, ext.processing_time_ms],
+        "INSERT INTO extractions (id, document_id, ai_provider, ai_model, contract_type, extracted_data, confidence_score, processing_time_ms, prompt_version, data_policy, ai_endpoint)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        params![id, ext.document_id, ext.ai_provider, ext.ai_model, ext.contract_type, ext.extracted_data, ext.confidence_score, ext.processing_time_ms, ext.prompt_version, ext.data_policy, ext.ai_endpoint],
     )?;
     get_by_id(conn, &id)
 }
 
 pub fn get_by_id(conn: &Connection, id: &str) -> AppResult<Extraction> {
     conn.query_row(
-        "SELECT id, document_id, ai_provider, ai_model, contract_type, extracted_data, confidence_score, processing_time_ms, created_at
+        "SELECT id, document_id, ai_provider, ai_model, contract_type, extracted_data, confidence_score, processing_time_ms, prompt_version, data_policy, ai_endpoint, created_at
          FROM extractions WHERE id = ?1",
         params![id],
         |row| {
@@ -51,7 +60,10 @@ pub fn get_by_id(conn: &Connection, id: &str) -> AppResult<Extraction> {
                 extracted_data: row.get(5)?,
                 confidence_score: row.get(6)?,
                 processing_time_ms: row.get(7)?,
-                created_at: row.get(8)?,
+                prompt_version: row.get(8)?,
+                data_policy: row.get(9)?,
+                ai_endpoint: row.get(10)?,
+                created_at: row.get(11)?,
             })
         },
     )
@@ -63,7 +75,7 @@ pub fn get_by_id(conn: &Connection, id: &str) -> AppResult<Extraction> {
 
 pub fn list_by_document(conn: &Connection, document_id: &str) -> AppResult<Vec<Extraction>> {
     let mut stmt = conn.prepare(
-        "SELECT id, document_id, ai_provider, ai_model, contract_type, extracted_data, confidence_score, processing_time_ms, created_at
+        "SELECT id, document_id, ai_provider, ai_model, contract_type, extracted_data, confidence_score, processing_time_ms, prompt_version, data_policy, ai_endpoint, created_at
          FROM extractions WHERE document_id = ?1 ORDER BY created_at DESC",
     )?;
     let results = stmt
@@ -77,13 +89,38 @@ pub fn list_by_document(conn: &Connection, document_id: &str) -> AppResult<Vec<E
                 extracted_data: row.get(5)?,
                 confidence_score: row.get(6)?,
                 processing_time_ms: row.get(7)?,
-                created_at: row.get(8)?,
+                prompt_version: row.get(8)?,
+                data_policy: row.get(9)?,
+                ai_endpoint: row.get(10)?,
+                created_at: row.get(11)?,
             })
         })?
         .collect::<Result<Vec<_>, _>>()?;
     Ok(results)
 }
 
+/// Blanks the contract text kept in the document's extractions: the text
+/// of each clause and the model's raw reply. Parties, dates and clause
+/// types are kept.
+pub fn clear_text(conn: &Connection, document_id: &str) -> AppResult<()> {
+    for extraction in list_by_document(conn, document_id)? {
+        let mut data: Value = serde_json::from_str(&extraction.extracted_data).unwrap_or_else(|_| json!({}));
+        if let Some(clauses) = data.get_mut("clauses").and_then(Value::as_array_mut) {
+            for text in clauses.iter_mut().filter_map(|clause| clause.get_mut("text")) {
+                *text = json!("");
+            }
+        }
+        if let Some(raw_json) = data.get_mut("raw_json") {
+            *raw_json = json!("");
+        }
+        conn.execute(
+            "UPDATE extractions SET extracted_data = ?1 WHERE id = ?2",
+            params![data.to_string(), extraction.id],
+        )?;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -109,7 +146,7 @@ mod tests {
     #[test]
     fn test_insert_and_get() {
         let db = setup();
-        let conn = db.conn.lock().unwrap();
+        let conn = db.writer();
         let doc_id = insert_doc(&conn);
 
         let ext = insert(&conn, &CreateExtraction {
@@ -120,6 +157,9 @@ mod tests {
             extracted_data: r#"{"parties": ["A", "B"]}"#.into(),
             confidence_score: Some(0.85),
             processing_time_ms: Some(1500),
+            prompt_version: Some(0),
+            data_policy: None,
+            ai_endpoint: None,
         }).unwrap();
 
         assert_eq!(ext.document_id, doc_id);
@@ -129,7 +169,7 @@ mod tests {
     #[test]
     fn test_list_by_document() {
         let db = setup();
-        let conn = db.conn.lock().unwrap();
+        let conn = db.writer();
         let doc_id = insert_doc(&conn);
 
         insert(&conn, &CreateExtraction {
@@ -140,6 +180,9 @@ mod tests {
             extracted_data: "{}".into(),
             confidence_score: None,
             processing_time_ms: None,
+            prompt_version: None,
+            data_policy: None,
+            ai_endpoint: None,
         }).unwrap();
 
         let results = list_by_document(&conn, &doc_id).unwrap();
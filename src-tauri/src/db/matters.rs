@@ -0,0 +1,213 @@
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Matter {
+    pub id: String,
+    pub name: String,
+    pub client: Option<String>,
+    /// The firm's reference for the matter; unique when set.
+    pub matter_number: Option<String>,
+    pub responsible_attorney: Option<String>,
+    /// `open` or `closed`.
+    pub status: String,
+    /// Holds every document in the matter.
+    pub legal_hold: bool,
+    pub closed_at: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// The editable details of a matter.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct MatterInput {
+    pub name: String,
+    pub client: Option<String>,
+    pub matter_number: Option<String>,
+    pub responsible_attorney: Option<String>,
+}
+
+impl MatterInput {
+    #[cfg(test)]
+    pub fn named(name: &str) -> Self {
+        Self { name: name.to_string(), ..Self::default() }
+    }
+
+    /// Trimmed, with blank optional fields as `None`.
+    fn normalized(&self) -> AppResult<Self> {
+        let name = self.name.trim();
+        if name.is_empty() {
+            return Err(AppError::Validation("Matter name is required".to_string()));
+        }
+        let optional = |value: &Option<String>| {
+            value.as_deref().map(str::trim).filter(|v| !v.is_empty()).map(str::to_string)
+        };
+        Ok(Self {
+            name: name.to_string(),
+            client: optional(&self.client),
+            matter_number: optional(&self.matter_number),
+            responsible_attorney: optional(&self.responsible_attorney),
+        })
+    }
+}
+
+const SELECT_COLUMNS: &str =
+    "id, name, client, matter_number, responsible_attorney, status, legal_hold, closed_at, created_at, updated_at";
+
+fn map_row(row: &rusqlite::Row) -> rusqlite::Result<Matter> {
+    Ok(Matter {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        client: row.get(2)?,
+        matter_number: row.get(3)?,
+        responsible_attorney: row.get(4)?,
+        status: row.get(5)?,
+        legal_hold: row.get(6)?,
+        closed_at: row.get(7)?,
+        created_at: row.get(8)?,
+        updated_at: row.get(9)?,
+    })
+}
+
+/// Reports a duplicate matter number as a validation error.
+fn write_error(e: rusqlite::Error, matter_number: Option<&str>) -> AppError {
+    match (&e, matter_number) {
+        (rusqlite::Error::SqliteFailure(f, _), Some(number)) if f.code == rusqlite::ErrorCode::ConstraintViolation => {
+            AppError::Validation(format!("Matter number {number} is already in use"))
+        }
+        _ => AppError::Database(e),
+    }
+}
+
+pub fn insert(conn: &Connection, matter: &MatterInput) -> AppResult<Matter> {
+    let MatterInput { name, client, matter_number: number, responsible_attorney: attorney } = matter.normalized()?;
+    let id = uuid::Uuid::new_v4().to_string();
+    conn.execute(
+        "INSERT INTO matters (id, name, client, matter_number, responsible_attorney) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![id, name, client, number, attorney],
+    )
+    .map_err(|e| write_error(e, number.as_deref()))?;
+    get_by_id(conn, &id)
+}
+
+pub fn update(conn: &Connection, id: &str, matter: &MatterInput) -> AppResult<Matter> {
+    let MatterInput { name, client, matter_number: number, responsible_attorney: attorney } = matter.normalized()?;
+    let rows = conn
+        .execute(
+            "UPDATE matters SET name = ?1, client = ?2, matter_number = ?3, responsible_attorney = ?4,
+                    updated_at = datetime('now')
+             WHERE id = ?5",
+            params![name, client, number, attorney, id],
+        )
+        .map_err(|e| write_error(e, number.as_deref()))?;
+    if rows == 0 {
+        return Err(AppError::NotFound(format!("Matter {id} not found")));
+    }
+    get_by_id(conn, id)
+}
+
+pub fn get_by_id(conn: &Connection, id: &str) -> AppResult<Matter> {
+    conn.query_row(&format!("SELECT {SELECT_COLUMNS} FROM matters WHERE id = ?1"), params![id], map_row)
+        .map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => AppError::NotFound(format!("Matter {id} not found")),
+            other => AppError::Database(other),
+        })
+}
+
+pub fn list_all(conn: &Connection) -> AppResult<Vec<Matter>> {
+    let mut stmt = conn.prepare(&format!("SELECT {SELECT_COLUMNS} FROM matters ORDER BY status DESC, name"))?;
+    let matters = stmt.query_map([], map_row)?.collect::<Result<Vec<_>, _>>()?;
+    Ok(matters)
+}
+
+/// Distinct client names across matters, alphabetically.
+pub fn list_clients(conn: &Connection) -> AppResult<Vec<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT client FROM matters WHERE client IS NOT NULL
+         GROUP BY client COLLATE NOCASE ORDER BY client COLLATE NOCASE",
+    )?;
+    let clients = stmt.query_map([], |row| row.get(0))?.collect::<Result<Vec<_>, _>>()?;
+    Ok(clients)
+}
+
+/// Closing starts the raw-text retention clock; reopening stops it.
+pub fn set_closed(conn: &Connection, id: &str, closed: bool) -> AppResult<()> {
+    let rows = conn.execute(
+        "UPDATE matters SET status = CASE WHEN ?1 THEN 'closed' ELSE 'open' END,
+                closed_at = CASE WHEN ?1 THEN datetime('now') END,
+                updated_at = datetime('now')
+         WHERE id = ?2",
+        params![closed, id],
+    )?;
+    if rows == 0 {
+        return Err(AppError::NotFound(format!("Matter {id} not found")));
+    }
+    Ok(())
+}
+
+pub fn set_legal_hold(conn: &Connection, id: &str, hold: bool) -> AppResult<()> {
+    let rows = conn.execute(
+        "UPDATE matters SET legal_hold = ?1, updated_at = datetime('now') WHERE id = ?2",
+        params![hold, id],
+    )?;
+    if rows == 0 {
+        return Err(AppError::NotFound(format!("Matter {id} not found")));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+
+    #[test]
+    fn test_close_and_reopen() {
+        let db = Database::in_memory().unwrap();
+        let conn = db.writer();
+        let matter = insert(&conn, &MatterInput::named("Acme acquisition")).unwrap();
+        assert_eq!(matter.status, "open");
+        assert!(insert(&conn, &MatterInput::named("  ")).is_err());
+
+        set_closed(&conn, &matter.id, true).unwrap();
+        let closed = get_by_id(&conn, &matter.id).unwrap();
+        assert_eq!(closed.status, "closed");
+        assert!(closed.closed_at.is_some());
+
+        set_closed(&conn, &matter.id, false).unwrap();
+        assert!(get_by_id(&conn, &matter.id).unwrap().closed_at.is_none());
+        assert!(set_closed(&conn, "missing", true).is_err());
+    }
+
+    #[test]
+    fn test_client_details_and_unique_number() {
+        let db = Database::in_memory().unwrap();
+        let conn = db.writer();
+        let input = MatterInput {
+            name: "Lease renewal".into(),
+            client: Some(" Acme Corp ".into()),
+            matter_number: Some("2024-001".into()),
+            responsible_attorney: Some("".into()),
+        };
+        let matter = insert(&conn, &input).unwrap();
+        assert_eq!(matter.client.as_deref(), Some("Acme Corp"));
+        assert_eq!(matter.responsible_attorney, None);
+
+        let duplicate = insert(&conn, &MatterInput { name: "Other".into(), ..input.clone() });
+        assert!(matches!(duplicate, Err(AppError::Validation(_))));
+        insert(&conn, &MatterInput { client: Some("acme corp".into()), ..MatterInput::named("Untitled") }).unwrap();
+        insert(&conn, &MatterInput { client: Some("Globex".into()), ..MatterInput::named("Supply") }).unwrap();
+        assert_eq!(list_clients(&conn).unwrap(), vec!["Acme Corp", "Globex"]);
+
+        let updated = update(&conn, &matter.id, &MatterInput {
+            responsible_attorney: Some("J. Smith".into()),
+            ..input
+        })
+        .unwrap();
+        assert_eq!(updated.responsible_attorney.as_deref(), Some("J. Smith"));
+        assert_eq!(updated.matter_number.as_deref(), Some("2024-001"));
+        assert!(matches!(update(&conn, "missing", &MatterInput::named("x")), Err(AppError::NotFound(_))));
+    }
+}
@@ -1,7 +1,440 @@
+use std::path::{Path, PathBuf};
+
 use rusqlite::Connection;
-use crate::error::AppResult;
+use crate::error::{AppError, AppResult};
+
+/// One step of a migration.
+pub enum Step {
+    Sql(&'static str),
+    Rust(fn(&Connection) -> AppResult<()>),
+    /// The migration cannot be undone.
+    Irreversible,
+}
+
+/// A numbered schema change. `PRAGMA user_version` holds the version of the
+/// last one applied.
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    pub up: Step,
+    pub down: Step,
+    /// Rewrites or drops existing data; the database file is backed up first.
+    pub risky: bool,
+}
+
+/// Append new migrations at the end with the next version number; never edit
+/// one that has shipped.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "baseline",
+        up: Step::Rust(baseline),
+        down: Step::Irreversible,
+        risky: false,
+    },
+    Migration {
+        version: 2,
+        name: "documents_deleted_at_index",
+        up: Step::Sql("CREATE INDEX IF NOT EXISTS idx_documents_deleted_at ON documents(deleted_at);"),
+        down: Step::Sql("DROP INDEX IF EXISTS idx_documents_deleted_at;"),
+        risky: false,
+    },
+    Migration {
+        version: 3,
+        name: "full_text_search",
+        up: Step::Sql(FULL_TEXT_SEARCH_UP),
+        down: Step::Sql(FULL_TEXT_SEARCH_DOWN),
+        risky: false,
+    },
+    Migration {
+        version: 4,
+        name: "clause_embeddings",
+        up: Step::Sql(
+            "CREATE TABLE clause_embeddings (
+                clause_id INTEGER PRIMARY KEY REFERENCES clauses(id) ON DELETE CASCADE,
+                model TEXT NOT NULL,
+                dimensions INTEGER NOT NULL,
+                vector BLOB NOT NULL,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+            CREATE INDEX idx_clause_embeddings_model ON clause_embeddings(model);",
+        ),
+        down: Step::Sql("DROP TABLE clause_embeddings;"),
+        risky: false,
+    },
+    Migration {
+        version: 5,
+        name: "conversations",
+        up: Step::Sql(
+            "CREATE TABLE conversations (
+                id TEXT PRIMARY KEY,
+                title TEXT NOT NULL,
+                created_at TEXT NOT NULL DEFAULT (datetime('now')),
+                updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+            CREATE TABLE conversation_documents (
+                conversation_id TEXT NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
+                document_id TEXT NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+                PRIMARY KEY (conversation_id, document_id)
+            );
+            CREATE INDEX idx_conversation_documents_document ON conversation_documents(document_id);
+            CREATE TABLE conversation_messages (
+                id TEXT PRIMARY KEY,
+                conversation_id TEXT NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
+                role TEXT NOT NULL CHECK (role IN ('user', 'assistant')),
+                content TEXT NOT NULL,
+                citations TEXT NOT NULL DEFAULT '[]',
+                ai_provider TEXT,
+                prompt_version INTEGER,
+                data_policy TEXT,
+                ai_endpoint TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            );
+            CREATE INDEX idx_conversation_messages_conversation ON conversation_messages(conversation_id);
+            -- Answers quote their documents, so they go when the last one does
+            CREATE TRIGGER conversation_documents_ad AFTER DELETE ON conversation_documents
+            WHEN NOT EXISTS (SELECT 1 FROM conversation_documents WHERE conversation_id = OLD.conversation_id)
+            BEGIN
+                DELETE FROM conversations WHERE id = OLD.conversation_id;
+            END;",
+        ),
+        down: Step::Sql(
+            "DROP TABLE conversation_messages;
+            DROP TABLE conversation_documents;
+            DROP TABLE conversations;",
+        ),
+        risky: false,
+    },
+    Migration {
+        version: 6,
+        name: "matters_and_tags",
+        up: Step::Sql(MATTERS_AND_TAGS_UP),
+        down: Step::Sql(MATTERS_AND_TAGS_DOWN),
+        risky: true,
+    },
+    Migration {
+        version: 7,
+        name: "document_list_indexes",
+        up: Step::Sql(
+            "CREATE INDEX idx_documents_created_at ON documents(created_at, id);
+            CREATE INDEX idx_documents_status ON documents(processing_status, id);
+            CREATE INDEX idx_risk_document_latest ON risk_assessments(document_id, created_at);",
+        ),
+        down: Step::Sql(
+            "DROP INDEX idx_risk_document_latest;
+            DROP INDEX idx_documents_status;
+            DROP INDEX idx_documents_created_at;",
+        ),
+        risky: false,
+    },
+    Migration {
+        version: 8,
+        name: "document_versions",
+        up: Step::Sql(
+            "ALTER TABLE documents ADD COLUMN family_id TEXT;
+            ALTER TABLE documents ADD COLUMN version_number INTEGER NOT NULL DEFAULT 1;
+            ALTER TABLE documents ADD COLUMN version_label TEXT;
+            UPDATE documents SET family_id = id;
+            CREATE UNIQUE INDEX idx_documents_family_version ON documents(family_id, version_number);",
+        ),
+        down: Step::Sql(
+            "DROP INDEX idx_documents_family_version;
+            ALTER TABLE documents DROP COLUMN version_label;
+            ALTER TABLE documents DROP COLUMN version_number;
+            ALTER TABLE documents DROP COLUMN family_id;",
+        ),
+        risky: true,
+    },
+    Migration {
+        version: 9,
+        name: "comparison_redlines",
+        // Comparison prompts now receive the redline's changes instead of
+        // both texts, so saved revisions written for the texts are retired
+        up: Step::Sql(
+            "ALTER TABLE comparisons ADD COLUMN redline TEXT;
+            UPDATE prompt_versions SET is_active = 0
+            WHERE prompt_kind = 'comparison' AND (user_template || system_template) GLOB '*{{*text_[ab]*}}*';",
+        ),
+        down: Step::Sql("ALTER TABLE comparisons DROP COLUMN redline;"),
+        risky: true,
+    },
+    Migration {
+        version: 10,
+        name: "comparison_reports",
+        // Reports on a comparison go when the comparison does
+        up: Step::Sql(
+            "ALTER TABLE reports ADD COLUMN comparison_id TEXT REFERENCES comparisons(id) ON DELETE CASCADE;
+            CREATE INDEX idx_reports_comparison ON reports(comparison_id);",
+        ),
+        down: Step::Sql(
+            "DROP INDEX idx_reports_comparison;
+            ALTER TABLE reports DROP COLUMN comparison_id;",
+        ),
+        risky: false,
+    },
+];
+
+/// Clauses get a row each, filled from the extraction JSON by trigger, and
+/// both clauses and document text get an FTS5 index kept current by
+/// triggers. `documents_fts` keeps its own copy of the text because
+/// `documents` has no stable integer key to use as external content.
+const FULL_TEXT_SEARCH_UP: &str = "
+    CREATE TABLE clauses (
+        id INTEGER PRIMARY KEY,
+        extraction_id TEXT NOT NULL REFERENCES extractions(id) ON DELETE CASCADE,
+        document_id TEXT NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+        clause_index INTEGER NOT NULL,
+        clause_type TEXT NOT NULL,
+        title TEXT NOT NULL,
+        text TEXT NOT NULL,
+        section_reference TEXT
+    );
+    CREATE INDEX idx_clauses_extraction ON clauses(extraction_id);
+    CREATE INDEX idx_clauses_document ON clauses(document_id);
+
+    CREATE TRIGGER extractions_clauses_ai AFTER INSERT ON extractions BEGIN
+        INSERT INTO clauses (extraction_id, document_id, clause_index, clause_type, title, text, section_reference)
+        SELECT NEW.id, NEW.document_id, c.key,
+               COALESCE(json_extract(c.value, '$.clause_type'), 'other'),
+               COALESCE(json_extract(c.value, '$.title'), ''),
+               json_extract(c.value, '$.text'),
+               json_extract(c.value, '$.section_reference')
+        FROM json_each(NEW.extracted_data, '$.clauses') AS c
+        WHERE json_extract(c.value, '$.text') IS NOT NULL;
+    END;
+
+    INSERT INTO clauses (extraction_id, document_id, clause_index, clause_type, title, text, section_reference)
+    SELECT e.id, e.document_id, c.key,
+           COALESCE(json_extract(c.value, '$.clause_type'), 'other'),
+           COALESCE(json_extract(c.value, '$.title'), ''),
+           json_extract(c.value, '$.text'),
+           json_extract(c.value, '$.section_reference')
+    FROM extractions AS e, json_each(e.extracted_data, '$.clauses') AS c
+    WHERE json_valid(e.extracted_data) AND json_extract(c.value, '$.text') IS NOT NULL;
+
+    CREATE VIRTUAL TABLE clauses_fts USING fts5(
+        title, text, content='clauses', content_rowid='id', tokenize='porter unicode61'
+    );
+    CREATE TRIGGER clauses_fts_ai AFTER INSERT ON clauses BEGIN
+        INSERT INTO clauses_fts (rowid, title, text) VALUES (NEW.id, NEW.title, NEW.text);
+    END;
+    CREATE TRIGGER clauses_fts_ad AFTER DELETE ON clauses BEGIN
+        INSERT INTO clauses_fts (clauses_fts, rowid, title, text) VALUES ('delete', OLD.id, OLD.title, OLD.text);
+    END;
+    CREATE TRIGGER clauses_fts_au AFTER UPDATE ON clauses BEGIN
+        INSERT INTO clauses_fts (clauses_fts, rowid, title, text) VALUES ('delete', OLD.id, OLD.title, OLD.text);
+        INSERT INTO clauses_fts (rowid, title, text) VALUES (NEW.id, NEW.title, NEW.text);
+    END;
+    INSERT INTO clauses_fts (clauses_fts) VALUES ('rebuild');
 
-pub fn run(conn: &Connection) -> AppResult<()> {
+    CREATE VIRTUAL TABLE documents_fts USING fts5(
+        document_id UNINDEXED, filename, raw_text, tokenize='porter unicode61'
+    );
+    CREATE TRIGGER documents_fts_ai AFTER INSERT ON documents BEGIN
+        INSERT INTO documents_fts (document_id, filename, raw_text)
+        VALUES (NEW.id, NEW.filename, COALESCE(NEW.raw_text, ''));
+    END;
+    CREATE TRIGGER documents_fts_ad AFTER DELETE ON documents BEGIN
+        DELETE FROM documents_fts WHERE document_id = OLD.id;
+    END;
+    CREATE TRIGGER documents_fts_au AFTER UPDATE OF filename, raw_text ON documents BEGIN
+        DELETE FROM documents_fts WHERE document_id = OLD.id;
+        INSERT INTO documents_fts (document_id, filename, raw_text)
+        VALUES (NEW.id, NEW.filename, COALESCE(NEW.raw_text, ''));
+    END;
+    INSERT INTO documents_fts (document_id, filename, raw_text)
+    SELECT id, filename, COALESCE(raw_text, '') FROM documents;
+";
+
+const FULL_TEXT_SEARCH_DOWN: &str = "
+    DROP TRIGGER IF EXISTS documents_fts_ai;
+    DROP TRIGGER IF EXISTS documents_fts_au;
+    DROP TRIGGER IF EXISTS documents_fts_ad;
+    DROP TRIGGER IF EXISTS extractions_clauses_ai;
+    DROP TABLE documents_fts;
+    DROP TABLE clauses_fts;
+    DROP TABLE clauses;
+";
+
+/// Matters get client details and a unique matter number; comparisons and
+/// reports can belong to a matter, starting with their document's; and
+/// documents, comparisons and reports can carry free-form tags.
+const MATTERS_AND_TAGS_UP: &str = "
+    ALTER TABLE matters ADD COLUMN client TEXT;
+    ALTER TABLE matters ADD COLUMN matter_number TEXT;
+    ALTER TABLE matters ADD COLUMN responsible_attorney TEXT;
+    CREATE UNIQUE INDEX idx_matters_number ON matters(matter_number COLLATE NOCASE);
+    CREATE INDEX idx_matters_client ON matters(client COLLATE NOCASE);
+
+    ALTER TABLE comparisons ADD COLUMN matter_id TEXT REFERENCES matters(id) ON DELETE SET NULL;
+    ALTER TABLE reports ADD COLUMN matter_id TEXT REFERENCES matters(id) ON DELETE SET NULL;
+    UPDATE comparisons SET matter_id = (SELECT matter_id FROM documents WHERE id = comparisons.document_a_id);
+    UPDATE reports SET matter_id = (SELECT matter_id FROM documents WHERE id = reports.document_id);
+    CREATE INDEX idx_documents_matter ON documents(matter_id);
+    CREATE INDEX idx_comparisons_matter ON comparisons(matter_id);
+    CREATE INDEX idx_reports_matter ON reports(matter_id);
+
+    CREATE TABLE tags (
+        id TEXT PRIMARY KEY,
+        name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+        created_at TEXT NOT NULL DEFAULT (datetime('now'))
+    );
+    CREATE TABLE document_tags (
+        document_id TEXT NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+        tag_id TEXT NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+        PRIMARY KEY (document_id, tag_id)
+    );
+    CREATE INDEX idx_document_tags_tag ON document_tags(tag_id);
+    CREATE TABLE comparison_tags (
+        comparison_id TEXT NOT NULL REFERENCES comparisons(id) ON DELETE CASCADE,
+        tag_id TEXT NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+        PRIMARY KEY (comparison_id, tag_id)
+    );
+    CREATE INDEX idx_comparison_tags_tag ON comparison_tags(tag_id);
+    CREATE TABLE report_tags (
+        report_id TEXT NOT NULL REFERENCES reports(id) ON DELETE CASCADE,
+        tag_id TEXT NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+        PRIMARY KEY (report_id, tag_id)
+    );
+    CREATE INDEX idx_report_tags_tag ON report_tags(tag_id);
+";
+
+const MATTERS_AND_TAGS_DOWN: &str = "
+    DROP TABLE report_tags;
+    DROP TABLE comparison_tags;
+    DROP TABLE document_tags;
+    DROP TABLE tags;
+    DROP INDEX idx_reports_matter;
+    DROP INDEX idx_comparisons_matter;
+    DROP INDEX idx_documents_matter;
+    ALTER TABLE reports DROP COLUMN matter_id;
+    ALTER TABLE comparisons DROP COLUMN matter_id;
+    DROP INDEX idx_matters_client;
+    DROP INDEX idx_matters_number;
+    ALTER TABLE matters DROP COLUMN responsible_attorney;
+    ALTER TABLE matters DROP COLUMN matter_number;
+    ALTER TABLE matters DROP COLUMN client;
+";
+
+pub fn latest_version(migrations: &[Migration]) -> i64 {
+    migrations.last().map_or(0, |m| m.version)
+}
+
+/// Brings the schema up to date. `db_path` is where backups are written
+/// next to; in-memory databases are not backed up.
+pub fn run(conn: &Connection, db_path: Option<&Path>) -> AppResult<()> {
+    migrate_to(conn, db_path, MIGRATIONS, latest_version(MIGRATIONS))
+}
+
+pub fn current_version(conn: &Connection) -> AppResult<i64> {
+    Ok(conn.query_row("PRAGMA user_version", [], |row| row.get(0))?)
+}
+
+/// Applies `up` steps or reverts `down` steps until the schema is at
+/// `target`. Each migration runs in its own transaction together with the
+/// version bump, so a failure leaves the schema at the last good version.
+pub fn migrate_to(conn: &Connection, db_path: Option<&Path>, migrations: &[Migration], target: i64) -> AppResult<()> {
+    let current = current_version(conn)?;
+    let latest = latest_version(migrations);
+    if current > latest {
+        return Err(AppError::Validation(format!(
+            "This database was created by a newer version of the app (schema version {current}; \
+             this version supports up to {latest}). Update the app to open it."
+        )));
+    }
+    if target > latest || target < 0 {
+        return Err(AppError::Validation(format!("Unknown schema version {target}")));
+    }
+
+    let mut backed_up = false;
+    if target > current {
+        for migration in migrations.iter().filter(|m| m.version > current && m.version <= target) {
+            if migration.risky && !backed_up {
+                backup(conn, db_path, current_version(conn)?)?;
+                backed_up = true;
+            }
+            apply(conn, &migration.up, migration.version)
+                .map_err(|e| migration_error(migration, "apply", e))?;
+        }
+    } else {
+        for migration in migrations.iter().rev().filter(|m| m.version <= current && m.version > target) {
+            if matches!(migration.down, Step::Irreversible) {
+                return Err(AppError::Validation(format!(
+                    "Migration {} ({}) cannot be reverted",
+                    migration.version, migration.name
+                )));
+            }
+            // Reverting drops whatever the migration added
+            if !backed_up {
+                backup(conn, db_path, current_version(conn)?)?;
+                backed_up = true;
+            }
+            apply(conn, &migration.down, migration.version - 1)
+                .map_err(|e| migration_error(migration, "revert", e))?;
+        }
+    }
+    Ok(())
+}
+
+fn apply(conn: &Connection, step: &Step, version_after: i64) -> AppResult<()> {
+    let tx = conn.unchecked_transaction()?;
+    match step {
+        Step::Sql(sql) => tx.execute_batch(sql)?,
+        Step::Rust(f) => f(&tx)?,
+        Step::Irreversible => unreachable!("irreversible steps are rejected before applying"),
+    }
+    tx.execute_batch(&format!("PRAGMA user_version = {version_after}"))?;
+    tx.commit()?;
+    Ok(())
+}
+
+fn migration_error(migration: &Migration, verb: &str, error: AppError) -> AppError {
+    AppError::Validation(format!(
+        "Failed to {verb} migration {} ({}): {error}",
+        migration.version, migration.name
+    ))
+}
+
+/// Copies the database file aside before a risky step, to
+/// `{db}.v{version}.bak` in the database's own folder. Only the latest copy
+/// is kept. The copy is taken after a WAL checkpoint so it is complete, and
+/// is encrypted whenever the database is.
+fn backup(conn: &Connection, db_path: Option<&Path>, version: i64) -> AppResult<Option<PathBuf>> {
+    let Some(path) = db_path else {
+        return Ok(None);
+    };
+    conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+    let backup = PathBuf::from(format!("{}.v{version}.bak", path.display()));
+    std::fs::copy(path, &backup)?;
+    for older in backups(path)?.into_iter().filter(|older| *older != backup) {
+        std::fs::remove_file(older)?;
+    }
+    log::info!("Backed up database to {} before migrating", backup.display());
+    Ok(Some(backup))
+}
+
+/// The migration backups of the database at `db_path`.
+pub fn backups(db_path: &Path) -> AppResult<Vec<PathBuf>> {
+    let Some(name) = db_path.file_name() else {
+        return Ok(Vec::new());
+    };
+    let dir = db_path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let prefix = format!("{}.v", name.to_string_lossy());
+    let mut found = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let file = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let version = file.strip_prefix(&prefix).and_then(|rest| rest.strip_suffix(".bak"));
+        if version.is_some_and(|v| v.parse::<i64>().is_ok()) {
+            found.push(path);
+        }
+    }
+    Ok(found)
+}
+
+/// The schema as it stood before migrations were versioned. Idempotent, so
+/// it also brings databases created by those versions up to date.
+fn baseline(conn: &Connection) -> AppResult<()> {
     conn.execute_batch(
         "
         CREATE TABLE IF NOT EXISTS documents (
@@ -90,11 +523,223 @@ pub fn run(conn: &Connection) -> AppResult<()> {
             updated_at TEXT NOT NULL DEFAULT (datetime('now'))
         );
 
+        CREATE TABLE IF NOT EXISTS prompt_versions (
+            id TEXT PRIMARY KEY,
+            prompt_kind TEXT NOT NULL,
+            version INTEGER NOT NULL,
+            system_template TEXT NOT NULL,
+            user_template TEXT NOT NULL,
+            notes TEXT,
+            is_active INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            UNIQUE (prompt_kind, version)
+        );
+
+        CREATE TABLE IF NOT EXISTS redaction_log (
+            id TEXT PRIMARY KEY,
+            document_id TEXT,
+            comparison_id TEXT,
+            ai_provider TEXT NOT NULL,
+            prompt_kind TEXT NOT NULL,
+            entity_type TEXT NOT NULL,
+            placeholder TEXT NOT NULL,
+            masked_value TEXT NOT NULL,
+            occurrences INTEGER NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY (document_id) REFERENCES documents(id) ON DELETE CASCADE,
+            FOREIGN KEY (comparison_id) REFERENCES comparisons(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS matters (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            status TEXT NOT NULL DEFAULT 'open',
+            legal_hold INTEGER NOT NULL DEFAULT 0,
+            closed_at TEXT,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        -- Append-only and hash-chained; see db::audit. No foreign keys, so
+        -- events outlive the records they describe.
+        CREATE TABLE IF NOT EXISTS audit_events (
+            seq INTEGER PRIMARY KEY,
+            id TEXT NOT NULL UNIQUE,
+            occurred_at TEXT NOT NULL,
+            actor TEXT NOT NULL,
+            action TEXT NOT NULL,
+            document_id TEXT,
+            matter_id TEXT,
+            subject_type TEXT,
+            subject_id TEXT,
+            details TEXT NOT NULL,
+            prev_hash TEXT NOT NULL,
+            hash TEXT NOT NULL
+        );
+
+        CREATE TRIGGER IF NOT EXISTS audit_events_no_update BEFORE UPDATE ON audit_events
+        BEGIN
+            SELECT RAISE(ABORT, 'audit_events is append-only');
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS audit_events_no_delete BEFORE DELETE ON audit_events
+        BEGIN
+            SELECT RAISE(ABORT, 'audit_events is append-only');
+        END;
+
         CREATE INDEX IF NOT EXISTS idx_extractions_document ON extractions(document_id);
         CREATE INDEX IF NOT EXISTS idx_risk_document ON risk_assessments(document_id);
         CREATE INDEX IF NOT EXISTS idx_comparisons_doc_a ON comparisons(document_a_id);
         CREATE INDEX IF NOT EXISTS idx_reports_document ON reports(document_id);
+        CREATE INDEX IF NOT EXISTS idx_redaction_log_document ON redaction_log(document_id);
+        CREATE INDEX IF NOT EXISTS idx_redaction_log_comparison ON redaction_log(comparison_id);
+        CREATE INDEX IF NOT EXISTS idx_audit_events_document ON audit_events(document_id);
+        CREATE INDEX IF NOT EXISTS idx_audit_events_matter ON audit_events(matter_id);
         "
     )?;
+
+    // Columns added after the initial schema; CREATE TABLE IF NOT EXISTS
+    // leaves existing tables untouched, so add them explicitly.
+    add_column_if_missing(conn, "extractions", "prompt_version", "INTEGER")?;
+    add_column_if_missing(conn, "risk_assessments", "prompt_version", "INTEGER")?;
+    add_column_if_missing(conn, "documents", "local_only", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "documents", "matter_id", "TEXT REFERENCES matters(id) ON DELETE SET NULL")?;
+    add_column_if_missing(conn, "documents", "deleted_at", "TEXT")?;
+    add_column_if_missing(conn, "documents", "legal_hold", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "documents", "legal_hold_reason", "TEXT")?;
+    add_column_if_missing(conn, "documents", "raw_text_removed_at", "TEXT")?;
+    for table in ["extractions", "risk_assessments", "comparisons"] {
+        add_column_if_missing(conn, table, "data_policy", "TEXT")?;
+        add_column_if_missing(conn, table, "ai_endpoint", "TEXT")?;
+    }
     Ok(())
 }
+
+fn add_column_if_missing(conn: &Connection, table: &str, column: &str, definition: &str) -> AppResult<()> {
+    let exists = conn
+        .prepare(&format!("PRAGMA table_info({table})"))?
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<Result<Vec<_>, _>>()?
+        .iter()
+        .any(|name| name == column);
+    if !exists {
+        conn.execute_batch(&format!("ALTER TABLE {table} ADD COLUMN {column} {definition}"))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+
+    const TEST_MIGRATIONS: &[Migration] = &[
+        Migration {
+            version: 1,
+            name: "notes",
+            up: Step::Sql("CREATE TABLE notes (id INTEGER PRIMARY KEY, body TEXT NOT NULL);"),
+            down: Step::Sql("DROP TABLE notes;"),
+            risky: false,
+        },
+        Migration {
+            version: 2,
+            name: "notes_pinned",
+            up: Step::Sql("ALTER TABLE notes ADD COLUMN pinned INTEGER NOT NULL DEFAULT 0;"),
+            down: Step::Sql("ALTER TABLE notes DROP COLUMN pinned;"),
+            risky: true,
+        },
+        Migration {
+            version: 3,
+            name: "broken",
+            up: Step::Sql("CREATE TABLE tags (id INTEGER PRIMARY KEY); INSERT INTO missing VALUES (1);"),
+            down: Step::Sql("DROP TABLE tags;"),
+            risky: false,
+        },
+    ];
+
+    fn has_table(conn: &Connection, name: &str) -> bool {
+        conn.query_row("SELECT count(*) FROM sqlite_master WHERE name = ?1", [name], |row| row.get::<_, i64>(0))
+            .unwrap()
+            > 0
+    }
+
+    #[test]
+    fn test_new_database_is_at_latest_version() {
+        let db = Database::in_memory().unwrap();
+        let conn = db.writer();
+        assert_eq!(current_version(&conn).unwrap(), latest_version(MIGRATIONS));
+        // Re-running is a no-op
+        run(&conn, None).unwrap();
+    }
+
+    #[test]
+    fn test_up_down_and_backup() {
+        let dir = std::env::temp_dir().join(format!("ldr-migrate-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.db");
+        let conn = Connection::open(&path).unwrap();
+
+        migrate_to(&conn, Some(&path), TEST_MIGRATIONS, 2).unwrap();
+        assert_eq!(current_version(&conn).unwrap(), 2);
+        assert!(PathBuf::from(format!("{}.v1.bak", path.display())).exists());
+
+        // A failing migration rolls back entirely
+        assert!(migrate_to(&conn, Some(&path), TEST_MIGRATIONS, 3).is_err());
+        assert_eq!(current_version(&conn).unwrap(), 2);
+        assert!(!has_table(&conn, "tags"));
+
+        migrate_to(&conn, Some(&path), TEST_MIGRATIONS, 0).unwrap();
+        assert_eq!(current_version(&conn).unwrap(), 0);
+        assert!(!has_table(&conn, "notes"));
+        // Only the latest backup is kept
+        assert_eq!(backups(&path).unwrap(), vec![PathBuf::from(format!("{}.v2.bak", path.display()))]);
+        drop(conn);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_upgrade_over_data_rewrites_backs_up() {
+        let dir = std::env::temp_dir().join(format!("ldr-migrate-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.db");
+        let conn = Connection::open(&path).unwrap();
+        let backup = |version: i64| PathBuf::from(format!("{}.v{version}.bak", path.display()));
+
+        migrate_to(&conn, Some(&path), MIGRATIONS, 5).unwrap();
+        assert!((0..5).all(|version| !backup(version).exists()));
+
+        migrate_to(&conn, Some(&path), MIGRATIONS, latest_version(MIGRATIONS)).unwrap();
+        assert!(backup(5).exists());
+        let saved = Connection::open(backup(5)).unwrap();
+        assert_eq!(current_version(&saved).unwrap(), 5);
+        drop((saved, conn));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_down_to_v2_still_takes_documents_and_extractions() {
+        let db = Database::in_memory().unwrap();
+        let conn = db.writer();
+        migrate_to(&conn, None, MIGRATIONS, 2).unwrap();
+        assert!(!has_table(&conn, "documents_fts"));
+
+        conn.execute_batch(
+            "INSERT INTO documents (id, filename, original_path, stored_path, file_hash, file_size, contract_type)
+             VALUES ('doc-1', 'nda.pdf', '/tmp/nda.pdf', '/data/nda.pdf', 'hash', 10, 'nda');
+             UPDATE documents SET raw_text = 'Mutual NDA', filename = 'mutual-nda.pdf' WHERE id = 'doc-1';
+             INSERT INTO extractions (id, document_id, ai_provider, contract_type, extracted_data)
+             VALUES ('ext-1', 'doc-1', 'mock', 'nda', '{\"clauses\": [{\"text\": \"Term\"}]}');
+             DELETE FROM documents WHERE id = 'doc-1';",
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_refuses_newer_database() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("PRAGMA user_version = 99").unwrap();
+        let err = run(&conn, None).unwrap_err();
+        assert!(err.to_string().contains("newer version"));
+        assert!(migrate_to(&conn, None, MIGRATIONS, 0).is_err());
+    }
+}
@@ -6,42 +6,294 @@ pub(crate) mod templates;
 pub(crate) mod comparisons;
 pub(crate) mod reports;
 pub(crate) mod settings;
+pub(crate) mod prompts;
+pub(crate) mod redactions;
+pub(crate) mod audit;
+pub(crate) mod matters;
+pub(crate) mod search;
+pub(crate) mod clauses;
+pub(crate) mod conversations;
+pub(crate) mod tags;
+pub(crate) mod versions;
 
-use rusqlite::Connection;
-use std::path::Path;
-use std::sync::Mutex;
+use rusqlite::{params, Connection};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, TryLockError};
 
-use crate::error::AppResult;
+use crate::error::{AppError, AppResult};
 
-pub struct Database {
-    pub conn: Mutex<Connection>,
+/// The database file in the app data directory.
+pub const DB_FILE: &str = "legal_docs_review.db";
+
+/// Read-only connections opened next to the writer. WAL lets them read
+/// while a write is in progress.
+const READER_COUNT: usize = 4;
+
+/// A handle to the connection pool: one writer and a few readers. Cloning is
+/// cheap, so a handle can be moved into blocking tasks.
+#[derive(Clone)]
+pub struct Database(Arc<Pool>);
+
+struct Pool {
+    writer: Mutex<Connection>,
+    /// Empty for in-memory databases, which a second connection cannot see.
+    readers: Vec<Mutex<Connection>>,
+    next_reader: AtomicUsize,
+    path: Option<PathBuf>,
+}
+
+/// The first bytes of an unencrypted SQLite file. SQLCipher files have no
+/// recognisable header.
+const SQLITE_HEADER: &[u8] = b"SQLite format 3\0";
+
+fn key_literal(key: &[u8; 32]) -> String {
+    let hex: String = key.iter().map(|b| format!("{b:02x}")).collect();
+    format!("x'{hex}'")
+}
+
+/// Takes a connection even if a thread panicked while holding it. The
+/// connection itself is still usable; a transaction the panic left open is
+/// rolled back so its partial writes are not committed by the next user.
+fn recover<'a>(mutex: &Mutex<Connection>, guard: MutexGuard<'a, Connection>) -> MutexGuard<'a, Connection> {
+    log::warn!("Recovering a database connection after a panic");
+    mutex.clear_poison();
+    if !guard.is_autocommit() {
+        if let Err(e) = guard.execute_batch("ROLLBACK") {
+            log::error!("Failed to roll back after a panic: {e}");
+        }
+    }
+    guard
+}
+
+fn lock(mutex: &Mutex<Connection>) -> MutexGuard<'_, Connection> {
+    match mutex.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => recover(mutex, poisoned.into_inner()),
+    }
 }
 
 impl Database {
     pub fn new(path: &Path) -> AppResult<Self> {
-        let conn = Connection::open(path)?;
-        conn.execute_batch("PRAGMA journal_mode=WAL; PRAGMA foreign_keys=ON;")?;
-        let db = Self {
-            conn: Mutex::new(conn),
+        let conn = Self::connect(path, None)?;
+        Self::open_pool(conn, Some(path), None)
+    }
+
+    /// Opens a SQLCipher database with the first of `keys` that decrypts it.
+    /// Returns the index of that key, or `None` if the file turned out to be
+    /// unencrypted (an interrupted migration).
+    pub fn open_encrypted(path: &Path, keys: &[&[u8; 32]]) -> AppResult<(Self, Option<usize>)> {
+        let current = keys
+            .first()
+            .ok_or_else(|| AppError::Validation("No encryption key available".to_string()))?;
+        let has_data = path.exists() && std::fs::metadata(path)?.len() > 0;
+        let (conn, index) = if !has_data {
+            (Self::connect(path, Some(current))?, Some(0))
+        } else if Self::is_plaintext(path)? {
+            (Self::connect(path, None)?, None)
+        } else {
+            keys.iter()
+                .enumerate()
+                .find_map(|(index, key)| Self::connect(path, Some(key)).ok().map(|conn| (conn, Some(index))))
+                .ok_or_else(|| {
+                    AppError::Validation("Wrong passphrase: the database could not be decrypted".to_string())
+                })?
         };
-        db.run_migrations()?;
-        Ok(db)
+        let db = Self::open_pool(conn, Some(path), index.map(|index| keys[index]))?;
+        Ok((db, index))
     }
 
     #[cfg(test)]
     pub fn in_memory() -> AppResult<Self> {
         let conn = Connection::open_in_memory()?;
         conn.execute_batch("PRAGMA foreign_keys=ON;")?;
-        let db = Self {
-            conn: Mutex::new(conn),
+        Self::open_pool(conn, None, None)
+    }
+
+    /// Migrates through the writer, then opens the readers.
+    fn open_pool(writer: Connection, path: Option<&Path>, key: Option<&[u8; 32]>) -> AppResult<Self> {
+        migrations::run(&writer, path)?;
+        let readers = match path {
+            Some(path) => Self::open_readers(path, key)?.into_iter().map(Mutex::new).collect(),
+            None => Vec::new(),
         };
-        db.run_migrations()?;
-        Ok(db)
+        Ok(Self(Arc::new(Pool {
+            writer: Mutex::new(writer),
+            readers,
+            next_reader: AtomicUsize::new(0),
+            path: path.map(Path::to_path_buf),
+        })))
     }
 
-    fn run_migrations(&self) -> AppResult<()> {
-        let conn = self.conn.lock().expect("db lock poisoned");
-        migrations::run(&conn)?;
+    fn open_readers(path: &Path, key: Option<&[u8; 32]>) -> AppResult<Vec<Connection>> {
+        (0..READER_COUNT)
+            .map(|_| {
+                let conn = Self::connect(path, key)?;
+                conn.execute_batch("PRAGMA query_only = ON;")?;
+                Ok(conn)
+            })
+            .collect()
+    }
+
+    fn connect(path: &Path, key: Option<&[u8; 32]>) -> AppResult<Connection> {
+        let conn = Connection::open(path)?;
+        if let Some(key) = key {
+            // Must come before anything reads the file
+            conn.execute_batch(&format!("PRAGMA key = \"{}\";", key_literal(key)))?;
+        }
+        // Fails with "file is not a database" when the key is wrong
+        conn.query_row("SELECT count(*) FROM sqlite_master", [], |row| row.get::<_, i64>(0))?;
+        conn.execute_batch("PRAGMA journal_mode=WAL; PRAGMA foreign_keys=ON; PRAGMA busy_timeout=5000;")?;
+        Ok(conn)
+    }
+
+    fn is_plaintext(path: &Path) -> AppResult<bool> {
+        use std::io::Read;
+        let mut header = [0u8; 16];
+        let read = std::fs::File::open(path)?.read(&mut header)?;
+        Ok(read == SQLITE_HEADER.len() && header == SQLITE_HEADER)
+    }
+
+    /// The single connection allowed to write. Blocks while another writer
+    /// holds it; use from blocking code or through [`Database::write`].
+    pub fn writer(&self) -> MutexGuard<'_, Connection> {
+        lock(&self.0.writer)
+    }
+
+    /// A read-only connection: the first idle reader, else the next one in
+    /// turn. In-memory databases read through the writer.
+    pub fn reader(&self) -> MutexGuard<'_, Connection> {
+        let readers = &self.0.readers;
+        if readers.is_empty() {
+            return self.writer();
+        }
+        let start = self.0.next_reader.fetch_add(1, Ordering::Relaxed);
+        for offset in 0..readers.len() {
+            let mutex = &readers[(start + offset) % readers.len()];
+            match mutex.try_lock() {
+                Ok(guard) => return guard,
+                Err(TryLockError::Poisoned(poisoned)) => return recover(mutex, poisoned.into_inner()),
+                Err(TryLockError::WouldBlock) => continue,
+            }
+        }
+        lock(&readers[start % readers.len()])
+    }
+
+    /// Runs `f` on a reader in a blocking task, off the async runtime.
+    pub async fn read<T, F>(&self, f: F) -> AppResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Connection) -> AppResult<T> + Send + 'static,
+    {
+        let db = self.clone();
+        tauri::async_runtime::spawn_blocking(move || f(&db.reader()))
+            .await
+            .map_err(|e| AppError::Task(e.to_string()))?
+    }
+
+    /// Runs `f` on the writer in a blocking task, off the async runtime.
+    pub async fn write<T, F>(&self, f: F) -> AppResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Connection) -> AppResult<T> + Send + 'static,
+    {
+        let db = self.clone();
+        tauri::async_runtime::spawn_blocking(move || f(&db.writer()))
+            .await
+            .map_err(|e| AppError::Task(e.to_string()))?
+    }
+
+    /// The database file; `None` for an in-memory database.
+    pub fn path(&self) -> Option<&Path> {
+        self.0.path.as_deref()
+    }
+
+    /// Rewrites the whole database encrypted under `key` and swaps it in
+    /// place. Works from plaintext or from another key.
+    pub fn rekey(&self, key: &[u8; 32]) -> AppResult<()> {
+        let path = self
+            .0
+            .path
+            .as_deref()
+            .ok_or_else(|| AppError::Validation("An in-memory database cannot be encrypted".to_string()))?;
+        let staging = path.with_extension("rekey");
+        if staging.exists() {
+            std::fs::remove_file(&staging)?;
+        }
+
+        let mut conn = self.writer();
+        // Held until the new file is in place so nothing reads the old one
+        let mut readers: Vec<_> = self.0.readers.iter().map(lock).collect();
+        let user_version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        conn.execute(
+            "ATTACH DATABASE ?1 AS rekeyed KEY ?2",
+            params![staging.to_string_lossy(), key_literal(key)],
+        )?;
+        conn.query_row("SELECT sqlcipher_export('rekeyed')", [], |_| Ok(()))?;
+        conn.execute_batch(&format!("PRAGMA rekeyed.user_version = {user_version}; DETACH DATABASE rekeyed;"))?;
+
+        // Close every connection to the old file before replacing it
+        for reader in readers.iter_mut() {
+            let old = std::mem::replace(&mut **reader, Connection::open_in_memory()?);
+            old.close().map_err(|(_, e)| e)?;
+        }
+        let old = std::mem::replace(&mut *conn, Connection::open_in_memory()?);
+        old.close().map_err(|(_, e)| e)?;
+        for suffix in ["-wal", "-shm"] {
+            let side = PathBuf::from(format!("{}{suffix}", path.display()));
+            if side.exists() {
+                std::fs::remove_file(side)?;
+            }
+        }
+        std::fs::rename(&staging, path)?;
+        *conn = Self::connect(path, Some(key))?;
+        for (reader, fresh) in readers.iter_mut().zip(Self::open_readers(path, Some(key))?) {
+            **reader = fresh;
+        }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_db() -> (Database, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("ldr-pool-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        (Database::new(&dir.join(DB_FILE)).unwrap(), dir)
+    }
+
+    #[tokio::test]
+    async fn test_readers_see_committed_writes() {
+        let (db, dir) = scratch_db();
+        db.write(|conn| settings::set(conn, "ai_provider", "claude")).await.unwrap();
+        let value = db.read(|conn| settings::get(conn, "ai_provider")).await.unwrap();
+        assert_eq!(value.as_deref(), Some("claude"));
+        // Readers are read-only
+        assert!(db.reader().execute("DELETE FROM settings", []).is_err());
+        drop(db);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_recovers_from_panic_while_writing() {
+        let (db, dir) = scratch_db();
+        let handle = db.clone();
+        let panicked = std::thread::spawn(move || {
+            let conn = handle.writer();
+            conn.execute_batch("BEGIN; INSERT INTO settings (key, value) VALUES ('ai_provider', 'openai');").unwrap();
+            panic!("simulated crash mid-transaction");
+        })
+        .join();
+        assert!(panicked.is_err());
+
+        // The lock is usable again and the half-done write was rolled back
+        let conn = db.writer();
+        assert!(conn.is_autocommit());
+        assert_eq!(settings::get(&conn, "ai_provider").unwrap(), None);
+        drop(conn);
+        drop(db);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
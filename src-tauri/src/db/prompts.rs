@@ -0,0 +1,240 @@
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ValueRef};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::ai::prompts::{self, PromptKind, PromptSet, PromptTemplate, BUILTIN_VERSION};
+use crate::error::{AppError, AppResult};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptVersion {
+    pub id: Option<String>,
+    pub prompt_kind: PromptKind,
+    pub version: i64,
+    pub system_template: String,
+    pub user_template: String,
+    pub notes: Option<String>,
+    pub is_active: bool,
+    pub created_at: Option<String>,
+}
+
+impl PromptVersion {
+    pub fn template(&self) -> PromptTemplate {
+        PromptTemplate {
+            kind: self.prompt_kind,
+            version: self.version,
+            system: self.system_template.clone(),
+            user: self.user_template.clone(),
+        }
+    }
+
+    fn builtin(kind: PromptKind, is_active: bool) -> Self {
+        let template = kind.builtin();
+        Self {
+            id: None,
+            prompt_kind: kind,
+            version: BUILTIN_VERSION,
+            system_template: template.system,
+            user_template: template.user,
+            notes: Some("Built-in default".to_string()),
+            is_active,
+            created_at: None,
+        }
+    }
+}
+
+impl FromSql for PromptKind {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        value
+            .as_str()?
+            .parse()
+            .map_err(|e: AppError| FromSqlError::Other(Box::new(e)))
+    }
+}
+
+fn row_to_version(row: &rusqlite::Row<'_>) -> rusqlite::Result<PromptVersion> {
+    Ok(PromptVersion {
+        id: row.get(0)?,
+        prompt_kind: row.get(1)?,
+        version: row.get(2)?,
+        system_template: row.get(3)?,
+        user_template: row.get(4)?,
+        notes: row.get(5)?,
+        is_active: row.get(6)?,
+        created_at: row.get(7)?,
+    })
+}
+
+/// All stored revisions of a prompt, newest first, followed by the built-in default.
+pub fn list_versions(conn: &Connection, kind: PromptKind) -> AppResult<Vec<PromptVersion>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, prompt_kind, version, system_template, user_template, notes, is_active, created_at
+         FROM prompt_versions WHERE prompt_kind = ?1 ORDER BY version DESC",
+    )?;
+    let mut versions = stmt
+        .query_map(params![kind.as_str()], row_to_version)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let builtin_active = !versions.iter().any(|v| v.is_active);
+    versions.push(PromptVersion::builtin(kind, builtin_active));
+    Ok(versions)
+}
+
+pub fn get_version(conn: &Connection, kind: PromptKind, version: i64) -> AppResult<PromptVersion> {
+    if version == BUILTIN_VERSION {
+        return Ok(PromptVersion::builtin(kind, active(conn, kind)?.version == BUILTIN_VERSION));
+    }
+    let found = conn.query_row(
+        "SELECT id, prompt_kind, version, system_template, user_template, notes, is_active, created_at
+         FROM prompt_versions WHERE prompt_kind = ?1 AND version = ?2",
+        params![kind.as_str(), version],
+        row_to_version,
+    );
+    match found {
+        Ok(v) => Ok(v),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Err(AppError::NotFound(format!(
+            "Prompt {} version {version} not found",
+            kind.as_str()
+        ))),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// The template currently used for a prompt kind; the built-in one when no
+/// stored revision is active.
+pub fn active(conn: &Connection, kind: PromptKind) -> AppResult<PromptTemplate> {
+    let found = conn.query_row(
+        "SELECT id, prompt_kind, version, system_template, user_template, notes, is_active, created_at
+         FROM prompt_versions WHERE prompt_kind = ?1 AND is_active = 1",
+        params![kind.as_str()],
+        row_to_version,
+    );
+    match found {
+        Ok(v) => Ok(v.template()),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(kind.builtin()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub fn load_active_set(conn: &Connection) -> AppResult<PromptSet> {
+    let mut set = PromptSet::builtin();
+    for kind in PromptKind::ALL {
+        set.set(active(conn, kind)?);
+    }
+    Ok(set)
+}
+
+/// Saves a new revision of a prompt and makes it the active one.
+pub fn create_version(
+    conn: &Connection,
+    kind: PromptKind,
+    system_template: &str,
+    user_template: &str,
+    notes: Option<&str>,
+) -> AppResult<PromptVersion> {
+    prompts::validate_template(kind, system_template, user_template)?;
+
+    let tx = conn.unchecked_transaction()?;
+    let next: i64 = tx.query_row(
+        "SELECT COALESCE(MAX(version), 0) + 1 FROM prompt_versions WHERE prompt_kind = ?1",
+        params![kind.as_str()],
+        |row| row.get(0),
+    )?;
+    tx.execute(
+        "UPDATE prompt_versions SET is_active = 0 WHERE prompt_kind = ?1",
+        params![kind.as_str()],
+    )?;
+    let id = uuid::Uuid::new_v4().to_string();
+    tx.execute(
+        "INSERT INTO prompt_versions (id, prompt_kind, version, system_template, user_template, notes, is_active)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, 1)",
+        params![id, kind.as_str(), next, system_template, user_template, notes],
+    )?;
+    tx.commit()?;
+    get_version(conn, kind, next)
+}
+
+/// Makes an earlier revision (or the built-in default, version 0) active again.
+/// Revisions themselves are never modified, so history stays intact.
+pub fn activate(conn: &Connection, kind: PromptKind, version: i64) -> AppResult<PromptVersion> {
+    // Fail before touching anything if the target does not exist, or was
+    // written for variables the prompt no longer supplies
+    let target = get_version(conn, kind, version)?;
+    prompts::validate_template(kind, &target.system_template, &target.user_template)?;
+
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "UPDATE prompt_versions SET is_active = 0 WHERE prompt_kind = ?1",
+        params![kind.as_str()],
+    )?;
+    if version != BUILTIN_VERSION {
+        tx.execute(
+            "UPDATE prompt_versions SET is_active = 1 WHERE prompt_kind = ?1 AND version = ?2",
+            params![kind.as_str(), version],
+        )?;
+    }
+    tx.commit()?;
+    get_version(conn, kind, version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+
+    #[test]
+    fn test_builtin_is_active_by_default() {
+        let db = Database::in_memory().unwrap();
+        let conn = db.writer();
+
+        let template = active(&conn, PromptKind::Risk).unwrap();
+        assert_eq!(template.version, BUILTIN_VERSION);
+
+        let versions = list_versions(&conn, PromptKind::Risk).unwrap();
+        assert_eq!(versions.len(), 1);
+        assert!(versions[0].is_active);
+    }
+
+    #[test]
+    fn test_create_version_activates_it() {
+        let db = Database::in_memory().unwrap();
+        let conn = db.writer();
+
+        let v1 = create_version(&conn, PromptKind::Summary, "Be brief.", "{{extraction_json}}", None).unwrap();
+        let v2 = create_version(&conn, PromptKind::Summary, "Be terse.", "{{risk_json}}", Some("shorter")).unwrap();
+        assert_eq!(v1.version, 1);
+        assert_eq!(v2.version, 2);
+
+        let set = load_active_set(&conn).unwrap();
+        assert_eq!(set.summary.version, 2);
+        assert_eq!(set.summary.system, "Be terse.");
+        assert_eq!(set.extraction.version, BUILTIN_VERSION);
+
+        let versions = list_versions(&conn, PromptKind::Summary).unwrap();
+        assert_eq!(versions.iter().filter(|v| v.is_active).count(), 1);
+    }
+
+    #[test]
+    fn test_create_version_rejects_unknown_variable() {
+        let db = Database::in_memory().unwrap();
+        let conn = db.writer();
+        assert!(create_version(&conn, PromptKind::Summary, "", "{{text_a}}", None).is_err());
+        assert_eq!(list_versions(&conn, PromptKind::Summary).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_rollback() {
+        let db = Database::in_memory().unwrap();
+        let conn = db.writer();
+
+        create_version(&conn, PromptKind::Risk, "v1", "{{extraction_json}}", None).unwrap();
+        create_version(&conn, PromptKind::Risk, "v2", "{{extraction_json}}", None).unwrap();
+
+        activate(&conn, PromptKind::Risk, 1).unwrap();
+        assert_eq!(active(&conn, PromptKind::Risk).unwrap().system, "v1");
+
+        activate(&conn, PromptKind::Risk, BUILTIN_VERSION).unwrap();
+        assert_eq!(active(&conn, PromptKind::Risk).unwrap().version, BUILTIN_VERSION);
+
+        assert!(activate(&conn, PromptKind::Risk, 9).is_err());
+    }
+}
@@ -0,0 +1,89 @@
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::ai::Redaction;
+use crate::error::AppResult;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedactionLogEntry {
+    pub id: String,
+    pub document_id: Option<String>,
+    pub comparison_id: Option<String>,
+    pub ai_provider: String,
+    pub prompt_kind: String,
+    pub entity_type: String,
+    pub placeholder: String,
+    pub masked_value: String,
+    pub occurrences: i64,
+    pub created_at: String,
+}
+
+/// What the log entries are about: a single document, or a comparison.
+pub enum RedactionSubject<'a> {
+    Document(&'a str),
+    Comparison(&'a str),
+}
+
+pub fn insert_all(
+    conn: &Connection,
+    subject: RedactionSubject<'_>,
+    ai_provider: &str,
+    redactions: &[Redaction],
+) -> AppResult<()> {
+    let (document_id, comparison_id) = match subject {
+        RedactionSubject::Document(id) => (Some(id), None),
+        RedactionSubject::Comparison(id) => (None, Some(id)),
+    };
+    // Joins the caller's transaction when there is one
+    let tx = conn.is_autocommit().then(|| conn.unchecked_transaction()).transpose()?;
+    for redaction in redactions {
+        conn.execute(
+            "INSERT INTO redaction_log (id, document_id, comparison_id, ai_provider, prompt_kind,
+                entity_type, placeholder, masked_value, occurrences)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                uuid::Uuid::new_v4().to_string(),
+                document_id,
+                comparison_id,
+                ai_provider,
+                redaction.prompt_kind.as_str(),
+                redaction.entity_type,
+                redaction.placeholder,
+                redaction.masked_value,
+                redaction.occurrences,
+            ],
+        )?;
+    }
+    if let Some(tx) = tx {
+        tx.commit()?;
+    }
+    Ok(())
+}
+
+pub fn list_by_document(conn: &Connection, document_id: &str) -> AppResult<Vec<RedactionLogEntry>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, document_id, comparison_id, ai_provider, prompt_kind, entity_type,
+                placeholder, masked_value, occurrences, created_at
+         FROM redaction_log
+         WHERE document_id = ?1
+            OR comparison_id IN (SELECT id FROM comparisons WHERE document_a_id = ?1 OR document_b_id = ?1)
+         ORDER BY created_at DESC, placeholder",
+    )?;
+    let results = stmt
+        .query_map(params![document_id], |row| {
+            Ok(RedactionLogEntry {
+                id: row.get(0)?,
+                document_id: row.get(1)?,
+                comparison_id: row.get(2)?,
+                ai_provider: row.get(3)?,
+                prompt_kind: row.get(4)?,
+                entity_type: row.get(5)?,
+                placeholder: row.get(6)?,
+                masked_value: row.get(7)?,
+                occurrences: row.get(8)?,
+                created_at: row.get(9)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(results)
+}
@@ -1,6 +1,7 @@
 use rusqlite::{params, Connection};
 use serde::{Deserialize, Serialize};
 
+use crate::db::tags;
 use crate::error::{AppError, AppResult};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -11,9 +12,35 @@ pub struct Report {
     pub content: String,
     pub export_path: Option<String>,
     pub format: String,
+    pub matter_id: Option<String>,
+    /// The comparison reported on; `None` for analysis reports.
+    pub comparison_id: Option<String>,
+    /// Tag names, sorted.
+    pub tags: Vec<String>,
     pub created_at: String,
 }
 
+const SELECT_COLUMNS: &str = "id, document_id, report_type, content, export_path, format, matter_id,
+        (SELECT json_group_array(name) FROM (SELECT t.name FROM report_tags rt JOIN tags t ON t.id = rt.tag_id
+         WHERE rt.report_id = reports.id ORDER BY t.name COLLATE NOCASE)),
+        created_at, comparison_id";
+
+fn map_row(row: &rusqlite::Row) -> rusqlite::Result<Report> {
+    Ok(Report {
+        id: row.get(0)?,
+        document_id: row.get(1)?,
+        report_type: row.get(2)?,
+        content: row.get(3)?,
+        export_path: row.get(4)?,
+        format: row.get(5)?,
+        matter_id: row.get(6)?,
+        tags: tags::parse_names(7, &row.get::<_, String>(7)?)?,
+        created_at: row.get(8)?,
+        comparison_id: row.get(9)?,
+    })
+}
+
+/// The report starts out in its document's matter.
 pub fn insert(
     conn: &Connection,
     document_id: &str,
@@ -23,52 +50,73 @@ pub fn insert(
 ) -> AppResult<Report> {
     let id = uuid::Uuid::new_v4().to_string();
     conn.execute(
-        "INSERT INTO reports (id, document_id, report_type, content, format) VALUES (?1, ?2, ?3, ?4, ?5)",
+        "INSERT INTO reports (id, document_id, report_type, content, format, matter_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, (SELECT matter_id FROM documents WHERE id = ?2))",
         params![id, document_id, report_type, content, format],
     )?;
     get_by_id(conn, &id)
 }
 
+/// A report on a comparison, filed under its first document and starting
+/// out in the comparison's matter.
+pub fn insert_for_comparison(
+    conn: &Connection,
+    comparison_id: &str,
+    report_type: &str,
+    content: &str,
+    format: &str,
+) -> AppResult<Report> {
+    let id = uuid::Uuid::new_v4().to_string();
+    let rows = conn.execute(
+        "INSERT INTO reports (id, document_id, report_type, content, format, matter_id, comparison_id)
+         SELECT ?1, document_a_id, ?2, ?3, ?4, matter_id, id FROM comparisons WHERE id = ?5",
+        params![id, report_type, content, format, comparison_id],
+    )?;
+    if rows == 0 {
+        return Err(AppError::NotFound(format!("Comparison {comparison_id} not found")));
+    }
+    get_by_id(conn, &id)
+}
+
 pub fn get_by_id(conn: &Connection, id: &str) -> AppResult<Report> {
-    conn.query_row(
-        "SELECT id, document_id, report_type, content, export_path, format, created_at
-         FROM reports WHERE id = ?1",
-        params![id],
-        |row| {
-            Ok(Report {
-                id: row.get(0)?,
-                document_id: row.get(1)?,
-                report_type: row.get(2)?,
-                content: row.get(3)?,
-                export_path: row.get(4)?,
-                format: row.get(5)?,
-                created_at: row.get(6)?,
-            })
-        },
-    )
-    .map_err(|e| match e {
-        rusqlite::Error::QueryReturnedNoRows => AppError::NotFound(format!("Report {id} not found")),
-        other => AppError::Database(other),
-    })
+    conn.query_row(&format!("SELECT {SELECT_COLUMNS} FROM reports WHERE id = ?1"), params![id], map_row)
+        .map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => AppError::NotFound(format!("Report {id} not found")),
+            other => AppError::Database(other),
+        })
 }
 
 pub fn list_by_document(conn: &Connection, document_id: &str) -> AppResult<Vec<Report>> {
-    let mut stmt = conn.prepare(
-        "SELECT id, document_id, report_type, content, export_path, format, created_at
-         FROM reports WHERE document_id = ?1 ORDER BY created_at DESC",
-    )?;
-    let results = stmt
-        .query_map(params![document_id], |row| {
-            Ok(Report {
-                id: row.get(0)?,
-                document_id: row.get(1)?,
-                report_type: row.get(2)?,
-                content: row.get(3)?,
-                export_path: row.get(4)?,
-                format: row.get(5)?,
-                created_at: row.get(6)?,
-            })
-        })?
-        .collect::<Result<Vec<_>, _>>()?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {SELECT_COLUMNS} FROM reports WHERE document_id = ?1 ORDER BY created_at DESC"
+    ))?;
+    let results = stmt.query_map(params![document_id], map_row)?.collect::<Result<Vec<_>, _>>()?;
+    Ok(results)
+}
+
+pub fn list_by_comparison(conn: &Connection, comparison_id: &str) -> AppResult<Vec<Report>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {SELECT_COLUMNS} FROM reports WHERE comparison_id = ?1 ORDER BY created_at DESC"
+    ))?;
+    let results = stmt.query_map(params![comparison_id], map_row)?.collect::<Result<Vec<_>, _>>()?;
     Ok(results)
 }
+
+/// Deletes the reports on the document and on comparisons with it, since
+/// both quote its text.
+pub fn delete_by_document(conn: &Connection, document_id: &str) -> AppResult<()> {
+    conn.execute(
+        "DELETE FROM reports WHERE document_id = ?1
+            OR comparison_id IN (SELECT id FROM comparisons WHERE document_a_id = ?1 OR document_b_id = ?1)",
+        params![document_id],
+    )?;
+    Ok(())
+}
+
+pub fn set_matter(conn: &Connection, id: &str, matter_id: Option<&str>) -> AppResult<()> {
+    let rows = conn.execute("UPDATE reports SET matter_id = ?1 WHERE id = ?2", params![matter_id, id])?;
+    if rows == 0 {
+        return Err(AppError::NotFound(format!("Report {id} not found")));
+    }
+    Ok(())
+}
@@ -13,6 +13,11 @@ pub struct RiskAssessment {
     pub flags: String,
     pub summary: Option<String>,
     pub ai_provider: String,
+    pub prompt_version: Option<i64>,
+    /// Data-handling policy in force when the provider was called.
+    pub data_policy: Option<String>,
+    /// Where the document text was sent; `None` when nothing left the process.
+    pub ai_endpoint: Option<String>,
     pub created_at: String,
 }
 
@@ -24,21 +29,24 @@ pub struct CreateRiskAssessment {
     pub flags: String,
     pub summary: Option<String>,
     pub ai_provider: String,
+    pub prompt_version: Option<i64>,
+    pub data_policy: Option<String>,
+    pub ai_endpoint: Option<String>,
 }
 
 pub fn insert(conn: &Connection, ra: &CreateRiskAssessment) -> AppResult<RiskAssessment> {
     let id = uuid::Uuid::new_v4().to_string();
     conn.execute(
-        "INSERT INTO risk_assessments (id, document_id, extraction_id, overall_score, risk_level, flags, summary, ai_provider)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
-        params![id, ra.document_id, ra.extraction_id, ra.overall_score, ra.risk_level, ra.flags, ra.summary, ra.ai_provider],
+        "INSERT INTO risk_assessments (id, document_id, extraction_id, overall_score, risk_level, flags, summary, ai_provider, prompt_version, data_policy, ai_endpoint)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        params![id, ra.document_id, ra.extraction_id, ra.overall_score, ra.risk_level, ra.flags, ra.summary, ra.ai_provider, ra.prompt_version, ra.data_policy, ra.ai_endpoint],
     )?;
     get_by_id(conn, &id)
 }
 
 pub fn get_by_id(conn: &Connection, id: &str) -> AppResult<RiskAssessment> {
     conn.query_row(
-        "SELECT id, document_id, extraction_id, overall_score, risk_level, flags, summary, ai_provider, created_at
+        "SELECT id, document_id, extraction_id, overall_score, risk_level, flags, summary, ai_provider, prompt_version, data_policy, ai_endpoint, created_at
          FROM risk_assessments WHERE id = ?1",
         params![id],
         |row| {
@@ -51,7 +59,10 @@ pub fn get_by_id(conn: &Connection, id: &str) -> AppResult<RiskAssessment> {
                 flags: row.get(5)?,
                 summary: row.get(6)?,
                 ai_provider: row.get(7)?,
-                created_at: row.get(8)?,
+                prompt_version: row.get(8)?,
+                data_policy: row.get(9)?,
+                ai_endpoint: row.get(10)?,
+                created_at: row.get(11)?,
             })
         },
     )
@@ -63,7 +74,7 @@ pub fn get_by_id(conn: &Connection, id: &str) -> AppResult<RiskAssessment> {
 
 pub fn get_by_document(conn: &Connection, document_id: &str) -> AppResult<Vec<RiskAssessment>> {
     let mut stmt = conn.prepare(
-        "SELECT id, document_id, extraction_id, overall_score, risk_level, flags, summary, ai_provider, created_at
+        "SELECT id, document_id, extraction_id, overall_score, risk_level, flags, summary, ai_provider, prompt_version, data_policy, ai_endpoint, created_at
          FROM risk_assessments WHERE document_id = ?1 ORDER BY created_at DESC",
     )?;
     let results = stmt
@@ -77,7 +88,10 @@ pub fn get_by_document(conn: &Connection, document_id: &str) -> AppResult<Vec<Ri
                 flags: row.get(5)?,
                 summary: row.get(6)?,
                 ai_provider: row.get(7)?,
-                created_at: row.get(8)?,
+                prompt_version: row.get(8)?,
+                data_policy: row.get(9)?,
+                ai_endpoint: row.get(10)?,
+                created_at: row.get(11)?,
             })
         })?
         .collect::<Result<Vec<_>, _>>()?;
@@ -0,0 +1,422 @@
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::db::clauses::CURRENT;
+use crate::error::{AppError, AppResult};
+
+const DEFAULT_LIMIT: u32 = 50;
+const MAX_LIMIT: u32 = 200;
+
+/// Markers `snippet()` wraps around matched terms; split out before returning.
+const HIGHLIGHT_START: char = '\u{2}';
+const HIGHLIGHT_END: char = '\u{3}';
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchScope {
+    #[default]
+    All,
+    Documents,
+    Clauses,
+}
+
+/// A full-text query plus filters on the document it matched in. `query`
+/// uses FTS5 syntax: `"exact phrase"`, `AND` / `OR` / `a NOT b`, `prefix*`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SearchQuery {
+    pub query: String,
+    #[serde(default)]
+    pub scope: SearchScope,
+    pub contract_type: Option<String>,
+    /// Matched against the document's latest risk assessment.
+    pub risk_level: Option<String>,
+    /// Inclusive `YYYY-MM-DD` bounds on the upload date.
+    pub date_from: Option<String>,
+    pub date_to: Option<String>,
+    pub matter_id: Option<String>,
+    /// Matched case-insensitively against the document's matter.
+    pub client: Option<String>,
+    pub tag: Option<String>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SnippetPart {
+    pub text: String,
+    pub highlighted: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    /// `document` for a match in the full text, `clause` for an extracted clause.
+    pub kind: String,
+    pub document_id: String,
+    pub filename: String,
+    pub contract_type: String,
+    pub matter_id: Option<String>,
+    pub risk_level: Option<String>,
+    pub created_at: String,
+    pub clause_id: Option<i64>,
+    pub clause_type: Option<String>,
+    pub clause_title: Option<String>,
+    pub section_reference: Option<String>,
+    pub snippet: Vec<SnippetPart>,
+    /// Higher is better. Document and clause scores come from separate
+    /// indexes, so they are only roughly comparable.
+    pub score: f64,
+}
+
+const LATEST_RISK_LEVEL: &str = "(SELECT r.risk_level FROM risk_assessments r
+        WHERE r.document_id = d.id ORDER BY r.created_at DESC, r.rowid DESC LIMIT 1)";
+
+/// Filters shared by both queries, bound as ?2..?6, ?8 and ?9.
+fn filters() -> String {
+    format!(
+        "d.deleted_at IS NULL
+         AND (?2 IS NULL OR d.contract_type = ?2)
+         AND (?3 IS NULL OR {LATEST_RISK_LEVEL} = ?3)
+         AND (?4 IS NULL OR date(d.created_at) >= date(?4))
+         AND (?5 IS NULL OR date(d.created_at) <= date(?5))
+         AND (?6 IS NULL OR d.matter_id = ?6)
+         AND (?8 IS NULL OR d.matter_id IN (SELECT id FROM matters WHERE client = ?8 COLLATE NOCASE))
+         AND (?9 IS NULL OR EXISTS (SELECT 1 FROM document_tags dt JOIN tags t ON t.id = dt.tag_id
+                                    WHERE dt.document_id = d.id AND t.name = ?9))"
+    )
+}
+
+pub fn search(conn: &Connection, query: &SearchQuery) -> AppResult<Vec<SearchHit>> {
+    let text = query.query.trim();
+    if text.is_empty() {
+        return Err(AppError::Validation("Search query is required".to_string()));
+    }
+    for date in [&query.date_from, &query.date_to].into_iter().flatten() {
+        let valid: bool = conn.query_row("SELECT date(?1) IS NOT NULL", params![date], |row| row.get(0))?;
+        if !valid {
+            return Err(AppError::Validation(format!("Invalid date: {date}")));
+        }
+    }
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+    let mut hits = Vec::new();
+    if query.scope != SearchScope::Clauses {
+        hits.extend(search_documents(conn, text, query, limit).map_err(query_error)?);
+    }
+    if query.scope != SearchScope::Documents {
+        hits.extend(search_clauses(conn, text, query, limit).map_err(query_error)?);
+    }
+    hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+    hits.truncate(limit as usize);
+    Ok(hits)
+}
+
+/// FTS5 reports malformed queries as ordinary SQLite errors.
+fn query_error(e: rusqlite::Error) -> AppError {
+    match &e {
+        rusqlite::Error::SqliteFailure(_, Some(msg))
+            if msg.starts_with("fts5:") || msg.starts_with("no such column") || msg.starts_with("unknown special query") =>
+        {
+            AppError::Validation(format!(
+                "Invalid search query ({msg}). Wrap phrases and words with punctuation in double quotes."
+            ))
+        }
+        _ => AppError::Database(e),
+    }
+}
+
+fn search_documents(conn: &Connection, text: &str, query: &SearchQuery, limit: u32) -> rusqlite::Result<Vec<SearchHit>> {
+    let sql = format!(
+        "SELECT d.id, d.filename, d.contract_type, d.matter_id, {LATEST_RISK_LEVEL}, d.created_at,
+                snippet(documents_fts, -1, char(2), char(3), '…', 24),
+                bm25(documents_fts, 0.0, 2.0, 1.0) AS rank
+         FROM documents_fts JOIN documents d ON d.id = documents_fts.document_id
+         WHERE documents_fts MATCH ?1 AND {}
+         ORDER BY rank LIMIT ?7",
+        filters()
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(
+        params![
+            text,
+            query.contract_type,
+            query.risk_level,
+            query.date_from,
+            query.date_to,
+            query.matter_id,
+            limit,
+            query.client,
+            query.tag
+        ],
+        |row| {
+            Ok(SearchHit {
+                kind: "document".to_string(),
+                document_id: row.get(0)?,
+                filename: row.get(1)?,
+                contract_type: row.get(2)?,
+                matter_id: row.get(3)?,
+                risk_level: row.get(4)?,
+                created_at: row.get(5)?,
+                clause_id: None,
+                clause_type: None,
+                clause_title: None,
+                section_reference: None,
+                snippet: split_snippet(&row.get::<_, String>(6)?),
+                score: -row.get::<_, f64>(7)?,
+            })
+        },
+    )?;
+    rows.collect()
+}
+
+/// Only clauses from each document's latest extraction are searched.
+fn search_clauses(conn: &Connection, text: &str, query: &SearchQuery, limit: u32) -> rusqlite::Result<Vec<SearchHit>> {
+    let sql = format!(
+        "SELECT d.id, d.filename, d.contract_type, d.matter_id, {LATEST_RISK_LEVEL}, d.created_at,
+                c.id, c.clause_type, c.title, c.section_reference,
+                snippet(clauses_fts, -1, char(2), char(3), '…', 24),
+                bm25(clauses_fts, 2.0, 1.0) AS rank
+         FROM clauses_fts
+         JOIN clauses c ON c.id = clauses_fts.rowid
+         JOIN documents d ON d.id = c.document_id
+         WHERE clauses_fts MATCH ?1
+           AND {CURRENT}
+           AND {}
+         ORDER BY rank LIMIT ?7",
+        filters()
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(
+        params![
+            text,
+            query.contract_type,
+            query.risk_level,
+            query.date_from,
+            query.date_to,
+            query.matter_id,
+            limit,
+            query.client,
+            query.tag
+        ],
+        |row| {
+            Ok(SearchHit {
+                kind: "clause".to_string(),
+                document_id: row.get(0)?,
+                filename: row.get(1)?,
+                contract_type: row.get(2)?,
+                matter_id: row.get(3)?,
+                risk_level: row.get(4)?,
+                created_at: row.get(5)?,
+                clause_id: Some(row.get(6)?),
+                clause_type: row.get(7)?,
+                clause_title: row.get(8)?,
+                section_reference: row.get(9)?,
+                snippet: split_snippet(&row.get::<_, String>(10)?),
+                score: -row.get::<_, f64>(11)?,
+            })
+        },
+    )?;
+    rows.collect()
+}
+
+fn split_snippet(snippet: &str) -> Vec<SnippetPart> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut highlighted = false;
+    for ch in snippet.chars() {
+        if ch == HIGHLIGHT_START || ch == HIGHLIGHT_END {
+            if !current.is_empty() {
+                parts.push(SnippetPart { text: std::mem::take(&mut current), highlighted });
+            }
+            highlighted = ch == HIGHLIGHT_START;
+        } else {
+            current.push(ch);
+        }
+    }
+    if !current.is_empty() {
+        parts.push(SnippetPart { text: current, highlighted });
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::documents::{self, CreateDocument};
+    use crate::db::extractions::{self, CreateExtraction};
+    use crate::db::risk_assessments::{self, CreateRiskAssessment};
+    use crate::db::tags::{self, TagSubject};
+    use crate::db::{matters, Database};
+
+    fn insert_doc(conn: &Connection, filename: &str, contract_type: &str, text: &str) -> String {
+        let doc = documents::insert(conn, &CreateDocument {
+            filename: filename.into(),
+            original_path: format!("/tmp/{filename}"),
+            stored_path: format!("/data/{filename}"),
+            file_hash: filename.into(),
+            file_size: 10,
+            contract_type: contract_type.into(),
+        }).unwrap();
+        documents::update_text(conn, &doc.id, text, 1).unwrap();
+        doc.id
+    }
+
+    fn analyze(conn: &Connection, doc_id: &str, clauses: serde_json::Value, risk_level: &str) {
+        let data = serde_json::json!({ "parties": [], "clauses": clauses, "contract_type": "nda" });
+        let ext = extractions::insert(conn, &CreateExtraction {
+            document_id: doc_id.into(),
+            ai_provider: "mock".into(),
+            ai_model: None,
+            contract_type: "nda".into(),
+            extracted_data: data.to_string(),
+            confidence_score: None,
+            processing_time_ms: None,
+            prompt_version: None,
+            data_policy: None,
+            ai_endpoint: None,
+        }).unwrap();
+        risk_assessments::insert(conn, &CreateRiskAssessment {
+            document_id: doc_id.into(),
+            extraction_id: ext.id,
+            overall_score: 50,
+            risk_level: risk_level.into(),
+            flags: "[]".into(),
+            summary: None,
+            ai_provider: "mock".into(),
+            prompt_version: None,
+            data_policy: None,
+            ai_endpoint: None,
+        }).unwrap();
+    }
+
+    fn query(text: &str) -> SearchQuery {
+        SearchQuery { query: text.into(), ..Default::default() }
+    }
+
+    fn doc_ids(hits: &[SearchHit]) -> Vec<&str> {
+        hits.iter().map(|h| h.document_id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_phrase_and_boolean_queries() {
+        let db = Database::in_memory().unwrap();
+        let conn = db.writer();
+        let nda = insert_doc(&conn, "nda.pdf", "nda", "The receiving party shall keep confidential information secret.");
+        let lease = insert_doc(&conn, "lease.pdf", "lease", "The tenant shall keep the premises in good repair.");
+
+        let hits = search(&conn, &query("\"confidential information\"")).unwrap();
+        assert_eq!(doc_ids(&hits), vec![nda.as_str()]);
+        assert!(hits[0].snippet.iter().any(|p| p.highlighted && p.text == "confidential information"));
+
+        let hits = search(&conn, &query("keep NOT tenant")).unwrap();
+        assert_eq!(doc_ids(&hits), vec![nda.as_str()]);
+
+        let hits = search(&conn, &query("premises OR secret")).unwrap();
+        assert_eq!(hits.len(), 2);
+        assert!(doc_ids(&hits).contains(&lease.as_str()));
+
+        // Porter stemming
+        assert_eq!(search(&conn, &query("repairs")).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_clause_hits_come_from_latest_extraction() {
+        let db = Database::in_memory().unwrap();
+        let conn = db.writer();
+        let doc = insert_doc(&conn, "msa.pdf", "msa", "Master services agreement.");
+        analyze(&conn, &doc, serde_json::json!([
+            { "clause_type": "indemnification", "title": "Indemnity", "text": "Supplier shall indemnify Customer.", "section_reference": "9.1", "importance": "high" },
+        ]), "high");
+
+        let scope = SearchQuery { scope: SearchScope::Clauses, ..query("indemnify") };
+        let hits = search(&conn, &scope).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].kind, "clause");
+        assert_eq!(hits[0].clause_type.as_deref(), Some("indemnification"));
+        assert_eq!(hits[0].section_reference.as_deref(), Some("9.1"));
+        assert_eq!(hits[0].risk_level.as_deref(), Some("high"));
+
+        conn.execute("UPDATE extractions SET created_at = datetime('now', '-1 day')", []).unwrap();
+        analyze(&conn, &doc, serde_json::json!([
+            { "clause_type": "limitation_of_liability", "title": "Liability Cap", "text": "Liability is capped at fees paid.", "importance": "high" },
+        ]), "low");
+        assert!(search(&conn, &scope).unwrap().is_empty());
+        assert_eq!(search(&conn, &SearchQuery { scope: SearchScope::Clauses, ..query("capped") }).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_filters() {
+        let db = Database::in_memory().unwrap();
+        let conn = db.writer();
+        let nda = insert_doc(&conn, "nda.pdf", "nda", "Governing law is Delaware.");
+        let lease = insert_doc(&conn, "lease.pdf", "lease", "Governing law is New York.");
+        analyze(&conn, &nda, serde_json::json!([]), "low");
+        analyze(&conn, &lease, serde_json::json!([]), "high");
+        let matter = matters::insert(&conn, &matters::MatterInput {
+            client: Some("Acme Corp".into()),
+            ..matters::MatterInput::named("Acme v. Globex")
+        })
+        .unwrap();
+        documents::set_matter(&conn, &lease, Some(&matter.id)).unwrap();
+        tags::set_tags(&conn, TagSubject::Document(&nda), &["Urgent".into()]).unwrap();
+        conn.execute("UPDATE documents SET created_at = '2024-01-15 10:00:00' WHERE id = ?1", params![nda]).unwrap();
+
+        let filtered = |q: SearchQuery| doc_ids(&search(&conn, &q).unwrap()).into_iter().map(String::from).collect::<Vec<_>>();
+
+        assert_eq!(filtered(SearchQuery { contract_type: Some("nda".into()), ..query("governing") }), vec![nda.clone()]);
+        assert_eq!(filtered(SearchQuery { risk_level: Some("high".into()), ..query("governing") }), vec![lease.clone()]);
+        assert_eq!(filtered(SearchQuery { matter_id: Some(matter.id.clone()), ..query("governing") }), vec![lease.clone()]);
+        assert_eq!(
+            filtered(SearchQuery { date_from: Some("2024-01-01".into()), date_to: Some("2024-01-15".into()), ..query("governing") }),
+            vec![nda.clone()]
+        );
+        assert_eq!(filtered(SearchQuery { client: Some("acme corp".into()), ..query("governing") }), vec![lease.clone()]);
+        assert_eq!(filtered(SearchQuery { tag: Some("urgent".into()), ..query("governing") }), vec![nda.clone()]);
+
+        documents::soft_delete(&conn, &nda).unwrap();
+        assert_eq!(filtered(query("governing")), vec![lease]);
+    }
+
+    #[test]
+    fn test_index_follows_text_changes() {
+        let db = Database::in_memory().unwrap();
+        let conn = db.writer();
+        let doc = insert_doc(&conn, "nda.pdf", "nda", "Non-solicitation of employees.");
+        assert_eq!(search(&conn, &query("employees")).unwrap().len(), 1);
+
+        documents::clear_raw_text(&conn, &doc).unwrap();
+        assert!(search(&conn, &query("employees")).unwrap().is_empty());
+        // The filename stays searchable
+        assert_eq!(search(&conn, &query("nda")).unwrap().len(), 1);
+
+        analyze(&conn, &doc, serde_json::json!([
+            { "clause_type": "other", "title": "Employees", "text": "No poaching.", "importance": "low" },
+        ]), "low");
+        documents::delete(&conn, &doc).unwrap();
+        assert!(search(&conn, &query("nda OR poaching")).unwrap().is_empty());
+        let leftover: i64 = conn.query_row("SELECT COUNT(*) FROM clauses_fts WHERE clauses_fts MATCH 'poaching'", [], |r| r.get(0)).unwrap();
+        assert_eq!(leftover, 0);
+    }
+
+    #[test]
+    fn test_invalid_queries() {
+        let db = Database::in_memory().unwrap();
+        let conn = db.writer();
+        insert_doc(&conn, "nda.pdf", "nda", "Text.");
+        assert!(matches!(search(&conn, &query("  ")), Err(AppError::Validation(_))));
+        assert!(matches!(search(&conn, &query("keep AND (secret")), Err(AppError::Validation(_))));
+        assert!(matches!(search(&conn, &query("AND OR")), Err(AppError::Validation(_))));
+        assert!(matches!(
+            search(&conn, &SearchQuery { date_from: Some("last week".into()), ..query("text") }),
+            Err(AppError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_split_snippet() {
+        let parts = split_snippet("a \u{2}b\u{3} c");
+        assert_eq!(parts, vec![
+            SnippetPart { text: "a ".into(), highlighted: false },
+            SnippetPart { text: "b".into(), highlighted: true },
+            SnippetPart { text: " c".into(), highlighted: false },
+        ]);
+    }
+}
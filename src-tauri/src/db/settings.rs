@@ -23,6 +23,11 @@ pub fn set(conn: &Connection, key: &str, value: &str) -> AppResult<()> {
     Ok(())
 }
 
+pub fn delete(conn: &Connection, key: &str) -> AppResult<()> {
+    conn.execute("DELETE FROM settings WHERE key = ?1", params![key])?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -31,7 +36,7 @@ mod tests {
     #[test]
     fn test_get_set() {
         let db = Database::in_memory().unwrap();
-        let conn = db.conn.lock().unwrap();
+        let conn = db.writer();
 
         assert_eq!(get(&conn, "ai_provider").unwrap(), None);
         set(&conn, "ai_provider", "ollama").unwrap();
@@ -0,0 +1,215 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult};
+
+const MAX_TAG_CHARS: usize = 50;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tag {
+    pub id: String,
+    pub name: String,
+    /// Documents, comparisons and reports carrying the tag.
+    pub usage: i64,
+    pub created_at: String,
+}
+
+/// What a set of tags is attached to.
+#[derive(Debug, Clone, Copy)]
+pub enum TagSubject<'a> {
+    Document(&'a str),
+    Comparison(&'a str),
+    Report(&'a str),
+}
+
+impl TagSubject<'_> {
+    /// Link table, its key column, and the tagged table.
+    fn tables(&self) -> (&'static str, &'static str, &'static str) {
+        match self {
+            Self::Document(_) => ("document_tags", "document_id", "documents"),
+            Self::Comparison(_) => ("comparison_tags", "comparison_id", "comparisons"),
+            Self::Report(_) => ("report_tags", "report_id", "reports"),
+        }
+    }
+
+    fn id(&self) -> &str {
+        match self {
+            Self::Document(id) | Self::Comparison(id) | Self::Report(id) => id,
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Document(_) => "Document",
+            Self::Comparison(_) => "Comparison",
+            Self::Report(_) => "Report",
+        }
+    }
+}
+
+/// Parses the sorted JSON array of tag names that document, comparison and
+/// report queries select with `json_group_array`.
+pub(crate) fn parse_names(idx: usize, json: &str) -> rusqlite::Result<Vec<String>> {
+    serde_json::from_str(json)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(idx, rusqlite::types::Type::Text, Box::new(e)))
+}
+
+fn normalize(name: &str) -> AppResult<String> {
+    let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+    if name.is_empty() {
+        return Err(AppError::Validation("Tag names cannot be empty".to_string()));
+    }
+    if name.chars().count() > MAX_TAG_CHARS {
+        return Err(AppError::Validation(format!("Tag names are limited to {MAX_TAG_CHARS} characters")));
+    }
+    Ok(name)
+}
+
+/// The tag's id, creating it on first use. Names match case-insensitively
+/// and keep the capitalization they were created with.
+fn get_or_create(conn: &Connection, name: &str) -> AppResult<String> {
+    if let Some(id) = conn
+        .query_row("SELECT id FROM tags WHERE name = ?1", params![name], |row| row.get(0))
+        .optional()?
+    {
+        return Ok(id);
+    }
+    let id = uuid::Uuid::new_v4().to_string();
+    conn.execute("INSERT INTO tags (id, name) VALUES (?1, ?2)", params![id, name])?;
+    Ok(id)
+}
+
+/// Replaces the subject's tags and returns them sorted.
+pub fn set_tags(conn: &Connection, subject: TagSubject<'_>, names: &[String]) -> AppResult<Vec<String>> {
+    let (link_table, key_column, table) = subject.tables();
+    let exists: bool = conn.query_row(
+        &format!("SELECT EXISTS (SELECT 1 FROM {table} WHERE id = ?1)"),
+        params![subject.id()],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        return Err(AppError::NotFound(format!("{} {} not found", subject.label(), subject.id())));
+    }
+    let names = names.iter().map(|name| normalize(name)).collect::<AppResult<Vec<_>>>()?;
+
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(&format!("DELETE FROM {link_table} WHERE {key_column} = ?1"), params![subject.id()])?;
+    for name in &names {
+        let tag_id = get_or_create(&tx, name)?;
+        tx.execute(
+            &format!("INSERT OR IGNORE INTO {link_table} ({key_column}, tag_id) VALUES (?1, ?2)"),
+            params![subject.id(), tag_id],
+        )?;
+    }
+    tx.commit()?;
+    tags_of(conn, subject)
+}
+
+pub fn tags_of(conn: &Connection, subject: TagSubject<'_>) -> AppResult<Vec<String>> {
+    let (link_table, key_column, _) = subject.tables();
+    let mut stmt = conn.prepare(&format!(
+        "SELECT t.name FROM {link_table} l JOIN tags t ON t.id = l.tag_id
+         WHERE l.{key_column} = ?1 ORDER BY t.name COLLATE NOCASE"
+    ))?;
+    let names = stmt.query_map(params![subject.id()], |row| row.get(0))?.collect::<Result<Vec<_>, _>>()?;
+    Ok(names)
+}
+
+/// Every tag with how often it is used, alphabetically.
+pub fn list_all(conn: &Connection) -> AppResult<Vec<Tag>> {
+    let mut stmt = conn.prepare(
+        "SELECT t.id, t.name,
+                (SELECT COUNT(*) FROM document_tags WHERE tag_id = t.id)
+                + (SELECT COUNT(*) FROM comparison_tags WHERE tag_id = t.id)
+                + (SELECT COUNT(*) FROM report_tags WHERE tag_id = t.id),
+                t.created_at
+         FROM tags t ORDER BY t.name COLLATE NOCASE",
+    )?;
+    let tags = stmt
+        .query_map([], |row| {
+            Ok(Tag { id: row.get(0)?, name: row.get(1)?, usage: row.get(2)?, created_at: row.get(3)? })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(tags)
+}
+
+pub fn rename(conn: &Connection, id: &str, name: &str) -> AppResult<()> {
+    let name = normalize(name)?;
+    let taken: Option<String> = conn
+        .query_row("SELECT id FROM tags WHERE name = ?1 AND id != ?2", params![name, id], |row| row.get(0))
+        .optional()?;
+    if taken.is_some() {
+        return Err(AppError::Validation(format!("A tag named {name} already exists")));
+    }
+    let rows = conn.execute("UPDATE tags SET name = ?1 WHERE id = ?2", params![name, id])?;
+    if rows == 0 {
+        return Err(AppError::NotFound(format!("Tag {id} not found")));
+    }
+    Ok(())
+}
+
+/// Removes the tag from everything it is on.
+pub fn delete(conn: &Connection, id: &str) -> AppResult<()> {
+    let rows = conn.execute("DELETE FROM tags WHERE id = ?1", params![id])?;
+    if rows == 0 {
+        return Err(AppError::NotFound(format!("Tag {id} not found")));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::documents::{self, CreateDocument};
+    use crate::db::Database;
+
+    fn insert_doc(conn: &Connection) -> String {
+        documents::insert(conn, &CreateDocument {
+            filename: "msa.pdf".into(),
+            original_path: "/tmp/msa.pdf".into(),
+            stored_path: "/data/msa.pdf".into(),
+            file_hash: "hash".into(),
+            file_size: 10,
+            contract_type: "service_agreement".into(),
+        }).unwrap().id
+    }
+
+    #[test]
+    fn test_set_tags_reuses_names_case_insensitively() {
+        let db = Database::in_memory().unwrap();
+        let conn = db.writer();
+        let doc = insert_doc(&conn);
+
+        let names = set_tags(&conn, TagSubject::Document(&doc), &["  Vendor ".into(), "urgent".into(), "URGENT".into()]).unwrap();
+        assert_eq!(names, vec!["urgent", "Vendor"]);
+        assert_eq!(documents::get_by_id(&conn, &doc).unwrap().tags, names);
+
+        set_tags(&conn, TagSubject::Document(&doc), &["Urgent".into()]).unwrap();
+        let all = list_all(&conn).unwrap();
+        assert_eq!(all.iter().map(|t| (t.name.as_str(), t.usage)).collect::<Vec<_>>(), vec![("urgent", 1), ("Vendor", 0)]);
+
+        assert!(matches!(set_tags(&conn, TagSubject::Document(&doc), &[" ".into()]), Err(AppError::Validation(_))));
+        assert!(matches!(set_tags(&conn, TagSubject::Report("missing"), &[]), Err(AppError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_rename_and_delete() {
+        let db = Database::in_memory().unwrap();
+        let conn = db.writer();
+        let doc = insert_doc(&conn);
+        set_tags(&conn, TagSubject::Document(&doc), &["draft".into(), "final".into()]).unwrap();
+        let all = list_all(&conn).unwrap();
+
+        assert!(matches!(rename(&conn, &all[0].id, "FINAL"), Err(AppError::Validation(_))));
+        rename(&conn, &all[0].id, "Draft v2").unwrap();
+        assert_eq!(tags_of(&conn, TagSubject::Document(&doc)).unwrap(), vec!["Draft v2", "final"]);
+
+        delete(&conn, &all[1].id).unwrap();
+        assert_eq!(tags_of(&conn, TagSubject::Document(&doc)).unwrap(), vec!["Draft v2"]);
+        assert!(matches!(delete(&conn, &all[1].id), Err(AppError::NotFound(_))));
+
+        // Links go with the document
+        documents::delete(&conn, &doc).unwrap();
+        assert_eq!(list_all(&conn).unwrap()[0].usage, 0);
+    }
+}
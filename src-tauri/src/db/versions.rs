@@ -0,0 +1,177 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+
+use crate::db::comparisons::{self, Comparison};
+use crate::db::documents::{self, Document, DocumentSummary, SUMMARY_COLUMNS};
+use crate::error::{AppError, AppResult};
+
+/// One entry in a document's version history.
+#[derive(Debug, Serialize)]
+pub struct DocumentVersion {
+    #[serde(flatten)]
+    pub document: DocumentSummary,
+    /// The latest comparison against the version before it, if one was run.
+    pub comparison: Option<Comparison>,
+}
+
+fn normalize_label(label: Option<&str>) -> Option<String> {
+    label.map(str::trim).filter(|l| !l.is_empty()).map(str::to_string)
+}
+
+/// Makes `document_id` the newest version in `version_of`'s family. The
+/// document must not already have a history of its own, and it joins the
+/// family's matter if it has none.
+pub fn add_version(conn: &Connection, version_of: &str, document_id: &str, label: Option<&str>) -> AppResult<Document> {
+    let base = documents::get_by_id(conn, version_of)?;
+    let doc = documents::get_by_id(conn, document_id)?;
+    if doc.family_id == base.family_id {
+        return Err(AppError::Validation(format!("{} is already a version of {}", doc.filename, base.filename)));
+    }
+    let has_history: bool = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM documents WHERE family_id = ?1 AND id != ?2)",
+        params![doc.family_id, doc.id],
+        |row| row.get(0),
+    )?;
+    if has_history || doc.family_id != doc.id {
+        return Err(AppError::Validation(format!("{} already belongs to another version history", doc.filename)));
+    }
+
+    // Trashed versions keep their numbers, so count them too
+    conn.execute(
+        "UPDATE documents
+         SET family_id = ?1,
+             version_number = (SELECT MAX(version_number) + 1 FROM documents WHERE family_id = ?1),
+             version_label = ?2,
+             matter_id = COALESCE(matter_id, ?3),
+             updated_at = datetime('now')
+         WHERE id = ?4",
+        params![base.family_id, normalize_label(label), base.matter_id, doc.id],
+    )?;
+    documents::get_by_id(conn, document_id)
+}
+
+pub fn set_label(conn: &Connection, id: &str, label: Option<&str>) -> AppResult<()> {
+    let rows = conn.execute(
+        "UPDATE documents SET version_label = ?1, updated_at = datetime('now') WHERE id = ?2",
+        params![normalize_label(label), id],
+    )?;
+    if rows == 0 {
+        return Err(AppError::NotFound(format!("Document {id} not found")));
+    }
+    Ok(())
+}
+
+/// The closest earlier version that is not in the trash.
+pub fn previous(conn: &Connection, id: &str) -> AppResult<Option<String>> {
+    let doc = documents::get_by_id(conn, id)?;
+    let previous = conn
+        .query_row(
+            "SELECT id FROM documents
+             WHERE family_id = ?1 AND version_number < ?2 AND deleted_at IS NULL
+             ORDER BY version_number DESC LIMIT 1",
+            params![doc.family_id, doc.version_number],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(previous)
+}
+
+/// Every version in the document's family that is not in the trash, oldest
+/// first, each with its comparison against the one before.
+pub fn history(conn: &Connection, id: &str) -> AppResult<Vec<DocumentVersion>> {
+    let doc = documents::get_by_id(conn, id)?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {SUMMARY_COLUMNS} FROM documents
+         WHERE family_id = ?1 AND deleted_at IS NULL ORDER BY version_number"
+    ))?;
+    let summaries = stmt
+        .query_map(params![doc.family_id], documents::map_summary)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut versions: Vec<DocumentVersion> = Vec::with_capacity(summaries.len());
+    for summary in summaries {
+        let comparison = match versions.last() {
+            Some(previous) => comparisons::latest_between(conn, &previous.document.id, &summary.id)?,
+            None => None,
+        };
+        versions.push(DocumentVersion { document: summary, comparison });
+    }
+    Ok(versions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::documents::CreateDocument;
+    use crate::db::matters::{self, MatterInput};
+    use crate::db::Database;
+
+    fn insert_doc(conn: &Connection, filename: &str) -> String {
+        documents::insert(conn, &CreateDocument {
+            filename: filename.into(),
+            original_path: format!("/tmp/{filename}"),
+            stored_path: format!("/data/{filename}"),
+            file_hash: filename.into(),
+            file_size: 10,
+            contract_type: "nda".into(),
+        }).unwrap().id
+    }
+
+    #[test]
+    fn test_versions_form_a_numbered_family() {
+        let db = Database::in_memory().unwrap();
+        let conn = db.writer();
+        let v1 = insert_doc(&conn, "v1.pdf");
+        let v2 = insert_doc(&conn, "v2.pdf");
+        let v3 = insert_doc(&conn, "v3.pdf");
+        let matter = matters::insert(&conn, &MatterInput::named("Acme NDA")).unwrap();
+        documents::set_matter(&conn, &v1, Some(&matter.id)).unwrap();
+
+        let added = add_version(&conn, &v1, &v2, Some(" Our markup ")).unwrap();
+        assert_eq!((added.family_id.as_str(), added.version_number), (v1.as_str(), 2));
+        assert_eq!(added.version_label.as_deref(), Some("Our markup"));
+        assert_eq!(added.matter_id.as_deref(), Some(matter.id.as_str()));
+        // Any member names the family
+        assert_eq!(add_version(&conn, &v2, &v3, Some("Executed")).unwrap().version_number, 3);
+
+        assert!(matches!(add_version(&conn, &v1, &v3, None), Err(AppError::Validation(_))));
+        let other = insert_doc(&conn, "other.pdf");
+        assert!(matches!(add_version(&conn, &other, &v2, None), Err(AppError::Validation(_))));
+
+        let history = history(&conn, &v3).unwrap();
+        assert_eq!(history.iter().map(|v| v.document.version_number).collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(previous(&conn, &v3).unwrap().as_deref(), Some(v2.as_str()));
+
+        // A trashed version drops out, and the next one is compared past it
+        documents::soft_delete(&conn, &v2).unwrap();
+        assert_eq!(previous(&conn, &v3).unwrap().as_deref(), Some(v1.as_str()));
+        assert_eq!(previous(&conn, &v1).unwrap(), None);
+        let v4 = insert_doc(&conn, "v4.pdf");
+        assert_eq!(add_version(&conn, &v1, &v4, None).unwrap().version_number, 4);
+    }
+
+    #[test]
+    fn test_history_attaches_comparisons_with_previous_version() {
+        let db = Database::in_memory().unwrap();
+        let conn = db.writer();
+        let v1 = insert_doc(&conn, "v1.pdf");
+        let v2 = insert_doc(&conn, "v2.pdf");
+        add_version(&conn, &v1, &v2, None).unwrap();
+        let comparison = comparisons::insert(&conn, &comparisons::CreateComparison {
+            document_a_id: &v1,
+            document_b_id: Some(&v2),
+            template_id: None,
+            comparison_type: "document_vs_document",
+            differences: "[]",
+            redline: None,
+            summary: Some("No changes"),
+            ai_provider: Some("mock"),
+            data_policy: None,
+            ai_endpoint: None,
+        }).unwrap();
+
+        let history = history(&conn, &v1).unwrap();
+        assert!(history[0].comparison.is_none());
+        assert_eq!(history[1].comparison.as_ref().map(|c| c.id.as_str()), Some(comparison.id.as_str()));
+    }
+}
@@ -1,17 +1,8 @@
 pub(crate) mod pdf;
+pub(crate) mod upload;
 
 use sha2::{Digest, Sha256};
-use std::path::Path;
 
-use crate::error::AppResult;
-
-pub fn compute_file_hash(path: &Path) -> AppResult<String> {
-    let bytes = std::fs::read(path)?;
-    let hash = Sha256::digest(&bytes);
-    Ok(format!("{:x}", hash))
-}
-
-pub fn get_file_size(path: &Path) -> AppResult<i64> {
-    let metadata = std::fs::metadata(path)?;
-    Ok(metadata.len() as i64)
+pub fn hash_bytes(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
 }
@@ -1,5 +1,3 @@
-use std::path::Path;
-
 use crate::error::{AppError, AppResult};
 
 pub struct PdfExtractionResult {
@@ -7,13 +5,19 @@ pub struct PdfExtractionResult {
     pub page_count: i32,
 }
 
-pub fn extract_text(path: &Path) -> AppResult<PdfExtractionResult> {
+#[cfg(test)]
+pub fn extract_text(path: &std::path::Path) -> AppResult<PdfExtractionResult> {
     let bytes = std::fs::read(path)?;
+    extract_text_from_bytes(&bytes, &path.display().to_string())
+}
 
-    let text = pdf_extract::extract_text_from_mem(&bytes)
-        .map_err(|e| AppError::PdfExtraction(format!("Failed to extract text from {}: {}", path.display(), e)))?;
+/// Extracts text from PDF bytes already in memory, e.g. a decrypted stored
+/// file. `name` is only used in error messages.
+pub fn extract_text_from_bytes(bytes: &[u8], name: &str) -> AppResult<PdfExtractionResult> {
+    let text = pdf_extract::extract_text_from_mem(bytes)
+        .map_err(|e| AppError::PdfExtraction(format!("Failed to extract text from {}: {}", name, e)))?;
 
-    let page_count = count_pages(&bytes).unwrap_or(1);
+    let page_count = count_pages(bytes).unwrap_or(1);
 
     if text.trim().is_empty() {
         return Err(AppError::PdfExtraction(
@@ -51,6 +55,7 @@ fn clean_text(text: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::path::Path;
 
     #[test]
     fn test_clean_text() {
@@ -0,0 +1,185 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::error::{AppError, AppResult};
+
+/// How far into a file the `%PDF-` marker may appear; readers accept a
+/// little leading junk before the header.
+const PDF_HEADER_WINDOW: usize = 1024;
+const MAX_STORED_NAME_LEN: usize = 100;
+
+/// Files the user picked in the native dialog during this session. A grant
+/// is used up by the upload it allows.
+#[derive(Default)]
+pub struct UploadGrants(Mutex<HashSet<PathBuf>>);
+
+impl UploadGrants {
+    pub fn grant(&self, path: &Path) -> AppResult<PathBuf> {
+        let canonical = path.canonicalize()?;
+        self.0.lock().expect("upload grants lock poisoned").insert(canonical.clone());
+        Ok(canonical)
+    }
+
+    fn take(&self, canonical: &Path) -> bool {
+        self.0.lock().expect("upload grants lock poisoned").remove(canonical)
+    }
+}
+
+/// A source file that passed every upload check, read into memory so the
+/// bytes stored are exactly the bytes checked.
+pub struct ValidatedUpload {
+    pub path: PathBuf,
+    pub filename: String,
+    pub bytes: Vec<u8>,
+}
+
+/// Checks a path sent by the webview before anything is copied: it must
+/// have been granted through the file dialog, be a regular file no larger
+/// than `max_bytes`, and actually be a PDF.
+pub fn validate_source(file_path: &str, grants: &UploadGrants, max_bytes: u64) -> AppResult<ValidatedUpload> {
+    // Resolves `..` and symlinks so neither can be used to dodge the grant
+    let path = Path::new(file_path)
+        .canonicalize()
+        .map_err(|_| AppError::Validation(format!("File not found: {file_path}")))?;
+
+    if !grants.take(&path) {
+        return Err(AppError::Validation("Choose the file with the file picker".to_string()));
+    }
+
+    let metadata = std::fs::metadata(&path)?;
+    if !metadata.is_file() {
+        return Err(AppError::Validation(format!("{} is not a file", path.display())));
+    }
+    check_size(metadata.len(), max_bytes)?;
+
+    let bytes = std::fs::read(&path)?;
+    // The file may have changed since the metadata was read
+    check_size(bytes.len() as u64, max_bytes)?;
+    if !is_pdf(&bytes) {
+        return Err(AppError::Validation("Only PDF files can be uploaded".to_string()));
+    }
+
+    let filename = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    Ok(ValidatedUpload { path, filename, bytes })
+}
+
+fn check_size(len: u64, max_bytes: u64) -> AppResult<()> {
+    if len == 0 {
+        return Err(AppError::Validation("The file is empty".to_string()));
+    }
+    if len > max_bytes {
+        return Err(AppError::Validation(format!(
+            "The file is {} MB; the upload limit is {} MB",
+            len.div_ceil(1024 * 1024),
+            max_bytes / (1024 * 1024)
+        )));
+    }
+    Ok(())
+}
+
+pub fn is_pdf(bytes: &[u8]) -> bool {
+    let window = &bytes[..bytes.len().min(PDF_HEADER_WINDOW)];
+    window.windows(5).any(|w| w == b"%PDF-")
+}
+
+/// A filename safe to store on any platform: no directory parts, no
+/// reserved or control characters, bounded length, always ending in `.pdf`.
+pub fn sanitize_filename(name: &str) -> String {
+    let base = name.rsplit(['/', '\\']).next().unwrap_or_default();
+    let cleaned: String = base
+        .chars()
+        .map(|c| if c.is_control() || "<>:\"|?*".contains(c) { '_' } else { c })
+        .collect();
+    let cleaned = cleaned.trim_matches(|c: char| c == '.' || c.is_whitespace());
+
+    let stem = cleaned
+        .strip_suffix(".pdf")
+        .or_else(|| cleaned.strip_suffix(".PDF"))
+        .unwrap_or(cleaned)
+        .trim_end_matches('.');
+    let mut stem: String = stem.chars().take(MAX_STORED_NAME_LEN - 4).collect();
+    if stem.is_empty() {
+        stem = "document".to_string();
+    }
+    // Windows device names cannot be used as filenames, with or without extension
+    const RESERVED: [&str; 22] = [
+        "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9",
+        "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+    ];
+    if RESERVED.iter().any(|r| r.eq_ignore_ascii_case(stem.split('.').next().unwrap_or_default())) {
+        stem.insert(0, '_');
+    }
+    format!("{stem}.pdf")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ldr-upload-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_sanitize_filename() {
+        assert_eq!(sanitize_filename("contract.pdf"), "contract.pdf");
+        assert_eq!(sanitize_filename("../../etc/passwd"), "passwd.pdf");
+        assert_eq!(sanitize_filename("C:\\Users\\me\\NDA final.PDF"), "NDA final.pdf");
+        assert_eq!(sanitize_filename("a<b>:c?.pdf"), "a_b__c_.pdf");
+        assert_eq!(sanitize_filename("..."), "document.pdf");
+        assert_eq!(sanitize_filename("con.pdf"), "_con.pdf");
+        assert_eq!(sanitize_filename(&"x".repeat(300)).len(), MAX_STORED_NAME_LEN);
+    }
+
+    #[test]
+    fn test_validate_requires_grant() {
+        let dir = scratch_dir();
+        let pdf = dir.join("nda.pdf");
+        std::fs::write(&pdf, b"%PDF-1.7 body").unwrap();
+        let grants = UploadGrants::default();
+        let path = pdf.to_string_lossy().to_string();
+
+        assert!(validate_source(&path, &grants, 1024).is_err());
+
+        grants.grant(&pdf).unwrap();
+        let upload = validate_source(&path, &grants, 1024).unwrap();
+        assert_eq!(upload.filename, "nda.pdf");
+        assert_eq!(upload.bytes, b"%PDF-1.7 body");
+        // A grant is single-use
+        assert!(validate_source(&path, &grants, 1024).is_err());
+
+        // `..` resolves to the granted file, so it uses up the same grant
+        std::fs::create_dir_all(dir.join("inner")).unwrap();
+        grants.grant(&pdf).unwrap();
+        let dotted = dir.join("inner").join("..").join("nda.pdf");
+        assert!(validate_source(&dotted.to_string_lossy(), &grants, 1024).is_ok());
+        assert!(validate_source(&path, &grants, 1024).is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_validate_checks_size_and_type() {
+        let dir = scratch_dir();
+        let grants = UploadGrants::default();
+        let fake = dir.join("fake.pdf");
+        std::fs::write(&fake, b"MZ\x90\x00 not a pdf").unwrap();
+        grants.grant(&fake).unwrap();
+        let err = validate_source(&fake.to_string_lossy(), &grants, 1024).err().unwrap();
+        assert!(err.to_string().contains("Only PDF"));
+
+        let big = dir.join("big.pdf");
+        std::fs::write(&big, [b"%PDF-1.4\n".as_slice(), &[b'x'; 2048]].concat()).unwrap();
+        grants.grant(&big).unwrap();
+        assert!(validate_source(&big.to_string_lossy(), &grants, 1024).is_err());
+
+        grants.grant(&dir).unwrap();
+        assert!(validate_source(&dir.to_string_lossy(), &grants, 1024).is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,477 @@
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::{self, Key, SealedValue};
+use crate::db::{documents, migrations, Database};
+use crate::error::{AppError, AppResult};
+use crate::secrets::vault::MIN_PASSPHRASE_LEN;
+
+/// Present in the app data directory once a workspace is encrypted.
+pub const HEADER_FILE: &str = "encryption.json";
+const HEADER_VERSION: u32 = 1;
+const CHECK_LABEL: &str = "__workspace_check__";
+
+/// Prefix of every encrypted stored file, followed by the key id, then
+/// nonce and ciphertext.
+const FILE_MAGIC: &[u8; 8] = b"LDRENC01";
+const KEY_ID_LEN: usize = 36;
+const ENCRYPTED_EXTENSION: &str = "enc";
+
+/// Folders of the app data directory that exports of client data are
+/// written to: reports and audit logs.
+const EXPORT_DIRS: [&str; 2] = ["reports", "exports"];
+
+#[derive(Serialize, Deserialize)]
+struct WrappedKey {
+    id: String,
+    key: SealedValue,
+}
+
+/// The data keys, wrapped with a key derived from the passphrase. The first
+/// key is current; any others are retired keys kept until every file and
+/// the database have been re-encrypted after a rotation.
+#[derive(Serialize, Deserialize)]
+struct Header {
+    version: u32,
+    salt: String,
+    check: SealedValue,
+    keys: Vec<WrappedKey>,
+    /// Unix seconds of the last enable or rotation.
+    rotated_at: u64,
+}
+
+struct DataKey {
+    id: String,
+    key: Key,
+}
+
+struct Unlocked {
+    salt: String,
+    kek: Key,
+    keys: Vec<DataKey>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EncryptionStatus {
+    pub enabled: bool,
+    pub unlocked: bool,
+    pub rotated_at: Option<u64>,
+}
+
+/// At-rest encryption for the workspace: the database through SQLCipher and
+/// stored documents with AES-256-GCM, all under one data key that is
+/// unlocked with a passphrase at startup.
+pub struct Encryption {
+    app_data: PathBuf,
+    state: RwLock<Option<Unlocked>>,
+}
+
+fn validate_passphrase(passphrase: &str) -> AppResult<()> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+        return Err(AppError::Validation(format!(
+            "Passphrase must be at least {MIN_PASSPHRASE_LEN} characters"
+        )));
+    }
+    Ok(())
+}
+
+fn wrong_passphrase(_: AppError) -> AppError {
+    AppError::Validation("Wrong passphrase for this workspace".to_string())
+}
+
+impl Encryption {
+    pub fn new(app_data: &Path) -> Self {
+        Self {
+            app_data: app_data.to_path_buf(),
+            state: RwLock::new(None),
+        }
+    }
+
+    fn header_path(&self) -> PathBuf {
+        self.app_data.join(HEADER_FILE)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.header_path().exists()
+    }
+
+    pub fn is_unlocked(&self) -> bool {
+        self.state.read().expect("encryption lock poisoned").is_some()
+    }
+
+    pub fn status(&self) -> EncryptionStatus {
+        let rotated_at = self.load_header().ok().flatten().map(|h| h.rotated_at);
+        EncryptionStatus {
+            enabled: self.is_enabled(),
+            unlocked: self.is_unlocked(),
+            rotated_at,
+        }
+    }
+
+    fn load_header(&self) -> AppResult<Option<Header>> {
+        let path = self.header_path();
+        if !path.exists() {
+            return Ok(None);
+        }
+        let header: Header = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        if header.version != HEADER_VERSION {
+            return Err(AppError::Validation(format!(
+                "Unsupported encryption header version {}",
+                header.version
+            )));
+        }
+        Ok(Some(header))
+    }
+
+    fn save_header(&self, unlocked: &Unlocked) -> AppResult<()> {
+        let keys = unlocked
+            .keys
+            .iter()
+            .map(|k| Ok(WrappedKey { id: k.id.clone(), key: crypto::seal(&unlocked.kek, &k.id, k.key.as_ref())? }))
+            .collect::<AppResult<Vec<_>>>()?;
+        let header = Header {
+            version: HEADER_VERSION,
+            salt: unlocked.salt.clone(),
+            check: crypto::seal(&unlocked.kek, CHECK_LABEL, CHECK_LABEL.as_bytes())?,
+            keys,
+            rotated_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+        };
+        // Write-then-rename so a crash never leaves the keys unreadable
+        let path = self.header_path();
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(&header)?)?;
+        std::fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    fn unwrap_keys(&self, passphrase: &str) -> AppResult<Unlocked> {
+        let header = self
+            .load_header()?
+            .ok_or_else(|| AppError::Validation("Workspace encryption is not enabled".to_string()))?;
+        let kek = crypto::derive_key(passphrase, &header.salt)?;
+        crypto::open(&kek, CHECK_LABEL, &header.check).map_err(wrong_passphrase)?;
+        let keys = header
+            .keys
+            .iter()
+            .map(|wrapped| {
+                let raw = crypto::open(&kek, &wrapped.id, &wrapped.key)?;
+                let mut key = Key::default();
+                if raw.len() != key.len() {
+                    return Err(AppError::Validation("Encryption header is corrupt".to_string()));
+                }
+                key.copy_from_slice(&raw);
+                Ok(DataKey { id: wrapped.id.clone(), key })
+            })
+            .collect::<AppResult<Vec<_>>>()?;
+        Ok(Unlocked { salt: header.salt, kek, keys })
+    }
+
+    /// Unlocks the workspace and opens its database, finishing any
+    /// interrupted migration or key rotation first.
+    pub fn unlock(&self, passphrase: &str, db_path: &Path) -> AppResult<Database> {
+        let unlocked = self.unwrap_keys(passphrase)?;
+        let keys: Vec<&[u8; 32]> = unlocked.keys.iter().map(|k| &*k.key).collect();
+        let (db, used) = Database::open_encrypted(db_path, &keys)?;
+        let pending = used != Some(0) || unlocked.keys.len() > 1;
+        *self.state.write().expect("encryption lock poisoned") = Some(unlocked);
+        if pending {
+            self.finish(&db)?;
+        }
+        Ok(db)
+    }
+
+    /// Encrypts an existing plaintext workspace: the database is rewritten
+    /// with SQLCipher and every stored document is encrypted in place.
+    pub fn enable(&self, db: &Database, passphrase: &str) -> AppResult<()> {
+        if self.is_enabled() {
+            return Err(AppError::Validation("Workspace encryption is already enabled".to_string()));
+        }
+        validate_passphrase(passphrase)?;
+        let salt = crypto::generate_salt();
+        let unlocked = Unlocked {
+            kek: crypto::derive_key(passphrase, &salt)?,
+            salt,
+            keys: vec![DataKey { id: uuid::Uuid::new_v4().to_string(), key: crypto::generate_key() }],
+        };
+        // The header goes first so an interrupted migration resumes on unlock
+        self.save_header(&unlocked)?;
+        *self.state.write().expect("encryption lock poisoned") = Some(unlocked);
+        self.finish(db)
+    }
+
+    /// Replaces the data key and the passphrase. The database and every
+    /// stored document are re-encrypted under the new key.
+    pub fn rotate(&self, db: &Database, current_passphrase: &str, new_passphrase: &str) -> AppResult<()> {
+        validate_passphrase(new_passphrase)?;
+        let old = self.unwrap_keys(current_passphrase)?;
+        let salt = crypto::generate_salt();
+        let mut keys = vec![DataKey { id: uuid::Uuid::new_v4().to_string(), key: crypto::generate_key() }];
+        keys.extend(old.keys);
+        let unlocked = Unlocked {
+            kek: crypto::derive_key(new_passphrase, &salt)?,
+            salt,
+            keys,
+        };
+        self.save_header(&unlocked)?;
+        *self.state.write().expect("encryption lock poisoned") = Some(unlocked);
+        self.finish(db)
+    }
+
+    /// Brings the database, stored files and exports under the current key,
+    /// then drops retired keys from the header. Migration backups are
+    /// shredded: each is a whole copy of the database in plaintext or under
+    /// a key that is about to be dropped.
+    fn finish(&self, db: &Database) -> AppResult<()> {
+        let current = self.current_key()?;
+        db.rekey(&current.1)?;
+        if let Some(path) = db.path() {
+            for backup in migrations::backups(path)? {
+                shred(&backup)?;
+            }
+        }
+
+        let docs = {
+            let conn = db.reader();
+            let mut docs = documents::list_all(&conn)?;
+            docs.extend(documents::list_trash(&conn)?);
+            docs
+        };
+        for doc in docs {
+            let path = PathBuf::from(&doc.stored_path);
+            if !path.exists() {
+                continue;
+            }
+            let stored = self.reencrypt(&path, &current.0)?;
+            if stored != path {
+                let conn = db.writer();
+                documents::set_stored_path(&conn, &doc.id, &stored.to_string_lossy())?;
+                shred(&path)?;
+            }
+        }
+
+        for dir in EXPORT_DIRS.map(|dir| self.app_data.join(dir)).iter().filter(|dir| dir.is_dir()) {
+            let files: Vec<PathBuf> = std::fs::read_dir(dir)?
+                .map(|entry| Ok(entry?.path()))
+                .collect::<AppResult<_>>()?;
+            for path in files.into_iter().filter(|path| path.is_file()) {
+                let stored = self.reencrypt(&path, &current.0)?;
+                if stored != path {
+                    shred(&path)?;
+                }
+            }
+        }
+
+        let mut state = self.state.write().expect("encryption lock poisoned");
+        let unlocked = state.as_mut().expect("unlocked above");
+        if unlocked.keys.len() > 1 {
+            unlocked.keys.truncate(1);
+            self.save_header(unlocked)?;
+        }
+        Ok(())
+    }
+
+    /// Rewrites a file under the current key unless it already is. Returns
+    /// where it now lives; the caller removes the original if that moved.
+    fn reencrypt(&self, path: &Path, current_id: &str) -> AppResult<PathBuf> {
+        if self.file_key_id(path)?.as_deref() == Some(current_id) {
+            return Ok(path.to_path_buf());
+        }
+        let bytes = self.read_file(path)?;
+        self.write_file(path, &bytes)
+    }
+
+    fn current_key(&self) -> AppResult<(String, Key)> {
+        let state = self.state.read().expect("encryption lock poisoned");
+        let unlocked = state
+            .as_ref()
+            .ok_or_else(|| AppError::Validation("The workspace is locked".to_string()))?;
+        let current = &unlocked.keys[0];
+        Ok((current.id.clone(), current.key.clone()))
+    }
+
+    fn file_key_id(&self, path: &Path) -> AppResult<Option<String>> {
+        use std::io::Read;
+        let mut prefix = [0u8; FILE_MAGIC.len() + KEY_ID_LEN];
+        let mut file = std::fs::File::open(path)?;
+        match file.read_exact(&mut prefix) {
+            Ok(()) if prefix.starts_with(FILE_MAGIC) => {
+                Ok(Some(String::from_utf8_lossy(&prefix[FILE_MAGIC.len()..]).to_string()))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Stores a document file, encrypted when the workspace is. Returns the
+    /// path written, which gains an `.enc` extension when encrypted.
+    pub fn write_file(&self, path: &Path, bytes: &[u8]) -> AppResult<PathBuf> {
+        if !self.is_enabled() {
+            std::fs::write(path, bytes)?;
+            return Ok(path.to_path_buf());
+        }
+        let (id, key) = self.current_key()?;
+        let mut aad = FILE_MAGIC.to_vec();
+        aad.extend_from_slice(id.as_bytes());
+        let mut out = aad.clone();
+        out.extend(crypto::encrypt(&key, &aad, bytes)?);
+
+        let target = if path.extension().is_some_and(|ext| ext == ENCRYPTED_EXTENSION) {
+            path.to_path_buf()
+        } else {
+            PathBuf::from(format!("{}.{ENCRYPTED_EXTENSION}", path.display()))
+        };
+        let tmp = target.with_extension("tmp");
+        std::fs::write(&tmp, out)?;
+        std::fs::rename(&tmp, &target)?;
+        Ok(target)
+    }
+
+    /// Reads a stored document file, decrypting it if it was encrypted.
+    pub fn read_file(&self, path: &Path) -> AppResult<Vec<u8>> {
+        let data = std::fs::read(path)?;
+        if !data.starts_with(FILE_MAGIC) {
+            return Ok(data);
+        }
+        let header_len = FILE_MAGIC.len() + KEY_ID_LEN;
+        if data.len() < header_len {
+            return Err(AppError::Validation(format!("{} is corrupt", path.display())));
+        }
+        let id = String::from_utf8_lossy(&data[FILE_MAGIC.len()..header_len]);
+        let state = self.state.read().expect("encryption lock poisoned");
+        let unlocked = state
+            .as_ref()
+            .ok_or_else(|| AppError::Validation("The workspace is locked".to_string()))?;
+        let key = unlocked
+            .keys
+            .iter()
+            .find(|k| k.id == id)
+            .ok_or_else(|| AppError::Validation(format!("{} was encrypted with an unknown key", path.display())))?;
+        Ok(crypto::decrypt(&key.key, &data[..header_len], &data[header_len..])?.to_vec())
+    }
+}
+
+/// Overwrites a file with zeros before removing it, so its plaintext does
+/// not linger in the freed blocks.
+fn shred(path: &Path) -> AppResult<()> {
+    use std::io::Write;
+    let len = std::fs::metadata(path)?.len();
+    let mut file = std::fs::OpenOptions::new().write(true).open(path)?;
+    let zeros = [0u8; 8192];
+    let mut left = len;
+    while left > 0 {
+        let n = left.min(zeros.len() as u64) as usize;
+        file.write_all(&zeros[..n])?;
+        left -= n as u64;
+    }
+    file.sync_all()?;
+    drop(file);
+    std::fs::remove_file(path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::documents::CreateDocument;
+
+    fn workspace() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ldr-workspace-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn add_document(db: &Database, dir: &Path) -> documents::Document {
+        let stored = dir.join("nda.pdf");
+        std::fs::write(&stored, b"%PDF-1.4 confidential").unwrap();
+        let conn = db.writer();
+        let doc = documents::insert(&conn, &CreateDocument {
+            filename: "nda.pdf".into(),
+            original_path: "/tmp/nda.pdf".into(),
+            stored_path: stored.to_string_lossy().to_string(),
+            file_hash: "hash".into(),
+            file_size: 21,
+            contract_type: "nda".into(),
+        }).unwrap();
+        documents::update_text(&conn, &doc.id, "The Recipient shall keep secrets.", 1).unwrap();
+        doc
+    }
+
+    #[test]
+    fn test_enable_encrypts_existing_workspace() {
+        let dir = workspace();
+        let db_path = dir.join("test.db");
+        let db = Database::new(&db_path).unwrap();
+        let doc = add_document(&db, &dir);
+
+        let encryption = Encryption::new(&dir);
+        assert!(encryption.enable(&db, "short").is_err());
+        encryption.enable(&db, "a long passphrase").unwrap();
+        drop(db);
+
+        // Neither the database nor the stored file is readable as plaintext
+        let raw = std::fs::read(&db_path).unwrap();
+        assert!(!raw.starts_with(b"SQLite format 3"));
+        assert!(!raw.windows(7).any(|w| w == b"Recipie"));
+        assert!(Database::new(&db_path).is_err());
+        assert!(!dir.join("nda.pdf").exists());
+
+        let reopened = Encryption::new(&dir);
+        assert!(reopened.unlock("wrong passphrase", &db_path).is_err());
+        let db = reopened.unlock("a long passphrase", &db_path).unwrap();
+        let stored = documents::get_by_id(&db.writer(), &doc.id).unwrap();
+        assert!(stored.stored_path.ends_with(".pdf.enc"));
+        assert_eq!(stored.raw_text.as_deref(), Some("The Recipient shall keep secrets."));
+        assert_eq!(reopened.read_file(Path::new(&stored.stored_path)).unwrap(), b"%PDF-1.4 confidential");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_enable_leaves_no_plaintext_copies() {
+        let dir = workspace();
+        let db_path = dir.join("test.db");
+        let db = Database::new(&db_path).unwrap();
+        add_document(&db, &dir);
+        let backup = PathBuf::from(format!("{}.v5.bak", db_path.display()));
+        std::fs::copy(&db_path, &backup).unwrap();
+        std::fs::create_dir_all(dir.join("reports")).unwrap();
+        std::fs::write(dir.join("reports/report_1.txt"), b"The Recipient shall keep secrets.").unwrap();
+
+        let encryption = Encryption::new(&dir);
+        encryption.enable(&db, "a long passphrase").unwrap();
+
+        assert!(!backup.exists());
+        assert!(!dir.join("reports/report_1.txt").exists());
+        let report = dir.join("reports/report_1.txt.enc");
+        assert!(!std::fs::read(&report).unwrap().windows(7).any(|w| w == b"Recipie"));
+        assert_eq!(encryption.read_file(&report).unwrap(), b"The Recipient shall keep secrets.");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_rotate_replaces_key_and_passphrase() {
+        let dir = workspace();
+        let db_path = dir.join("test.db");
+        let db = Database::new(&db_path).unwrap();
+        let doc = add_document(&db, &dir);
+        let encryption = Encryption::new(&dir);
+        encryption.enable(&db, "first passphrase").unwrap();
+        let enc_path = PathBuf::from(documents::get_by_id(&db.writer(), &doc.id).unwrap().stored_path);
+        let key_before = encryption.file_key_id(&enc_path).unwrap();
+
+        assert!(encryption.rotate(&db, "not the passphrase", "second passphrase").is_err());
+        encryption.rotate(&db, "first passphrase", "second passphrase").unwrap();
+        assert_ne!(encryption.file_key_id(&enc_path).unwrap(), key_before);
+        drop(db);
+
+        let reopened = Encryption::new(&dir);
+        assert!(reopened.unlock("first passphrase", &db_path).is_err());
+        let db = reopened.unlock("second passphrase", &db_path).unwrap();
+        assert_eq!(documents::list_all(&db.writer()).unwrap().len(), 1);
+        assert_eq!(reopened.read_file(&enc_path).unwrap(), b"%PDF-1.4 confidential");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
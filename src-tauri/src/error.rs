@@ -25,6 +25,12 @@ pub enum AppError {
 
     #[error("Not found: {0}")]
     NotFound(String),
+
+    #[error("Policy violation: {0}")]
+    PolicyViolation(String),
+
+    #[error("Background task failed: {0}")]
+    Task(String),
 }
 
 impl From<reqwest::Error> for AppError {
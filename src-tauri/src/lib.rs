@@ -4,6 +4,11 @@ mod ai;
 mod documents;
 mod analysis;
 mod commands;
+mod secrets;
+mod config;
+mod crypto;
+mod encryption;
+mod retention;
 
 use tauri::Manager;
 
@@ -15,6 +20,16 @@ use commands::analysis_commands::*;
 use commands::comparison_commands::*;
 use commands::template_commands::*;
 use commands::report_commands::*;
+use commands::prompt_commands::*;
+use commands::ollama_commands::*;
+use commands::secret_commands::*;
+use commands::encryption_commands::*;
+use commands::audit_commands::*;
+use commands::matter_commands::*;
+use commands::tag_commands::*;
+use commands::version_commands::*;
+use commands::search_commands::*;
+use commands::question_commands::*;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -34,38 +49,137 @@ pub fn run() {
             std::fs::create_dir_all(&app_data)
                 .expect("failed to create app data directory");
 
-            let db_path = app_data.join("legal_docs_review.db");
-            let database = Database::new(&db_path)
-                .expect("failed to initialize database");
+            let secret_store = secrets::SecretStore::open(&app_data);
+            let encryption = encryption::Encryption::new(&app_data);
 
-            app.manage(database);
+            // An encrypted workspace opens its database in unlock_workspace,
+            // once the passphrase has been entered
+            if !encryption.is_enabled() {
+                let database = Database::new(&app_data.join(db::DB_FILE))
+                    .expect("failed to initialize database");
+
+                // Credentials live in the OS keyring or the encrypted vault, never
+                // in the settings table; move any left there by older versions
+                {
+                    let conn = database.writer();
+                    if let Err(e) = secrets::migrate_plaintext(&conn, &secret_store) {
+                        log::error!("Failed to migrate stored API keys: {e}");
+                    }
+                }
+                if let Err(e) = retention::apply(&database) {
+                    log::error!("Failed to apply retention policies: {e}");
+                }
+                app.manage(database);
+            }
+
+            app.manage(encryption);
+            app.manage(secret_store);
+            app.manage(ai::ProviderCache::default());
+            app.manage(documents::upload::UploadGrants::default());
+            app.manage(OllamaPulls::default());
 
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             // Documents
+            pick_document_file,
             upload_document,
             extract_document_text,
             get_document,
             list_documents,
             delete_document,
+            list_trash,
+            restore_document,
+            purge_document,
+            set_document_legal_hold,
+            apply_retention,
+            set_document_local_only,
             get_document_stats,
             // Settings
             get_setting,
             set_setting,
+            get_all_settings,
+            // Encryption
+            get_encryption_status,
+            unlock_workspace,
+            enable_encryption,
+            rotate_encryption_key,
+            // Secrets
+            get_secrets_status,
+            unlock_secrets,
+            set_secret,
+            has_secret,
+            delete_secret,
+            // Matters
+            create_matter,
+            update_matter,
+            list_matters,
+            list_clients,
+            close_matter,
+            reopen_matter,
+            set_matter_legal_hold,
+            set_document_matter,
+            set_comparison_matter,
+            set_report_matter,
+            // Tags
+            list_tags,
+            set_document_tags,
+            set_comparison_tags,
+            set_report_tags,
+            rename_tag,
+            delete_tag,
+            // Versions
+            add_document_version,
+            get_document_versions,
+            compare_with_previous_version,
+            set_version_label,
+            // Audit
+            verify_audit_log,
+            get_audit_events,
+            export_audit_log,
+            // Search
+            search,
+            find_similar_clauses,
+            index_clause_embeddings,
+            // Questions
+            ask_question,
+            list_conversations,
+            get_conversation_messages,
+            delete_conversation,
             // Analysis
             analyze_document,
             get_extractions,
             get_risk_assessments,
+            get_redaction_log,
             // Comparison
             compare_documents,
+            compare_clauses,
+            compare_to_template,
+            list_comparisons,
+            get_comparison,
+            delete_comparison,
+            rerun_comparison,
             // Templates
             create_template,
             list_templates,
             delete_template,
             // Reports
             generate_report,
+            generate_comparison_report,
+            get_comparison_reports,
             get_reports,
+            // Prompts
+            list_prompts,
+            get_prompt_history,
+            update_prompt,
+            diff_prompt_versions,
+            rollback_prompt,
+            // Ollama
+            check_ollama_health,
+            list_ollama_models,
+            pull_ollama_model,
+            get_ollama_pulls,
+            warm_ollama_model,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
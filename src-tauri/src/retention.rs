@@ -0,0 +1,281 @@
+use std::path::PathBuf;
+
+use rusqlite::Connection;
+use serde::Serialize;
+use serde_json::json;
+
+use crate::config;
+use crate::db::audit::{self, NewAuditEvent};
+use crate::db::documents::{self, Document};
+use crate::db::{clauses, comparisons, conversations, extractions, reports};
+use crate::db::Database;
+use crate::error::AppResult;
+
+#[derive(Debug, Default, Serialize)]
+pub struct RetentionReport {
+    /// Ids of trashed documents that were permanently deleted.
+    pub purged: Vec<String>,
+    /// Ids of documents whose text was deleted.
+    pub text_removed: Vec<String>,
+}
+
+/// Permanently deletes a document's row and everything that cascades from
+/// it, and audits the purge. Refused while the document is under hold.
+///
+/// Run it inside a transaction and pass the returned stored file to
+/// [`remove_files`] once that commits. No file is returned while another
+/// document still refers to it.
+pub fn purge(conn: &Connection, doc: &Document, reason: &str) -> AppResult<Option<PathBuf>> {
+    documents::delete(conn, &doc.id)?;
+    let mut event = NewAuditEvent::new("document_purged")
+        .document(&doc.id)
+        .details(json!({ "filename": doc.filename, "file_hash": doc.file_hash, "reason": reason }));
+    if let Some(matter_id) = &doc.matter_id {
+        event = event.matter(matter_id);
+    }
+    audit::record(conn, event)?;
+    stored_file(conn, doc)
+}
+
+/// Removes the contract text of a document everywhere it is kept: its
+/// extracted text, clauses and their search entries, the clause text and
+/// raw reply in its extractions, the redlines and quoted differences of its
+/// comparisons, reports on it or its comparisons, and conversations that
+/// include it. The row stays, as do parties, dates, risk assessments,
+/// comparison summaries, the redaction log and the audit trail.
+///
+/// Like [`purge`], the stored file is returned for [`remove_files`] once
+/// the transaction commits.
+pub fn remove_text(conn: &Connection, doc: &Document, reason: &str) -> AppResult<Option<PathBuf>> {
+    documents::clear_raw_text(conn, &doc.id)?;
+    clauses::delete_by_document(conn, &doc.id)?;
+    extractions::clear_text(conn, &doc.id)?;
+    reports::delete_by_document(conn, &doc.id)?;
+    comparisons::clear_text(conn, &doc.id)?;
+    conversations::delete_by_document(conn, &doc.id)?;
+    let mut event = NewAuditEvent::new("raw_text_removed").document(&doc.id).details(json!({ "reason": reason }));
+    if let Some(matter_id) = &doc.matter_id {
+        event = event.matter(matter_id);
+    }
+    audit::record(conn, event)?;
+    stored_file(conn, doc)
+}
+
+/// The document's stored file, unless another document refers to it too.
+fn stored_file(conn: &Connection, doc: &Document) -> AppResult<Option<PathBuf>> {
+    let shared = documents::stored_path_in_use(conn, &doc.stored_path, &doc.id)?;
+    Ok((!shared).then(|| PathBuf::from(&doc.stored_path)))
+}
+
+/// Removes the files of purged documents and of those whose text was
+/// removed. The database changes are already committed, so a file that
+/// cannot be removed is logged rather than reported.
+pub fn remove_files(paths: &[PathBuf]) {
+    for path in paths.iter().filter(|path| path.exists()) {
+        if let Err(e) = std::fs::remove_file(path) {
+            log::warn!("Failed to remove stored file {}: {e}", path.display());
+        }
+    }
+}
+
+fn days_setting(conn: &Connection, key: &str) -> AppResult<Option<i64>> {
+    Ok(config::get(conn, key)?.and_then(|value| value.parse().ok()))
+}
+
+/// Applies the configured retention periods: purges trash older than
+/// `trash_retention_days` and removes the text of documents whose matter
+/// closed more than `closed_matter_text_retention_days` ago (see
+/// [`remove_text`]). Documents
+/// under hold are never touched. Either every change is made or none is.
+pub fn apply(db: &Database) -> AppResult<RetentionReport> {
+    let writer = db.writer();
+    let conn = writer.unchecked_transaction()?;
+    let mut report = RetentionReport::default();
+    let mut files = Vec::new();
+
+    if let Some(days) = days_setting(&conn, "trash_retention_days")?.filter(|days| *days > 0) {
+        for doc in documents::expired_trash(&conn, days)? {
+            files.extend(purge(&conn, &doc, "trash_retention")?);
+            report.purged.push(doc.id);
+        }
+    }
+
+    if let Some(days) = days_setting(&conn, "closed_matter_text_retention_days")? {
+        for id in documents::expired_raw_text(&conn, days)? {
+            let doc = documents::get_by_id(&conn, &id)?;
+            files.extend(remove_text(&conn, &doc, "matter_closed")?);
+            report.text_removed.push(id);
+        }
+    }
+    conn.commit()?;
+    remove_files(&files);
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::documents::CreateDocument;
+    use crate::db::{matters, settings};
+
+    fn insert_doc(conn: &Connection) -> Document {
+        let doc = documents::insert(conn, &CreateDocument {
+            filename: "nda.pdf".into(),
+            original_path: "/tmp/nda.pdf".into(),
+            stored_path: "/nonexistent/nda.pdf".into(),
+            file_hash: "hash".into(),
+            file_size: 10,
+            contract_type: "nda".into(),
+        }).unwrap();
+        documents::update_text(conn, &doc.id, "Confidential terms", 1).unwrap();
+        doc
+    }
+
+    #[test]
+    fn test_purges_expired_trash_except_held() {
+        let db = Database::in_memory().unwrap();
+        let (old, held, recent) = {
+            let conn = db.writer();
+            let docs = [insert_doc(&conn), insert_doc(&conn), insert_doc(&conn)];
+            for doc in &docs {
+                documents::soft_delete(&conn, &doc.id).unwrap();
+            }
+            conn.execute(
+                "UPDATE documents SET deleted_at = datetime('now', '-31 days') WHERE id IN (?1, ?2)",
+                rusqlite::params![docs[0].id, docs[1].id],
+            ).unwrap();
+            documents::set_legal_hold(&conn, &docs[1].id, true, None).unwrap();
+            let [old, held, recent] = docs;
+            (old, held, recent)
+        };
+
+        let report = apply(&db).unwrap();
+        assert_eq!(report.purged, vec![old.id.clone()]);
+
+        let conn = db.writer();
+        assert!(documents::get_by_id(&conn, &old.id).is_err());
+        assert!(documents::get_by_id(&conn, &held.id).is_ok());
+        assert!(documents::get_by_id(&conn, &recent.id).is_ok());
+        let events = audit::list(&conn, Some(&old.id), None).unwrap();
+        assert_eq!(events.last().unwrap().action, "document_purged");
+    }
+
+    #[test]
+    fn test_keeps_a_stored_file_another_document_uses() {
+        let db = Database::in_memory().unwrap();
+        let stored = std::env::temp_dir().join(format!("ldr-retention-{}.pdf", uuid::Uuid::new_v4()));
+        std::fs::write(&stored, b"%PDF-1.4").unwrap();
+        let (first, second) = {
+            let conn = db.writer();
+            let docs = [insert_doc(&conn), insert_doc(&conn)];
+            for doc in &docs {
+                conn.execute(
+                    "UPDATE documents SET stored_path = ?1 WHERE id = ?2",
+                    rusqlite::params![stored.to_string_lossy(), doc.id],
+                ).unwrap();
+            }
+            let [first, second] = docs;
+            (first, second)
+        };
+        let expire = |id: &str| {
+            let conn = db.writer();
+            documents::soft_delete(&conn, id).unwrap();
+            conn.execute("UPDATE documents SET deleted_at = datetime('now', '-31 days') WHERE id = ?1", [id]).unwrap();
+        };
+
+        expire(&first.id);
+        assert_eq!(apply(&db).unwrap().purged, vec![first.id]);
+        assert!(stored.exists());
+
+        expire(&second.id);
+        assert_eq!(apply(&db).unwrap().purged, vec![second.id]);
+        assert!(!stored.exists());
+    }
+
+    #[test]
+    fn test_removes_text_after_matter_closes() {
+        let db = Database::in_memory().unwrap();
+        let stored = std::env::temp_dir().join(format!("ldr-retention-{}.pdf", uuid::Uuid::new_v4()));
+        std::fs::write(&stored, b"%PDF-1.4").unwrap();
+        let (doc, other, comparison) = {
+            let conn = db.writer();
+            settings::set(&conn, "closed_matter_text_retention_days", "0").unwrap();
+            let matter = matters::insert(&conn, &matters::MatterInput::named("Acme")).unwrap();
+            let doc = insert_doc(&conn);
+            let other = insert_doc(&conn);
+            conn.execute(
+                "UPDATE documents SET stored_path = ?1 WHERE id = ?2",
+                rusqlite::params![stored.to_string_lossy(), doc.id],
+            ).unwrap();
+            documents::set_matter(&conn, &doc.id, Some(&matter.id)).unwrap();
+            extractions::insert(&conn, &extractions::CreateExtraction {
+                document_id: doc.id.clone(),
+                ai_provider: "ollama".into(),
+                ai_model: None,
+                contract_type: "nda".into(),
+                extracted_data: json!({
+                    "parties": ["Acme"],
+                    "clauses": [{ "clause_type": "confidentiality", "title": "Secrets", "text": "Confidential terms" }],
+                    "raw_json": "Confidential terms",
+                }).to_string(),
+                confidence_score: None,
+                processing_time_ms: None,
+                prompt_version: None,
+                data_policy: None,
+                ai_endpoint: None,
+            }).unwrap();
+            let comparison = comparisons::insert(&conn, &comparisons::CreateComparison {
+                document_a_id: &other.id,
+                document_b_id: Some(&doc.id),
+                template_id: None,
+                comparison_type: "version",
+                differences: &json!([{ "category": "terms", "diff_type": "modified", "description": "Changed",
+                    "text_a": "Old terms", "text_b": "Confidential terms", "significance": "low" }]).to_string(),
+                redline: Some("Confidential terms"),
+                summary: Some("Terms changed"),
+                ai_provider: None,
+                data_policy: None,
+                ai_endpoint: None,
+            }).unwrap();
+            reports::insert(&conn, &doc.id, "full_analysis", "Confidential terms", "text").unwrap();
+            reports::insert_for_comparison(&conn, &comparison.id, "comparison_redline", "Confidential terms", "text")
+                .unwrap();
+            conversations::create(&conn, "Terms", &[doc.id.clone(), other.id.clone()]).unwrap();
+            // Open matters keep their text
+            assert!(documents::expired_raw_text(&conn, 0).unwrap().is_empty());
+            matters::set_closed(&conn, &matter.id, true).unwrap();
+            (doc, other, comparison)
+        };
+
+        let report = apply(&db).unwrap();
+        assert_eq!(report.text_removed, vec![doc.id.clone()]);
+        assert!(!stored.exists());
+        let conn = db.writer();
+        let doc = documents::get_by_id(&conn, &doc.id).unwrap();
+        assert!(doc.raw_text.is_none());
+        assert!(doc.raw_text_removed_at.is_some());
+
+        let found: i64 = conn.query_row(
+            "SELECT (SELECT COUNT(*) FROM documents_fts WHERE documents_fts MATCH 'confidential' AND document_id = ?1)
+                  + (SELECT COUNT(*) FROM clauses WHERE document_id = ?1)
+                  + (SELECT COUNT(*) FROM clauses_fts WHERE clauses_fts MATCH 'confidential')",
+            [&doc.id],
+            |row| row.get(0),
+        ).unwrap();
+        assert_eq!(found, 0);
+        let extraction = &extractions::list_by_document(&conn, &doc.id).unwrap()[0];
+        assert!(!extraction.extracted_data.contains("Confidential"));
+        assert!(extraction.extracted_data.contains("Acme"));
+        let comparison = comparisons::get_by_id(&conn, &comparison.id).unwrap();
+        assert!(comparison.redline.is_none());
+        let differences: serde_json::Value = serde_json::from_str(&comparison.differences).unwrap();
+        assert_eq!(differences[0]["text_a"], serde_json::Value::Null);
+        assert_eq!(differences[0]["text_b"], serde_json::Value::Null);
+        assert_eq!(comparison.summary.as_deref(), Some("Terms changed"));
+        assert!(reports::list_by_document(&conn, &doc.id).unwrap().is_empty());
+        assert!(reports::list_by_comparison(&conn, &comparison.id).unwrap().is_empty());
+        assert!(conversations::list_by_document(&conn, &other.id).unwrap().is_empty());
+        // The other document keeps its own text
+        assert!(documents::get_by_id(&conn, &other.id).unwrap().raw_text.is_some());
+    }
+}
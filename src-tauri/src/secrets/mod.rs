@@ -0,0 +1,310 @@
+pub(crate) mod vault;
+
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use rusqlite::Connection;
+use serde::Serialize;
+
+use crate::db::settings;
+use crate::error::{AppError, AppResult};
+use vault::Vault;
+
+/// Settings that hold credentials. They never live in the settings table and
+/// can be written, checked or deleted from the UI, but never read back.
+pub const SECRET_KEYS: [&str; 2] = ["claude_api_key", "openai_api_key"];
+
+const KEYRING_SERVICE: &str = "legal-docs-review";
+const VAULT_FILE: &str = "secrets.vault";
+
+pub fn is_secret_key(key: &str) -> bool {
+    SECRET_KEYS.contains(&key)
+}
+
+fn require_secret_key(key: &str) -> AppResult<()> {
+    if is_secret_key(key) {
+        Ok(())
+    } else {
+        Err(AppError::Validation(format!("{key} is not a secret setting")))
+    }
+}
+
+pub(crate) trait SecretBackend: Send + Sync {
+    fn get(&self, key: &str) -> AppResult<Option<String>>;
+    fn set(&self, key: &str, value: &str) -> AppResult<()>;
+    fn delete(&self, key: &str) -> AppResult<()>;
+}
+
+struct KeyringBackend;
+
+impl KeyringBackend {
+    fn entry(key: &str) -> AppResult<keyring::Entry> {
+        keyring::Entry::new(KEYRING_SERVICE, key)
+            .map_err(|e| AppError::Validation(format!("OS keyring error: {e}")))
+    }
+
+    /// Whether the platform keyring can be used at all, e.g. a Secret Service
+    /// daemon is running on Linux.
+    fn is_available() -> bool {
+        match Self::entry("__probe__").map(|entry| entry.get_password()) {
+            Ok(Ok(_)) | Ok(Err(keyring::Error::NoEntry)) => true,
+            Ok(Err(e)) => {
+                log::info!("OS keyring unavailable, using the encrypted vault: {e}");
+                false
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+impl SecretBackend for KeyringBackend {
+    fn get(&self, key: &str) -> AppResult<Option<String>> {
+        match Self::entry(key)?.get_password() {
+            Ok(value) => Ok(Some(value)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(AppError::Validation(format!("OS keyring error: {e}"))),
+        }
+    }
+
+    fn set(&self, key: &str, value: &str) -> AppResult<()> {
+        Self::entry(key)?
+            .set_password(value)
+            .map_err(|e| AppError::Validation(format!("OS keyring error: {e}")))
+    }
+
+    fn delete(&self, key: &str) -> AppResult<()> {
+        match Self::entry(key)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(AppError::Validation(format!("OS keyring error: {e}"))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SecretBackendKind {
+    Keyring,
+    EncryptedFile,
+    Memory,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SecretsStatus {
+    pub backend: SecretBackendKind,
+    /// The encrypted vault needs its passphrase before secrets can be used.
+    pub locked: bool,
+    /// Whether a vault file exists yet; unlocking a missing vault creates it.
+    pub vault_exists: bool,
+    pub keys: Vec<SecretKeyStatus>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SecretKeyStatus {
+    pub key: String,
+    pub is_set: bool,
+}
+
+/// Credentials store: the OS keyring when available, otherwise a
+/// passphrase-protected vault file in the app data directory.
+pub struct SecretStore {
+    kind: SecretBackendKind,
+    vault_path: PathBuf,
+    backend: RwLock<Option<Box<dyn SecretBackend>>>,
+}
+
+impl SecretStore {
+    pub fn open(app_data: &Path) -> Self {
+        let vault_path = app_data.join(VAULT_FILE);
+        if KeyringBackend::is_available() {
+            Self {
+                kind: SecretBackendKind::Keyring,
+                vault_path,
+                backend: RwLock::new(Some(Box::new(KeyringBackend))),
+            }
+        } else {
+            Self {
+                kind: SecretBackendKind::EncryptedFile,
+                vault_path,
+                backend: RwLock::new(None),
+            }
+        }
+    }
+
+    #[cfg(test)]
+    pub fn in_memory() -> Self {
+        Self {
+            kind: SecretBackendKind::Memory,
+            vault_path: PathBuf::new(),
+            backend: RwLock::new(Some(Box::new(MemoryBackend::default()))),
+        }
+    }
+
+    #[cfg(test)]
+    fn locked_vault(vault_path: PathBuf) -> Self {
+        Self {
+            kind: SecretBackendKind::EncryptedFile,
+            vault_path,
+            backend: RwLock::new(None),
+        }
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.backend.read().expect("secrets lock poisoned").is_none()
+    }
+
+    /// Unlocks (or creates) the encrypted vault. A no-op for the OS keyring.
+    pub fn unlock(&self, passphrase: &str) -> AppResult<()> {
+        if self.kind != SecretBackendKind::EncryptedFile {
+            return Ok(());
+        }
+        let vault = Vault::unlock(&self.vault_path, passphrase)?;
+        *self.backend.write().expect("secrets lock poisoned") = Some(Box::new(vault));
+        Ok(())
+    }
+
+    fn with_backend<T>(&self, f: impl FnOnce(&dyn SecretBackend) -> AppResult<T>) -> AppResult<T> {
+        let guard = self.backend.read().expect("secrets lock poisoned");
+        match guard.as_deref() {
+            Some(backend) => f(backend),
+            None => Err(AppError::Validation(
+                "The secrets vault is locked; enter its passphrase in Settings".to_string(),
+            )),
+        }
+    }
+
+    /// For use by the backend only; never return this to the frontend.
+    pub fn get(&self, key: &str) -> AppResult<Option<String>> {
+        require_secret_key(key)?;
+        self.with_backend(|b| b.get(key))
+    }
+
+    pub fn set(&self, key: &str, value: &str) -> AppResult<()> {
+        require_secret_key(key)?;
+        if value.trim().is_empty() {
+            return self.delete(key);
+        }
+        self.with_backend(|b| b.set(key, value.trim()))
+    }
+
+    pub fn delete(&self, key: &str) -> AppResult<()> {
+        require_secret_key(key)?;
+        self.with_backend(|b| b.delete(key))
+    }
+
+    pub fn is_set(&self, key: &str) -> AppResult<bool> {
+        Ok(self.get(key)?.is_some())
+    }
+
+    pub fn status(&self) -> SecretsStatus {
+        let locked = self.is_locked();
+        let keys = SECRET_KEYS
+            .iter()
+            .map(|key| SecretKeyStatus {
+                key: key.to_string(),
+                is_set: !locked && self.is_set(key).unwrap_or(false),
+            })
+            .collect();
+        SecretsStatus {
+            backend: self.kind,
+            locked,
+            vault_exists: self.vault_path.exists(),
+            keys,
+        }
+    }
+}
+
+/// Moves any credentials left in the plaintext settings table into the store.
+/// Does nothing while the vault is locked; call again after unlocking.
+pub fn migrate_plaintext(conn: &Connection, store: &SecretStore) -> AppResult<usize> {
+    if store.is_locked() {
+        return Ok(0);
+    }
+    let mut migrated = 0;
+    for key in SECRET_KEYS {
+        let Some(value) = settings::get(conn, key)? else {
+            continue;
+        };
+        // An existing secret wins over a stale plaintext copy
+        if !value.trim().is_empty() && !store.is_set(key)? {
+            store.set(key, &value)?;
+        }
+        settings::delete(conn, key)?;
+        migrated += 1;
+    }
+    Ok(migrated)
+}
+
+#[cfg(test)]
+#[derive(Default)]
+struct MemoryBackend(std::sync::Mutex<std::collections::HashMap<String, String>>);
+
+#[cfg(test)]
+impl SecretBackend for MemoryBackend {
+    fn get(&self, key: &str) -> AppResult<Option<String>> {
+        Ok(self.0.lock().unwrap().get(key).cloned())
+    }
+
+    fn set(&self, key: &str, value: &str) -> AppResult<()> {
+        self.0.lock().unwrap().insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    fn delete(&self, key: &str) -> AppResult<()> {
+        self.0.lock().unwrap().remove(key);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+
+    #[test]
+    fn test_only_known_secret_keys_are_accepted() {
+        let store = SecretStore::in_memory();
+        store.set("claude_api_key", "sk-ant-1").unwrap();
+        assert!(store.is_set("claude_api_key").unwrap());
+        assert!(store.set("ollama_url", "http://localhost").is_err());
+
+        // Clearing the field in the UI deletes the secret
+        store.set("claude_api_key", "  ").unwrap();
+        assert!(!store.is_set("claude_api_key").unwrap());
+    }
+
+    #[test]
+    fn test_migrate_plaintext_keys_out_of_settings() {
+        let db = Database::in_memory().unwrap();
+        let conn = db.writer();
+        settings::set(&conn, "claude_api_key", "sk-ant-plain").unwrap();
+        settings::set(&conn, "ai_provider", "claude").unwrap();
+
+        let store = SecretStore::in_memory();
+        assert_eq!(migrate_plaintext(&conn, &store).unwrap(), 1);
+        assert_eq!(store.get("claude_api_key").unwrap().as_deref(), Some("sk-ant-plain"));
+        assert_eq!(settings::get(&conn, "claude_api_key").unwrap(), None);
+        assert_eq!(settings::get(&conn, "ai_provider").unwrap().as_deref(), Some("claude"));
+        assert_eq!(migrate_plaintext(&conn, &store).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_locked_vault_defers_migration_until_unlocked() {
+        let db = Database::in_memory().unwrap();
+        let conn = db.writer();
+        settings::set(&conn, "openai_api_key", "sk-plain").unwrap();
+
+        let path = std::env::temp_dir().join(format!("ldr-vault-{}.json", uuid::Uuid::new_v4()));
+        let store = SecretStore::locked_vault(path.clone());
+        assert!(store.status().locked);
+        assert!(store.get("openai_api_key").is_err());
+        assert_eq!(migrate_plaintext(&conn, &store).unwrap(), 0);
+        assert!(settings::get(&conn, "openai_api_key").unwrap().is_some());
+
+        store.unlock("a long passphrase").unwrap();
+        assert_eq!(migrate_plaintext(&conn, &store).unwrap(), 1);
+        let status = store.status();
+        assert!(!status.locked);
+        assert!(status.keys.iter().any(|k| k.key == "openai_api_key" && k.is_set));
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,150 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::{self, Key, SealedValue};
+use crate::error::{AppError, AppResult};
+use super::SecretBackend;
+
+const VAULT_VERSION: u32 = 1;
+const CHECK_LABEL: &str = "__vault_check__";
+const CHECK_PLAINTEXT: &[u8] = b"legal-docs-review vault";
+pub const MIN_PASSPHRASE_LEN: usize = 8;
+
+#[derive(Serialize, Deserialize)]
+struct VaultFile {
+    version: u32,
+    salt: String,
+    check: SealedValue,
+    entries: BTreeMap<String, SealedValue>,
+}
+
+/// Secrets encrypted with AES-256-GCM under a key derived from a passphrase
+/// with Argon2id. Used when the OS keyring is unavailable.
+pub struct Vault {
+    path: PathBuf,
+    key: Key,
+    file: std::sync::Mutex<VaultFile>,
+}
+
+fn wrong_passphrase(e: AppError) -> AppError {
+    match e {
+        AppError::Validation(msg) if msg.starts_with("Decryption failed") => {
+            AppError::Validation("Wrong passphrase for the secrets vault".to_string())
+        }
+        other => other,
+    }
+}
+
+impl Vault {
+    /// Opens the vault at `path`, creating it with this passphrase if it does not exist yet.
+    pub fn unlock(path: &Path, passphrase: &str) -> AppResult<Self> {
+        if !path.exists() {
+            return Self::create(path, passphrase);
+        }
+        let file: VaultFile = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        if file.version != VAULT_VERSION {
+            return Err(AppError::Validation(format!(
+                "Unsupported secrets vault version {}",
+                file.version
+            )));
+        }
+        let key = crypto::derive_key(passphrase, &file.salt)?;
+        crypto::open(&key, CHECK_LABEL, &file.check).map_err(wrong_passphrase)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            key,
+            file: std::sync::Mutex::new(file),
+        })
+    }
+
+    fn create(path: &Path, passphrase: &str) -> AppResult<Self> {
+        if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+            return Err(AppError::Validation(format!(
+                "Vault passphrase must be at least {MIN_PASSPHRASE_LEN} characters"
+            )));
+        }
+        let salt = crypto::generate_salt();
+        let key = crypto::derive_key(passphrase, &salt)?;
+        let file = VaultFile {
+            version: VAULT_VERSION,
+            check: crypto::seal(&key, CHECK_LABEL, CHECK_PLAINTEXT)?,
+            salt,
+            entries: BTreeMap::new(),
+        };
+        let vault = Self {
+            path: path.to_path_buf(),
+            key,
+            file: std::sync::Mutex::new(file),
+        };
+        vault.save(&vault.file.lock().expect("vault lock poisoned"))?;
+        Ok(vault)
+    }
+
+    fn save(&self, file: &VaultFile) -> AppResult<()> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        // Write-then-rename so a crash never leaves a half-written vault
+        let tmp = self.path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(file)?)?;
+        std::fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+impl SecretBackend for Vault {
+    fn get(&self, key: &str) -> AppResult<Option<String>> {
+        let file = self.file.lock().expect("vault lock poisoned");
+        let Some(sealed) = file.entries.get(key) else {
+            return Ok(None);
+        };
+        let plaintext = crypto::open(&self.key, key, sealed)?;
+        String::from_utf8(plaintext.to_vec())
+            .map(Some)
+            .map_err(|_| AppError::Validation("Secrets vault is corrupt".to_string()))
+    }
+
+    fn set(&self, key: &str, value: &str) -> AppResult<()> {
+        let mut file = self.file.lock().expect("vault lock poisoned");
+        file.entries.insert(key.to_string(), crypto::seal(&self.key, key, value.as_bytes())?);
+        self.save(&file)
+    }
+
+    fn delete(&self, key: &str) -> AppResult<()> {
+        let mut file = self.file.lock().expect("vault lock poisoned");
+        if file.entries.remove(key).is_some() {
+            self.save(&file)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vault_roundtrip_and_wrong_passphrase() {
+        let path = std::env::temp_dir().join(format!("ldr-vault-{}.json", uuid::Uuid::new_v4()));
+
+        assert!(Vault::unlock(&path, "short").is_err());
+        let vault = Vault::unlock(&path, "correct horse battery").unwrap();
+        vault.set("claude_api_key", "sk-ant-secret").unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(!contents.contains("sk-ant-secret"));
+
+        let reopened = Vault::unlock(&path, "correct horse battery").unwrap();
+        assert_eq!(reopened.get("claude_api_key").unwrap().as_deref(), Some("sk-ant-secret"));
+        assert_eq!(reopened.get("openai_api_key").unwrap(), None);
+
+        let err = Vault::unlock(&path, "wrong horse battery").err().unwrap();
+        assert!(err.to_string().contains("Wrong passphrase"));
+
+        reopened.delete("claude_api_key").unwrap();
+        assert_eq!(reopened.get("claude_api_key").unwrap(), None);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -1,4 +1,7 @@
+import { useEffect, useState } from "react";
 import { Routes, Route } from "react-router";
+import { getEncryptionStatus } from "@/lib/commands";
+import UnlockWorkspace from "./components/security/UnlockWorkspace";
 import MainLayout from "./components/layout/MainLayout";
 import Dashboard from "./pages/Dashboard";
 import Upload from "./pages/Upload";
@@ -7,8 +10,22 @@ import Comparison from "./pages/Comparison";
 import Templates from "./pages/Templates";
 import Reports from "./pages/Reports";
 import Settings from "./pages/Settings";
+import Trash from "./pages/Trash";
+import Search from "./pages/Search";
+import Matters from "./pages/Matters";
 
 function App() {
+  const [locked, setLocked] = useState<boolean | null>(null);
+
+  useEffect(() => {
+    getEncryptionStatus()
+      .then((status) => setLocked(status.enabled && !status.unlocked))
+      .catch(() => setLocked(false));
+  }, []);
+
+  if (locked === null) return null;
+  if (locked) return <UnlockWorkspace onUnlocked={() => setLocked(false)} />;
+
   return (
     <Routes>
       <Route element={<MainLayout />}>
@@ -17,7 +34,10 @@ function App() {
         <Route path="/documents/:id" element={<ReviewDetail />} />
         <Route path="/compare" element={<Comparison />} />
         <Route path="/templates" element={<Templates />} />
+        <Route path="/matters" element={<Matters />} />
         <Route path="/reports/:id" element={<Reports />} />
+        <Route path="/search" element={<Search />} />
+        <Route path="/trash" element={<Trash />} />
         <Route path="/settings" element={<Settings />} />
       </Route>
     </Routes>
@@ -0,0 +1,80 @@
+import { render, screen } from "@testing-library/react";
+import { describe, it, expect } from "vitest";
+import ConversationMessages from "./ConversationMessages";
+import type { ConversationMessage } from "@/lib/commands";
+
+const text =
+  "1. Termination. Either party may terminate on thirty (30) days written notice.";
+
+const messages: ConversationMessage[] = [
+  {
+    id: "q1",
+    conversation_id: "c1",
+    role: "user",
+    content: "What is the notice period?",
+    citations: [],
+    ai_provider: null,
+    prompt_version: null,
+    data_policy: null,
+    ai_endpoint: null,
+    created_at: "2024-01-01 00:00:00",
+  },
+  {
+    id: "a1",
+    conversation_id: "c1",
+    role: "assistant",
+    content: "Thirty days [E1].",
+    citations: [
+      {
+        document_id: "doc-1",
+        excerpt: "E1",
+        text: "thirty (30) days",
+        start: 46,
+        end: 62,
+        verified: true,
+      },
+      {
+        document_id: "doc-1",
+        excerpt: "E1",
+        text,
+        start: 0,
+        end: text.length,
+        verified: false,
+      },
+    ],
+    ai_provider: "ollama",
+    prompt_version: 1,
+    data_policy: "standard",
+    ai_endpoint: "http://localhost:11434",
+    created_at: "2024-01-01 00:00:01",
+  },
+];
+
+describe("ConversationMessages", () => {
+  it("renders questions and answers", () => {
+    render(
+      <ConversationMessages messages={messages} documentId="doc-1" text={text} />,
+    );
+    expect(screen.getByText("What is the notice period?")).toBeInTheDocument();
+    expect(screen.getByText("Thirty days [E1].")).toBeInTheDocument();
+  });
+
+  it("highlights cited text in context", () => {
+    render(
+      <ConversationMessages messages={messages} documentId="doc-1" text={text} />,
+    );
+    const mark = screen.getByText("thirty (30) days");
+    expect(mark.tagName).toBe("MARK");
+    expect(mark.previousSibling?.textContent).toContain("may terminate on");
+    expect(mark.nextSibling?.textContent).toContain("written notice.");
+  });
+
+  it("flags citations whose quote was not found", () => {
+    render(
+      <ConversationMessages messages={messages} documentId="doc-1" text={text} />,
+    );
+    expect(
+      screen.getByText("quote not found; showing the whole excerpt"),
+    ).toBeInTheDocument();
+  });
+});
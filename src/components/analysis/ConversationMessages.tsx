@@ -0,0 +1,88 @@
+import { AlertCircle, Quote } from "lucide-react";
+import type { Citation, ConversationMessage } from "@/lib/commands";
+
+/** Characters of surrounding text shown on each side of a citation. */
+const CONTEXT_CHARS = 160;
+
+interface ConversationMessagesProps {
+  messages: ConversationMessage[];
+  /** The document shown; citations into others are listed without context. */
+  documentId: string;
+  text: string | null;
+}
+
+function CitedPassage({
+  citation,
+  chars,
+}: {
+  citation: Citation;
+  chars: string[] | null;
+}) {
+  // Offsets count characters, so slice by code point rather than UTF-16 unit
+  const before = chars
+    ? chars
+        .slice(Math.max(0, citation.start - CONTEXT_CHARS), citation.start)
+        .join("")
+    : "";
+  const after = chars
+    ? chars.slice(citation.end, citation.end + CONTEXT_CHARS).join("")
+    : "";
+
+  return (
+    <div className="mt-2 text-xs bg-gray-50 border border-gray-200 rounded-lg p-3">
+      <div className="flex items-center gap-1 text-gray-500 mb-1">
+        <Quote className="h-3 w-3" />
+        <span>
+          {citation.excerpt} &middot; characters {citation.start}&ndash;
+          {citation.end}
+        </span>
+        {!citation.verified && (
+          <span className="flex items-center gap-1 text-amber-600 ml-2">
+            <AlertCircle className="h-3 w-3" />
+            quote not found; showing the whole excerpt
+          </span>
+        )}
+      </div>
+      <p className="text-gray-600 whitespace-pre-wrap">
+        {before && <span>&hellip;{before}</span>}
+        <mark className="bg-yellow-200 rounded px-0.5">{citation.text}</mark>
+        {after && <span>{after}&hellip;</span>}
+      </p>
+    </div>
+  );
+}
+
+function ConversationMessages({
+  messages,
+  documentId,
+  text,
+}: ConversationMessagesProps) {
+  const chars = text ? Array.from(text) : null;
+
+  return (
+    <div className="space-y-3">
+      {messages.map((message) =>
+        message.role === "user" ? (
+          <div key={message.id} className="flex justify-end">
+            <p className="bg-brand-600 text-white text-sm rounded-lg px-3 py-2 max-w-[85%]">
+              {message.content}
+            </p>
+          </div>
+        ) : (
+          <div key={message.id} className="text-sm text-gray-800">
+            <p className="whitespace-pre-wrap">{message.content}</p>
+            {message.citations.map((citation, i) => (
+              <CitedPassage
+                key={i}
+                citation={citation}
+                chars={citation.document_id === documentId ? chars : null}
+              />
+            ))}
+          </div>
+        ),
+      )}
+    </div>
+  );
+}
+
+export default ConversationMessages;
@@ -0,0 +1,163 @@
+import { useCallback, useEffect, useState } from "react";
+import { Loader2, MessageSquare, Send, Trash2 } from "lucide-react";
+import toast from "react-hot-toast";
+import {
+  askQuestion,
+  deleteConversation,
+  getConversationMessages,
+  listConversations,
+} from "@/lib/commands";
+import type { Conversation, ConversationMessage } from "@/lib/commands";
+import ConversationMessages from "./ConversationMessages";
+
+interface QuestionPanelProps {
+  documentId: string;
+  text: string | null;
+}
+
+function QuestionPanel({ documentId, text }: QuestionPanelProps) {
+  const [conversations, setConversations] = useState<Conversation[]>([]);
+  const [conversationId, setConversationId] = useState<string | null>(null);
+  const [messages, setMessages] = useState<ConversationMessage[]>([]);
+  const [question, setQuestion] = useState("");
+  const [asking, setAsking] = useState(false);
+
+  useEffect(() => {
+    listConversations(documentId)
+      .then(setConversations)
+      .catch(() => setConversations([]));
+  }, [documentId]);
+
+  const handleSelect = useCallback(async (id: string) => {
+    setConversationId(id || null);
+    if (!id) {
+      setMessages([]);
+      return;
+    }
+    try {
+      setMessages(await getConversationMessages(id));
+    } catch (err) {
+      toast.error(err instanceof Error ? err.message : String(err));
+    }
+  }, []);
+
+  const handleAsk = useCallback(
+    async (e: React.FormEvent) => {
+      e.preventDefault();
+      if (!question.trim()) return;
+      try {
+        setAsking(true);
+        const result = await askQuestion(
+          question,
+          conversationId
+            ? { conversationId }
+            : { documentIds: [documentId] },
+        );
+        setMessages((previous) => [
+          ...previous,
+          result.question,
+          result.answer,
+        ]);
+        setConversationId(result.conversation.id);
+        setConversations((previous) => [
+          result.conversation,
+          ...previous.filter((c) => c.id !== result.conversation.id),
+        ]);
+        setQuestion("");
+      } catch (err) {
+        toast.error(err instanceof Error ? err.message : String(err));
+      } finally {
+        setAsking(false);
+      }
+    },
+    [question, conversationId, documentId],
+  );
+
+  const handleDelete = useCallback(async () => {
+    if (!conversationId) return;
+    try {
+      await deleteConversation(conversationId);
+      setConversations((previous) =>
+        previous.filter((c) => c.id !== conversationId),
+      );
+      setConversationId(null);
+      setMessages([]);
+      toast.success("Conversation deleted");
+    } catch (err) {
+      toast.error(err instanceof Error ? err.message : String(err));
+    }
+  }, [conversationId]);
+
+  return (
+    <div className="bg-white rounded-xl border border-gray-200">
+      <div className="flex items-center justify-between px-4 py-3 border-b border-gray-200">
+        <h2 className="font-semibold flex items-center gap-2">
+          <MessageSquare className="h-4 w-4" />
+          Ask the Document
+        </h2>
+        <div className="flex items-center gap-2">
+          <select
+            value={conversationId ?? ""}
+            onChange={(e) => handleSelect(e.target.value)}
+            className="border border-gray-300 rounded-lg px-2 py-1 text-sm max-w-xs"
+          >
+            <option value="">New conversation</option>
+            {conversations.map((conversation) => (
+              <option key={conversation.id} value={conversation.id}>
+                {conversation.title}
+              </option>
+            ))}
+          </select>
+          {conversationId && (
+            <button
+              onClick={handleDelete}
+              className="p-1.5 text-gray-400 hover:text-red-600 rounded-lg transition-colors"
+              title="Delete conversation"
+            >
+              <Trash2 className="h-4 w-4" />
+            </button>
+          )}
+        </div>
+      </div>
+
+      <div className="p-4 space-y-4">
+        {messages.length > 0 ? (
+          <div className="max-h-[32rem] overflow-y-auto">
+            <ConversationMessages
+              messages={messages}
+              documentId={documentId}
+              text={text}
+            />
+          </div>
+        ) : (
+          <p className="text-sm text-gray-400">
+            Answers cite the passages they rely on
+          </p>
+        )}
+
+        <form onSubmit={handleAsk} className="flex gap-2">
+          <input
+            value={question}
+            onChange={(e) => setQuestion(e.target.value)}
+            placeholder="What is the notice period for termination?"
+            className="flex-1 border border-gray-300 rounded-lg px-3 py-2 text-sm"
+          />
+          <button
+            type="submit"
+            disabled={asking || !question.trim()}
+            className="flex items-center gap-2 bg-brand-600 text-white px-4 py-2 rounded-lg hover:bg-brand-700 disabled:opacity-50 transition-colors text-sm font-medium"
+          >
+            {asking ? (
+              <Loader2 className="h-4 w-4 animate-spin" />
+            ) : (
+              <Send className="h-4 w-4" />
+            )}
+            Ask
+          </button>
+        </form>
+      </div>
+    </div>
+  );
+}
+
+export default QuestionPanel;
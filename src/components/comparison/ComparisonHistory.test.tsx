@@ -0,0 +1,101 @@
+import { fireEvent, render, screen } from "@testing-library/react";
+import { describe, it, expect, vi } from "vitest";
+import ComparisonHistory from "./ComparisonHistory";
+import type { Comparison } from "@/lib/commands";
+
+function comparison(overrides: Partial<Comparison> = {}): Comparison {
+  return {
+    id: "cmp-1",
+    document_a_id: "doc-1",
+    document_b_id: "doc-2",
+    template_id: null,
+    comparison_type: "document_vs_document",
+    differences: "[]",
+    redline: null,
+    summary: "Liability cap raised",
+    ai_provider: "mock",
+    data_policy: null,
+    ai_endpoint: null,
+    matter_id: null,
+    tags: ["Round 2"],
+    created_at: "2026-01-02 00:00:00",
+    ...overrides,
+  };
+}
+
+const names = {
+  "doc-1": "msa-v1.pdf",
+  "doc-2": "msa-v2.pdf",
+  "tpl-1": "Standard MSA",
+};
+
+function renderHistory(comparisons: Comparison[]) {
+  const handlers = {
+    onFilterChange: vi.fn(),
+    onOpen: vi.fn(),
+    onRerun: vi.fn(),
+    onDelete: vi.fn(),
+    onReport: vi.fn(),
+  };
+  render(
+    <ComparisonHistory
+      comparisons={comparisons}
+      names={names}
+      matters={[]}
+      tags={[
+        {
+          id: "tag-1",
+          name: "Round 2",
+          usage: 1,
+          created_at: "2026-01-01 00:00:00",
+        },
+      ]}
+      filter={{}}
+      {...handlers}
+    />,
+  );
+  return handlers;
+}
+
+describe("ComparisonHistory", () => {
+  it("lists documents and templates by name", () => {
+    renderHistory([
+      comparison(),
+      comparison({
+        id: "cmp-2",
+        document_b_id: null,
+        template_id: "tpl-1",
+        comparison_type: "document_vs_template",
+      }),
+    ]);
+    expect(screen.getByText("msa-v1.pdf → msa-v2.pdf")).toBeDefined();
+    expect(screen.getByText("msa-v1.pdf vs Standard MSA")).toBeDefined();
+    expect(screen.getByText("Against template")).toBeDefined();
+  });
+
+  it("runs actions on the chosen comparison", () => {
+    const handlers = renderHistory([comparison({ redline: "{}" })]);
+    fireEvent.click(screen.getByText("msa-v1.pdf → msa-v2.pdf"));
+    expect(handlers.onOpen).toHaveBeenCalledWith(
+      expect.objectContaining({ id: "cmp-1" }),
+    );
+    fireEvent.click(screen.getByText("Re-run"));
+    expect(handlers.onRerun).toHaveBeenCalledWith("cmp-1");
+    fireEvent.click(screen.getByText("Summary table"));
+    expect(handlers.onReport).toHaveBeenCalledWith(
+      "cmp-1",
+      "comparison_summary",
+    );
+    fireEvent.click(screen.getByTitle("Delete comparison"));
+    expect(handlers.onDelete).toHaveBeenCalledWith("cmp-1");
+  });
+
+  it("filters by tag", () => {
+    const handlers = renderHistory([]);
+    expect(screen.getByText("No comparisons yet")).toBeDefined();
+    fireEvent.change(screen.getByLabelText("Tag"), {
+      target: { value: "Round 2" },
+    });
+    expect(handlers.onFilterChange).toHaveBeenCalledWith({ tag: "Round 2" });
+  });
+});
@@ -0,0 +1,175 @@
+import {
+  FileText,
+  History,
+  Loader2,
+  RotateCw,
+  Table,
+  Trash2,
+} from "lucide-react";
+import type {
+  Comparison,
+  ComparisonFilter,
+  ComparisonReportType,
+  Matter,
+  Tag,
+} from "@/lib/commands";
+
+const TYPE_LABELS: Record<string, string> = {
+  document_vs_document: "Full text",
+  clause_aligned: "By clause",
+  document_vs_template: "Against template",
+};
+
+interface ComparisonHistoryProps {
+  comparisons: Comparison[];
+  /** Display names of documents and templates, by id. */
+  names: Record<string, string>;
+  matters: Matter[];
+  tags: Tag[];
+  filter: ComparisonFilter;
+  onFilterChange: (filter: ComparisonFilter) => void;
+  /** The comparison being re-run, if any. */
+  busyId?: string | null;
+  onOpen: (comparison: Comparison) => void;
+  onRerun: (comparisonId: string) => void;
+  onDelete: (comparisonId: string) => void;
+  onReport: (comparisonId: string, reportType: ComparisonReportType) => void;
+}
+
+function ComparisonHistory({
+  comparisons,
+  names,
+  matters,
+  tags,
+  filter,
+  onFilterChange,
+  busyId = null,
+  onOpen,
+  onRerun,
+  onDelete,
+  onReport,
+}: ComparisonHistoryProps) {
+  const name = (id: string | null) =>
+    id ? (names[id] ?? "Unknown") : "Deleted";
+
+  return (
+    <div className="bg-white rounded-xl border border-gray-200 mt-6">
+      <div className="flex items-center justify-between px-4 py-3 border-b border-gray-200">
+        <h2 className="font-semibold flex items-center gap-2">
+          <History className="h-4 w-4" />
+          History
+        </h2>
+        <div className="flex items-center gap-2">
+          <select
+            aria-label="Matter"
+            value={filter.matter_id ?? ""}
+            onChange={(e) =>
+              onFilterChange({ ...filter, matter_id: e.target.value || null })
+            }
+            className="border border-gray-300 rounded-lg px-2 py-1 text-xs"
+          >
+            <option value="">All matters</option>
+            {matters.map((matter) => (
+              <option key={matter.id} value={matter.id}>
+                {matter.name}
+              </option>
+            ))}
+          </select>
+          <select
+            aria-label="Tag"
+            value={filter.tag ?? ""}
+            onChange={(e) =>
+              onFilterChange({ ...filter, tag: e.target.value || null })
+            }
+            className="border border-gray-300 rounded-lg px-2 py-1 text-xs"
+          >
+            <option value="">All tags</option>
+            {tags.map((tag) => (
+              <option key={tag.id} value={tag.name}>
+                {tag.name}
+              </option>
+            ))}
+          </select>
+        </div>
+      </div>
+      {comparisons.length === 0 ? (
+        <p className="p-4 text-sm text-gray-400">No comparisons yet</p>
+      ) : (
+        <ul className="divide-y divide-gray-100">
+          {comparisons.map((comparison) => (
+            <li key={comparison.id} className="px-4 py-3">
+              <div className="flex items-center gap-2 text-sm">
+                <button
+                  onClick={() => onOpen(comparison)}
+                  className="text-brand-600 hover:text-brand-700 font-medium text-left"
+                >
+                  {comparison.template_id
+                    ? `${name(comparison.document_a_id)} vs ${name(comparison.template_id)}`
+                    : `${name(comparison.document_a_id)} → ${name(comparison.document_b_id)}`}
+                </button>
+                <span className="text-xs px-1.5 py-0.5 rounded bg-gray-100 text-gray-600">
+                  {TYPE_LABELS[comparison.comparison_type] ??
+                    comparison.comparison_type}
+                </span>
+                {comparison.tags.map((tag) => (
+                  <span
+                    key={tag}
+                    className="text-xs px-1.5 py-0.5 rounded bg-blue-50 text-blue-700"
+                  >
+                    {tag}
+                  </span>
+                ))}
+                <span className="text-xs text-gray-400 ml-auto">
+                  {comparison.created_at}
+                </span>
+              </div>
+              {comparison.summary && (
+                <p className="text-xs text-gray-500 mt-1">
+                  {comparison.summary}
+                </p>
+              )}
+              <div className="flex items-center gap-1 mt-2">
+                <button
+                  onClick={() => onRerun(comparison.id)}
+                  disabled={busyId !== null}
+                  className="flex items-center gap-1 text-xs text-gray-600 hover:text-gray-900 px-2 py-1 disabled:opacity-50"
+                >
+                  {busyId === comparison.id ? (
+                    <Loader2 className="h-3 w-3 animate-spin" />
+                  ) : (
+                    <RotateCw className="h-3 w-3" />
+                  )}
+                  Re-run
+                </button>
+                <button
+                  onClick={() => onReport(comparison.id, "comparison_redline")}
+                  disabled={!comparison.redline}
+                  className="flex items-center gap-1 text-xs text-gray-600 hover:text-gray-900 px-2 py-1 disabled:opacity-50"
+                >
+                  <FileText className="h-3 w-3" />
+                  Redline report
+                </button>
+                <button
+                  onClick={() => onReport(comparison.id, "comparison_summary")}
+                  className="flex items-center gap-1 text-xs text-gray-600 hover:text-gray-900 px-2 py-1"
+                >
+                  <Table className="h-3 w-3" />
+                  Summary table
+                </button>
+                <button
+                  onClick={() => onDelete(comparison.id)}
+                  className="p-1 text-red-400 hover:text-red-600 hover:bg-red-50 rounded ml-auto"
+                  title="Delete comparison"
+                >
+                  <Trash2 className="h-3 w-3" />
+                </button>
+              </div>
+            </li>
+          ))}
+        </ul>
+      )}
+    </div>
+  );
+}
+
+export default ComparisonHistory;
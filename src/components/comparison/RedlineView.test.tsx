@@ -0,0 +1,58 @@
+import { fireEvent, render, screen } from "@testing-library/react";
+import { describe, it, expect } from "vitest";
+import RedlineView from "./RedlineView";
+import type { Redline } from "@/lib/commands";
+
+const redline: Redline = {
+  sections: [
+    {
+      heading_a: "1. Services",
+      heading_b: "1. Services",
+      status: "unchanged",
+      segments: [{ op: "equal", text: "1. Services. Supplier provides support." }],
+    },
+    {
+      heading_a: "2. Termination",
+      heading_b: "2. Termination",
+      status: "modified",
+      segments: [
+        { op: "equal", text: "2. Termination. Either party" },
+        { op: "delete", text: " shall", change_id: "C1" },
+        { op: "insert", text: " may", change_id: "C1" },
+        { op: "equal", text: " terminate on notice." },
+      ],
+    },
+    {
+      heading_a: "4. Notices",
+      heading_b: "3. Notices",
+      status: "moved",
+      segments: [{ op: "equal", text: "3. Notices. In writing." }],
+    },
+  ],
+  changes: [],
+  stats: { insertions: 0, deletions: 0, replacements: 1, moves: 1 },
+};
+
+describe("RedlineView", () => {
+  it("marks deleted and inserted text", () => {
+    render(<RedlineView redline={redline} activeChange="C1" />);
+    expect(screen.getByText("shall").tagName).toBe("DEL");
+    expect(screen.getByText("may").tagName).toBe("INS");
+    expect(screen.getByText("may")).toHaveClass("ring-2");
+    expect(screen.getByText(/1 replaced · 1 moved/)).toBeInTheDocument();
+  });
+
+  it("shows where a moved section came from", () => {
+    render(<RedlineView redline={redline} />);
+    expect(screen.getByText("moved")).toBeInTheDocument();
+    expect(screen.getByText("was 4. Notices")).toBeInTheDocument();
+  });
+
+  it("hides unchanged sections until asked", () => {
+    render(<RedlineView redline={redline} />);
+    expect(screen.queryByText("1. Services")).not.toBeInTheDocument();
+    fireEvent.click(screen.getByText("Show 1 unchanged section"));
+    expect(screen.getByText("1. Services")).toBeInTheDocument();
+    expect(screen.getByText("Hide unchanged sections")).toBeInTheDocument();
+  });
+});
@@ -0,0 +1,130 @@
+import { useState } from "react";
+import { FileDiff } from "lucide-react";
+import type { Redline, RedlineSection, RedlineSegment } from "@/lib/commands";
+
+interface RedlineViewProps {
+  redline: Redline;
+  /** The change to highlight, e.g. the difference card being hovered. */
+  activeChange?: string | null;
+}
+
+const STATUS_STYLES: Record<RedlineSection["status"], string> = {
+  unchanged: "bg-gray-100 text-gray-600",
+  modified: "bg-yellow-100 text-yellow-800",
+  added: "bg-green-100 text-green-800",
+  removed: "bg-red-100 text-red-800",
+  moved: "bg-blue-100 text-blue-800",
+};
+
+function sectionTitle(section: RedlineSection) {
+  return section.heading_b ?? section.heading_a ?? "Preamble";
+}
+
+function SegmentText({
+  segment,
+  active,
+}: {
+  segment: RedlineSegment;
+  active: boolean;
+}) {
+  const ring = active ? " ring-2 ring-brand-400" : "";
+  if (segment.op === "delete") {
+    return (
+      <del
+        className={`bg-red-100 text-red-700 line-through rounded-sm${ring}`}
+        title={segment.change_id}
+      >
+        {segment.text}
+      </del>
+    );
+  }
+  if (segment.op === "insert") {
+    return (
+      <ins
+        className={`bg-green-100 text-green-800 no-underline rounded-sm${ring}`}
+        title={segment.change_id}
+      >
+        {segment.text}
+      </ins>
+    );
+  }
+  return <span>{segment.text}</span>;
+}
+
+function RedlineView({ redline, activeChange = null }: RedlineViewProps) {
+  const [showUnchanged, setShowUnchanged] = useState(false);
+  const { stats } = redline;
+  const hidden = redline.sections.filter((s) => s.status === "unchanged");
+  const sections = showUnchanged
+    ? redline.sections
+    : redline.sections.filter((s) => s.status !== "unchanged");
+
+  return (
+    <div className="bg-white rounded-xl border border-gray-200">
+      <div className="flex items-center justify-between px-4 py-3 border-b border-gray-200">
+        <h2 className="font-semibold flex items-center gap-2">
+          <FileDiff className="h-4 w-4" />
+          Redline
+        </h2>
+        <p className="text-xs text-gray-500">
+          {stats.insertions} inserted · {stats.deletions} deleted ·{" "}
+          {stats.replacements} replaced · {stats.moves} moved
+        </p>
+      </div>
+      <div className="divide-y divide-gray-100">
+        {sections.map((section, i) => (
+          <div key={i} className="px-4 py-3">
+            <div className="flex items-center gap-2 mb-1">
+              <span className="text-xs font-medium text-gray-700">
+                {sectionTitle(section)}
+              </span>
+              {section.status !== "unchanged" && (
+                <span
+                  className={`text-xs px-1.5 py-0.5 rounded ${STATUS_STYLES[section.status]}`}
+                >
+                  {section.status}
+                </span>
+              )}
+              {section.status === "moved" &&
+                section.heading_a &&
+                section.heading_a !== section.heading_b && (
+                  <span className="text-xs text-gray-400">
+                    was {section.heading_a}
+                  </span>
+                )}
+            </div>
+            <p className="text-sm text-gray-700 leading-relaxed">
+              {section.segments.map((segment, j) => (
+                <SegmentText
+                  key={j}
+                  segment={segment}
+                  active={
+                    activeChange !== null &&
+                    segment.change_id === activeChange
+                  }
+                />
+              ))}
+            </p>
+          </div>
+        ))}
+        {sections.length === 0 && (
+          <p className="px-4 py-3 text-sm text-gray-500">
+            The documents have the same text
+          </p>
+        )}
+      </div>
+      {hidden.length > 0 && (
+        <button
+          onClick={() => setShowUnchanged(!showUnchanged)}
+          className="w-full px-4 py-2 text-xs text-brand-600 hover:text-brand-700 border-t border-gray-200"
+        >
+          {showUnchanged
+            ? "Hide unchanged sections"
+            : `Show ${hidden.length} unchanged ${hidden.length === 1 ? "section" : "sections"}`}
+        </button>
+      )}
+    </div>
+  );
+}
+
+export default RedlineView;
@@ -25,7 +25,10 @@ describe("MainLayout", () => {
     expect(screen.getByText("Dashboard")).toBeInTheDocument();
     expect(screen.getByText("Upload")).toBeInTheDocument();
     expect(screen.getByText("Comparison")).toBeInTheDocument();
+    expect(screen.getByText("Matters")).toBeInTheDocument();
     expect(screen.getByText("Templates")).toBeInTheDocument();
+    expect(screen.getByText("Search")).toBeInTheDocument();
+    expect(screen.getByText("Trash")).toBeInTheDocument();
     expect(screen.getByText("Settings")).toBeInTheDocument();
   });
 });
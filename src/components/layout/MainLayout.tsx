@@ -7,13 +7,19 @@ import {
   Scale,
   GitCompareArrows,
   FileCheck,
+  Trash2,
+  Search,
+  Briefcase,
 } from "lucide-react";
 
 const navItems = [
   { to: "/", label: "Dashboard", icon: LayoutDashboard },
   { to: "/upload", label: "Upload", icon: Upload },
   { to: "/compare", label: "Comparison", icon: GitCompareArrows },
+  { to: "/matters", label: "Matters", icon: Briefcase },
   { to: "/templates", label: "Templates", icon: FileCheck },
+  { to: "/search", label: "Search", icon: Search },
+  { to: "/trash", label: "Trash", icon: Trash2 },
   { to: "/settings", label: "Settings", icon: Settings },
 ];
 
@@ -0,0 +1,26 @@
+import { fireEvent, render, screen } from "@testing-library/react";
+import { describe, it, expect, vi } from "vitest";
+import TagEditor from "./TagEditor";
+
+describe("TagEditor", () => {
+  it("adds a new tag and ignores case-insensitive duplicates", () => {
+    const onChange = vi.fn();
+    render(<TagEditor tags={["Urgent"]} onChange={onChange} />);
+    const input = screen.getByPlaceholderText("Add tag");
+
+    fireEvent.change(input, { target: { value: " urgent " } });
+    fireEvent.click(screen.getByLabelText("Add tag"));
+    expect(onChange).not.toHaveBeenCalled();
+
+    fireEvent.change(input, { target: { value: "Vendor" } });
+    fireEvent.click(screen.getByLabelText("Add tag"));
+    expect(onChange).toHaveBeenCalledWith(["Urgent", "Vendor"]);
+  });
+
+  it("removes a tag", () => {
+    const onChange = vi.fn();
+    render(<TagEditor tags={["Urgent", "Vendor"]} onChange={onChange} />);
+    fireEvent.click(screen.getByLabelText("Remove Urgent"));
+    expect(onChange).toHaveBeenCalledWith(["Vendor"]);
+  });
+});
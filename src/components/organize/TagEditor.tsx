@@ -0,0 +1,66 @@
+import { useState } from "react";
+import { Plus, X } from "lucide-react";
+
+interface TagEditorProps {
+  tags: string[];
+  /** Existing tag names offered as suggestions. */
+  suggestions?: string[];
+  onChange: (tags: string[]) => void;
+}
+
+function TagEditor({ tags, suggestions = [], onChange }: TagEditorProps) {
+  const [draft, setDraft] = useState("");
+
+  const add = (e: React.FormEvent) => {
+    e.preventDefault();
+    const name = draft.trim();
+    setDraft("");
+    if (!name) return;
+    const lower = name.toLowerCase();
+    if (tags.some((tag) => tag.toLowerCase() === lower)) return;
+    onChange([...tags, name]);
+  };
+
+  return (
+    <div className="flex flex-wrap items-center gap-2">
+      {tags.map((tag) => (
+        <span
+          key={tag}
+          className="flex items-center gap-1 text-xs px-2 py-0.5 rounded-full bg-gray-100 text-gray-700"
+        >
+          {tag}
+          <button
+            onClick={() => onChange(tags.filter((t) => t !== tag))}
+            className="text-gray-400 hover:text-gray-700"
+            aria-label={`Remove ${tag}`}
+          >
+            <X className="h-3 w-3" />
+          </button>
+        </span>
+      ))}
+      <form onSubmit={add} className="flex items-center gap-1">
+        <input
+          value={draft}
+          onChange={(e) => setDraft(e.target.value)}
+          list="tag-suggestions"
+          placeholder="Add tag"
+          className="border border-gray-300 rounded-lg px-2 py-0.5 text-xs w-28"
+        />
+        <datalist id="tag-suggestions">
+          {suggestions.map((name) => (
+            <option key={name} value={name} />
+          ))}
+        </datalist>
+        <button
+          type="submit"
+          className="p-1 text-gray-400 hover:text-gray-700"
+          aria-label="Add tag"
+        >
+          <Plus className="h-3 w-3" />
+        </button>
+      </form>
+    </div>
+  );
+}
+
+export default TagEditor;
@@ -0,0 +1,94 @@
+import { fireEvent, render, screen } from "@testing-library/react";
+import { MemoryRouter } from "react-router";
+import { describe, it, expect, vi } from "vitest";
+import VersionHistory from "./VersionHistory";
+import type { DocumentVersion } from "@/lib/commands";
+
+function version(
+  n: number,
+  overrides: Partial<DocumentVersion> = {},
+): DocumentVersion {
+  return {
+    id: `doc-${n}`,
+    filename: `msa-v${n}.pdf`,
+    contract_type: "service_agreement",
+    file_size: 1024,
+    page_count: 3,
+    processing_status: "extracted",
+    error_message: null,
+    created_at: "2026-01-01 00:00:00",
+    updated_at: "2026-01-01 00:00:00",
+    local_only: false,
+    matter_id: null,
+    legal_hold: false,
+    has_text: true,
+    tags: [],
+    risk_score: null,
+    risk_level: null,
+    family_id: "doc-1",
+    version_number: n,
+    version_label: null,
+    comparison: null,
+    ...overrides,
+  };
+}
+
+const comparison = {
+  id: "cmp-1",
+  document_a_id: "doc-1",
+  document_b_id: "doc-2",
+  template_id: null,
+  comparison_type: "document_vs_document",
+  differences: JSON.stringify([{}, {}]),
+  redline: null,
+  summary: "Liability cap raised",
+  ai_provider: "mock",
+  data_policy: null,
+  ai_endpoint: null,
+  matter_id: null,
+  tags: [],
+  created_at: "2026-01-02 00:00:00",
+};
+
+describe("VersionHistory", () => {
+  const versions = [
+    version(1, { version_label: "Counterparty draft" }),
+    version(2, { comparison }),
+    version(3),
+  ];
+
+  it("lists versions with labels and comparison summaries", () => {
+    render(
+      <MemoryRouter>
+        <VersionHistory
+          versions={versions}
+          currentId="doc-2"
+          onCompare={() => {}}
+        />
+      </MemoryRouter>,
+    );
+    expect(screen.getByText("Counterparty draft")).toBeInTheDocument();
+    expect(screen.getByText("2 changes from v1")).toBeInTheDocument();
+    expect(screen.getByText("msa-v1.pdf").closest("a")).toHaveAttribute(
+      "href",
+      "/documents/doc-1",
+    );
+    // The current version is not a link
+    expect(screen.getByText("msa-v2.pdf").closest("a")).toBeNull();
+  });
+
+  it("offers to compare versions that have no comparison", () => {
+    const onCompare = vi.fn();
+    render(
+      <MemoryRouter>
+        <VersionHistory
+          versions={versions}
+          currentId="doc-3"
+          onCompare={onCompare}
+        />
+      </MemoryRouter>,
+    );
+    fireEvent.click(screen.getByText("Compare with v2"));
+    expect(onCompare).toHaveBeenCalledWith("doc-3");
+  });
+});
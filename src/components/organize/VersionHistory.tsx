@@ -0,0 +1,109 @@
+import { Link } from "react-router";
+import { GitCompareArrows, History, Loader2, Plus } from "lucide-react";
+import type { DocumentVersion } from "@/lib/commands";
+import { STATUS_LABELS } from "@/types";
+
+interface VersionHistoryProps {
+  versions: DocumentVersion[];
+  /** The document being viewed, highlighted in the list. */
+  currentId: string;
+  /** The version whose comparison is running, if any. */
+  comparingId?: string | null;
+  onCompare: (documentId: string) => void;
+}
+
+function countDifferences(json: string) {
+  try {
+    return (JSON.parse(json) as unknown[]).length;
+  } catch {
+    return 0;
+  }
+}
+
+function VersionHistory({
+  versions,
+  currentId,
+  comparingId = null,
+  onCompare,
+}: VersionHistoryProps) {
+  return (
+    <div className="bg-white rounded-xl border border-gray-200">
+      <div className="flex items-center justify-between px-4 py-3 border-b border-gray-200">
+        <h2 className="font-semibold flex items-center gap-2">
+          <History className="h-4 w-4" />
+          Versions
+        </h2>
+        <Link
+          to={`/upload?versionOf=${currentId}`}
+          className="flex items-center gap-1 text-sm text-brand-600 hover:text-brand-700 font-medium"
+        >
+          <Plus className="h-3 w-3" />
+          New version
+        </Link>
+      </div>
+      <ol className="divide-y divide-gray-100">
+        {versions.map((version, i) => (
+          <li
+            key={version.id}
+            className={`px-4 py-3 ${version.id === currentId ? "bg-brand-50" : ""}`}
+          >
+            <div className="flex items-center gap-2 text-sm">
+              <span className="font-semibold text-gray-900">
+                v{version.version_number}
+              </span>
+              {version.id === currentId ? (
+                <span className="text-gray-900">{version.filename}</span>
+              ) : (
+                <Link
+                  to={`/documents/${version.id}`}
+                  className="text-brand-600 hover:text-brand-700"
+                >
+                  {version.filename}
+                </Link>
+              )}
+              {version.version_label && (
+                <span className="text-xs px-2 py-0.5 rounded-full bg-gray-100 text-gray-600">
+                  {version.version_label}
+                </span>
+              )}
+              <span className="text-xs text-gray-400 ml-auto">
+                {new Date(version.created_at).toLocaleDateString()}
+              </span>
+            </div>
+            {i > 0 &&
+              (version.comparison ? (
+                <p className="text-xs text-gray-600 mt-1">
+                  <span className="font-medium">
+                    {countDifferences(version.comparison.differences)} changes
+                    from v{versions[i - 1]?.version_number}
+                  </span>
+                  {version.comparison.summary &&
+                    ` — ${version.comparison.summary}`}
+                </p>
+              ) : (
+                <button
+                  onClick={() => onCompare(version.id)}
+                  disabled={comparingId !== null || !version.has_text}
+                  className="flex items-center gap-1 text-xs text-brand-600 hover:text-brand-700 disabled:opacity-50 mt-1"
+                  title={
+                    version.has_text
+                      ? undefined
+                      : `Text not available (${STATUS_LABELS[version.processing_status]})`
+                  }
+                >
+                  {comparingId === version.id ? (
+                    <Loader2 className="h-3 w-3 animate-spin" />
+                  ) : (
+                    <GitCompareArrows className="h-3 w-3" />
+                  )}
+                  Compare with v{versions[i - 1]?.version_number}
+                </button>
+              ))}
+          </li>
+        ))}
+      </ol>
+    </div>
+  );
+}
+
+export default VersionHistory;
@@ -0,0 +1,61 @@
+import { useState } from "react";
+import { Lock } from "lucide-react";
+import toast from "react-hot-toast";
+import { unlockWorkspace } from "@/lib/commands";
+
+interface UnlockWorkspaceProps {
+  onUnlocked: () => void;
+}
+
+function UnlockWorkspace({ onUnlocked }: UnlockWorkspaceProps) {
+  const [passphrase, setPassphrase] = useState("");
+  const [unlocking, setUnlocking] = useState(false);
+
+  async function handleUnlock(e: React.FormEvent) {
+    e.preventDefault();
+    setUnlocking(true);
+    try {
+      await unlockWorkspace(passphrase);
+      onUnlocked();
+    } catch (err) {
+      toast.error(err instanceof Error ? err.message : String(err));
+    } finally {
+      setPassphrase("");
+      setUnlocking(false);
+    }
+  }
+
+  return (
+    <div className="flex items-center justify-center h-screen bg-gray-50">
+      <form
+        onSubmit={handleUnlock}
+        className="bg-white rounded-xl border border-gray-200 p-8 w-full max-w-sm space-y-4"
+      >
+        <div className="flex items-center gap-3">
+          <Lock className="h-6 w-6 text-brand-600" />
+          <h1 className="text-lg font-bold">Workspace Locked</h1>
+        </div>
+        <p className="text-sm text-gray-500">
+          This workspace is encrypted. Enter its passphrase to continue.
+        </p>
+        <input
+          type="password"
+          autoFocus
+          value={passphrase}
+          onChange={(e) => setPassphrase(e.target.value)}
+          className="w-full border border-gray-300 rounded-lg px-3 py-2 text-sm focus:outline-none focus:ring-2 focus:ring-brand-500 focus:border-transparent"
+          placeholder="Passphrase"
+        />
+        <button
+          type="submit"
+          disabled={unlocking || !passphrase}
+          className="w-full bg-brand-600 text-white px-6 py-2.5 rounded-lg font-medium hover:bg-brand-700 transition-colors text-sm disabled:opacity-50"
+        >
+          {unlocking ? "Unlocking..." : "Unlock"}
+        </button>
+      </form>
+    </div>
+  );
+}
+
+export default UnlockWorkspace;
@@ -17,19 +17,26 @@ vi.mock("@/lib/commands", () => ({
 const sampleDoc = {
   id: "doc-1",
   filename: "contract.pdf",
-  original_path: "/tmp/contract.pdf",
-  stored_path: "/data/contract.pdf",
-  file_hash: "abc123",
   file_size: 1024,
   contract_type: "nda" as const,
-  raw_text: null,
   page_count: null,
   processing_status: "pending" as const,
   error_message: null,
   created_at: "2026-01-01T00:00:00Z",
   updated_at: "2026-01-01T00:00:00Z",
+  local_only: false,
+  matter_id: null,
+  legal_hold: false,
+  has_text: false,
+  tags: [],
+  risk_score: null,
+  risk_level: null,
 };
 
+function page(items: (typeof sampleDoc)[], next_cursor: string | null = null) {
+  return { items, next_cursor };
+}
+
 const sampleStats = {
   total: 1,
   analyzed: 0,
@@ -43,7 +50,7 @@ describe("useDocuments", () => {
   });
 
   it("loads documents and stats on mount", async () => {
-    mocks.listDocuments.mockResolvedValue([sampleDoc]);
+    mocks.listDocuments.mockResolvedValue(page([sampleDoc]));
     mocks.getDocumentStats.mockResolvedValue(sampleStats);
 
     const { result } = renderHook(() => useDocuments());
@@ -57,6 +64,61 @@ describe("useDocuments", () => {
     expect(result.current.error).toBeNull();
   });
 
+  it("passes the query to list and the filter to stats, reloading when it changes", async () => {
+    mocks.listDocuments.mockResolvedValue(page([sampleDoc]));
+    mocks.getDocumentStats.mockResolvedValue(sampleStats);
+
+    const { result, rerender } = renderHook(
+      ({ tag }: { tag: string | null }) =>
+        useDocuments({ tag, sort: "filename", direction: "asc" }),
+      { initialProps: { tag: null as string | null } },
+    );
+
+    await waitFor(() => {
+      expect(result.current.loading).toBe(false);
+    });
+    expect(mocks.listDocuments).toHaveBeenLastCalledWith({
+      tag: null,
+      sort: "filename",
+      direction: "asc",
+      cursor: null,
+    });
+
+    rerender({ tag: "Urgent" });
+
+    await waitFor(() => {
+      expect(mocks.getDocumentStats).toHaveBeenLastCalledWith({
+        tag: "Urgent",
+      });
+    });
+    expect(mocks.listDocuments).toHaveBeenCalledTimes(2);
+  });
+
+  it("appends the next page when loading more", async () => {
+    const second = { ...sampleDoc, id: "doc-2" };
+    mocks.listDocuments
+      .mockResolvedValueOnce(page([sampleDoc], "cursor-1"))
+      .mockResolvedValueOnce(page([second]));
+    mocks.getDocumentStats.mockResolvedValue(sampleStats);
+
+    const { result } = renderHook(() => useDocuments());
+
+    await waitFor(() => {
+      expect(result.current.loading).toBe(false);
+    });
+    expect(result.current.hasMore).toBe(true);
+
+    await act(async () => {
+      await result.current.loadMore();
+    });
+
+    expect(mocks.listDocuments).toHaveBeenLastCalledWith({
+      cursor: "cursor-1",
+    });
+    expect(result.current.documents).toEqual([sampleDoc, second]);
+    expect(result.current.hasMore).toBe(false);
+  });
+
   it("sets an error when initial load fails", async () => {
     mocks.listDocuments.mockRejectedValue(new Error("load failed"));
     mocks.getDocumentStats.mockResolvedValue(sampleStats);
@@ -74,8 +136,8 @@ describe("useDocuments", () => {
 
   it("deletes a document and refreshes data", async () => {
     mocks.listDocuments
-      .mockResolvedValueOnce([sampleDoc])
-      .mockResolvedValueOnce([]);
+      .mockResolvedValueOnce(page([sampleDoc]))
+      .mockResolvedValueOnce(page([]));
     mocks.getDocumentStats
       .mockResolvedValueOnce(sampleStats)
       .mockResolvedValueOnce({ ...sampleStats, total: 0, pending: 0 });
@@ -97,7 +159,7 @@ describe("useDocuments", () => {
   });
 
   it("surfaces delete errors and preserves existing data", async () => {
-    mocks.listDocuments.mockResolvedValue([sampleDoc]);
+    mocks.listDocuments.mockResolvedValue(page([sampleDoc]));
     mocks.getDocumentStats.mockResolvedValue(sampleStats);
     mocks.deleteDocument.mockRejectedValue(new Error("delete failed"));
 
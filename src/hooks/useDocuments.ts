@@ -1,35 +1,65 @@
 import { useCallback, useEffect, useState } from "react";
-import type { Document, DocumentStats } from "@/types";
+import type {
+  DocumentQuery,
+  DocumentStats,
+  DocumentSummary,
+} from "@/types";
 import {
   listDocuments,
   getDocumentStats,
   deleteDocument as deleteDocCmd,
 } from "@/lib/commands";
 
-export function useDocuments() {
-  const [documents, setDocuments] = useState<Document[]>([]);
+export function useDocuments(query: DocumentQuery = {}) {
+  const [documents, setDocuments] = useState<DocumentSummary[]>([]);
+  const [nextCursor, setNextCursor] = useState<string | null>(null);
   const [stats, setStats] = useState<DocumentStats | null>(null);
   const [loading, setLoading] = useState(true);
   const [error, setError] = useState<string | null>(null);
 
+  // Callers usually pass a fresh object each render; reload only when it changes
+  const serialized = JSON.stringify(query);
+
   const refresh = useCallback(async () => {
+    const { sort, direction, limit, ...filter } = JSON.parse(
+      serialized,
+    ) as DocumentQuery;
     try {
       setLoading(true);
       setError(null);
-      const [docs, docStats] = await Promise.all([
-        listDocuments(),
-        getDocumentStats(),
+      const [page, docStats] = await Promise.all([
+        listDocuments({ ...filter, sort, direction, limit, cursor: null }),
+        getDocumentStats(filter),
       ]);
-      setDocuments(docs);
+      setDocuments(page.items);
+      setNextCursor(page.next_cursor);
       setStats(docStats);
     } catch (err) {
       setDocuments([]);
+      setNextCursor(null);
       setStats(null);
       setError(err instanceof Error ? err.message : String(err));
     } finally {
       setLoading(false);
     }
-  }, []);
+  }, [serialized]);
+
+  const loadMore = useCallback(async () => {
+    if (!nextCursor) return;
+    try {
+      setLoading(true);
+      const page = await listDocuments({
+        ...(JSON.parse(serialized) as DocumentQuery),
+        cursor: nextCursor,
+      });
+      setDocuments((previous) => [...previous, ...page.items]);
+      setNextCursor(page.next_cursor);
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+    } finally {
+      setLoading(false);
+    }
+  }, [serialized, nextCursor]);
 
   const removeDocument = useCallback(
     async (id: string) => {
@@ -50,5 +80,14 @@ export function useDocuments() {
     refresh();
   }, [refresh]);
 
-  return { documents, stats, loading, error, refresh, removeDocument };
+  return {
+    documents,
+    stats,
+    loading,
+    error,
+    hasMore: nextCursor !== null,
+    refresh,
+    loadMore,
+    removeDocument,
+  };
 }
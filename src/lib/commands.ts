@@ -1,12 +1,26 @@
 import { invoke } from "@tauri-apps/api/core";
 import type {
+  ContractType,
+  DataPolicy,
   Document,
+  DocumentFilter,
+  DocumentPage,
+  DocumentQuery,
   DocumentStats,
+  DocumentSummary,
   Extraction,
+  RedactionLogEntry,
   RiskAssessment,
+  RiskLevel,
 } from "@/types";
 
 // Documents
+
+/** Opens the native file picker; only a path chosen here can be uploaded. */
+export async function pickDocumentFile(): Promise<string | null> {
+  return invoke<string | null>("pick_document_file");
+}
+
 export async function uploadDocument(
   filePath: string,
   contractType: string,
@@ -24,16 +38,79 @@ export async function getDocument(documentId: string): Promise<Document> {
   return invoke<Document>("get_document", { documentId });
 }
 
-export async function listDocuments(): Promise<Document[]> {
-  return invoke<Document[]>("list_documents");
+export async function listDocuments(
+  query: DocumentQuery | null = null,
+): Promise<DocumentPage> {
+  return invoke<DocumentPage>("list_documents", { query });
+}
+
+/** Follows the cursor to the last page; for pickers that need every document. */
+export async function listAllDocuments(
+  query: DocumentQuery = {},
+): Promise<DocumentSummary[]> {
+  const items: DocumentSummary[] = [];
+  let cursor: string | null = null;
+  do {
+    const page: DocumentPage = await listDocuments({
+      ...query,
+      cursor,
+      limit: 200,
+    });
+    items.push(...page.items);
+    cursor = page.next_cursor;
+  } while (cursor);
+  return items;
 }
 
 export async function deleteDocument(documentId: string): Promise<void> {
   return invoke<void>("delete_document", { documentId });
 }
 
-export async function getDocumentStats(): Promise<DocumentStats> {
-  return invoke<DocumentStats>("get_document_stats");
+export async function setDocumentLocalOnly(
+  documentId: string,
+  localOnly: boolean,
+): Promise<Document> {
+  return invoke<Document>("set_document_local_only", { documentId, localOnly });
+}
+
+export async function listTrash(): Promise<Document[]> {
+  return invoke<Document[]>("list_trash");
+}
+
+export async function restoreDocument(documentId: string): Promise<Document> {
+  return invoke<Document>("restore_document", { documentId });
+}
+
+/** Permanently deletes a trashed document; cannot be undone. */
+export async function purgeDocument(documentId: string): Promise<void> {
+  return invoke<void>("purge_document", { documentId });
+}
+
+export async function setDocumentLegalHold(
+  documentId: string,
+  hold: boolean,
+  reason: string | null = null,
+): Promise<Document> {
+  return invoke<Document>("set_document_legal_hold", {
+    documentId,
+    hold,
+    reason,
+  });
+}
+
+export interface RetentionReport {
+  purged: string[];
+  text_removed: string[];
+}
+
+export async function applyRetention(): Promise<RetentionReport> {
+  return invoke<RetentionReport>("apply_retention");
+}
+
+export async function getDocumentStats(
+  filter: DocumentFilter | null = null,
+): Promise<DocumentStats> {
+  return invoke<DocumentStats>("get_document_stats", { filter });
 }
 
 // Settings
@@ -45,6 +122,107 @@ export async function setSetting(key: string, value: string): Promise<void> {
   return invoke<void>("set_setting", { key, value });
 }
 
+export const SETTINGS_CHANGED_EVENT = "settings-changed";
+
+export type SettingGroup = "ai" | "ollama" | "cloud" | "privacy" | "developer";
+
+export type SettingType =
+  | { type: "choice"; options: string[] }
+  | { type: "boolean" }
+  | { type: "integer"; min: number; max: number }
+  | { type: "number"; min: number; max: number }
+  | { type: "url" }
+  | { type: "model_name" }
+  | { type: "duration" }
+  | { type: "patterns" }
+  | { type: "path" };
+
+export type SettingEntry = SettingType & {
+  key: string;
+  group: SettingGroup;
+  default: string | null;
+  description: string;
+  value: string | number | boolean | null;
+  is_default: boolean;
+  error: string | null;
+};
+
+export interface SettingsSection {
+  group: SettingGroup;
+  settings: SettingEntry[];
+}
+
+export interface SettingsChanged {
+  keys: string[];
+}
+
+export async function getAllSettings(): Promise<SettingsSection[]> {
+  return invoke<SettingsSection[]>("get_all_settings");
+}
+
+// Encryption
+export interface EncryptionStatus {
+  enabled: boolean;
+  unlocked: boolean;
+  rotated_at: number | null;
+}
+
+export async function getEncryptionStatus(): Promise<EncryptionStatus> {
+  return invoke<EncryptionStatus>("get_encryption_status");
+}
+
+export async function unlockWorkspace(
+  passphrase: string,
+): Promise<EncryptionStatus> {
+  return invoke<EncryptionStatus>("unlock_workspace", { passphrase });
+}
+
+export async function enableEncryption(
+  passphrase: string,
+): Promise<EncryptionStatus> {
+  return invoke<EncryptionStatus>("enable_encryption", { passphrase });
+}
+
+export async function rotateEncryptionKey(
+  currentPassphrase: string,
+  newPassphrase: string,
+): Promise<EncryptionStatus> {
+  return invoke<EncryptionStatus>("rotate_encryption_key", {
+    currentPassphrase,
+    newPassphrase,
+  });
+}
+
+// Secrets
+export type SecretKey = "claude_api_key" | "openai_api_key";
+
+export interface SecretsStatus {
+  backend: "keyring" | "encrypted_file" | "memory";
+  locked: boolean;
+  vault_exists: boolean;
+  keys: { key: SecretKey; is_set: boolean }[];
+}
+
+export async function getSecretsStatus(): Promise<SecretsStatus> {
+  return invoke<SecretsStatus>("get_secrets_status");
+}
+
+export async function unlockSecrets(passphrase: string): Promise<SecretsStatus> {
+  return invoke<SecretsStatus>("unlock_secrets", { passphrase });
+}
+
+export async function setSecret(key: SecretKey, value: string): Promise<void> {
+  return invoke<void>("set_secret", { key, value });
+}
+
+export async function hasSecret(key: SecretKey): Promise<boolean> {
+  return invoke<boolean>("has_secret", { key });
+}
+
+export async function deleteSecret(key: SecretKey): Promise<void> {
+  return invoke<void>("delete_secret", { key });
+}
+
 // Analysis
 export interface AnalysisResult {
   extraction_id: string;
@@ -74,6 +252,12 @@ export async function getRiskAssessments(
   return invoke<RiskAssessment[]>("get_risk_assessments", { documentId });
 }
 
+export async function getRedactionLog(
+  documentId: string,
+): Promise<RedactionLogEntry[]> {
+  return invoke<RedactionLogEntry[]>("get_redaction_log", { documentId });
+}
+
 // Comparison
 export interface Comparison {
   id: string;
@@ -82,11 +266,66 @@ export interface Comparison {
   template_id: string | null;
   comparison_type: string;
   differences: string;
+  /** JSON `Redline`; null for comparisons made before redlines were kept. */
+  redline: string | null;
   summary: string | null;
   ai_provider: string | null;
+  data_policy: DataPolicy | null;
+  ai_endpoint: string | null;
+  matter_id: string | null;
+  tags: string[];
   created_at: string;
 }
 
+export interface Difference {
+  change_id: string | null;
+  clause_type: string | null;
+  /** Our position on a deviation from a template. */
+  position: "acceptable" | "negotiable" | "unacceptable" | null;
+  category: string;
+  diff_type: string;
+  description: string;
+  text_a: string | null;
+  text_b: string | null;
+  significance: string;
+}
+
+export interface RedlineSegment {
+  op: "equal" | "insert" | "delete";
+  text: string;
+  change_id?: string;
+}
+
+export interface RedlineSection {
+  heading_a: string | null;
+  heading_b: string | null;
+  status: "unchanged" | "modified" | "added" | "removed" | "moved";
+  segments: RedlineSegment[];
+}
+
+export interface RedlineChange {
+  id: string;
+  kind: "insert" | "delete" | "replace" | "move";
+  section: string;
+  moved_from: string | null;
+  text_a: string | null;
+  text_b: string | null;
+  context_before: string;
+  context_after: string;
+  char_diff: RedlineSegment[] | null;
+}
+
+export interface Redline {
+  sections: RedlineSection[];
+  changes: RedlineChange[];
+  stats: {
+    insertions: number;
+    deletions: number;
+    replacements: number;
+    moves: number;
+  };
+}
+
 export async function compareDocuments(
   documentAId: string,
   documentBId: string,
@@ -94,6 +333,49 @@ export async function compareDocuments(
   return invoke<Comparison>("compare_documents", { documentAId, documentBId });
 }
 
+export async function compareClauses(
+  documentAId: string,
+  documentBId: string,
+): Promise<Comparison> {
+  return invoke<Comparison>("compare_clauses", { documentAId, documentBId });
+}
+
+export interface ComparisonFilter {
+  /** Matches either compared document. */
+  document_id?: string | null;
+  matter_id?: string | null;
+  tag?: string | null;
+}
+
+export async function listComparisons(
+  filter: ComparisonFilter = {},
+): Promise<Comparison[]> {
+  return invoke<Comparison[]>("list_comparisons", { filter });
+}
+
+export async function getComparison(
+  comparisonId: string,
+): Promise<Comparison> {
+  return invoke<Comparison>("get_comparison", { comparisonId });
+}
+
+export async function deleteComparison(comparisonId: string): Promise<void> {
+  return invoke<void>("delete_comparison", { comparisonId });
+}
+
+export async function rerunComparison(
+  comparisonId: string,
+): Promise<Comparison> {
+  return invoke<Comparison>("rerun_comparison", { comparisonId });
+}
+
+export async function compareToTemplate(
+  documentId: string,
+  templateId: string,
+): Promise<Comparison> {
+  return invoke<Comparison>("compare_to_template", { documentId, templateId });
+}
+
 // Templates
 export interface Template {
   id: string;
@@ -136,9 +418,15 @@ export interface Report {
   content: string;
   export_path: string | null;
   format: string;
+  matter_id: string | null;
+  /** The comparison reported on; null for analysis reports. */
+  comparison_id: string | null;
+  tags: string[];
   created_at: string;
 }
 
+export type ComparisonReportType = "comparison_redline" | "comparison_summary";
+
 export async function generateReport(documentId: string): Promise<Report> {
   return invoke<Report>("generate_report", { documentId });
 }
@@ -146,3 +434,509 @@ export async function generateReport(documentId: string): Promise<Report> {
 export async function getReports(documentId: string): Promise<Report[]> {
   return invoke<Report[]>("get_reports", { documentId });
 }
+
+export async function generateComparisonReport(
+  comparisonId: string,
+  reportType: ComparisonReportType,
+): Promise<Report> {
+  return invoke<Report>("generate_comparison_report", {
+    comparisonId,
+    reportType,
+  });
+}
+
+export async function getComparisonReports(
+  comparisonId: string,
+): Promise<Report[]> {
+  return invoke<Report[]>("get_comparison_reports", { comparisonId });
+}
+
+// Prompts
+export type PromptKind =
+  | "extraction"
+  | "risk"
+  | "comparison"
+  | "summary"
+  | "question"
+  | "template_review";
+
+export interface PromptSummary {
+  prompt_kind: PromptKind;
+  active_version: number;
+  latest_version: number;
+  variables: string[];
+}
+
+export interface PromptVersion {
+  id: string | null;
+  prompt_kind: PromptKind;
+  version: number;
+  system_template: string;
+  user_template: string;
+  notes: string | null;
+  is_active: boolean;
+  created_at: string | null;
+}
+
+export interface PromptDiffLine {
+  op: "equal" | "insert" | "delete";
+  text: string;
+}
+
+export interface PromptDiff {
+  prompt_kind: PromptKind;
+  from_version: number;
+  to_version: number;
+  system: PromptDiffLine[];
+  user: PromptDiffLine[];
+}
+
+export async function listPrompts(): Promise<PromptSummary[]> {
+  return invoke<PromptSummary[]>("list_prompts");
+}
+
+export async function getPromptHistory(
+  promptKind: PromptKind,
+): Promise<PromptVersion[]> {
+  return invoke<PromptVersion[]>("get_prompt_history", { promptKind });
+}
+
+export async function updatePrompt(
+  promptKind: PromptKind,
+  systemTemplate: string,
+  userTemplate: string,
+  notes: string | null,
+): Promise<PromptVersion> {
+  return invoke<PromptVersion>("update_prompt", {
+    promptKind,
+    systemTemplate,
+    userTemplate,
+    notes,
+  });
+}
+
+export async function diffPromptVersions(
+  promptKind: PromptKind,
+  fromVersion: number,
+  toVersion: number,
+): Promise<PromptDiff> {
+  return invoke<PromptDiff>("diff_prompt_versions", {
+    promptKind,
+    fromVersion,
+    toVersion,
+  });
+}
+
+export async function rollbackPrompt(
+  promptKind: PromptKind,
+  version: number,
+): Promise<PromptVersion> {
+  return invoke<PromptVersion>("rollback_prompt", { promptKind, version });
+}
+
+// Ollama
+export const OLLAMA_PULL_PROGRESS_EVENT = "ollama-pull-progress";
+
+export interface OllamaModelDetails {
+  family: string | null;
+  parameter_size: string | null;
+  quantization_level: string | null;
+}
+
+export interface OllamaModel {
+  name: string;
+  size: number;
+  digest: string;
+  modified_at: string | null;
+  details: OllamaModelDetails | null;
+}
+
+export interface OllamaHealth {
+  url: string;
+  reachable: boolean;
+  version: string | null;
+  model: string;
+  model_available: boolean;
+  error: string | null;
+}
+
+export interface PullProgress {
+  model: string;
+  status: string;
+  completed: number | null;
+  total: number | null;
+  done: boolean;
+  error: string | null;
+}
+
+export async function checkOllamaHealth(): Promise<OllamaHealth> {
+  return invoke<OllamaHealth>("check_ollama_health");
+}
+
+export async function listOllamaModels(): Promise<OllamaModel[]> {
+  return invoke<OllamaModel[]>("list_ollama_models");
+}
+
+export async function pullOllamaModel(
+  model: string | null = null,
+): Promise<PullProgress> {
+  return invoke<PullProgress>("pull_ollama_model", { model });
+}
+
+export async function getOllamaPulls(): Promise<PullProgress[]> {
+  return invoke<PullProgress[]>("get_ollama_pulls");
+}
+
+export async function warmOllamaModel(
+  keepAlive: string | null = null,
+): Promise<void> {
+  return invoke<void>("warm_ollama_model", { keepAlive });
+}
+
+// Matters
+export interface Matter {
+  id: string;
+  name: string;
+  client: string | null;
+  matter_number: string | null;
+  responsible_attorney: string | null;
+  status: "open" | "closed";
+  legal_hold: boolean;
+  closed_at: string | null;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface MatterInput {
+  name: string;
+  client: string | null;
+  matter_number: string | null;
+  responsible_attorney: string | null;
+}
+
+export async function createMatter(matter: MatterInput): Promise<Matter> {
+  return invoke<Matter>("create_matter", { matter });
+}
+
+export async function updateMatter(
+  matterId: string,
+  matter: MatterInput,
+): Promise<Matter> {
+  return invoke<Matter>("update_matter", { matterId, matter });
+}
+
+export async function listMatters(): Promise<Matter[]> {
+  return invoke<Matter[]>("list_matters");
+}
+
+export async function listClients(): Promise<string[]> {
+  return invoke<string[]>("list_clients");
+}
+
+export async function closeMatter(matterId: string): Promise<Matter> {
+  return invoke<Matter>("close_matter", { matterId });
+}
+
+export async function reopenMatter(matterId: string): Promise<Matter> {
+  return invoke<Matter>("reopen_matter", { matterId });
+}
+
+export async function setMatterLegalHold(
+  matterId: string,
+  hold: boolean,
+): Promise<Matter> {
+  return invoke<Matter>("set_matter_legal_hold", { matterId, hold });
+}
+
+export async function setDocumentMatter(
+  documentId: string,
+  matterId: string | null,
+): Promise<Document> {
+  return invoke<Document>("set_document_matter", { documentId, matterId });
+}
+
+export async function setComparisonMatter(
+  comparisonId: string,
+  matterId: string | null,
+): Promise<Comparison> {
+  return invoke<Comparison>("set_comparison_matter", {
+    comparisonId,
+    matterId,
+  });
+}
+
+export async function setReportMatter(
+  reportId: string,
+  matterId: string | null,
+): Promise<Report> {
+  return invoke<Report>("set_report_matter", { reportId, matterId });
+}
+
+// Versions
+export interface DocumentVersion extends DocumentSummary {
+  /** Latest comparison against the previous version, if one was run. */
+  comparison: Comparison | null;
+}
+
+export interface VersionAdded {
+  document: Document;
+  comparison: Comparison | null;
+  /** Why the automatic comparison did not run; the version is still added. */
+  comparison_error: string | null;
+}
+
+/** Makes `documentId` the newest version in `versionOf`'s family and compares it with the previous one. */
+export async function addDocumentVersion(
+  versionOf: string,
+  documentId: string,
+  label: string | null = null,
+): Promise<VersionAdded> {
+  return invoke<VersionAdded>("add_document_version", {
+    versionOf,
+    documentId,
+    label,
+  });
+}
+
+export async function getDocumentVersions(
+  documentId: string,
+): Promise<DocumentVersion[]> {
+  return invoke<DocumentVersion[]>("get_document_versions", { documentId });
+}
+
+export async function compareWithPreviousVersion(
+  documentId: string,
+): Promise<Comparison> {
+  return invoke<Comparison>("compare_with_previous_version", { documentId });
+}
+
+export async function setVersionLabel(
+  documentId: string,
+  label: string | null,
+): Promise<Document> {
+  return invoke<Document>("set_version_label", { documentId, label });
+}
+
+// Tags
+export interface Tag {
+  id: string;
+  name: string;
+  /** Documents, comparisons and reports carrying the tag. */
+  usage: number;
+  created_at: string;
+}
+
+export async function listTags(): Promise<Tag[]> {
+  return invoke<Tag[]>("list_tags");
+}
+
+/** Replaces the document's tags and returns them sorted. */
+export async function setDocumentTags(
+  documentId: string,
+  tags: string[],
+): Promise<string[]> {
+  return invoke<string[]>("set_document_tags", { documentId, tags });
+}
+
+export async function setComparisonTags(
+  comparisonId: string,
+  tags: string[],
+): Promise<string[]> {
+  return invoke<string[]>("set_comparison_tags", { comparisonId, tags });
+}
+
+export async function setReportTags(
+  reportId: string,
+  tags: string[],
+): Promise<string[]> {
+  return invoke<string[]>("set_report_tags", { reportId, tags });
+}
+
+export async function renameTag(tagId: string, name: string): Promise<void> {
+  return invoke<void>("rename_tag", { tagId, name });
+}
+
+export async function deleteTag(tagId: string): Promise<void> {
+  return invoke<void>("delete_tag", { tagId });
+}
+
+// Audit log
+export interface AuditEvent {
+  seq: number;
+  id: string;
+  occurred_at: string;
+  actor: string;
+  action: string;
+  document_id: string | null;
+  matter_id: string | null;
+  subject_type: string | null;
+  subject_id: string | null;
+  details: string;
+  prev_hash: string;
+  hash: string;
+}
+
+export interface AuditVerification {
+  valid: boolean;
+  events_checked: number;
+  head_hash: string | null;
+  first_invalid_seq: number | null;
+  problem: string | null;
+}
+
+export async function verifyAuditLog(): Promise<AuditVerification> {
+  return invoke<AuditVerification>("verify_audit_log");
+}
+
+export async function getAuditEvents(
+  documentId: string | null = null,
+  matterId: string | null = null,
+): Promise<AuditEvent[]> {
+  return invoke<AuditEvent[]>("get_audit_events", { documentId, matterId });
+}
+
+export async function exportAuditLog(
+  documentId: string | null,
+  matterId: string | null = null,
+): Promise<string> {
+  return invoke<string>("export_audit_log", { documentId, matterId });
+}
+
+// Search
+
+export type SearchScope = "all" | "documents" | "clauses";
+
+export interface SearchQuery {
+  query: string;
+  scope?: SearchScope;
+  contract_type?: ContractType | null;
+  risk_level?: RiskLevel | null;
+  date_from?: string | null;
+  date_to?: string | null;
+  matter_id?: string | null;
+  client?: string | null;
+  tag?: string | null;
+  limit?: number | null;
+}
+
+export interface SnippetPart {
+  text: string;
+  highlighted: boolean;
+}
+
+export interface SearchHit {
+  kind: "document" | "clause";
+  document_id: string;
+  filename: string;
+  contract_type: ContractType;
+  matter_id: string | null;
+  risk_level: RiskLevel | null;
+  created_at: string;
+  clause_id: number | null;
+  clause_type: string | null;
+  clause_title: string | null;
+  section_reference: string | null;
+  snippet: SnippetPart[];
+  score: number;
+}
+
+export async function search(query: SearchQuery): Promise<SearchHit[]> {
+  return invoke<SearchHit[]>("search", { query });
+}
+
+export interface SimilarClause {
+  clause_id: number;
+  document_id: string;
+  filename: string;
+  contract_type: ContractType;
+  clause_type: string;
+  title: string;
+  text: string;
+  section_reference: string | null;
+  score: number;
+}
+
+export async function findSimilarClauses(
+  target: { clauseId: number } | { query: string },
+  limit: number | null = null,
+): Promise<SimilarClause[]> {
+  return invoke<SimilarClause[]>("find_similar_clauses", {
+    clauseId: "clauseId" in target ? target.clauseId : null,
+    query: "query" in target ? target.query : null,
+    limit,
+  });
+}
+
+export async function indexClauseEmbeddings(): Promise<number> {
+  return invoke<number>("index_clause_embeddings");
+}
+
+// Questions
+export interface Conversation {
+  id: string;
+  title: string;
+  document_ids: string[];
+  created_at: string;
+  updated_at: string;
+}
+
+export interface Citation {
+  document_id: string;
+  excerpt: string;
+  text: string;
+  /** Character offsets into the document's raw text. */
+  start: number;
+  end: number;
+  verified: boolean;
+}
+
+export interface ConversationMessage {
+  id: string;
+  conversation_id: string;
+  role: "user" | "assistant";
+  content: string;
+  citations: Citation[];
+  ai_provider: string | null;
+  prompt_version: number | null;
+  data_policy: DataPolicy | null;
+  ai_endpoint: string | null;
+  created_at: string;
+}
+
+export interface Answer {
+  conversation: Conversation;
+  question: ConversationMessage;
+  answer: ConversationMessage;
+  answered: boolean;
+}
+
+export async function askQuestion(
+  question: string,
+  target: { documentIds: string[] } | { conversationId: string },
+): Promise<Answer> {
+  return invoke<Answer>("ask_question", {
+    documentIds: "documentIds" in target ? target.documentIds : [],
+    conversationId: "conversationId" in target ? target.conversationId : null,
+    question,
+  });
+}
+
+export async function listConversations(
+  documentId: string,
+): Promise<Conversation[]> {
+  return invoke<Conversation[]>("list_conversations", { documentId });
+}
+
+export async function getConversationMessages(
+  conversationId: string,
+): Promise<ConversationMessage[]> {
+  return invoke<ConversationMessage[]>("get_conversation_messages", {
+    conversationId,
+  });
+}
+
+export async function deleteConversation(
+  conversationId: string,
+): Promise<void> {
+  return invoke<void>("delete_conversation", { conversationId });
+}
@@ -1,24 +1,57 @@
-import { useState, useCallback, useEffect } from "react";
+import { useState, useCallback, useEffect, useMemo } from "react";
 import { GitCompareArrows, Loader2, ArrowRight } from "lucide-react";
 import toast from "react-hot-toast";
 import {
-  listDocuments,
+  listAllDocuments,
   compareDocuments,
+  compareClauses,
+  compareToTemplate,
+  deleteComparison,
+  generateComparisonReport,
+  listComparisons,
+  listMatters,
+  listTags,
+  listTemplates,
+  rerunComparison,
 } from "@/lib/commands";
-import type { Comparison as ComparisonType } from "@/lib/commands";
-import type { Document } from "@/types";
+import type {
+  Comparison as ComparisonType,
+  ComparisonFilter,
+  ComparisonReportType,
+  Difference,
+  Matter,
+  Redline,
+  Tag,
+  Template,
+} from "@/lib/commands";
+import type { DocumentSummary } from "@/types";
 import { CONTRACT_TYPE_LABELS } from "@/types";
+import ComparisonHistory from "@/components/comparison/ComparisonHistory";
+import RedlineView from "@/components/comparison/RedlineView";
 
-interface Difference {
-  category: string;
-  diff_type: string;
-  description: string;
-  text_a: string | null;
-  text_b: string | null;
-  significance: string;
-}
+type Mode = "text" | "clauses" | "template";
+
+const MODE_LABELS: Record<Mode, string> = {
+  text: "Full text",
+  clauses: "By clause",
+  template: "Against template",
+};
 
-function DiffCard({ diff }: { diff: Difference }) {
+const POSITION_STYLES: Record<string, string> = {
+  acceptable: "bg-green-100 text-green-800",
+  negotiable: "bg-yellow-100 text-yellow-800",
+  unacceptable: "bg-red-100 text-red-800",
+};
+
+function DiffCard({
+  diff,
+  sides,
+  onHover,
+}: {
+  diff: Difference;
+  sides: [string, string];
+  onHover: (changeId: string | null) => void;
+}) {
   const sigColor = {
     high: "border-red-200 bg-red-50",
     medium: "border-yellow-200 bg-yellow-50",
@@ -31,14 +64,35 @@ function DiffCard({ diff }: { diff: Difference }) {
       : "bg-gray-100 text-gray-600";
 
   return (
-    <div className={`border rounded-lg p-4 ${sigColor}`}>
+    <div
+      className={`border rounded-lg p-4 ${sigColor}`}
+      onMouseEnter={() => onHover(diff.change_id ?? null)}
+      onMouseLeave={() => onHover(null)}
+    >
       <div className="flex items-center gap-2 mb-2">
+        {diff.change_id && (
+          <span className="text-xs font-mono text-gray-400">
+            {diff.change_id}
+          </span>
+        )}
+        {diff.clause_type && (
+          <span className="text-xs px-1.5 py-0.5 rounded bg-blue-100 text-blue-800">
+            {diff.clause_type.replace(/_/g, " ")}
+          </span>
+        )}
         <span className="text-xs font-medium uppercase text-gray-500">
           {diff.category.replace(/_/g, " ")}
         </span>
         <span className={`text-xs px-1.5 py-0.5 rounded ${typeBadge}`}>
           {diff.diff_type}
         </span>
+        {diff.position && (
+          <span
+            className={`text-xs px-1.5 py-0.5 rounded font-medium ${POSITION_STYLES[diff.position] ?? ""}`}
+          >
+            {diff.position}
+          </span>
+        )}
         <span className="text-xs text-gray-400 ml-auto">
           {diff.significance} significance
         </span>
@@ -49,7 +103,7 @@ function DiffCard({ diff }: { diff: Difference }) {
           {diff.text_a && (
             <div className="p-2 bg-white rounded border border-red-200">
               <p className="text-xs text-red-600 font-medium mb-1">
-                Document A
+                {sides[0]}
               </p>
               <p className="text-xs text-gray-600">{diff.text_a}</p>
             </div>
@@ -57,7 +111,7 @@ function DiffCard({ diff }: { diff: Difference }) {
           {diff.text_b && (
             <div className="p-2 bg-white rounded border border-green-200">
               <p className="text-xs text-green-600 font-medium mb-1">
-                Document B
+                {sides[1]}
               </p>
               <p className="text-xs text-gray-600">{diff.text_b}</p>
             </div>
@@ -69,37 +123,146 @@ function DiffCard({ diff }: { diff: Difference }) {
 }
 
 function Comparison() {
-  const [documents, setDocuments] = useState<Document[]>([]);
+  const [documents, setDocuments] = useState<DocumentSummary[]>([]);
+  const [templates, setTemplates] = useState<Template[]>([]);
   const [docAId, setDocAId] = useState("");
   const [docBId, setDocBId] = useState("");
+  const [mode, setMode] = useState<Mode>("text");
   const [comparing, setComparing] = useState(false);
   const [result, setResult] = useState<ComparisonType | null>(null);
   const [differences, setDifferences] = useState<Difference[]>([]);
+  const [redline, setRedline] = useState<Redline | null>(null);
+  const [activeChange, setActiveChange] = useState<string | null>(null);
+  const [history, setHistory] = useState<ComparisonType[]>([]);
+  const [historyFilter, setHistoryFilter] = useState<ComparisonFilter>({});
+  const [matters, setMatters] = useState<Matter[]>([]);
+  const [tags, setTags] = useState<Tag[]>([]);
+  const [rerunningId, setRerunningId] = useState<string | null>(null);
 
   useEffect(() => {
-    listDocuments()
-      .then((docs) =>
-        setDocuments(docs.filter((d) => d.raw_text !== null)),
-      )
+    listAllDocuments({ sort: "filename", direction: "asc" })
+      .then((docs) => setDocuments(docs.filter((d) => d.has_text)))
       .catch(() => toast.error("Failed to load documents"));
+    listTemplates()
+      .then(setTemplates)
+      .catch(() => toast.error("Failed to load templates"));
+    Promise.all([listMatters(), listTags()])
+      .then(([m, t]) => {
+        setMatters(m);
+        setTags(t);
+      })
+      .catch(() => toast.error("Failed to load matters"));
+  }, []);
+
+  // The history follows the first document picked
+  const refreshHistory = useCallback(async () => {
+    try {
+      setHistory(
+        await listComparisons({ ...historyFilter, document_id: docAId || null }),
+      );
+    } catch {
+      toast.error("Failed to load comparison history");
+    }
+  }, [historyFilter, docAId]);
+
+  useEffect(() => {
+    refreshHistory();
+  }, [refreshHistory]);
+
+  const names = useMemo(
+    () =>
+      Object.fromEntries([
+        ...documents.map((doc) => [doc.id, doc.filename]),
+        ...templates.map((template) => [template.id, template.name]),
+      ]) as Record<string, string>,
+    [documents, templates],
+  );
+
+  const againstTemplate = mode === "template";
+  const sides: [string, string] =
+    result?.comparison_type === "document_vs_template"
+      ? ["Template", "Draft"]
+      : ["Document A", "Document B"];
+
+  const showComparison = useCallback((comp: ComparisonType) => {
+    setResult(comp);
+    setDifferences(JSON.parse(comp.differences) as Difference[]);
+    setRedline(comp.redline ? (JSON.parse(comp.redline) as Redline) : null);
+    setActiveChange(null);
   }, []);
 
+  const handleRerun = useCallback(
+    async (comparisonId: string) => {
+      setRerunningId(comparisonId);
+      try {
+        showComparison(await rerunComparison(comparisonId));
+        toast.success("Comparison re-run");
+        refreshHistory();
+      } catch (err) {
+        toast.error(
+          `Re-run failed: ${err instanceof Error ? err.message : String(err)}`,
+        );
+      } finally {
+        setRerunningId(null);
+      }
+    },
+    [showComparison, refreshHistory],
+  );
+
+  const handleDeleteComparison = useCallback(
+    async (comparisonId: string) => {
+      try {
+        await deleteComparison(comparisonId);
+        if (result?.id === comparisonId) setResult(null);
+        toast.success("Comparison deleted");
+        refreshHistory();
+      } catch (err) {
+        toast.error(
+          `Delete failed: ${err instanceof Error ? err.message : String(err)}`,
+        );
+      }
+    },
+    [result, refreshHistory],
+  );
+
+  const handleReport = useCallback(
+    async (comparisonId: string, reportType: ComparisonReportType) => {
+      try {
+        await generateComparisonReport(comparisonId, reportType);
+        toast.success("Report generated");
+      } catch (err) {
+        toast.error(
+          `Report failed: ${err instanceof Error ? err.message : String(err)}`,
+        );
+      }
+    },
+    [],
+  );
+
   const handleCompare = useCallback(async () => {
     if (!docAId || !docBId) {
-      toast.error("Select two documents to compare");
+      toast.error(
+        againstTemplate
+          ? "Select a document and a template"
+          : "Select two documents to compare",
+      );
       return;
     }
-    if (docAId === docBId) {
+    if (!againstTemplate && docAId === docBId) {
       toast.error("Select two different documents");
       return;
     }
     setComparing(true);
     try {
-      const comp = await compareDocuments(docAId, docBId);
-      setResult(comp);
-      const diffs = JSON.parse(comp.differences) as Difference[];
-      setDifferences(diffs);
+      const comp =
+        mode === "template"
+          ? await compareToTemplate(docAId, docBId)
+          : mode === "clauses"
+            ? await compareClauses(docAId, docBId)
+            : await compareDocuments(docAId, docBId);
+      showComparison(comp);
       toast.success("Comparison complete");
+      refreshHistory();
     } catch (err) {
       toast.error(
         `Comparison failed: ${err instanceof Error ? err.message : String(err)}`,
@@ -107,7 +270,7 @@ function Comparison() {
     } finally {
       setComparing(false);
     }
-  }, [docAId, docBId]);
+  }, [docAId, docBId, mode, againstTemplate, showComparison, refreshHistory]);
 
   return (
     <div className="p-8 max-w-5xl">
@@ -119,10 +282,34 @@ function Comparison() {
       </div>
 
       <div className="bg-white rounded-xl border border-gray-200 p-6 mb-6">
+        <div className="flex gap-1 mb-4">
+          {(Object.keys(MODE_LABELS) as Mode[]).map((m) => (
+            <button
+              key={m}
+              onClick={() => {
+                setMode(m);
+                // Template ids and document ids are not interchangeable
+                if (m === "template" || mode === "template") setDocBId("");
+              }}
+              className={`px-3 py-1 rounded-lg text-sm ${
+                mode === m
+                  ? "bg-brand-50 text-brand-700 font-medium"
+                  : "text-gray-500 hover:bg-gray-50"
+              }`}
+            >
+              {MODE_LABELS[m]}
+            </button>
+          ))}
+          {mode === "clauses" && (
+            <span className="text-xs text-gray-400 self-center ml-2">
+              Aligns the clauses extracted during analysis
+            </span>
+          )}
+        </div>
         <div className="flex items-end gap-4">
           <div className="flex-1">
             <label className="block text-sm font-medium text-gray-700 mb-2">
-              Document A
+              {againstTemplate ? "Counterparty Draft" : "Document A"}
             </label>
             <select
               value={docAId}
@@ -140,19 +327,32 @@ function Comparison() {
           <ArrowRight className="h-5 w-5 text-gray-400 mb-2" />
           <div className="flex-1">
             <label className="block text-sm font-medium text-gray-700 mb-2">
-              Document B
+              {againstTemplate ? "Our Template" : "Document B"}
             </label>
             <select
               value={docBId}
               onChange={(e) => setDocBId(e.target.value)}
               className="w-full border border-gray-300 rounded-lg px-3 py-2 text-sm"
             >
-              <option value="">Select document...</option>
-              {documents.map((doc) => (
-                <option key={doc.id} value={doc.id}>
-                  {doc.filename} ({CONTRACT_TYPE_LABELS[doc.contract_type]})
-                </option>
-              ))}
+              {againstTemplate ? (
+                <>
+                  <option value="">Select template...</option>
+                  {templates.map((template) => (
+                    <option key={template.id} value={template.id}>
+                      {template.name}
+                    </option>
+                  ))}
+                </>
+              ) : (
+                <>
+                  <option value="">Select document...</option>
+                  {documents.map((doc) => (
+                    <option key={doc.id} value={doc.id}>
+                      {doc.filename} ({CONTRACT_TYPE_LABELS[doc.contract_type]})
+                    </option>
+                  ))}
+                </>
+              )}
             </select>
           </div>
           <button
@@ -178,7 +378,7 @@ function Comparison() {
             </div>
           )}
 
-          <div className="space-y-4">
+          <div className="space-y-4 mb-6">
             <h2 className="font-semibold">
               Differences ({differences.length})
             </h2>
@@ -187,11 +387,36 @@ function Comparison() {
                 No significant differences found
               </p>
             ) : (
-              differences.map((diff, i) => <DiffCard key={i} diff={diff} />)
+              differences.map((diff, i) => (
+                <DiffCard
+                  key={i}
+                  diff={diff}
+                  sides={sides}
+                  onHover={setActiveChange}
+                />
+              ))
             )}
           </div>
+
+          {redline && (
+            <RedlineView redline={redline} activeChange={activeChange} />
+          )}
         </>
       )}
+
+      <ComparisonHistory
+        comparisons={history}
+        names={names}
+        matters={matters}
+        tags={tags}
+        filter={historyFilter}
+        onFilterChange={setHistoryFilter}
+        busyId={rerunningId}
+        onOpen={showComparison}
+        onRerun={handleRerun}
+        onDelete={handleDeleteComparison}
+        onReport={handleReport}
+      />
     </div>
   );
 }
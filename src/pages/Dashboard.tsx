@@ -1,3 +1,4 @@
+import { useEffect, useState } from "react";
 import { Link } from "react-router";
 import {
   FileText,
@@ -7,8 +8,24 @@ import {
   Clock,
 } from "lucide-react";
 import { useDocuments } from "@/hooks/useDocuments";
-import { CONTRACT_TYPE_LABELS, STATUS_LABELS } from "@/types";
-import type { Document } from "@/types";
+import { listClients, listMatters, listTags } from "@/lib/commands";
+import type { Matter, Tag } from "@/lib/commands";
+import { CONTRACT_TYPE_LABELS, RISK_LEVEL_BG, STATUS_LABELS } from "@/types";
+import type {
+  DocumentQuery,
+  DocumentSort,
+  DocumentSummary,
+} from "@/types";
+
+const SORT_OPTIONS: { value: string; label: string }[] = [
+  { value: "created_at:desc", label: "Newest first" },
+  { value: "created_at:asc", label: "Oldest first" },
+  { value: "filename:asc", label: "Name A–Z" },
+  { value: "filename:desc", label: "Name Z–A" },
+  { value: "risk_score:desc", label: "Highest risk" },
+  { value: "risk_score:asc", label: "Lowest risk" },
+  { value: "status:asc", label: "Status" },
+];
 
 function StatCard({
   label,
@@ -36,7 +53,7 @@ function StatCard({
   );
 }
 
-function DocumentRow({ doc }: { doc: Document }) {
+function DocumentRow({ doc }: { doc: DocumentSummary }) {
   return (
     <Link
       to={`/documents/${doc.id}`}
@@ -45,32 +62,83 @@ function DocumentRow({ doc }: { doc: Document }) {
       <div className="flex items-center gap-3">
         <FileText className="h-5 w-5 text-gray-400" />
         <div>
-          <p className="text-sm font-medium text-gray-900">{doc.filename}</p>
+          <p className="text-sm font-medium text-gray-900">
+            {doc.filename}
+            {doc.version_number > 1 && (
+              <span className="text-gray-400 font-normal ml-1">
+                v{doc.version_number}
+              </span>
+            )}
+          </p>
           <p className="text-xs text-gray-500">
             {CONTRACT_TYPE_LABELS[doc.contract_type]} &middot;{" "}
             {new Date(doc.created_at).toLocaleDateString()}
           </p>
         </div>
+        {doc.tags.map((tag) => (
+          <span
+            key={tag}
+            className="text-xs px-2 py-0.5 rounded-full bg-gray-100 text-gray-600"
+          >
+            {tag}
+          </span>
+        ))}
+      </div>
+      <div className="flex items-center gap-2">
+        {doc.risk_level && (
+          <span
+            className={`text-xs px-2 py-1 rounded-full ${RISK_LEVEL_BG[doc.risk_level]}`}
+          >
+            Risk {doc.risk_score}
+          </span>
+        )}
+        <span
+          className={`text-xs px-2 py-1 rounded-full ${
+            doc.processing_status === "analyzed"
+              ? "bg-green-100 text-green-800"
+              : doc.processing_status === "error"
+                ? "bg-red-100 text-red-800"
+                : "bg-gray-100 text-gray-600"
+          }`}
+        >
+          {STATUS_LABELS[doc.processing_status]}
+        </span>
       </div>
-      <span
-        className={`text-xs px-2 py-1 rounded-full ${
-          doc.processing_status === "analyzed"
-            ? "bg-green-100 text-green-800"
-            : doc.processing_status === "error"
-              ? "bg-red-100 text-red-800"
-              : "bg-gray-100 text-gray-600"
-        }`}
-      >
-        {STATUS_LABELS[doc.processing_status]}
-      </span>
     </Link>
   );
 }
 
 function Dashboard() {
-  const { documents, stats, loading, error } = useDocuments();
+  const [query, setQuery] = useState<DocumentQuery>({});
+  const [matters, setMatters] = useState<Matter[]>([]);
+  const [clients, setClients] = useState<string[]>([]);
+  const [tags, setTags] = useState<Tag[]>([]);
+  const { documents, stats, loading, error, hasMore, loadMore } =
+    useDocuments(query);
 
-  if (loading) {
+  useEffect(() => {
+    Promise.all([listMatters(), listClients(), listTags()])
+      .then(([m, c, t]) => {
+        setMatters(m);
+        setClients(c);
+        setTags(t);
+      })
+      .catch(() => {});
+  }, []);
+
+  const updateFilter = (key: keyof DocumentQuery, value: string) =>
+    setQuery((previous) => ({ ...previous, [key]: value || null }));
+
+  const updateSort = (value: string) => {
+    const [sort, direction] = value.split(":") as [
+      DocumentSort,
+      "asc" | "desc",
+    ];
+    setQuery((previous) => ({ ...previous, sort, direction }));
+  };
+
+  // Keep the page in place while a new filter loads
+  if (loading && !stats) {
     return (
       <div className="flex items-center justify-center h-full">
         <div className="animate-spin h-8 w-8 border-2 border-brand-600 border-t-transparent rounded-full" />
@@ -106,6 +174,99 @@ function Dashboard() {
         </Link>
       </div>
 
+      <div className="flex flex-wrap gap-3 mb-4">
+        <select
+          value={query.matter_id ?? ""}
+          onChange={(e) => updateFilter("matter_id", e.target.value)}
+          className="border border-gray-300 rounded-lg px-3 py-2 text-sm"
+          aria-label="Matter"
+        >
+          <option value="">All matters</option>
+          {matters.map((matter) => (
+            <option key={matter.id} value={matter.id}>
+              {matter.matter_number
+                ? `${matter.matter_number} · ${matter.name}`
+                : matter.name}
+            </option>
+          ))}
+        </select>
+        <select
+          value={query.client ?? ""}
+          onChange={(e) => updateFilter("client", e.target.value)}
+          className="border border-gray-300 rounded-lg px-3 py-2 text-sm"
+          aria-label="Client"
+        >
+          <option value="">All clients</option>
+          {clients.map((client) => (
+            <option key={client} value={client}>
+              {client}
+            </option>
+          ))}
+        </select>
+        <select
+          value={query.tag ?? ""}
+          onChange={(e) => updateFilter("tag", e.target.value)}
+          className="border border-gray-300 rounded-lg px-3 py-2 text-sm"
+          aria-label="Tag"
+        >
+          <option value="">All tags</option>
+          {tags.map((tag) => (
+            <option key={tag.id} value={tag.name}>
+              {tag.name}
+            </option>
+          ))}
+        </select>
+        <select
+          value={query.contract_type ?? ""}
+          onChange={(e) => updateFilter("contract_type", e.target.value)}
+          className="border border-gray-300 rounded-lg px-3 py-2 text-sm"
+          aria-label="Contract type"
+        >
+          <option value="">All contract types</option>
+          {Object.entries(CONTRACT_TYPE_LABELS).map(([value, label]) => (
+            <option key={value} value={value}>
+              {label}
+            </option>
+          ))}
+        </select>
+        <select
+          value={query.status ?? ""}
+          onChange={(e) => updateFilter("status", e.target.value)}
+          className="border border-gray-300 rounded-lg px-3 py-2 text-sm"
+          aria-label="Status"
+        >
+          <option value="">All statuses</option>
+          {Object.entries(STATUS_LABELS).map(([value, label]) => (
+            <option key={value} value={value}>
+              {label}
+            </option>
+          ))}
+        </select>
+        <select
+          value={query.risk_level ?? ""}
+          onChange={(e) => updateFilter("risk_level", e.target.value)}
+          className="border border-gray-300 rounded-lg px-3 py-2 text-sm"
+          aria-label="Risk level"
+        >
+          <option value="">Any risk level</option>
+          <option value="low">Low</option>
+          <option value="medium">Medium</option>
+          <option value="high">High</option>
+        </select>
+        <select
+          value={`${query.sort ?? "created_at"}:${query.direction ?? "desc"}`}
+          onChange={(e) => updateSort(e.target.value)}
+          className="border border-gray-300 rounded-lg px-3 py-2 text-sm ml-auto"
+          aria-label="Sort"
+        >
+          {SORT_OPTIONS.map((option) => (
+            <option key={option.value} value={option.value}>
+              {option.label}
+            </option>
+          ))}
+        </select>
+      </div>
+
       <div className="grid grid-cols-4 gap-4 mb-8">
         <StatCard
           label="Total Documents"
@@ -135,7 +296,7 @@ function Dashboard() {
 
       <div className="bg-white rounded-xl border border-gray-200">
         <div className="px-4 py-3 border-b border-gray-200">
-          <h2 className="font-semibold">Recent Documents</h2>
+          <h2 className="font-semibold">Documents</h2>
         </div>
         {documents.length === 0 ? (
           <div className="p-12 text-center">
@@ -150,9 +311,18 @@ function Dashboard() {
           </div>
         ) : (
           <div className="divide-y divide-gray-100">
-            {documents.slice(0, 10).map((doc) => (
+            {documents.map((doc) => (
               <DocumentRow key={doc.id} doc={doc} />
             ))}
+            {hasMore && (
+              <button
+                onClick={loadMore}
+                disabled={loading}
+                className="w-full px-4 py-3 text-sm text-brand-600 hover:bg-gray-50 disabled:opacity-50 font-medium"
+              >
+                Load more
+              </button>
+            )}
           </div>
         )}
       </div>
@@ -0,0 +1,292 @@
+import { useState, useCallback, useEffect } from "react";
+import {
+  Briefcase,
+  Lock,
+  Pencil,
+  Plus,
+  Tag as TagIcon,
+  Trash2,
+  X,
+} from "lucide-react";
+import toast from "react-hot-toast";
+import {
+  closeMatter,
+  createMatter,
+  deleteTag,
+  listMatters,
+  listTags,
+  reopenMatter,
+  setMatterLegalHold,
+  updateMatter,
+} from "@/lib/commands";
+import type { Matter, MatterInput, Tag } from "@/lib/commands";
+
+const EMPTY_INPUT: MatterInput = {
+  name: "",
+  client: null,
+  matter_number: null,
+  responsible_attorney: null,
+};
+
+function errorMessage(err: unknown) {
+  return `Failed: ${err instanceof Error ? err.message : String(err)}`;
+}
+
+function Matters() {
+  const [matters, setMatters] = useState<Matter[]>([]);
+  const [tags, setTags] = useState<Tag[]>([]);
+  const [loading, setLoading] = useState(true);
+  const [editing, setEditing] = useState<string | null>(null);
+  const [showForm, setShowForm] = useState(false);
+  const [input, setInput] = useState<MatterInput>(EMPTY_INPUT);
+
+  const refresh = useCallback(async () => {
+    try {
+      setLoading(true);
+      const [m, t] = await Promise.all([listMatters(), listTags()]);
+      setMatters(m);
+      setTags(t);
+    } catch {
+      toast.error("Failed to load matters");
+    } finally {
+      setLoading(false);
+    }
+  }, []);
+
+  useEffect(() => {
+    refresh();
+  }, [refresh]);
+
+  const openForm = useCallback((matter: Matter | null) => {
+    setEditing(matter?.id ?? null);
+    setInput(
+      matter
+        ? {
+            name: matter.name,
+            client: matter.client,
+            matter_number: matter.matter_number,
+            responsible_attorney: matter.responsible_attorney,
+          }
+        : EMPTY_INPUT,
+    );
+    setShowForm(true);
+  }, []);
+
+  const handleSave = useCallback(async () => {
+    if (!input.name.trim()) {
+      toast.error("Name is required");
+      return;
+    }
+    try {
+      if (editing) {
+        await updateMatter(editing, input);
+        toast.success("Matter updated");
+      } else {
+        await createMatter(input);
+        toast.success("Matter created");
+      }
+      setShowForm(false);
+      refresh();
+    } catch (err) {
+      toast.error(errorMessage(err));
+    }
+  }, [editing, input, refresh]);
+
+  const run = useCallback(
+    async (action: () => Promise<unknown>, success: string) => {
+      try {
+        await action();
+        toast.success(success);
+        refresh();
+      } catch (err) {
+        toast.error(errorMessage(err));
+      }
+    },
+    [refresh],
+  );
+
+  const field = (
+    key: keyof MatterInput,
+    label: string,
+    placeholder: string,
+  ) => (
+    <div>
+      <label className="block text-sm font-medium text-gray-700 mb-1">
+        {label}
+      </label>
+      <input
+        type="text"
+        value={input[key] ?? ""}
+        onChange={(e) => setInput({ ...input, [key]: e.target.value })}
+        className="w-full border border-gray-300 rounded-lg px-3 py-2 text-sm"
+        placeholder={placeholder}
+      />
+    </div>
+  );
+
+  if (loading) {
+    return (
+      <div className="flex items-center justify-center h-full">
+        <div className="animate-spin h-8 w-8 border-2 border-brand-600 border-t-transparent rounded-full" />
+      </div>
+    );
+  }
+
+  return (
+    <div className="p-8 max-w-4xl">
+      <div className="flex items-center justify-between mb-8">
+        <div>
+          <h1 className="text-2xl font-bold">Matters</h1>
+          <p className="text-gray-500 mt-1">
+            Clients, matters and tags for organizing documents
+          </p>
+        </div>
+        <button
+          onClick={() => openForm(null)}
+          className="flex items-center gap-2 bg-brand-600 text-white px-4 py-2 rounded-lg hover:bg-brand-700 transition-colors text-sm font-medium"
+        >
+          <Plus className="h-4 w-4" />
+          New Matter
+        </button>
+      </div>
+
+      {showForm && (
+        <div className="bg-white rounded-xl border border-gray-200 p-6 mb-6">
+          <div className="flex items-center justify-between mb-4">
+            <h2 className="font-semibold">
+              {editing ? "Edit Matter" : "Create Matter"}
+            </h2>
+            <button
+              onClick={() => setShowForm(false)}
+              className="p-1 hover:bg-gray-100 rounded"
+            >
+              <X className="h-4 w-4" />
+            </button>
+          </div>
+          <div className="grid grid-cols-2 gap-4">
+            {field("name", "Name", "Acme supply agreement")}
+            {field("client", "Client", "Acme Corp")}
+            {field("matter_number", "Matter Number", "2024-0117")}
+            {field("responsible_attorney", "Responsible Attorney", "J. Smith")}
+          </div>
+          <button
+            onClick={handleSave}
+            className="mt-4 bg-brand-600 text-white px-6 py-2 rounded-lg hover:bg-brand-700 transition-colors text-sm font-medium"
+          >
+            {editing ? "Save Changes" : "Create Matter"}
+          </button>
+        </div>
+      )}
+
+      {matters.length === 0 && !showForm ? (
+        <div className="bg-white rounded-xl border border-gray-200 p-12 text-center mb-8">
+          <Briefcase className="h-12 w-12 text-gray-300 mx-auto mb-4" />
+          <p className="text-gray-500">No matters yet</p>
+        </div>
+      ) : (
+        <div className="space-y-3 mb-8">
+          {matters.map((matter) => (
+            <div
+              key={matter.id}
+              className="bg-white rounded-xl border border-gray-200 p-4 flex items-center justify-between"
+            >
+              <div>
+                <p className="font-medium text-gray-900 flex items-center gap-2">
+                  {matter.matter_number && (
+                    <span className="text-gray-500">{matter.matter_number}</span>
+                  )}
+                  {matter.name}
+                  {matter.status === "closed" && (
+                    <span className="text-xs px-2 py-0.5 rounded-full bg-gray-100 text-gray-600">
+                      Closed
+                    </span>
+                  )}
+                  {matter.legal_hold && (
+                    <span className="flex items-center gap-1 text-xs px-2 py-0.5 rounded-full bg-amber-100 text-amber-800">
+                      <Lock className="h-3 w-3" />
+                      Legal hold
+                    </span>
+                  )}
+                </p>
+                <p className="text-xs text-gray-500">
+                  {matter.client ?? "No client"}
+                  {matter.responsible_attorney &&
+                    ` · ${matter.responsible_attorney}`}
+                </p>
+              </div>
+              <div className="flex items-center gap-2">
+                <button
+                  onClick={() =>
+                    run(
+                      () => setMatterLegalHold(matter.id, !matter.legal_hold),
+                      matter.legal_hold ? "Hold released" : "Hold placed",
+                    )
+                  }
+                  className="text-xs text-gray-600 hover:text-gray-900 px-2 py-1"
+                >
+                  {matter.legal_hold ? "Release hold" : "Place hold"}
+                </button>
+                <button
+                  onClick={() =>
+                    matter.status === "open"
+                      ? run(() => closeMatter(matter.id), "Matter closed")
+                      : run(() => reopenMatter(matter.id), "Matter reopened")
+                  }
+                  className="text-xs text-gray-600 hover:text-gray-900 px-2 py-1"
+                >
+                  {matter.status === "open" ? "Close" : "Reopen"}
+                </button>
+                <button
+                  onClick={() => openForm(matter)}
+                  className="p-2 text-gray-400 hover:text-gray-600 hover:bg-gray-50 rounded-lg transition-colors"
+                  title="Edit matter"
+                >
+                  <Pencil className="h-4 w-4" />
+                </button>
+              </div>
+            </div>
+          ))}
+        </div>
+      )}
+
+      <div className="bg-white rounded-xl border border-gray-200">
+        <div className="px-4 py-3 border-b border-gray-200">
+          <h2 className="font-semibold flex items-center gap-2">
+            <TagIcon className="h-4 w-4" />
+            Tags
+          </h2>
+        </div>
+        {tags.length === 0 ? (
+          <p className="p-4 text-sm text-gray-400">
+            Tags added to documents appear here
+          </p>
+        ) : (
+          <div className="divide-y divide-gray-100">
+            {tags.map((tag) => (
+              <div
+                key={tag.id}
+                className="flex items-center justify-between px-4 py-2"
+              >
+                <span className="text-sm text-gray-900">
+                  {tag.name}
+                  <span className="text-xs text-gray-500 ml-2">
+                    {tag.usage} in use
+                  </span>
+                </span>
+                <button
+                  onClick={() => run(() => deleteTag(tag.id), "Tag deleted")}
+                  className="p-2 text-red-400 hover:text-red-600 hover:bg-red-50 rounded-lg transition-colors"
+                  title="Delete tag"
+                >
+                  <Trash2 className="h-4 w-4" />
+                </button>
+              </div>
+            ))}
+          </div>
+        )}
+      </div>
+    </div>
+  );
+}
+
+export default Matters;
@@ -7,6 +7,7 @@ import {
   Brain,
   FileBarChart,
   Loader2,
+  Lock,
 } from "lucide-react";
 import toast from "react-hot-toast";
 import {
@@ -16,12 +17,22 @@ import {
   getExtractions,
   getRiskAssessments,
   generateReport,
+  setDocumentLegalHold,
+  setDocumentMatter,
+  setDocumentTags,
+  listMatters,
+  listTags,
+  getDocumentVersions,
+  compareWithPreviousVersion,
 } from "@/lib/commands";
-import type { AnalysisResult } from "@/lib/commands";
+import type { AnalysisResult, DocumentVersion, Matter } from "@/lib/commands";
 import { CONTRACT_TYPE_LABELS, STATUS_LABELS } from "@/types";
 import type { Document, ExtractedClause, RiskFlag, RiskLevel } from "@/types";
 import ClauseTable from "@/components/analysis/ClauseTable";
 import RiskPanel from "@/components/analysis/RiskPanel";
+import QuestionPanel from "@/components/analysis/QuestionPanel";
+import TagEditor from "@/components/organize/TagEditor";
+import VersionHistory from "@/components/organize/VersionHistory";
 
 function ReviewDetail() {
   const { id } = useParams<{ id: string }>();
@@ -36,6 +47,21 @@ function ReviewDetail() {
   const [riskLevel, setRiskLevel] = useState<RiskLevel | null>(null);
   const [riskFlags, setRiskFlags] = useState<RiskFlag[]>([]);
   const [riskSummary, setRiskSummary] = useState<string | null>(null);
+  const [matters, setMatters] = useState<Matter[]>([]);
+  const [tagNames, setTagNames] = useState<string[]>([]);
+  const [versions, setVersions] = useState<DocumentVersion[]>([]);
+  const [comparingVersion, setComparingVersion] = useState<string | null>(
+    null,
+  );
+
+  useEffect(() => {
+    Promise.all([listMatters(), listTags()])
+      .then(([m, t]) => {
+        setMatters(m);
+        setTagNames(t.map((tag) => tag.name));
+      })
+      .catch(() => {});
+  }, []);
 
   const loadDocument = useCallback(async () => {
     if (!id) return;
@@ -43,12 +69,20 @@ function ReviewDetail() {
       setLoading(true);
       const document = await getDocument(id);
       setDoc(document);
+      // Moving between versions reuses this page, so clear the last analysis
+      setClauses([]);
+      setRiskScore(null);
+      setRiskLevel(null);
+      setRiskFlags([]);
+      setRiskSummary(null);
 
       // Load existing analysis if available
-      const [exts, risks] = await Promise.all([
+      const [exts, risks, history] = await Promise.all([
         getExtractions(id).catch(() => []),
         getRiskAssessments(id).catch(() => []),
+        getDocumentVersions(id).catch(() => []),
       ]);
+      setVersions(history);
 
       if (exts.length > 0) {
         const latestExt = exts[0];
@@ -134,7 +168,7 @@ function ReviewDetail() {
     if (!id) return;
     try {
       await deleteDocument(id);
-      toast.success("Document deleted");
+      toast.success("Document moved to trash");
       navigate("/");
     } catch (err) {
       toast.error(
@@ -143,6 +177,68 @@ function ReviewDetail() {
     }
   }, [id, navigate]);
 
+  const handleToggleHold = useCallback(async () => {
+    if (!id || !doc) return;
+    try {
+      setDoc(await setDocumentLegalHold(id, !doc.legal_hold));
+      toast.success(doc.legal_hold ? "Legal hold lifted" : "Legal hold placed");
+    } catch (err) {
+      toast.error(
+        `Failed: ${err instanceof Error ? err.message : String(err)}`,
+      );
+    }
+  }, [id, doc]);
+
+  const handleCompareVersion = useCallback(
+    async (versionId: string) => {
+      if (!id) return;
+      try {
+        setComparingVersion(versionId);
+        await compareWithPreviousVersion(versionId);
+        setVersions(await getDocumentVersions(id));
+        toast.success("Comparison complete");
+      } catch (err) {
+        toast.error(
+          `Comparison failed: ${err instanceof Error ? err.message : String(err)}`,
+        );
+      } finally {
+        setComparingVersion(null);
+      }
+    },
+    [id],
+  );
+
+  const handleMatterChange = useCallback(
+    async (matterId: string) => {
+      if (!id) return;
+      try {
+        setDoc(await setDocumentMatter(id, matterId || null));
+      } catch (err) {
+        toast.error(
+          `Failed: ${err instanceof Error ? err.message : String(err)}`,
+        );
+      }
+    },
+    [id],
+  );
+
+  const handleTagsChange = useCallback(
+    async (tags: string[]) => {
+      if (!id) return;
+      try {
+        const saved = await setDocumentTags(id, tags);
+        setDoc((previous) =>
+          previous ? { ...previous, tags: saved } : previous,
+        );
+      } catch (err) {
+        toast.error(
+          `Failed: ${err instanceof Error ? err.message : String(err)}`,
+        );
+      }
+    },
+    [id],
+  );
+
   if (loading) {
     return (
       <div className="flex items-center justify-center h-full">
@@ -177,7 +273,14 @@ function ReviewDetail() {
             <ArrowLeft className="h-5 w-5" />
           </button>
           <div>
-            <h1 className="text-2xl font-bold">{doc.filename}</h1>
+            <h1 className="text-2xl font-bold">
+              {doc.filename}
+              {versions.length > 1 && (
+                <span className="text-gray-400 font-normal ml-2">
+                  v{doc.version_number}
+                </span>
+              )}
+            </h1>
             <p className="text-gray-500 text-sm">
               {CONTRACT_TYPE_LABELS[doc.contract_type]} &middot;{" "}
               {STATUS_LABELS[doc.processing_status]}
@@ -213,12 +316,24 @@ function ReviewDetail() {
               Generate Report
             </button>
           )}
+          <button
+            onClick={handleToggleHold}
+            className={`flex items-center gap-2 px-3 py-2 rounded-lg transition-colors text-sm ${
+              doc.legal_hold
+                ? "bg-amber-50 text-amber-700 hover:bg-amber-100"
+                : "text-gray-500 hover:text-gray-700 hover:bg-gray-100"
+            }`}
+          >
+            <Lock className="h-4 w-4" />
+            {doc.legal_hold ? "On Legal Hold" : "Legal Hold"}
+          </button>
           <button
             onClick={handleDelete}
-            className="flex items-center gap-2 text-red-500 hover:text-red-700 hover:bg-red-50 px-3 py-2 rounded-lg transition-colors text-sm"
+            disabled={doc.legal_hold}
+            className="flex items-center gap-2 text-red-500 hover:text-red-700 hover:bg-red-50 disabled:opacity-50 px-3 py-2 rounded-lg transition-colors text-sm"
           >
             <Trash2 className="h-4 w-4" />
-            Delete
+            Move to Trash
           </button>
         </div>
       </div>
@@ -249,6 +364,35 @@ function ReviewDetail() {
                 <dt className="text-gray-500">Hash</dt>
                 <dd className="font-mono text-xs">{doc.file_hash}</dd>
               </div>
+              <div>
+                <dt className="text-gray-500">Matter</dt>
+                <dd>
+                  <select
+                    value={doc.matter_id ?? ""}
+                    onChange={(e) => handleMatterChange(e.target.value)}
+                    className="border border-gray-300 rounded-lg px-2 py-1 text-sm"
+                  >
+                    <option value="">No matter</option>
+                    {matters.map((matter) => (
+                      <option key={matter.id} value={matter.id}>
+                        {matter.matter_number
+                          ? `${matter.matter_number} · ${matter.name}`
+                          : matter.name}
+                      </option>
+                    ))}
+                  </select>
+                </dd>
+              </div>
+              <div>
+                <dt className="text-gray-500">Tags</dt>
+                <dd>
+                  <TagEditor
+                    tags={doc.tags}
+                    suggestions={tagNames}
+                    onChange={handleTagsChange}
+                  />
+                </dd>
+              </div>
             </dl>
           </div>
 
@@ -274,6 +418,10 @@ function ReviewDetail() {
             </div>
           )}
 
+          {doc.raw_text && (
+            <QuestionPanel documentId={doc.id} text={doc.raw_text} />
+          )}
+
           {doc.error_message && (
             <div className="bg-red-50 border border-red-200 rounded-xl p-6">
               <h2 className="font-semibold text-red-800 mb-2">Error</h2>
@@ -324,6 +472,15 @@ function ReviewDetail() {
               </p>
             </div>
           )}
+
+          {versions.length > 0 && (
+            <VersionHistory
+              versions={versions}
+              currentId={doc.id}
+              comparingId={comparingVersion}
+              onCompare={handleCompareVersion}
+            />
+          )}
         </div>
       </div>
     </div>
@@ -0,0 +1,362 @@
+import { useState, useCallback, useEffect } from "react";
+import { Link } from "react-router";
+import { Loader2, Search as SearchIcon } from "lucide-react";
+import toast from "react-hot-toast";
+import {
+  search,
+  listMatters,
+  listClients,
+  listTags,
+  findSimilarClauses,
+} from "@/lib/commands";
+import type {
+  Matter,
+  SearchHit,
+  SearchScope,
+  SimilarClause,
+  SnippetPart,
+  Tag,
+} from "@/lib/commands";
+import { CONTRACT_TYPE_LABELS, RISK_LEVEL_BG } from "@/types";
+import type { ContractType, RiskLevel } from "@/types";
+
+function Snippet({ parts }: { parts: SnippetPart[] }) {
+  return (
+    <p className="text-sm text-gray-700 mt-2">
+      {parts.map((part, i) =>
+        part.highlighted ? (
+          <mark key={i} className="bg-yellow-200 rounded px-0.5">
+            {part.text}
+          </mark>
+        ) : (
+          <span key={i}>{part.text}</span>
+        ),
+      )}
+    </p>
+  );
+}
+
+type Mode = "keyword" | "similar";
+
+function Search() {
+  const [mode, setMode] = useState<Mode>("keyword");
+  const [query, setQuery] = useState("");
+  const [scope, setScope] = useState<SearchScope>("all");
+  const [contractType, setContractType] = useState<ContractType | "">("");
+  const [riskLevel, setRiskLevel] = useState<RiskLevel | "">("");
+  const [dateFrom, setDateFrom] = useState("");
+  const [dateTo, setDateTo] = useState("");
+  const [matterId, setMatterId] = useState("");
+  const [matters, setMatters] = useState<Matter[]>([]);
+  const [client, setClient] = useState("");
+  const [clients, setClients] = useState<string[]>([]);
+  const [tag, setTag] = useState("");
+  const [tags, setTags] = useState<Tag[]>([]);
+  const [hits, setHits] = useState<SearchHit[] | null>(null);
+  const [similar, setSimilar] = useState<SimilarClause[] | null>(null);
+  const [similarTo, setSimilarTo] = useState("");
+  const [searching, setSearching] = useState(false);
+
+  useEffect(() => {
+    listMatters()
+      .then(setMatters)
+      .catch(() => setMatters([]));
+    listClients()
+      .then(setClients)
+      .catch(() => setClients([]));
+    listTags()
+      .then(setTags)
+      .catch(() => setTags([]));
+  }, []);
+
+  const handleSearch = useCallback(
+    async (e: React.FormEvent) => {
+      e.preventDefault();
+      if (!query.trim()) return;
+      try {
+        setSearching(true);
+        if (mode === "similar") {
+          setSimilar(await findSimilarClauses({ query }));
+          setSimilarTo(query);
+          setHits(null);
+          return;
+        }
+        setSimilar(null);
+        setHits(
+          await search({
+            query,
+            scope,
+            contract_type: contractType || null,
+            risk_level: riskLevel || null,
+            date_from: dateFrom || null,
+            date_to: dateTo || null,
+            matter_id: matterId || null,
+            client: client || null,
+            tag: tag || null,
+          }),
+        );
+      } catch (err) {
+        toast.error(err instanceof Error ? err.message : String(err));
+      } finally {
+        setSearching(false);
+      }
+    },
+    [
+      mode,
+      query,
+      scope,
+      contractType,
+      riskLevel,
+      dateFrom,
+      dateTo,
+      matterId,
+      client,
+      tag,
+    ],
+  );
+
+  const handleFindSimilar = useCallback(async (hit: SearchHit) => {
+    if (hit.clause_id === null) return;
+    try {
+      setSearching(true);
+      setSimilar(await findSimilarClauses({ clauseId: hit.clause_id }));
+      setSimilarTo(`${hit.clause_title} in ${hit.filename}`);
+      setHits(null);
+    } catch (err) {
+      toast.error(err instanceof Error ? err.message : String(err));
+    } finally {
+      setSearching(false);
+    }
+  }, []);
+
+  const selectClass = "border border-gray-300 rounded-lg px-3 py-2 text-sm";
+
+  return (
+    <div className="p-8 max-w-5xl">
+      <div className="mb-8">
+        <h1 className="text-2xl font-bold">Search</h1>
+        <p className="text-gray-500 mt-1">
+          {mode === "keyword"
+            ? "Use quotes for phrases and AND, OR, NOT to combine terms"
+            : "Describe a clause to find the closest clauses in every document"}
+        </p>
+      </div>
+
+      <form
+        onSubmit={handleSearch}
+        className="bg-white rounded-xl border border-gray-200 p-6 mb-6 space-y-4"
+      >
+        <div className="flex gap-3">
+          <select
+            value={mode}
+            onChange={(e) => setMode(e.target.value as Mode)}
+            className={selectClass}
+          >
+            <option value="keyword">Keywords</option>
+            <option value="similar">Similar clauses</option>
+          </select>
+          <input
+            value={query}
+            onChange={(e) => setQuery(e.target.value)}
+            placeholder={'"limitation of liability" OR indemn*'}
+            className="flex-1 border border-gray-300 rounded-lg px-3 py-2 text-sm"
+          />
+          <button
+            type="submit"
+            disabled={searching || !query.trim()}
+            className="flex items-center gap-2 bg-brand-600 text-white px-6 py-2 rounded-lg hover:bg-brand-700 disabled:opacity-50 transition-colors text-sm font-medium"
+          >
+            {searching ? (
+              <Loader2 className="h-4 w-4 animate-spin" />
+            ) : (
+              <SearchIcon className="h-4 w-4" />
+            )}
+            Search
+          </button>
+        </div>
+        <div
+          className={`flex flex-wrap gap-3 ${mode === "similar" ? "hidden" : ""}`}
+        >
+          <select
+            value={scope}
+            onChange={(e) => setScope(e.target.value as SearchScope)}
+            className={selectClass}
+          >
+            <option value="all">Documents and clauses</option>
+            <option value="documents">Document text</option>
+            <option value="clauses">Clauses</option>
+          </select>
+          <select
+            value={contractType}
+            onChange={(e) => setContractType(e.target.value as ContractType)}
+            className={selectClass}
+          >
+            <option value="">Any contract type</option>
+            {Object.entries(CONTRACT_TYPE_LABELS).map(([value, label]) => (
+              <option key={value} value={value}>
+                {label}
+              </option>
+            ))}
+          </select>
+          <select
+            value={riskLevel}
+            onChange={(e) => setRiskLevel(e.target.value as RiskLevel)}
+            className={selectClass}
+          >
+            <option value="">Any risk level</option>
+            <option value="low">Low</option>
+            <option value="medium">Medium</option>
+            <option value="high">High</option>
+          </select>
+          <select
+            value={matterId}
+            onChange={(e) => setMatterId(e.target.value)}
+            className={selectClass}
+          >
+            <option value="">Any matter</option>
+            {matters.map((matter) => (
+              <option key={matter.id} value={matter.id}>
+                {matter.name}
+              </option>
+            ))}
+          </select>
+          <select
+            value={client}
+            onChange={(e) => setClient(e.target.value)}
+            className={selectClass}
+          >
+            <option value="">Any client</option>
+            {clients.map((name) => (
+              <option key={name} value={name}>
+                {name}
+              </option>
+            ))}
+          </select>
+          <select
+            value={tag}
+            onChange={(e) => setTag(e.target.value)}
+            className={selectClass}
+          >
+            <option value="">Any tag</option>
+            {tags.map((t) => (
+              <option key={t.id} value={t.name}>
+                {t.name}
+              </option>
+            ))}
+          </select>
+          <input
+            type="date"
+            value={dateFrom}
+            onChange={(e) => setDateFrom(e.target.value)}
+            className={selectClass}
+            title="Uploaded from"
+          />
+          <input
+            type="date"
+            value={dateTo}
+            onChange={(e) => setDateTo(e.target.value)}
+            className={selectClass}
+            title="Uploaded until"
+          />
+        </div>
+      </form>
+
+      {hits &&
+        (hits.length === 0 ? (
+          <div className="bg-white rounded-xl border border-gray-200 p-12 text-center">
+            <SearchIcon className="h-12 w-12 text-gray-300 mx-auto mb-4" />
+            <p className="text-gray-500">No matches</p>
+          </div>
+        ) : (
+          <div className="space-y-3">
+            {hits.map((hit) => (
+              <Link
+                key={`${hit.kind}-${hit.clause_id ?? hit.document_id}`}
+                to={`/documents/${hit.document_id}`}
+                className="block bg-white rounded-xl border border-gray-200 p-4 hover:border-brand-300 transition-colors"
+              >
+                <div className="flex items-center justify-between">
+                  <p className="font-medium text-gray-900">
+                    {hit.filename}
+                    {hit.kind === "clause" && (
+                      <span className="text-gray-500 font-normal">
+                        {" "}
+                        &middot; {hit.clause_title}
+                        {hit.section_reference && ` (${hit.section_reference})`}
+                      </span>
+                    )}
+                  </p>
+                  {hit.risk_level && (
+                    <span
+                      className={`text-xs px-2 py-0.5 rounded-full ${RISK_LEVEL_BG[hit.risk_level]}`}
+                    >
+                      {hit.risk_level}
+                    </span>
+                  )}
+                </div>
+                <p className="text-xs text-gray-500">
+                  {CONTRACT_TYPE_LABELS[hit.contract_type]} &middot;{" "}
+                  {hit.kind === "clause" ? hit.clause_type : "document text"}
+                </p>
+                <Snippet parts={hit.snippet} />
+                {hit.kind === "clause" && (
+                  <button
+                    onClick={(e) => {
+                      e.preventDefault();
+                      handleFindSimilar(hit);
+                    }}
+                    className="mt-2 text-xs text-brand-600 hover:text-brand-800"
+                  >
+                    Find similar clauses
+                  </button>
+                )}
+              </Link>
+            ))}
+          </div>
+        ))}
+
+      {similar && (
+        <div className="space-y-3">
+          <p className="text-sm text-gray-500">Closest to: {similarTo}</p>
+          {similar.length === 0 ? (
+            <div className="bg-white rounded-xl border border-gray-200 p-12 text-center">
+              <p className="text-gray-500">No analyzed clauses to compare</p>
+            </div>
+          ) : (
+            similar.map((clause) => (
+              <Link
+                key={clause.clause_id}
+                to={`/documents/${clause.document_id}`}
+                className="block bg-white rounded-xl border border-gray-200 p-4 hover:border-brand-300 transition-colors"
+              >
+                <div className="flex items-center justify-between">
+                  <p className="font-medium text-gray-900">
+                    {clause.filename}
+                    <span className="text-gray-500 font-normal">
+                      {" "}
+                      &middot; {clause.title}
+                      {clause.section_reference &&
+                        ` (${clause.section_reference})`}
+                    </span>
+                  </p>
+                  <span className="text-xs text-gray-500">
+                    {Math.round(clause.score * 100)}% similar
+                  </span>
+                </div>
+                <p className="text-xs text-gray-500">
+                  {CONTRACT_TYPE_LABELS[clause.contract_type]} &middot;{" "}
+                  {clause.clause_type}
+                </p>
+                <p className="text-sm text-gray-700 mt-2 line-clamp-3">
+                  {clause.text}
+                </p>
+              </Link>
+            ))
+          )}
+        </div>
+      )}
+    </div>
+  );
+}
+
+export default Search;
@@ -1,25 +1,75 @@
 import { useEffect, useState, useCallback } from "react";
 import { Settings as SettingsIcon, Save } from "lucide-react";
 import toast from "react-hot-toast";
-import { getSetting, setSetting } from "@/lib/commands";
+import {
+  enableEncryption,
+  getEncryptionStatus,
+  getSecretsStatus,
+  rotateEncryptionKey,
+  getSetting,
+  setSecret,
+  setSetting,
+  unlockSecrets,
+  type EncryptionStatus,
+  type SecretKey,
+  type SecretsStatus,
+} from "@/lib/commands";
 
 function Settings() {
   const [aiProvider, setAiProvider] = useState("ollama");
   const [ollamaUrl, setOllamaUrl] = useState("http://localhost:11434");
   const [ollamaModel, setOllamaModel] = useState("llama3");
+  const [ollamaNumCtx, setOllamaNumCtx] = useState("");
+  const [embeddingProvider, setEmbeddingProvider] = useState("ollama");
+  const [embeddingModel, setEmbeddingModel] = useState("nomic-embed-text");
+  const [redactionEnabled, setRedactionEnabled] = useState(true);
+  const [localOnly, setLocalOnly] = useState(false);
+  const [secrets, setSecrets] = useState<SecretsStatus | null>(null);
+  const [apiKeys, setApiKeys] = useState<Partial<Record<SecretKey, string>>>({});
+  const [passphrase, setPassphrase] = useState("");
+  const [encryption, setEncryption] = useState<EncryptionStatus | null>(null);
+  const [currentPassphrase, setCurrentPassphrase] = useState("");
+  const [newPassphrase, setNewPassphrase] = useState("");
+  const [confirmPassphrase, setConfirmPassphrase] = useState("");
+  const [encrypting, setEncrypting] = useState(false);
   const [loading, setLoading] = useState(true);
 
   useEffect(() => {
     async function load() {
       try {
-        const [provider, url, model] = await Promise.all([
+        const [
+          provider,
+          url,
+          model,
+          numCtx,
+          embedder,
+          embedModel,
+          redaction,
+          local,
+          status,
+          enc,
+        ] = await Promise.all([
           getSetting("ai_provider"),
           getSetting("ollama_url"),
           getSetting("ollama_model"),
+          getSetting("ollama_num_ctx"),
+          getSetting("embedding_provider"),
+          getSetting("ollama_embedding_model"),
+          getSetting("redaction_enabled"),
+          getSetting("local_only"),
+          getSecretsStatus(),
+          getEncryptionStatus(),
         ]);
         if (provider) setAiProvider(provider);
         if (url) setOllamaUrl(url);
         if (model) setOllamaModel(model);
+        if (numCtx) setOllamaNumCtx(numCtx);
+        if (embedder) setEmbeddingProvider(embedder);
+        if (embedModel) setEmbeddingModel(embedModel);
+        setRedactionEnabled(redaction !== "false");
+        setLocalOnly(local === "true");
+        setSecrets(status);
+        setEncryption(enc);
       } finally {
         setLoading(false);
       }
@@ -33,14 +83,82 @@ function Settings() {
         setSetting("ai_provider", aiProvider),
         setSetting("ollama_url", ollamaUrl),
         setSetting("ollama_model", ollamaModel),
+        setSetting("ollama_num_ctx", ollamaNumCtx.trim()),
+        setSetting("embedding_provider", embeddingProvider),
+        setSetting("ollama_embedding_model", embeddingModel.trim()),
+        setSetting("redaction_enabled", String(redactionEnabled)),
+        setSetting("local_only", String(localOnly)),
       ]);
+      // Keys are write-only: only send the ones typed in this session
+      const entered = Object.entries(apiKeys).filter(([, value]) => value);
+      for (const [key, value] of entered) {
+        await setSecret(key as SecretKey, value as string);
+      }
+      if (entered.length > 0) {
+        setApiKeys({});
+        setSecrets(await getSecretsStatus());
+      }
       toast.success("Settings saved");
     } catch (err) {
       toast.error(
         `Failed to save: ${err instanceof Error ? err.message : String(err)}`,
       );
     }
-  }, [aiProvider, ollamaUrl, ollamaModel]);
+  }, [
+    aiProvider,
+    ollamaUrl,
+    ollamaModel,
+    ollamaNumCtx,
+    embeddingProvider,
+    embeddingModel,
+    redactionEnabled,
+    localOnly,
+    apiKeys,
+  ]);
+
+  const handleUnlock = useCallback(async () => {
+    try {
+      setSecrets(await unlockSecrets(passphrase));
+      setPassphrase("");
+      toast.success("Secrets vault unlocked");
+    } catch (err) {
+      toast.error(err instanceof Error ? err.message : String(err));
+    }
+  }, [passphrase]);
+
+  const handleEncryption = useCallback(async () => {
+    if (newPassphrase !== confirmPassphrase) {
+      toast.error("Passphrases do not match");
+      return;
+    }
+    setEncrypting(true);
+    try {
+      const status = encryption?.enabled
+        ? await rotateEncryptionKey(currentPassphrase, newPassphrase)
+        : await enableEncryption(newPassphrase);
+      toast.success(
+        encryption?.enabled ? "Encryption key rotated" : "Workspace encrypted",
+      );
+      setEncryption(status);
+      setCurrentPassphrase("");
+      setNewPassphrase("");
+      setConfirmPassphrase("");
+    } catch (err) {
+      toast.error(err instanceof Error ? err.message : String(err));
+    } finally {
+      setEncrypting(false);
+    }
+  }, [encryption, currentPassphrase, newPassphrase, confirmPassphrase]);
+
+  const keyIsSet = (key: SecretKey) =>
+    secrets?.keys.some((k) => k.key === key && k.is_set) ?? false;
+
+  const vaultLocked =
+    secrets?.backend === "encrypted_file" && secrets.locked;
+  const keyStorage =
+    secrets?.backend === "keyring"
+      ? "Stored in your operating system's keychain"
+      : "Stored in an encrypted vault on this device";
 
   if (loading) {
     return (
@@ -73,6 +191,53 @@ function Settings() {
           </select>
         </div>
 
+        <label className="flex items-start gap-3">
+          <input
+            type="checkbox"
+            checked={localOnly}
+            onChange={(e) => setLocalOnly(e.target.checked)}
+            className="mt-1 h-4 w-4 rounded border-gray-300 text-brand-600 focus:ring-brand-500"
+          />
+          <span>
+            <span className="block text-sm font-medium text-gray-700">
+              Local only
+            </span>
+            <span className="block text-xs text-gray-500 mt-1">
+              Never send documents to cloud providers or to an Ollama server
+              on another machine
+            </span>
+          </span>
+        </label>
+
+        <div>
+          <label className="block text-sm font-medium text-gray-700 mb-2">
+            Clause Embeddings
+          </label>
+          <div className="flex gap-2">
+            <select
+              value={embeddingProvider}
+              onChange={(e) => setEmbeddingProvider(e.target.value)}
+              className="border border-gray-300 rounded-lg px-3 py-2 text-sm focus:outline-none focus:ring-2 focus:ring-brand-500 focus:border-transparent"
+            >
+              <option value="ollama">Ollama</option>
+              <option value="local">Built-in</option>
+            </select>
+            {embeddingProvider === "ollama" && (
+              <input
+                type="text"
+                value={embeddingModel}
+                onChange={(e) => setEmbeddingModel(e.target.value)}
+                className="flex-1 border border-gray-300 rounded-lg px-3 py-2 text-sm focus:outline-none focus:ring-2 focus:ring-brand-500 focus:border-transparent"
+                placeholder="nomic-embed-text"
+              />
+            )}
+          </div>
+          <p className="text-xs text-gray-500 mt-1">
+            Used to find similar clauses. The built-in option needs no model
+            but only matches shared wording
+          </p>
+        </div>
+
         {aiProvider === "ollama" && (
           <>
             <div>
@@ -99,9 +264,54 @@ function Settings() {
                 placeholder="llama3"
               />
             </div>
+            <div>
+              <label className="block text-sm font-medium text-gray-700 mb-2">
+                Context Window (tokens)
+              </label>
+              <input
+                type="number"
+                min={2048}
+                step={1024}
+                value={ollamaNumCtx}
+                onChange={(e) => setOllamaNumCtx(e.target.value)}
+                className="w-full border border-gray-300 rounded-lg px-3 py-2 text-sm focus:outline-none focus:ring-2 focus:ring-brand-500 focus:border-transparent"
+                placeholder="Per-task default"
+              />
+              <p className="text-xs text-gray-500 mt-1">
+                Leave empty to size the window for each task automatically
+              </p>
+            </div>
           </>
         )}
 
+        {vaultLocked && (aiProvider === "claude" || aiProvider === "openai") && (
+          <div>
+            <label className="block text-sm font-medium text-gray-700 mb-2">
+              {secrets?.vault_exists ? "Unlock API Keys" : "Create Key Vault"}
+            </label>
+            <div className="flex gap-2">
+              <input
+                type="password"
+                value={passphrase}
+                onChange={(e) => setPassphrase(e.target.value)}
+                onKeyDown={(e) => e.key === "Enter" && handleUnlock()}
+                className="flex-1 border border-gray-300 rounded-lg px-3 py-2 text-sm focus:outline-none focus:ring-2 focus:ring-brand-500 focus:border-transparent"
+                placeholder="Vault passphrase"
+              />
+              <button
+                onClick={handleUnlock}
+                className="px-4 py-2 rounded-lg border border-gray-300 text-sm font-medium hover:bg-gray-50"
+              >
+                Unlock
+              </button>
+            </div>
+            <p className="text-xs text-gray-500 mt-1">
+              No system keychain is available, so API keys are kept in a
+              passphrase-protected file
+            </p>
+          </div>
+        )}
+
         {aiProvider === "claude" && (
           <div>
             <label className="block text-sm font-medium text-gray-700 mb-2">
@@ -109,15 +319,15 @@ function Settings() {
             </label>
             <input
               type="password"
-              className="w-full border border-gray-300 rounded-lg px-3 py-2 text-sm focus:outline-none focus:ring-2 focus:ring-brand-500 focus:border-transparent"
-              placeholder="sk-ant-..."
+              disabled={vaultLocked}
+              value={apiKeys.claude_api_key ?? ""}
+              className="w-full border border-gray-300 rounded-lg px-3 py-2 text-sm focus:outline-none focus:ring-2 focus:ring-brand-500 focus:border-transparent disabled:bg-gray-50"
+              placeholder={keyIsSet("claude_api_key") ? "Key saved — enter a new key to replace it" : "sk-ant-..."}
               onChange={(e) =>
-                setSetting("claude_api_key", e.target.value)
+                setApiKeys((keys) => ({ ...keys, claude_api_key: e.target.value }))
               }
             />
-            <p className="text-xs text-gray-500 mt-1">
-              Stored locally on your device only
-            </p>
+            <p className="text-xs text-gray-500 mt-1">{keyStorage}</p>
           </div>
         )}
 
@@ -128,17 +338,37 @@ function Settings() {
             </label>
             <input
               type="password"
-              className="w-full border border-gray-300 rounded-lg px-3 py-2 text-sm focus:outline-none focus:ring-2 focus:ring-brand-500 focus:border-transparent"
-              placeholder="sk-..."
+              disabled={vaultLocked}
+              value={apiKeys.openai_api_key ?? ""}
+              className="w-full border border-gray-300 rounded-lg px-3 py-2 text-sm focus:outline-none focus:ring-2 focus:ring-brand-500 focus:border-transparent disabled:bg-gray-50"
+              placeholder={keyIsSet("openai_api_key") ? "Key saved — enter a new key to replace it" : "sk-..."}
               onChange={(e) =>
-                setSetting("openai_api_key", e.target.value)
+                setApiKeys((keys) => ({ ...keys, openai_api_key: e.target.value }))
               }
             />
-            <p className="text-xs text-gray-500 mt-1">
-              Stored locally on your device only
-            </p>
+            <p className="text-xs text-gray-500 mt-1">{keyStorage}</p>
           </div>
         )}
+
+        {(aiProvider === "claude" || aiProvider === "openai") && (
+          <label className="flex items-start gap-3">
+            <input
+              type="checkbox"
+              checked={redactionEnabled}
+              onChange={(e) => setRedactionEnabled(e.target.checked)}
+              className="mt-1 h-4 w-4 rounded border-gray-300 text-brand-600 focus:ring-brand-500"
+            />
+            <span>
+              <span className="block text-sm font-medium text-gray-700">
+                Redact personal information
+              </span>
+              <span className="block text-xs text-gray-500 mt-1">
+                Names, emails, phone numbers, account numbers and addresses
+                are replaced with placeholders before text leaves this device
+              </span>
+            </span>
+          </label>
+        )}
       </div>
 
       <button
@@ -148,6 +378,53 @@ function Settings() {
         <Save className="h-4 w-4" />
         Save Settings
       </button>
+
+      <div className="bg-white rounded-xl border border-gray-200 p-6 space-y-4 mt-8">
+        <div>
+          <h2 className="text-sm font-medium text-gray-700">
+            Encryption at Rest
+          </h2>
+          <p className="text-xs text-gray-500 mt-1">
+            {encryption?.enabled
+              ? "The database and stored documents are encrypted. Rotating replaces the key and the passphrase."
+              : "Encrypt the database and stored documents. The passphrase is required every time the app starts and cannot be recovered."}
+          </p>
+        </div>
+        {encryption?.enabled && (
+          <input
+            type="password"
+            value={currentPassphrase}
+            onChange={(e) => setCurrentPassphrase(e.target.value)}
+            className="w-full border border-gray-300 rounded-lg px-3 py-2 text-sm focus:outline-none focus:ring-2 focus:ring-brand-500 focus:border-transparent"
+            placeholder="Current passphrase"
+          />
+        )}
+        <input
+          type="password"
+          value={newPassphrase}
+          onChange={(e) => setNewPassphrase(e.target.value)}
+          className="w-full border border-gray-300 rounded-lg px-3 py-2 text-sm focus:outline-none focus:ring-2 focus:ring-brand-500 focus:border-transparent"
+          placeholder="New passphrase (at least 8 characters)"
+        />
+        <input
+          type="password"
+          value={confirmPassphrase}
+          onChange={(e) => setConfirmPassphrase(e.target.value)}
+          className="w-full border border-gray-300 rounded-lg px-3 py-2 text-sm focus:outline-none focus:ring-2 focus:ring-brand-500 focus:border-transparent"
+          placeholder="Confirm new passphrase"
+        />
+        <button
+          onClick={handleEncryption}
+          disabled={encrypting || !newPassphrase}
+          className="px-4 py-2 rounded-lg border border-gray-300 text-sm font-medium hover:bg-gray-50 disabled:opacity-50"
+        >
+          {encrypting
+            ? "Encrypting..."
+            : encryption?.enabled
+              ? "Rotate Key"
+              : "Encrypt Workspace"}
+        </button>
+      </div>
     </div>
   );
 }
@@ -0,0 +1,121 @@
+import { useState, useCallback, useEffect } from "react";
+import { RotateCcw, Trash2, XCircle } from "lucide-react";
+import toast from "react-hot-toast";
+import { listTrash, restoreDocument, purgeDocument } from "@/lib/commands";
+import { CONTRACT_TYPE_LABELS } from "@/types";
+import type { Document } from "@/types";
+
+function Trash() {
+  const [documents, setDocuments] = useState<Document[]>([]);
+  const [loading, setLoading] = useState(true);
+
+  const refresh = useCallback(async () => {
+    try {
+      setLoading(true);
+      setDocuments(await listTrash());
+    } catch {
+      toast.error("Failed to load trash");
+    } finally {
+      setLoading(false);
+    }
+  }, []);
+
+  useEffect(() => {
+    refresh();
+  }, [refresh]);
+
+  const handleRestore = useCallback(
+    async (id: string) => {
+      try {
+        await restoreDocument(id);
+        toast.success("Document restored");
+        refresh();
+      } catch (err) {
+        toast.error(
+          `Failed: ${err instanceof Error ? err.message : String(err)}`,
+        );
+      }
+    },
+    [refresh],
+  );
+
+  const handlePurge = useCallback(
+    async (id: string) => {
+      try {
+        await purgeDocument(id);
+        toast.success("Document permanently deleted");
+        refresh();
+      } catch (err) {
+        toast.error(
+          `Failed: ${err instanceof Error ? err.message : String(err)}`,
+        );
+      }
+    },
+    [refresh],
+  );
+
+  if (loading) {
+    return (
+      <div className="flex items-center justify-center h-full">
+        <div className="animate-spin h-8 w-8 border-2 border-brand-600 border-t-transparent rounded-full" />
+      </div>
+    );
+  }
+
+  return (
+    <div className="p-8 max-w-4xl">
+      <div className="mb-8">
+        <h1 className="text-2xl font-bold">Trash</h1>
+        <p className="text-gray-500 mt-1">
+          Deleted documents are purged after the retention period set in
+          Settings
+        </p>
+      </div>
+
+      {documents.length === 0 ? (
+        <div className="bg-white rounded-xl border border-gray-200 p-12 text-center">
+          <Trash2 className="h-12 w-12 text-gray-300 mx-auto mb-4" />
+          <p className="text-gray-500">The trash is empty</p>
+        </div>
+      ) : (
+        <div className="space-y-3">
+          {documents.map((doc) => (
+            <div
+              key={doc.id}
+              className="bg-white rounded-xl border border-gray-200 p-4 flex items-center justify-between"
+            >
+              <div>
+                <p className="font-medium text-gray-900">{doc.filename}</p>
+                <p className="text-xs text-gray-500">
+                  {CONTRACT_TYPE_LABELS[doc.contract_type]} &middot; deleted{" "}
+                  {doc.deleted_at &&
+                    new Date(doc.deleted_at + "Z").toLocaleString()}
+                  {doc.legal_hold && " — on legal hold"}
+                </p>
+              </div>
+              <div className="flex items-center gap-1">
+                <button
+                  onClick={() => handleRestore(doc.id)}
+                  title="Restore"
+                  className="p-2 text-gray-500 hover:text-brand-700 hover:bg-brand-50 rounded-lg transition-colors"
+                >
+                  <RotateCcw className="h-4 w-4" />
+                </button>
+                <button
+                  onClick={() => handlePurge(doc.id)}
+                  disabled={doc.legal_hold}
+                  title="Delete permanently"
+                  className="p-2 text-red-400 hover:text-red-600 hover:bg-red-50 disabled:opacity-40 rounded-lg transition-colors"
+                >
+                  <XCircle className="h-4 w-4" />
+                </button>
+              </div>
+            </div>
+          ))}
+        </div>
+      )}
+    </div>
+  );
+}
+
+export default Trash;
@@ -1,12 +1,17 @@
-import { useState, useCallback } from "react";
-import { useNavigate } from "react-router";
+import { useState, useCallback, useEffect } from "react";
+import { useNavigate, useSearchParams } from "react-router";
 import { useDropzone } from "react-dropzone";
-import { open } from "@tauri-apps/plugin-dialog";
-import { Upload as UploadIcon, FileText, X } from "lucide-react";
+import { Upload as UploadIcon, FileText, History, X } from "lucide-react";
 import toast from "react-hot-toast";
-import { uploadDocument, extractDocumentText } from "@/lib/commands";
+import {
+  pickDocumentFile,
+  uploadDocument,
+  extractDocumentText,
+  getDocument,
+  addDocumentVersion,
+} from "@/lib/commands";
 import { CONTRACT_TYPE_LABELS } from "@/types";
-import type { ContractType } from "@/types";
+import type { ContractType, Document } from "@/types";
 
 function Upload() {
   const navigate = useNavigate();
@@ -14,12 +19,26 @@ function Upload() {
   const [fileName, setFileName] = useState<string | null>(null);
   const [contractType, setContractType] = useState<ContractType>("nda");
   const [uploading, setUploading] = useState(false);
+  const [searchParams] = useSearchParams();
+  const versionOfId = searchParams.get("versionOf");
+  const [versionOf, setVersionOf] = useState<Document | null>(null);
+  const [versionLabel, setVersionLabel] = useState("");
+
+  useEffect(() => {
+    if (!versionOfId) {
+      setVersionOf(null);
+      return;
+    }
+    getDocument(versionOfId)
+      .then((doc) => {
+        setVersionOf(doc);
+        setContractType(doc.contract_type);
+      })
+      .catch(() => toast.error("Failed to load the earlier version"));
+  }, [versionOfId]);
 
   const handleSelectFile = useCallback(async () => {
-    const result = await open({
-      filters: [{ name: "PDF", extensions: ["pdf"] }],
-      multiple: false,
-    });
+    const result = await pickDocumentFile();
     if (result) {
       setSelectedFile(result);
       const parts = result.split(/[/\\]/);
@@ -57,6 +76,26 @@ function Upload() {
         );
       }
 
+      if (versionOf) {
+        try {
+          const added = await addDocumentVersion(
+            versionOf.id,
+            doc.id,
+            versionLabel || null,
+          );
+          toast.success(`Added as version ${added.document.version_number}`);
+          if (added.comparison_error) {
+            toast.error(
+              `Not compared with the previous version: ${added.comparison_error}`,
+            );
+          }
+        } catch (err) {
+          toast.error(
+            `Adding the version failed: ${err instanceof Error ? err.message : String(err)}`,
+          );
+        }
+      }
+
       navigate(`/documents/${doc.id}`);
     } catch (err) {
       toast.error(
@@ -65,7 +104,7 @@ function Upload() {
     } finally {
       setUploading(false);
     }
-  }, [selectedFile, contractType, navigate]);
+  }, [selectedFile, contractType, versionOf, versionLabel, navigate]);
 
   return (
     <div className="p-8 max-w-2xl mx-auto">
@@ -74,6 +113,24 @@ function Upload() {
         Upload a PDF contract for AI-powered review and analysis
       </p>
 
+      {versionOf && (
+        <div className="bg-brand-50 border border-brand-200 rounded-xl p-4 mb-6">
+          <p className="text-sm text-brand-800 flex items-center gap-2 mb-3">
+            <History className="h-4 w-4" />
+            New version of <strong>{versionOf.filename}</strong> (v
+            {versionOf.version_number}); it will be compared with the latest
+            version
+          </p>
+          <input
+            type="text"
+            value={versionLabel}
+            onChange={(e) => setVersionLabel(e.target.value)}
+            className="w-full border border-gray-300 rounded-lg px-3 py-2 text-sm"
+            placeholder="Label, e.g. Our markup or Executed"
+          />
+        </div>
+      )}
+
       <div
         {...getRootProps()}
         onClick={handleSelectFile}
@@ -23,8 +23,69 @@ export interface Document {
   error_message: string | null;
   created_at: string;
   updated_at: string;
+  local_only: boolean;
+  matter_id: string | null;
+  deleted_at: string | null;
+  legal_hold: boolean;
+  legal_hold_reason: string | null;
+  raw_text_removed_at: string | null;
+  tags: string[];
+  /** Shared by every version of the same contract. */
+  family_id: string;
+  version_number: number;
+  version_label: string | null;
 }
 
+/** A document without its text, as listed; `getDocument` has the rest. */
+export interface DocumentSummary {
+  id: string;
+  filename: string;
+  contract_type: ContractType;
+  file_size: number;
+  page_count: number | null;
+  processing_status: ProcessingStatus;
+  error_message: string | null;
+  created_at: string;
+  updated_at: string;
+  local_only: boolean;
+  matter_id: string | null;
+  legal_hold: boolean;
+  has_text: boolean;
+  tags: string[];
+  risk_score: number | null;
+  risk_level: RiskLevel | null;
+  family_id: string;
+  version_number: number;
+  version_label: string | null;
+}
+
+/** Narrows document lists and stats; unset fields do not filter. */
+export interface DocumentFilter {
+  matter_id?: string | null;
+  client?: string | null;
+  tag?: string | null;
+  contract_type?: ContractType | null;
+  status?: ProcessingStatus | null;
+  risk_level?: RiskLevel | null;
+}
+
+export type DocumentSort = "created_at" | "filename" | "risk_score" | "status";
+
+export interface DocumentQuery extends DocumentFilter {
+  sort?: DocumentSort;
+  direction?: "asc" | "desc";
+  /** `next_cursor` from the previous page. */
+  cursor?: string | null;
+  limit?: number | null;
+}
+
+export interface DocumentPage {
+  items: DocumentSummary[];
+  next_cursor: string | null;
+}
+
+export type DataPolicy = "standard" | "local_only";
+
 export interface DocumentStats {
   total: number;
   analyzed: number;
@@ -41,6 +102,9 @@ export interface Extraction {
   extracted_data: string;
   confidence_score: number | null;
   processing_time_ms: number | null;
+  prompt_version: number | null;
+  data_policy: DataPolicy | null;
+  ai_endpoint: string | null;
   created_at: string;
 }
 
@@ -61,6 +125,22 @@ export interface RiskAssessment {
   flags: string;
   summary: string | null;
   ai_provider: string;
+  prompt_version: number | null;
+  data_policy: DataPolicy | null;
+  ai_endpoint: string | null;
+  created_at: string;
+}
+
+export interface RedactionLogEntry {
+  id: string;
+  document_id: string | null;
+  comparison_id: string | null;
+  ai_provider: string;
+  prompt_kind: string;
+  entity_type: string;
+  placeholder: string;
+  masked_value: string;
+  occurrences: number;
   created_at: string;
 }
 